@@ -4,11 +4,20 @@
 //! including recording, storage, and Bluetooth interactions. It provides a set of asynchronous APIs
 //! to handle various operations such as starting/stopping recordings, loading/storing data, and managing
 //! Bluetooth devices.
-use crate::model::bluetooth::{AdapterDescriptor, DeviceDescriptor, HeartrateMessage};
+use crate::model::{
+    bluetooth::{AdapterDescriptor, ConnectionState, DeviceDescriptor, HeartrateMessage},
+    cache::SessionCache,
+    hrv::{HrvConfig, SettlingFilter},
+    import::RrUnit,
+    metadata::MeasurementMetadata,
+    metrics::{DetrendOption, DifferenceMode},
+    settings::MarkerInterval,
+};
 use anyhow::Result;
 use async_trait::async_trait;
-use btleplug::api::Central;
+use btleplug::api::{Central, ScanFilter};
 use std::{path::PathBuf, sync::Arc};
+use time::Duration;
 use tokio::sync::RwLock;
 
 use super::model::{BluetoothModelApi, MeasurementModelApi};
@@ -21,20 +30,24 @@ pub enum OutlierFilter {
 /// RecordingApi trait
 ///
 /// This trait defines the asynchronous API for managing the recording process in the application.
-/// It provides methods to start and stop the recording process.
+/// It provides methods to start, pause, resume and stop the recording process.
 #[async_trait]
 pub trait RecordingApi {
     /// start the recording process
     async fn start_recording(&mut self) -> Result<()>;
     /// stop the recording process
     async fn stop_recording(&mut self) -> Result<()>;
+    /// pause the recording process, the session stays open
+    async fn pause_recording(&mut self) -> Result<()>;
+    /// resume a paused recording process
+    async fn resume_recording(&mut self) -> Result<()>;
 }
 
 /// StorageEventApi trait
 ///
 /// This trait defines the asynchronous API for managing storage-related events in the application.
-/// It provides methods to clear storage, load data from a file, store data to a file, and handle
-/// new and recorded measurements.
+/// It provides methods to clear storage, load data from a file, store data to a file and export
+/// the selected measurement.
 #[async_trait]
 pub trait StorageEventApi {
     /// Clear the storage.
@@ -51,6 +64,16 @@ pub trait StorageEventApi {
     /// * `path` - A `PathBuf` representing the file path from which to load data.
     async fn load_from_file(&mut self, path: PathBuf) -> Result<()>;
 
+    /// Add the measurements of a file to the stored measurements.
+    ///
+    /// Unlike `load_from_file`, the stored measurements and the selection are kept.
+    /// Measurements with the start time and beat count of a stored measurement are skipped.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - A `PathBuf` representing the file path from which to load data.
+    async fn append_from_file(&mut self, path: PathBuf) -> Result<()>;
+
     /// Store data to a file.
     ///
     /// This method stores data to the specified file path.
@@ -59,6 +82,112 @@ pub trait StorageEventApi {
     ///
     /// * `path` - A `PathBuf` representing the file path to which to store data.
     async fn store_to_file(&mut self, path: PathBuf) -> Result<()>;
+
+    /// Store a single measurement to a file.
+    ///
+    /// The file has the format of `store_to_file` and holds only the measurement, selected.
+    ///
+    /// # Arguments
+    ///
+    /// * `file` - Index of the stored measurement and the file path to which to store it.
+    async fn store_measurement_to_file(&mut self, file: (usize, PathBuf)) -> Result<()>;
+
+    /// Export the selected measurement as EDF+ file.
+    ///
+    /// This method writes the RR intervals of the selected measurement as annotations.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - A `PathBuf` representing the file path to which to export.
+    async fn export_edf(&mut self, path: PathBuf) -> Result<()>;
+
+    /// Export the RR intervals of the selected measurement in the Kubios HRV text format.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - A `PathBuf` representing the file path to which to export.
+    async fn export_kubios(&mut self, path: PathBuf) -> Result<()>;
+
+    /// Export the artifact correction log of the selected measurement as CSV file.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - A `PathBuf` representing the file path to which to export.
+    async fn export_correction_log(&mut self, path: PathBuf) -> Result<()>;
+
+    /// Export the selected measurement as self-contained HTML report.
+    ///
+    /// The report holds the summary statistics and the plots embedded as images.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - A `PathBuf` representing the file path to which to export.
+    async fn export_html(&mut self, path: PathBuf) -> Result<()>;
+
+    /// Export every stored measurement into a directory.
+    ///
+    /// Each measurement is written as CSV file of its RR intervals, named after its start
+    /// time, and the summaries of all measurements are combined in `summary.csv`. Excluded
+    /// measurements are skipped.
+    ///
+    /// # Arguments
+    ///
+    /// * `dir` - A `PathBuf` representing the directory, created if missing.
+    async fn export_all(&mut self, dir: PathBuf) -> Result<()>;
+
+    /// Import an RR interval series as new measurement and select it.
+    ///
+    /// # Arguments
+    ///
+    /// * `file` - The text file holding the RR intervals and the unit of the intervals.
+    async fn import_rr(&mut self, file: (PathBuf, RrUnit)) -> Result<()>;
+
+    /// Import the HRV records of a `.fit` activity file as new measurement and select it.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The `.fit` file, it must hold RR intervals recorded with HRV logging.
+    async fn import_fit(&mut self, path: PathBuf) -> Result<()>;
+
+    /// Add a synthetic demo measurement and select it.
+    async fn load_demo(&mut self) -> Result<()>;
+
+    /// Concatenate two stored measurements into a new measurement and select it.
+    ///
+    /// The measurements are ordered by their start time and must not overlap.
+    ///
+    /// # Arguments
+    ///
+    /// * `indices` - Indices of the stored measurements to merge.
+    async fn merge_measurements(&mut self, indices: (usize, usize)) -> Result<()>;
+
+    /// Split a stored measurement into two new measurements and select the first part.
+    ///
+    /// The stored measurement is kept unchanged.
+    ///
+    /// # Arguments
+    ///
+    /// * `split` - Index of the stored measurement and the elapsed time to split at.
+    async fn split_measurement(&mut self, split: (usize, Duration)) -> Result<()>;
+
+    /// Move a stored measurement to another position of the list.
+    ///
+    /// The selection and the comparison keep referring to the same measurements.
+    ///
+    /// # Arguments
+    ///
+    /// * `indices` - The current and the new index of the measurement.
+    async fn move_measurement(&mut self, indices: (usize, usize)) -> Result<()>;
+
+    /// Check that a stored measurement can be replayed.
+    ///
+    /// The controller streams the messages of the measurement into a new recording.
+    ///
+    /// # Arguments
+    ///
+    /// * `replay` - Index of the stored measurement and the replay speed as multiple of
+    ///   real time.
+    async fn replay_measurement(&mut self, replay: (usize, f64)) -> Result<()>;
 }
 
 /// StorageApi trait
@@ -76,7 +205,55 @@ pub trait StorageApi<MT: MeasurementModelApi> {
     /// This method returns a reference to the measurement at index.
     fn get_measurement(&self, index: usize) -> Result<Arc<RwLock<MT>>>;
 
+    /// Select a stored measurement.
+    ///
+    /// This method returns the measurement at index and marks it as selected.
+    fn select_measurement(&mut self, index: usize) -> Result<Arc<RwLock<MT>>>;
+
+    /// Select the measurement the selected one is compared to.
+    ///
+    /// # Arguments
+    /// * `index` - Index of the stored measurement, `None` ends the comparison.
+    fn set_compare(&mut self, index: Option<usize>) -> Result<()>;
+
     fn store_measurement(&mut self, measurement: Arc<RwLock<MT>>) -> Result<()>;
+
+    /// Create a new measurement to record into.
+    ///
+    /// The returned measurement becomes the active recording, replacing any recording that
+    /// was not stored.
+    fn new_measurement(&mut self) -> Result<Arc<RwLock<MT>>>;
+
+    /// Move the active recording into the storage and select it.
+    ///
+    /// # Returns
+    /// The stored measurement, or an error if there is no active recording.
+    fn store_recorded_measurement(&mut self) -> Result<Arc<RwLock<MT>>>;
+
+    /// Drop the active recording without storing it.
+    ///
+    /// # Returns
+    /// The discarded recording, `None` if there was no active recording.
+    fn discard_recording(&mut self) -> Option<Arc<RwLock<MT>>>;
+
+    /// Set whether loaded measurements are analysed on first access instead of while loading.
+    ///
+    /// # Arguments
+    /// * `lazy` - `true` makes opening large files fast, the analysis is deferred until a
+    ///   measurement is shown.
+    fn set_lazy_analysis(&mut self, lazy: bool);
+
+    /// Get the stored measurements without valid cached results.
+    ///
+    /// Measurements locked for writing are skipped, they are returned once released.
+    fn stale_caches(&self) -> Vec<Arc<RwLock<MT>>>;
+
+    /// Store the computed results of a stored measurement.
+    ///
+    /// # Arguments
+    /// * `measurement` - The stored measurement the results were computed from.
+    /// * `cache` - The results, dropped if the measurement changed since they were computed.
+    fn set_session_cache(&mut self, measurement: &Arc<RwLock<MT>>, cache: SessionCache);
 }
 
 /// MeasurementApi trait
@@ -104,6 +281,66 @@ pub trait MeasurementApi: MeasurementModelApi {
     /// * `filter` - An `OutlierFilter` specifying the type and parameters of the filter.
     async fn set_outlier_filter(&mut self, filter: OutlierFilter) -> Result<()>;
 
+    /// Set the analysis range.
+    ///
+    /// This method restricts the statistics to the measurements received between the
+    /// given elapsed times. The recorded data itself is left untouched.
+    ///
+    /// # Arguments
+    ///
+    /// * `range` - An optional `(start, end)` tuple of elapsed times, `None` analyzes the full recording.
+    async fn set_analysis_range(&mut self, range: Option<(Duration, Duration)>) -> Result<()>;
+
+    /// Set the settling period excluded from the analysis.
+    ///
+    /// RR intervals ending within this period after the start of the recording are ignored.
+    ///
+    /// # Arguments
+    ///
+    /// * `skip` - The non-negative duration to skip.
+    async fn set_skip_initial(&mut self, skip: Duration) -> Result<()>;
+
+    /// Set the threshold of the pNNx.
+    ///
+    /// The pNNx is the percentage of successive RR differences exceeding the threshold.
+    ///
+    /// # Arguments
+    ///
+    /// * `threshold` - The threshold in ms within `PNN_THRESHOLD_RANGE`.
+    async fn set_pnn_threshold(&mut self, threshold: f64) -> Result<()>;
+
+    /// Set the trend removed from the RR intervals before the statistics are calculated.
+    ///
+    /// The recorded RR intervals are kept, e.g. for the tachogram.
+    ///
+    /// # Arguments
+    ///
+    /// * `detrend` - The `DetrendOption` to apply.
+    async fn set_detrend(&mut self, detrend: DetrendOption) -> Result<()>;
+
+    /// Set the pairing of the inlier RR intervals for RMSSD and pNNx.
+    ///
+    /// # Arguments
+    ///
+    /// * `differences` - Whether successive differences may span a removed beat.
+    async fn set_differences(&mut self, differences: DifferenceMode) -> Result<()>;
+
+    /// Set a separate, usually looser outlier filter scale for the settling phase.
+    ///
+    /// RR intervals after the settling phase are judged by the regular outlier filter.
+    ///
+    /// # Arguments
+    ///
+    /// * `settling` - The filter of the settling phase, `None` uses one scale throughout.
+    async fn set_settling_filter(&mut self, settling: Option<SettlingFilter>) -> Result<()>;
+
+    /// Set all parameters of the analysis at once.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - The `HrvConfig` to analyze the measurement with.
+    async fn set_hrv_config(&mut self, config: HrvConfig) -> Result<()>;
+
     /// Record a heart rate message.
     ///
     /// This method processes and records a new heart rate message.
@@ -112,6 +349,113 @@ pub trait MeasurementApi: MeasurementModelApi {
     ///
     /// * `msg` - A `HeartrateMessage` containing the heart rate data to be recorded.
     async fn record_message(&mut self, msg: HeartrateMessage) -> Result<()>;
+
+    /// Set the outlier filter scale that classifies the target fraction of the RR intervals
+    /// as artifacts.
+    ///
+    /// # Arguments
+    ///
+    /// * `target_ratio` - Desired fraction of artifacts, e.g. `0.02`.
+    async fn auto_tune_outlier_scale(&mut self, target_ratio: f64) -> Result<()>;
+
+    /// Calculate the statistics of a loaded measurement now.
+    ///
+    /// Loaded measurements are analysed on first access, this moves the analysis up front.
+    fn prepare_analysis(&self);
+
+    /// Add a marker to the recording, e.g. to annotate a change of posture.
+    ///
+    /// # Arguments
+    ///
+    /// * `label` - Description of the event, the marker is placed at the current elapsed time.
+    async fn add_marker(&mut self, label: String) -> Result<()>;
+
+    /// Set the subject information of the measurement.
+    ///
+    /// # Arguments
+    ///
+    /// * `metadata` - The subject information, `None` or empty metadata removes it.
+    async fn set_metadata(&mut self, metadata: Option<MeasurementMetadata>) -> Result<()>;
+
+    /// Exclude the measurement from the aggregates of the stored measurements.
+    ///
+    /// The data is kept, but the readiness baseline, the correlations and the batch export
+    /// skip the measurement.
+    ///
+    /// # Arguments
+    ///
+    /// * `excluded` - Whether the measurement is excluded.
+    async fn set_excluded(&mut self, excluded: bool) -> Result<()>;
+
+    /// Mark that the connection to the sensor was lost.
+    ///
+    /// The recording continues, the time until the sensor is reconnected is recorded as gap.
+    async fn mark_disconnected(&mut self) -> Result<()>;
+
+    /// Mark that the sensor was reconnected after a lost connection.
+    async fn mark_reconnected(&mut self) -> Result<()>;
+
+    /// Report events lost before they reached the measurement.
+    ///
+    /// Lost events may have carried RR intervals, so the RR series is split at this point.
+    ///
+    /// # Arguments
+    ///
+    /// * `count` - Number of lost events.
+    fn report_dropped_events(&mut self, count: u64);
+
+    /// Set the interval of the markers inserted automatically while recording.
+    ///
+    /// The markers are labelled `auto 1`, `auto 2`, ... and placed at the end of each
+    /// interval.
+    ///
+    /// # Arguments
+    ///
+    /// * `interval` - Interval of the markers, `None` disables them.
+    fn set_auto_markers(&mut self, interval: Option<MarkerInterval>);
+
+    /// Set the speed the messages of the recording arrive at as multiple of real time.
+    ///
+    /// The elapsed time of messages, markers and gaps is scaled by the speed, so a replay
+    /// keeps the timestamps of the replayed measurement.
+    ///
+    /// # Arguments
+    ///
+    /// * `speed` - The replay speed, `1.0` for a recording of the sensor.
+    fn set_replay_speed(&mut self, speed: f64);
+
+    /// Replace the measured data with messages recorded elsewhere.
+    ///
+    /// # Arguments
+    ///
+    /// * `messages` - `(elapsed time, message)` pairs, sorted by the elapsed time.
+    fn import_messages(&mut self, messages: Vec<(Duration, HeartrateMessage)>) -> Result<()>;
+
+    /// Append a measurement recorded after this one.
+    ///
+    /// The elapsed times of `other` are offset by the difference of the start times, the time
+    /// between both recordings is kept as gap.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - The measurement started after this one ended.
+    fn append_measurement(&mut self, other: &Self) -> Result<()>
+    where
+        Self: Sized;
+
+    /// Split off the part of the measurement recorded after the given elapsed time.
+    ///
+    /// The returned measurement starts at the split point, its gaps and markers are shifted
+    /// accordingly. A gap containing the split point is divided between both parts. The
+    /// number of events lost while recording is kept by the first part. Both parts must hold
+    /// at least one message.
+    ///
+    /// # Arguments
+    ///
+    /// * `at` - Elapsed time of the split point.
+    fn split_off(&mut self, at: Duration) -> Result<Self>
+    where
+        Self: Sized;
 }
 
 /// BluetoothApi trait
@@ -153,7 +497,6 @@ pub trait BluetoothApi: BluetoothModelApi + Send + Sync {
     /// Stop scanning for Bluetooth devices.
     ///
     /// This method stops the ongoing scanning process for discovering Bluetooth peripherals.
-    #[allow(dead_code)]
     async fn stop_scan(&mut self) -> Result<()>;
 
     /// Start listening to the last selected bluetooth peripheral
@@ -161,6 +504,31 @@ pub trait BluetoothApi: BluetoothModelApi + Send + Sync {
 
     /// Stop listening to the bluetooth peripheral
     async fn stop_listening(&mut self) -> Result<()>;
+
+    /// Sets the time between two refreshes of the device list.
+    ///
+    /// Takes effect when the next scan is started.
+    fn set_scan_interval(&mut self, interval: std::time::Duration);
+
+    /// Sets the number of attempts to reconnect a lost sensor before it is given up.
+    ///
+    /// Takes effect when the next sensor is connected.
+    fn set_reconnect_attempts(&mut self, attempts: usize);
+
+    /// Sets the filter of the devices the adapter scans for.
+    ///
+    /// Takes effect when the next scan is started.
+    fn set_scan_filter(&mut self, filter: ScanFilter);
+
+    /// Disconnects the selected peripheral, if it is connected.
+    async fn disconnect(&mut self) -> Result<()>;
+
+    /// Apply a connection state reported by the peripheral listener.
+    ///
+    /// # Arguments
+    ///
+    /// * `state` - The new `ConnectionState`, `Connected` is kept as `Recording` while recording.
+    async fn connection_state_changed(&mut self, state: ConnectionState) -> Result<()>;
 }
 
 /// AdapterDiscovery trait
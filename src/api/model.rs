@@ -2,14 +2,20 @@
 //! It provides interfaces for accessing data related to HRV measurements,
 //! Bluetooth adapters, and stored acquisitions.
 use crate::model::{
-    bluetooth::{AdapterDescriptor, DeviceDescriptor, HeartrateMessage},
-    hrv::PoincarePoints,
+    bluetooth::{AdapterDescriptor, ConnectionState, DeviceDescriptor, HeartrateMessage},
+    cache::SessionCache,
+    hrv::{
+        AnalysisState, CorrectionEntry, HrvConfig, HrvSummary, PoincareEllipse, PoincarePoints,
+        SettlingFilter,
+    },
+    metadata::MeasurementMetadata,
+    metrics::{DetrendOption, DifferenceMode, MetricSeries},
 };
 use anyhow::Result;
 use btleplug::api::BDAddr;
 use std::{fmt::Debug, sync::Arc};
 use time::{Duration, OffsetDateTime};
-use tokio::sync::RwLock;
+use tokio::sync::{watch, RwLock};
 
 /// `MeasurementModelApi` trait.
 ///
@@ -28,19 +34,96 @@ pub trait MeasurementModelApi: Debug + Send + Sync {
     /// An optional `HeartrateMessage` representing the most recent measurement.
     fn get_last_msg(&self) -> Option<&HeartrateMessage>;
 
-    fn get_rmssd(&self) -> Option<f64>;
-    fn get_sdrr(&self) -> Option<f64>;
-    fn get_sd1(&self) -> Option<f64>;
-    fn get_sd2(&self) -> Option<f64>;
     fn get_hr(&self) -> Option<f64>;
     fn get_dfa1a(&self) -> Option<f64>;
 
+    /// Retrieves the SDANN (std. dev. of the 5 minute segment mean RR intervals).
+    ///
+    /// # Returns
+    /// `None` if the recording is too short to contain enough 5 minute segments.
+    fn get_sdann(&self) -> Option<f64>;
+
+    /// Retrieves the SDNN index (mean of the 5 minute segment SDNNs).
+    ///
+    /// # Returns
+    /// `None` if the recording is too short to contain enough 5 minute segments.
+    fn get_sdnn_index(&self) -> Option<f64>;
+
+    /// Retrieves the unfiltered RR intervals as `[time in s, RR in ms]` points.
+    fn get_rr_ts(&self) -> Vec<[f64; 2]>;
     fn get_rmssd_ts(&self) -> Vec<[f64; 2]>;
-    fn get_sdrr_ts(&self) -> Vec<[f64; 2]>;
-    fn get_sd1_ts(&self) -> Vec<[f64; 2]>;
-    fn get_sd2_ts(&self) -> Vec<[f64; 2]>;
     fn get_hr_ts(&self) -> Vec<[f64; 2]>;
-    fn get_dfa1a_ts(&self) -> Vec<[f64; 2]>;
+
+    /// Retrieves the progress of the DFA warmup.
+    ///
+    /// The DFA alpha is unstable for short recordings and reported as unavailable until
+    /// enough beats were analysed. Outliers and beats outside the analysis are not counted.
+    ///
+    /// # Returns
+    /// `Some((beats, required))` while warming up, `None` once the DFA alpha is available.
+    fn get_dfa_warmup(&self) -> Option<(usize, usize)>;
+
+    /// Retrieves the state of the analysis.
+    ///
+    /// # Returns
+    /// An `AnalysisState` distinguishing missing data from unavailable metrics.
+    fn get_analysis_state(&self) -> AnalysisState;
+
+    /// Retrieves the RMSSD of the final five minutes, after the recording stabilized.
+    ///
+    /// # Returns
+    /// The RMSSD in ms, `None` if too few RR intervals were received.
+    fn get_final_rmssd(&self) -> Option<f64>;
+
+    /// Retrieves the SD1 of the most recent beats, independent of the configured window.
+    ///
+    /// # Returns
+    /// The SD1 in ms, `None` if too few recent RR intervals were received.
+    fn get_instant_sd1(&self) -> Option<f64>;
+
+    /// Retrieves the number of analysed RR intervals after the most recent outlier.
+    fn get_beats_since_outlier(&self) -> usize;
+
+    /// Retrieves the fraction of outliers among the RR intervals of the last minute.
+    ///
+    /// # Returns
+    /// The artifact rate, `None` if no RR intervals were analysed.
+    fn get_recent_artifact_rate(&self) -> Option<f64>;
+
+    /// Retrieves a summary of the HRV statistics.
+    ///
+    /// # Returns
+    /// An `HrvSummary` of the analysed data.
+    fn get_summary(&self) -> HrvSummary;
+
+    /// Retrieves the time series of all registered metrics.
+    ///
+    /// # Returns
+    /// A vector of `MetricSeries` in registration order.
+    fn get_metrics(&self) -> Vec<MetricSeries>;
+
+    /// Retrieves the most recent RR intervals and their outlier classification.
+    ///
+    /// # Arguments
+    /// * `count` - The maximum number of RR intervals to return.
+    ///
+    /// # Returns
+    /// A vector of `(RR in ms, is_outlier)` tuples ordered from oldest to newest.
+    fn get_recent_rr(&self, count: usize) -> Vec<(f64, bool)>;
+
+    /// Retrieves the sensor contact of the analysed RR intervals.
+    ///
+    /// Sensors without contact detection are reported as having contact.
+    ///
+    /// # Returns
+    /// A vector of `(time in s, has contact)` tuples aligned with `get_rr_ts`.
+    fn get_contact_track(&self) -> Vec<(f64, bool)>;
+
+    /// Retrieves the log of the RR intervals altered by the artifact correction.
+    ///
+    /// # Returns
+    /// A vector of `CorrectionEntry` ordered by the index of the RR interval.
+    fn get_correction_log(&self) -> Vec<CorrectionEntry>;
 
     /// Retrieves the configured statistics window.
     ///
@@ -48,18 +131,164 @@ pub trait MeasurementModelApi: Debug + Send + Sync {
     /// A reference to an optional `Duration` representing the analysis window size.
     fn get_stats_window(&self) -> Option<usize>;
 
+    /// Retrieves all parameters of the analysis.
+    ///
+    /// # Returns
+    /// The `HrvConfig` the measurement is analyzed with.
+    fn get_hrv_config(&self) -> HrvConfig;
+
+    /// Retrieves the configured analysis range.
+    ///
+    /// # Returns
+    /// An optional `(start, end)` tuple of elapsed times the statistics are restricted to.
+    fn get_analysis_range(&self) -> Option<(Duration, Duration)>;
+
+    /// Retrieves the settling period excluded from the analysis.
+    ///
+    /// # Returns
+    /// The `Duration` skipped at the start of the recording.
+    fn get_skip_initial(&self) -> Duration;
+
+    /// Retrieves the revision of the measurement.
+    ///
+    /// # Returns
+    /// A counter increasing whenever the recorded data, the analysis parameters or the
+    /// state of the recording change.
+    fn get_revision(&self) -> u64;
+
+    /// Retrieves the threshold of the pNNx.
+    ///
+    /// # Returns
+    /// The threshold of the successive RR differences in ms.
+    fn get_pnn_threshold(&self) -> f64;
+
+    /// Retrieves the trend removed from the RR intervals before the statistics are calculated.
+    ///
+    /// # Returns
+    /// The `DetrendOption` of the analysis.
+    fn get_detrend(&self) -> DetrendOption;
+
+    /// Retrieves the pairing of the inlier RR intervals for the successive differences.
+    ///
+    /// # Returns
+    /// The `DifferenceMode` of the analysis.
+    fn get_differences(&self) -> DifferenceMode;
+
+    /// Retrieves the outlier filter of the settling phase.
+    ///
+    /// # Returns
+    /// The `SettlingFilter`, or `None` if one scale is used throughout.
+    fn get_settling_filter(&self) -> Option<SettlingFilter>;
+
+    /// Retrieves the interruptions of the recording.
+    ///
+    /// # Returns
+    /// `(start, end)` elapsed times of the pauses and lost connections, sorted by time.
+    fn get_gaps(&self) -> &[(Duration, Duration)];
+
+    /// Retrieves the markers added during the recording.
+    ///
+    /// # Returns
+    /// `(elapsed time, label)` of the markers in the order they were added.
+    fn get_markers(&self) -> &[(Duration, String)];
+
+    /// Retrieves the received messages.
+    ///
+    /// # Returns
+    /// `(elapsed time, message)` of the messages in the order they were received.
+    fn get_messages(&self) -> &[(Duration, HeartrateMessage)];
+
+    /// Retrieves the subject information.
+    ///
+    /// # Returns
+    /// The metadata of the measurement, `None` if not entered.
+    fn get_metadata(&self) -> Option<&MeasurementMetadata>;
+
+    /// Checks if the measurement is excluded from the aggregates, e.g. because it is too
+    /// noisy.
+    fn is_excluded(&self) -> bool;
+
+    /// Checks if the recording is currently paused.
+    ///
+    /// # Returns
+    /// `true` if incoming measurements are currently discarded due to a pause.
+    fn is_paused(&self) -> bool;
+
+    /// Checks if the measurement is currently recording.
+    ///
+    /// # Returns
+    /// `true` between starting and stopping the recording, also while paused.
+    fn is_recording(&self) -> bool;
+
+    /// Getter for the number of events lost while recording.
+    ///
+    /// # Returns
+    /// The number of events dropped by the event bus during the recording.
+    fn get_dropped_events(&self) -> u64;
+
+    /// Checks if the sensor provides RR intervals.
+    ///
+    /// # Returns
+    /// `false` if messages were received, but none of them contained RR intervals.
+    fn is_rr_available(&self) -> bool;
+
+    /// Retrieves the heart rate reported by the sensor.
+    ///
+    /// # Returns
+    /// `[time in s, HR in 1/min]` points of all received messages with a plausible heart rate.
+    fn get_sensor_hr_ts(&self) -> Vec<[f64; 2]>;
+
+    /// Retrieves the energy expended during the recording.
+    ///
+    /// # Returns
+    /// The accumulated energy in kJ, `None` if the sensor does not report energy expended.
+    fn get_total_energy(&self) -> Option<f64>;
+
+    /// Getter for the number of recorded beats.
+    ///
+    /// # Returns
+    /// The number of RR intervals received, regardless of the analysis range.
+    fn get_beat_count(&self) -> usize;
+
     /// Getter for the filter parameter value (fraction of std. dev).
     ///
     /// # Returns
     /// The parameter value for the outlier filter.
     fn get_outlier_filter_value(&self) -> f64;
 
+    /// Retrieves the fraction of the analysed RR intervals classified as outliers.
+    ///
+    /// # Returns
+    /// The artifact ratio between `0` and `1`.
+    fn get_artifact_ratio(&self) -> f64;
+
+    /// Retrieves the share of the beats expected from the reception time that were received.
+    ///
+    /// # Returns
+    /// The completeness between `0` and `1`, `None` without inlier RR intervals.
+    fn get_completeness(&self) -> Option<f64>;
+
+    /// Retrieves the number of analysed RR intervals that are not classified as outliers.
+    fn get_inlier_count(&self) -> usize;
+
+    /// Retrieves the differences of successive inlier RR intervals in the statistics window.
+    ///
+    /// # Returns
+    /// The ΔRR values in ms RMSSD and pNNx are calculated from.
+    fn get_rr_differences(&self) -> Vec<f64>;
+
     /// Retrieves the points for the Poincare plot.
     ///
     /// # Returns
     /// A vector of `[f64; 2]` pairs representing the Poincare points.
     fn get_poincare_points(&self) -> Result<PoincarePoints>;
 
+    /// Retrieves the SD1/SD2 ellipse of the Poincare plot.
+    ///
+    /// # Returns
+    /// The `PoincareEllipse` of the inlier points.
+    fn get_poincare_ellipse(&self) -> Result<PoincareEllipse>;
+
     /// Retrieves the elapsed time since the start of the acquisition.
     ///
     /// # Returns
@@ -96,11 +325,34 @@ pub trait BluetoothModelApi: Debug + Send + Sync {
     fn is_scanning(&self) -> bool;
 
     fn is_listening_to(&self) -> Option<BDAddr>;
+
+    /// Gets the state of the connection to the sensor.
+    fn get_connection_state(&self) -> ConnectionState;
 }
 
 pub trait StorageModelApi: Debug + Sync + Send {
     /// Returns a slice of handles to the stored acquisition models.
     fn get_acquisitions(&self) -> &[ModelHandle<dyn MeasurementModelApi>];
+
+    /// Returns the index of the selected acquisition, if any.
+    fn get_selected_index(&self) -> Option<usize>;
+
+    /// Returns the index of the acquisition the selected one is compared to, if any.
+    fn get_compare_index(&self) -> Option<usize>;
+
+    /// Returns whether a recording that is not stored yet is active.
+    fn has_recording(&self) -> bool;
+
+    /// Returns the cached results of the stored acquisition at `index`, if any.
+    ///
+    /// The results may be outdated, see `SessionCache::is_valid_for`.
+    fn get_session_cache(&self, index: usize) -> Option<Arc<SessionCache>>;
 }
 
 pub type ModelHandle<T> = Arc<RwLock<T>>;
+
+/// Latest published state of a measurement.
+///
+/// Views read snapshots instead of locking the measurement, so rendering never waits for a
+/// running analysis.
+pub type ModelSnapshot = watch::Receiver<Arc<dyn MeasurementModelApi>>;
@@ -6,34 +6,192 @@
 use crate::{
     api::{
         controller::{BluetoothApi, MeasurementApi, RecordingApi, StorageApi, StorageEventApi},
-        model::{BluetoothModelApi, ModelHandle, StorageModelApi},
+        model::{
+            BluetoothModelApi, MeasurementModelApi, ModelHandle, ModelSnapshot, StorageModelApi,
+        },
     },
-    core::events::{AppEvent, StateChangeEvent},
-    view::manager::{ViewManager, ViewState},
+    core::{
+        error::AppError,
+        events::{
+            AppEvent, BluetoothEvent, MeasurementEvent, RecordingEvent, SettingsEvent,
+            StateChangeEvent, StorageEvent,
+        },
+    },
+    model::{
+        alert::HrAlertMonitor,
+        bluetooth::{scan_filter, ConnectionState},
+        cache::SessionCache,
+        hrv::{set_analysis_threads, STABILIZATION_THRESHOLD_RANGE},
+        operation::FileOperation,
+        replay,
+        settings::{
+            AppSettings, AUTO_SAVE_PREFIX, AUTO_START_RANGE, DFA_SMOOTHING_RANGE,
+            MIN_SAVE_BEATS_RANGE, RECOMPUTE_DEBOUNCE_RANGE, RECONNECT_ATTEMPTS_RANGE,
+            REPAINT_INTERVAL_RANGE, SCAN_INTERVAL_RANGE, SMOOTHING_RANGE, UI_SCALE_RANGE,
+        },
+    },
+    view::manager::{Notification, ViewManager, ViewState},
 };
 
 use anyhow::Result;
-use log::{error, trace};
-use std::sync::Arc;
-use tokio::sync::{broadcast::Sender, RwLock};
+use log::{error, info, trace, warn};
+use std::{
+    path::PathBuf,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use time::macros::format_description;
+use tokio::{
+    sync::{
+        broadcast::{error::RecvError, Receiver, Sender},
+        watch, RwLock,
+    },
+    task::JoinHandle,
+};
+
+/// Coalesces repaint requests of the GUI.
+///
+/// Bursts of events repaint the GUI at most once per interval, state transitions are shown
+/// immediately.
+#[derive(Debug, Clone, Default)]
+struct RepaintThrottle {
+    /// Minimum time between two repaints.
+    interval: Duration,
+    /// Time of the last immediate repaint.
+    last: Option<Instant>,
+}
+
+impl RepaintThrottle {
+    /// Returns the delay after which the GUI has to be repainted.
+    ///
+    /// # Arguments
+    /// - `now`: The current time.
+    /// - `immediate`: Whether the repaint must not be delayed.
+    fn delay(&mut self, now: Instant, immediate: bool) -> Duration {
+        let elapsed = self.last.map_or(Duration::MAX, |last| now - last);
+        if immediate || elapsed >= self.interval {
+            self.last = Some(now);
+            Duration::ZERO
+        } else {
+            self.interval - elapsed
+        }
+    }
+
+    /// Requests a repaint of the GUI, delayed if the last repaint was too recent.
+    fn request(&mut self, gui_ctx: &egui::Context, immediate: bool) {
+        match self.delay(Instant::now(), immediate) {
+            Duration::ZERO => gui_ctx.request_repaint(),
+            delay => gui_ctx.request_repaint_after(delay),
+        }
+    }
+}
+
+/// Coalesces the analysis parameter changes of slider drags.
+///
+/// A change of the window or the outlier filter replaces the pending change of the same
+/// parameter, the pending changes are applied once no change arrived for the interval.
+#[derive(Debug, Clone, Default)]
+struct RecomputeDebounce {
+    /// Time without changes before the pending changes are applied.
+    interval: Duration,
+    /// Latest change of every parameter, in the order of their first change.
+    pending: Vec<MeasurementEvent>,
+    /// Time the pending changes are applied at, if any are pending.
+    deadline: Option<Instant>,
+}
+
+impl RecomputeDebounce {
+    /// Returns the events to handle right away.
+    ///
+    /// Parameter changes are held back. Any other event applies the pending changes first, so
+    /// it sees the measurement as entered and is not overridden by an older pending change.
+    ///
+    /// # Arguments
+    /// - `event`: The received event.
+    /// - `now`: The current time.
+    fn schedule(&mut self, event: AppEvent, now: Instant) -> Vec<AppEvent> {
+        let event = match event {
+            AppEvent::Measurement(
+                event @ (MeasurementEvent::SetStatsWindow(_)
+                | MeasurementEvent::SetOutlierFilter(_)),
+            ) if !self.interval.is_zero() => event,
+            event => {
+                let mut events = self.take();
+                events.push(event);
+                return events;
+            }
+        };
+        let kind = std::mem::discriminant(&event);
+        match self
+            .pending
+            .iter_mut()
+            .find(|pending| std::mem::discriminant(&**pending) == kind)
+        {
+            Some(pending) => *pending = event,
+            None => self.pending.push(event),
+        }
+        self.deadline = Some(now + self.interval);
+        Vec::new()
+    }
+
+    /// Returns the pending changes and clears them.
+    fn take(&mut self) -> Vec<AppEvent> {
+        self.deadline = None;
+        self.pending.drain(..).map(AppEvent::Measurement).collect()
+    }
+}
 
 /// Main application controller.
 ///
 /// This structure manages the lifecycle of other controllers and handles application-level events.
 pub struct AppController<
-    MT: MeasurementApi + RecordingApi + 'static,
+    MT: MeasurementApi + RecordingApi + Clone + 'static,
     ST: StorageApi<MT> + Send + 'static,
     BT: BluetoothApi + RecordingApi + 'static,
 > {
     view_tx: Sender<ViewState>,
+    /// Sends notifications to the views.
+    notification_tx: Sender<Notification>,
     event_bus: Sender<AppEvent>,
     ble_controller: Arc<RwLock<BT>>,
     acq_controller: Arc<RwLock<ST>>,
     active_measurement: Option<Arc<RwLock<MT>>>,
+    /// Publishes snapshots of the active measurement to the views.
+    snapshot_tx: watch::Sender<Arc<dyn MeasurementModelApi>>,
+    /// The measurement and its revision the published snapshot was taken from.
+    snapshot_source: Option<(Arc<RwLock<MT>>, u64)>,
+    /// Whether the active measurement is already part of the storage.
+    active_stored: bool,
+    /// Settings shared with the views.
+    settings: ModelHandle<AppSettings>,
+    /// File the settings are saved to on change, if any.
+    settings_path: Option<PathBuf>,
+    /// Number of events the event handler lost by lagging behind the event bus.
+    dropped_events: u64,
+    /// File loaded into the storage at startup, if any.
+    initial_file: Option<PathBuf>,
+    /// Coalesces the repaint requests of the event handler.
+    repaint: RepaintThrottle,
+    /// Holds back the parameter changes of slider drags.
+    debounce: RecomputeDebounce,
+    /// Address of the last selected peripheral, used to look up its analysis profile.
+    selected_address: Option<String>,
+    /// The running file operation of the storage, shared with the views to cancel it.
+    file_operation: Arc<FileOperation>,
+    /// Watches the heart rate of the active recording.
+    hr_monitor: HrAlertMonitor,
+    /// Whether the sensor of the active recording was lost and did not send data since.
+    sensor_lost: bool,
+    /// Countdown of the recording starting automatically after the sensor connected.
+    auto_start: Option<JoinHandle<()>>,
+    /// Streams the messages of a replayed measurement into the active recording.
+    replay: Option<JoinHandle<()>>,
+    /// Computes the missing results of the stored measurements shown by the overview.
+    cache_warmup: Option<JoinHandle<()>>,
 }
 
 impl<
-        MT: MeasurementApi + RecordingApi + Default + 'static,
+        MT: MeasurementApi + RecordingApi + Clone + Default + 'static,
         ST: StorageApi<MT> + StorageEventApi + StorageModelApi + Send + 'static,
         BT: BluetoothApi + RecordingApi + 'static,
     > AppController<MT, ST, BT>
@@ -51,24 +209,91 @@ impl<
     pub fn new(ble_controller: BT, acq_controller: ST, event_bus: Sender<AppEvent>) -> Self {
         trace!("Initializing AppController.");
         let (vtx, _) = tokio::sync::broadcast::channel(16);
+        let (ntx, _) = tokio::sync::broadcast::channel(16);
         Self {
             view_tx: vtx.clone(),
+            notification_tx: ntx,
             event_bus: event_bus.clone(),
             ble_controller: Arc::new(RwLock::new(ble_controller)),
             acq_controller: Arc::new(RwLock::new(acq_controller)),
             active_measurement: None,
+            snapshot_tx: watch::channel(Arc::new(MT::default()) as Arc<dyn MeasurementModelApi>).0,
+            snapshot_source: None,
+            active_stored: false,
+            settings: Arc::new(RwLock::new(AppSettings::default())),
+            settings_path: None,
+            dropped_events: 0,
+            initial_file: None,
+            repaint: RepaintThrottle::default(),
+            debounce: RecomputeDebounce::default(),
+            selected_address: None,
+            file_operation: Arc::new(FileOperation::default()),
+            hr_monitor: HrAlertMonitor::default(),
+            sensor_lost: false,
+            auto_start: None,
+            replay: None,
+            cache_warmup: None,
         }
     }
 
+    /// Replaces the initial settings.
+    ///
+    /// # Arguments
+    /// - `settings`: The settings shared with the views.
+    /// - `path`: File the settings are saved to on change, `None` disables saving.
+    ///
+    /// # Returns
+    /// The `AppController` using `settings`.
+    pub fn with_settings(mut self, settings: AppSettings, path: Option<PathBuf>) -> Self {
+        self.settings = Arc::new(RwLock::new(settings));
+        self.settings_path = path;
+        self
+    }
+
+    /// Sets a file to load into the storage at startup.
+    ///
+    /// # Arguments
+    /// - `path`: The file to load, `None` starts with an empty storage.
+    ///
+    /// # Returns
+    /// The `AppController` loading `path`.
+    pub fn with_initial_file(mut self, path: Option<PathBuf>) -> Self {
+        self.initial_file = path;
+        self
+    }
+
+    /// Shares the file operations of the storage with the views.
+    ///
+    /// # Arguments
+    /// - `operation`: The `FileOperation` the storage reports to.
+    ///
+    /// # Returns
+    /// The `AppController` showing `operation`.
+    pub fn with_file_operation(mut self, operation: Arc<FileOperation>) -> Self {
+        self.file_operation = operation;
+        self
+    }
+
     /// Returns the view manager.
     ///
     /// # Returns
     /// A `ViewManager` instance.
     pub fn get_viewmanager(&self) -> ViewManager {
-        ViewManager::new(self.view_tx.subscribe(), self.event_bus.clone())
+        ViewManager::new(
+            self.view_tx.subscribe(),
+            self.notification_tx.subscribe(),
+            self.event_bus.clone(),
+            self.settings.clone(),
+            self.file_operation.clone(),
+        )
     }
 
     async fn handle_state_events(&mut self, event: StateChangeEvent) -> Result<()> {
+        // leaving or changing the recording ends a running countdown or replay
+        if !matches!(event, StateChangeEvent::Rescan) {
+            self.cancel_auto_start();
+            self.stop_replay();
+        }
         match event {
             StateChangeEvent::InitialState => {
                 self.view_tx.send(ViewState::Overview((
@@ -81,6 +306,7 @@ impl<
             }
             StateChangeEvent::DiscardRecording => {
                 self.active_measurement = None;
+                self.acq_controller.write().await.discard_recording();
                 self.view_tx.send(ViewState::Overview((
                     {
                         let mh: Arc<RwLock<dyn StorageModelApi>> = self.acq_controller.clone();
@@ -90,50 +316,763 @@ impl<
                 )))?;
             }
             StateChangeEvent::StoreRecording => {
-                if let Some(measurement) = self.active_measurement.as_ref() {
-                    let mut lck = self.acq_controller.write().await;
-                    lck.store_measurement(measurement.clone())?;
+                self.store_active().await?;
+                if self.active_measurement.is_some() {
+                    let snapshot = self.publish_snapshot().await;
                     self.view_tx.send(ViewState::Overview((
                         ModelHandle::from(self.acq_controller.clone()),
-                        Some(measurement.clone()),
+                        Some(snapshot),
                     )))?;
                 }
             }
+            StateChangeEvent::StoreAndContinue => {
+                if let Some(measurement) = self.active_measurement.clone() {
+                    // only the measurement stops, the sensor keeps streaming into the next one
+                    measurement.write().await.stop_recording().await?;
+                    let auto_save_dir = self.settings.read().await.auto_save_dir.clone();
+                    if let Some(dir) = auto_save_dir {
+                        self.auto_save(dir).await?;
+                    }
+                    self.store_active().await?;
+                }
+                let m = self.to_recording_state().await?;
+                m.write().await.start_recording().await?;
+            }
             StateChangeEvent::ToRecordingState => {
-                // move to recording view
-                let m: Arc<RwLock<MT>> = Arc::new(RwLock::new(MT::default()));
-                self.active_measurement = Some(m.clone());
-                let bm: ModelHandle<dyn BluetoothModelApi> = self.ble_controller.clone();
-                self.view_tx.send(ViewState::Acquisition((m, bm)))?;
+                self.to_recording_state().await?;
             }
             StateChangeEvent::SelectMeasurement(idx) => {
-                let acq = self.acq_controller.read().await.get_measurement(idx)?;
-                self.active_measurement = Some(acq.clone());
+                let acq = self.acq_controller.write().await.select_measurement(idx)?;
+                self.active_measurement = Some(acq);
+                self.active_stored = true;
+                let snapshot = self.publish_snapshot().await;
                 self.view_tx.send(ViewState::Overview((
                     ModelHandle::from(self.acq_controller.clone()),
-                    Some(acq.clone()),
+                    Some(snapshot),
                 )))?;
             }
+            StateChangeEvent::CompareMeasurement(idx) => {
+                self.acq_controller.write().await.set_compare(idx)?;
+            }
+            StateChangeEvent::Shutdown => {
+                self.shutdown().await?;
+            }
+            StateChangeEvent::CancelAutoStart => {
+                info!("automatic start of the recording cancelled");
+            }
+            StateChangeEvent::Rescan => {
+                self.rescan().await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Loads the file given at startup into the storage.
+    ///
+    /// If the file can not be loaded, the views are notified and the storage stays empty.
+    async fn load_initial_file(&mut self) {
+        let Some(path) = self.initial_file.take() else {
+            return;
+        };
+        let event = AppEvent::Storage(StorageEvent::LoadFromFile(path.clone()));
+        if let Err(e) = self.dispatch_event(event).await {
+            let msg = format!("could not open {}: {}", path.display(), e);
+            error!("{}", msg);
+            if self
+                .notification_tx
+                .send(Notification::Error((e.kind(), msg)))
+                .is_err()
+            {
+                warn!("no view to notify");
+            }
+        }
+    }
+
+    /// Shows an error of an event to the user.
+    ///
+    /// Analysis errors of received messages are only logged, they can repeat with every
+    /// message.
+    fn notify_error(&self, error: &AppError) {
+        if matches!(error, AppError::Analysis(_)) {
+            return;
+        }
+        let msg = match error.hint() {
+            Some(hint) => format!("{}, {}", error, hint),
+            None => error.to_string(),
+        };
+        if self
+            .notification_tx
+            .send(Notification::Error((error.kind(), msg)))
+            .is_err()
+        {
+            warn!("no view to notify");
+        }
+    }
+
+    /// Restarts the scan of the selected adapter, refreshing the list of devices.
+    async fn rescan(&mut self) -> Result<()> {
+        let mut ble = self.ble_controller.write().await;
+        let adapter = ble
+            .get_selected_adapter()
+            .ok_or(anyhow::anyhow!("no adapter selected"))?;
+        ble.select_adapter(adapter).await
+    }
+
+    /// Stops the active recording, stops listening and disconnects the sensor.
+    ///
+    /// An unsaved recording is saved to the auto-save directory, if one is set. Stored and
+    /// discarded recordings are not saved again.
+    async fn shutdown(&mut self) -> Result<()> {
+        info!("shutting down");
+        let unsaved = self
+            .active_measurement
+            .clone()
+            .filter(|_| !self.active_stored);
+        if let Some(measurement) = unsaved {
+            measurement.write().await.stop_recording().await?;
+            let auto_save_dir = self.settings.read().await.auto_save_dir.clone();
+            if let Some(dir) = auto_save_dir {
+                self.auto_save(dir).await?;
+            }
+        }
+        let mut ble = self.ble_controller.write().await;
+        ble.stop_listening().await?;
+        ble.disconnect().await
+    }
+
+    /// Sends a warning to the views.
+    fn notify_warning(&self, msg: &str) {
+        warn!("{}", msg);
+        if self
+            .notification_tx
+            .send(Notification::Warning(msg.to_owned()))
+            .is_err()
+        {
+            warn!("no view to notify");
+        }
+    }
+
+    /// Notifies the views that the connection to the sensor was lost during a recording.
+    ///
+    /// The sensor is reconnected, the warning is only sent once until it sends data again.
+    fn handle_disconnect(&mut self) {
+        if !self.sensor_lost {
+            self.sensor_lost = true;
+            self.notify_warning("connection to the sensor lost, reconnecting");
+        }
+    }
+
+    /// Notifies the views that the lost sensor did not reconnect.
+    ///
+    /// If enabled in the settings, the recording is stopped and stored.
+    async fn handle_reconnect_failed(&mut self) -> Result<()> {
+        self.sensor_lost = false;
+        let (auto_stop, auto_save_dir) = {
+            let settings = self.settings.read().await;
+            (
+                settings.auto_stop_on_disconnect,
+                settings.auto_save_dir.clone(),
+            )
+        };
+        if !auto_stop {
+            self.notify_warning("the sensor did not reconnect, stop the recording to keep it");
+            return Ok(());
+        }
+        self.notify_warning("the sensor did not reconnect, the recording was stopped and stored");
+        if let Some(measurement) = self.active_measurement.clone() {
+            measurement.write().await.stop_recording().await?;
+        }
+        self.ble_controller.write().await.stop_recording().await?;
+        if let Some(dir) = auto_save_dir {
+            self.auto_save(dir).await?;
+        }
+        self.handle_state_events(StateChangeEvent::StoreRecording)
+            .await
+    }
+
+    /// Notifies the views if the heart rate of the active recording stays out of bounds.
+    async fn check_hr_alert(&mut self) {
+        let Some(measurement) = self.active_measurement.as_ref() else {
+            return;
+        };
+        let (elapsed, hr) = {
+            let lck = measurement.read().await;
+            (lck.get_elapsed_time(), lck.get_hr())
+        };
+        let Some(hr) = hr else {
+            return;
+        };
+        let alert = self.settings.read().await.hr_alert.clone();
+        if let Some(msg) = self.hr_monitor.update(&alert, elapsed, hr) {
+            warn!("{}", msg);
+            if self.notification_tx.send(Notification::Alert(msg)).is_err() {
+                warn!("no view to notify");
+            }
+        }
+    }
+
+    /// Checks if the active measurement is a new recording that did not start yet.
+    async fn awaits_recording(&self) -> bool {
+        match self.active_measurement.as_ref() {
+            Some(measurement) if !self.active_stored => !measurement.read().await.is_recording(),
+            _ => false,
+        }
+    }
+
+    /// Connects the selected peripheral without recording, if the recording starts
+    /// automatically.
+    ///
+    /// The countdown starts once the sensor reports the connection.
+    async fn connect_for_auto_start(&mut self) -> Result<()> {
+        if self.settings.read().await.auto_start.is_none() || !self.awaits_recording().await {
+            return Ok(());
+        }
+        self.ble_controller.write().await.start_listening().await
+    }
+
+    /// Starts the countdown of the recording once the sensor connected, if enabled.
+    ///
+    /// Only a new recording that is not running yet is started, a sensor reconnecting during
+    /// a recording is ignored.
+    async fn schedule_auto_start(&mut self) {
+        let Some(countdown) = self.settings.read().await.auto_start else {
+            return;
+        };
+        if self.auto_start.is_some() || !self.awaits_recording().await {
+            return;
+        }
+        let countdown = Duration::from_secs_f64(countdown);
+        info!("starting the recording in {:?}", countdown);
+        let event_bus = self.event_bus.clone();
+        self.auto_start = Some(tokio::spawn(async move {
+            tokio::time::sleep(countdown).await;
+            if event_bus
+                .send(AppEvent::Recording(RecordingEvent::StartRecording))
+                .is_err()
+            {
+                warn!("could not start the recording, the event bus was closed");
+            }
+        }));
+        let start = Notification::Countdown(Some(Instant::now() + countdown));
+        if self.notification_tx.send(start).is_err() {
+            warn!("no view to notify");
+        }
+    }
+
+    /// Stops the countdown of the recording, if one is running.
+    fn cancel_auto_start(&mut self) {
+        let Some(task) = self.auto_start.take() else {
+            return;
+        };
+        task.abort();
+        if self
+            .notification_tx
+            .send(Notification::Countdown(None))
+            .is_err()
+        {
+            warn!("no view to notify");
+        }
+    }
+
+    /// Replays a stored measurement as new recording.
+    ///
+    /// The messages are sent as `RecordMessage` events with their recorded pauses scaled by
+    /// the speed, so the replay is analysed and rendered like a recording of the sensor.
+    ///
+    /// # Arguments
+    /// - `replay`: Index of the stored measurement and the replay speed.
+    async fn start_replay(&mut self, replay: (usize, f64)) -> Result<()> {
+        let (index, speed) = replay;
+        let messages = {
+            let measurement = self.acq_controller.read().await.get_measurement(index)?;
+            let lck = measurement.read().await;
+            replay::schedule(lck.get_messages(), speed)?
+        };
+        self.handle_state_events(StateChangeEvent::ToRecordingState)
+            .await?;
+        if let Some(measurement) = self.active_measurement.as_ref() {
+            let mut lck = measurement.write().await;
+            // the recording keeps the timestamps of the replayed measurement
+            lck.set_replay_speed(speed);
+            lck.start_recording().await?;
+        }
+        info!("replaying {} messages at {}x speed", messages.len(), speed);
+        let event_bus = self.event_bus.clone();
+        self.replay = Some(tokio::spawn(async move {
+            for (pause, msg) in messages {
+                tokio::time::sleep(pause).await;
+                let event = AppEvent::Measurement(MeasurementEvent::RecordMessage(msg));
+                if event_bus.send(event).is_err() {
+                    warn!("could not replay the measurement, the event bus was closed");
+                    return;
+                }
+            }
+            info!("replay finished");
+        }));
+        Ok(())
+    }
+
+    /// Stops the running replay, if any.
+    fn stop_replay(&mut self) {
+        if let Some(task) = self.replay.take() {
+            task.abort();
+        }
+    }
+
+    /// Computes the missing results of the stored measurements on blocking tasks.
+    ///
+    /// With lazy analysis the stored measurements are not analysed while loading, the
+    /// overview only shows the cached results so it never analyses them on the UI thread.
+    /// A new warmup is only started once the previous one finished.
+    ///
+    /// # Arguments
+    /// - `gui_ctx`: The GUI context repainted after each computed measurement.
+    async fn warm_session_caches(&mut self, gui_ctx: &egui::Context) {
+        if self
+            .cache_warmup
+            .as_ref()
+            .is_some_and(|task| !task.is_finished())
+        {
+            return;
+        }
+        let stale = self.acq_controller.read().await.stale_caches();
+        if stale.is_empty() {
+            self.cache_warmup = None;
+            return;
+        }
+        trace!("computing the results of {} measurements", stale.len());
+        let storage = self.acq_controller.clone();
+        let gui_ctx = gui_ctx.clone();
+        self.cache_warmup = Some(tokio::spawn(async move {
+            for measurement in stale {
+                let model = measurement.clone();
+                let cache = match tokio::task::spawn_blocking(move || {
+                    SessionCache::from_model(&*model.blocking_read())
+                })
+                .await
+                {
+                    Ok(cache) => cache,
+                    Err(e) => {
+                        warn!("could not compute the results of a measurement: {}", e);
+                        continue;
+                    }
+                };
+                storage.write().await.set_session_cache(&measurement, cache);
+                gui_ctx.request_repaint();
+            }
+        }));
+    }
+
+    /// Moves the active recording into the storage, unless it is already stored.
+    async fn store_active(&mut self) -> Result<()> {
+        if self.active_measurement.is_some() && !self.active_stored {
+            self.acq_controller
+                .write()
+                .await
+                .store_recorded_measurement()?;
+            self.active_stored = true;
+        }
+        Ok(())
+    }
+
+    /// Creates a new active measurement and moves to the recording view.
+    ///
+    /// # Returns
+    /// The new measurement.
+    // named after the `ToRecordingState` event it handles
+    #[allow(clippy::wrong_self_convention)]
+    async fn to_recording_state(&mut self) -> Result<Arc<RwLock<MT>>> {
+        let m = self.acq_controller.write().await.new_measurement()?;
+        self.active_measurement = Some(m.clone());
+        self.active_stored = false;
+        self.hr_monitor = HrAlertMonitor::default();
+        self.sensor_lost = false;
+        let auto_markers = self.settings.read().await.auto_markers;
+        m.write().await.set_auto_markers(auto_markers);
+        self.apply_default_template(&m).await?;
+        self.apply_profile().await?;
+        let bm: ModelHandle<dyn BluetoothModelApi> = self.ble_controller.clone();
+        let snapshot = self.publish_snapshot().await;
+        self.view_tx.send(ViewState::Acquisition((snapshot, bm)))?;
+        Ok(m)
+    }
+
+    /// Applies the default analysis template, if one is set, to a new measurement.
+    ///
+    /// A profile of the selected peripheral is applied afterwards and takes precedence.
+    async fn apply_default_template(&self, measurement: &Arc<RwLock<MT>>) -> Result<()> {
+        let config = self.settings.read().await.default_config().cloned();
+        match config {
+            Some(config) => measurement.write().await.set_hrv_config(config).await,
+            None => Ok(()),
+        }
+    }
+
+    /// Applies the analysis profile of the selected peripheral to the active recording.
+    ///
+    /// Stored measurements keep their parameters.
+    async fn apply_profile(&mut self) -> Result<()> {
+        let (Some(address), Some(measurement)) = (
+            self.selected_address.as_ref(),
+            self.active_measurement.as_ref(),
+        ) else {
+            return Ok(());
+        };
+        if self.active_stored {
+            return Ok(());
+        }
+        let config = match self.settings.read().await.get_profile(address) {
+            Some(profile) => profile.config.clone(),
+            None => return Ok(()),
+        };
+        measurement.write().await.set_hrv_config(config).await
+    }
+
+    /// Publishes a snapshot of the active measurement.
+    ///
+    /// The measurement is only copied if it changed since the last snapshot, otherwise the
+    /// published snapshot is shared.
+    ///
+    /// # Returns
+    /// A receiver of the published snapshots.
+    async fn publish_snapshot(&mut self) -> ModelSnapshot {
+        if let Some(measurement) = self.active_measurement.as_ref() {
+            let lck = measurement.read().await;
+            let revision = lck.get_revision();
+            let unchanged = self
+                .snapshot_source
+                .as_ref()
+                .is_some_and(|(source, published)| {
+                    Arc::ptr_eq(source, measurement) && *published == revision
+                });
+            if !unchanged {
+                let snapshot: Arc<dyn MeasurementModelApi> = Arc::new(lck.clone());
+                self.snapshot_tx.send_replace(snapshot);
+                self.snapshot_source = Some((measurement.clone(), revision));
+            }
+        }
+        self.snapshot_tx.subscribe()
+    }
+
+    async fn handle_settings_events(&mut self, event: SettingsEvent) -> Result<()> {
+        let mut settings = self.settings.write().await;
+        match event {
+            SettingsEvent::SetAutoSaveDir(dir) => {
+                settings.auto_save_dir = dir;
+            }
+            SettingsEvent::SetDateFormat(format) => {
+                settings.display.set_date_format(format)?;
+            }
+            SettingsEvent::SetPrecision(precision) => {
+                settings.display.set_precision(precision)?;
+            }
+            SettingsEvent::SetUiScale(scale) => {
+                if !UI_SCALE_RANGE.contains(&scale) {
+                    return Err(anyhow::anyhow!("invalid UI scale: {}", scale));
+                }
+                settings.ui_scale = scale;
+            }
+            SettingsEvent::SetSmoothing(smoothing) => {
+                if !SMOOTHING_RANGE.contains(&smoothing) {
+                    return Err(anyhow::anyhow!("invalid smoothing: {}", smoothing));
+                }
+                settings.smoothing = smoothing;
+            }
+            SettingsEvent::SetPoincareStyle(style) => {
+                settings.poincare = style;
+            }
+            SettingsEvent::SetPalette(palette) => {
+                settings.palette = palette;
+            }
+            SettingsEvent::SetRepaintInterval(interval) => {
+                if !REPAINT_INTERVAL_RANGE.contains(&interval) {
+                    return Err(anyhow::anyhow!("invalid repaint interval: {}", interval));
+                }
+                settings.repaint_interval = interval;
+                self.repaint.interval = Duration::from_millis(interval);
+            }
+            SettingsEvent::SetRecomputeDebounce(debounce) => {
+                if !RECOMPUTE_DEBOUNCE_RANGE.contains(&debounce) {
+                    return Err(anyhow::anyhow!("invalid recalculation delay: {}", debounce));
+                }
+                settings.recompute_debounce = debounce;
+                self.debounce.interval = Duration::from_millis(debounce);
+            }
+            SettingsEvent::SaveProfile(profile) => {
+                profile.config.validate()?;
+                settings.save_profile(profile);
+            }
+            SettingsEvent::DeleteProfile(address) => {
+                settings.profiles.retain(|p| p.address != address);
+            }
+            SettingsEvent::SaveTemplate(template) => {
+                if template.name.trim().is_empty() {
+                    return Err(anyhow::anyhow!("analysis template without name"));
+                }
+                template.config.validate()?;
+                settings.save_template(template);
+            }
+            SettingsEvent::DeleteTemplate(name) => {
+                settings.delete_template(&name);
+            }
+            SettingsEvent::SetDefaultTemplate(name) => {
+                if let Some(name) = &name {
+                    if settings.get_template(name).is_none() {
+                        return Err(anyhow::anyhow!("unknown analysis template: {}", name));
+                    }
+                }
+                settings.default_template = name;
+            }
+            SettingsEvent::SetReadinessBaseline(baseline) => {
+                if let Some(baseline) = &baseline {
+                    baseline.validate()?;
+                }
+                settings.readiness = baseline;
+            }
+            SettingsEvent::SetHrZones(zones) => {
+                zones.validate()?;
+                settings.hr_zones = zones;
+            }
+            SettingsEvent::SetAutoStopOnDisconnect(auto_stop) => {
+                settings.auto_stop_on_disconnect = auto_stop;
+            }
+            SettingsEvent::SetCompressFiles(compress) => {
+                settings.compress_files = compress;
+            }
+            SettingsEvent::SetAutoStart(countdown) => {
+                if let Some(countdown) = countdown {
+                    if !AUTO_START_RANGE.contains(&countdown) {
+                        return Err(anyhow::anyhow!(
+                            "invalid auto-start countdown: {}",
+                            countdown
+                        ));
+                    }
+                }
+                settings.auto_start = countdown;
+            }
+            SettingsEvent::SetAutoMarkers(interval) => {
+                if let Some(interval) = interval {
+                    interval.validate()?;
+                }
+                settings.auto_markers = interval;
+            }
+            SettingsEvent::SetMinSaveBeats(beats) => {
+                if !MIN_SAVE_BEATS_RANGE.contains(&beats) {
+                    return Err(anyhow::anyhow!("invalid minimum beats to save: {}", beats));
+                }
+                settings.min_save_beats = beats;
+            }
+            SettingsEvent::SetHrAlert(alert) => {
+                alert.validate()?;
+                settings.hr_alert = alert;
+            }
+            SettingsEvent::SetLazyAnalysis(lazy) => {
+                settings.lazy_analysis = lazy;
+                self.acq_controller.write().await.set_lazy_analysis(lazy);
+            }
+            SettingsEvent::SetAnalysisThreads(threads) => {
+                set_analysis_threads(threads)?;
+                settings.analysis_threads = threads;
+            }
+            SettingsEvent::SetRetention(retention) => {
+                retention.validate()?;
+                settings.retention = retention;
+            }
+            SettingsEvent::SetRrUnit(unit) => {
+                settings.rr_unit = unit;
+            }
+            SettingsEvent::SetStabilizationThreshold(threshold) => {
+                if !STABILIZATION_THRESHOLD_RANGE.contains(&threshold) {
+                    return Err(anyhow::anyhow!(
+                        "invalid stabilization threshold: {}",
+                        threshold
+                    ));
+                }
+                settings.stabilization_threshold = threshold;
+            }
+            SettingsEvent::SetDfaSmoothing(windows) => {
+                if !DFA_SMOOTHING_RANGE.contains(&windows) {
+                    return Err(anyhow::anyhow!("invalid DFA smoothing: {}", windows));
+                }
+                settings.dfa_smoothing = windows;
+            }
+            SettingsEvent::SetHiddenMetrics(names) => {
+                settings.hidden_metrics = names;
+            }
+            SettingsEvent::SetAdapterAlias((key, alias)) => {
+                settings.set_adapter_alias(key, alias)?;
+            }
+            SettingsEvent::SetScanInterval(interval) => {
+                if !SCAN_INTERVAL_RANGE.contains(&interval) {
+                    return Err(anyhow::anyhow!("invalid scan interval: {}", interval));
+                }
+                settings.scan_interval = interval;
+                self.ble_controller
+                    .write()
+                    .await
+                    .set_scan_interval(std::time::Duration::from_secs_f64(interval));
+            }
+            SettingsEvent::SetReconnectAttempts(attempts) => {
+                if !RECONNECT_ATTEMPTS_RANGE.contains(&attempts) {
+                    return Err(anyhow::anyhow!("invalid reconnect attempts: {}", attempts));
+                }
+                settings.reconnect_attempts = attempts;
+                self.ble_controller
+                    .write()
+                    .await
+                    .set_reconnect_attempts(attempts);
+            }
+            SettingsEvent::SetScanHeartRateOnly(heart_rate_only) => {
+                settings.scan_heart_rate_only = heart_rate_only;
+                self.ble_controller
+                    .write()
+                    .await
+                    .set_scan_filter(scan_filter(heart_rate_only));
+            }
+        }
+        if let Some(path) = self.settings_path.as_ref() {
+            settings.save(path)?;
+        }
+        Ok(())
+    }
+
+    /// Stores the active measurement and saves it to the auto-save directory.
+    ///
+    /// Only the measurement is saved, the file name is derived from its start time.
+    async fn auto_save(&mut self, dir: PathBuf) -> Result<()> {
+        let Some(measurement) = self.active_measurement.clone() else {
+            return Ok(());
+        };
+        if self.active_stored {
+            return Ok(());
         }
+        let fd = format_description!("[year][month][day]_[hour][minute][second]");
+        let extension = self.settings.read().await.storage_extension();
+        let name = format!(
+            "{}{}.{}",
+            AUTO_SAVE_PREFIX,
+            measurement.read().await.get_start_time().format(fd)?,
+            extension
+        );
+        self.store_active().await?;
+        let path = dir.join(name);
+        let mut acq = self.acq_controller.write().await;
+        // the stored recording is selected
+        let index = acq
+            .get_selected_index()
+            .ok_or(anyhow::anyhow!("the recording was not stored"))?;
+        acq.store_measurement_to_file((index, path.clone())).await?;
+        info!("auto-saved recording to {}", path.display());
         Ok(())
     }
 
+    /// Removes the auto-saved recordings exceeding the retention of the settings.
+    async fn prune_auto_saves(&self) {
+        let (dir, retention) = {
+            let settings = self.settings.read().await;
+            (settings.auto_save_dir.clone(), settings.retention.clone())
+        };
+        let Some(dir) = dir else {
+            return;
+        };
+        match retention.prune(&dir, std::time::SystemTime::now()) {
+            Ok(removed) if !removed.is_empty() => {
+                info!(
+                    "removed {} old auto-saved recordings from {}",
+                    removed.len(),
+                    dir.display()
+                );
+            }
+            Ok(_) => {}
+            Err(e) => warn!("could not clean up {}: {}", dir.display(), e),
+        }
+    }
+
     /// Dispatches application-level events to the appropriate controllers.
-    async fn dispatch_event(&mut self, event: AppEvent) -> Result<()> {
+    ///
+    /// # Returns
+    /// The error of the event, classified by its cause and the component handling it.
+    async fn dispatch_event(&mut self, event: AppEvent) -> Result<(), AppError> {
+        let origin: fn(anyhow::Error) -> AppError = match &event {
+            AppEvent::Bluetooth(_)
+            | AppEvent::Recording(_)
+            | AppEvent::AppState(StateChangeEvent::Rescan) => AppError::Bluetooth,
+            // the analysis of a received message can fail with every message
+            AppEvent::Measurement(MeasurementEvent::RecordMessage(_)) => AppError::Analysis,
+            // reported by the connection to the sensor
+            AppEvent::Measurement(
+                MeasurementEvent::MarkDisconnected | MeasurementEvent::MarkReconnected,
+            ) => AppError::Bluetooth,
+            // parameters, markers and metadata entered by the user
+            AppEvent::Measurement(_) | AppEvent::Settings(_) => AppError::Input,
+            // state changes select and store measurements
+            AppEvent::Storage(_) | AppEvent::AppState(_) => AppError::Storage,
+        };
+        self.handle_event(event)
+            .await
+            .map_err(|e| AppError::classify(e, origin))
+    }
+
+    /// Forwards an event to the controllers handling it.
+    async fn handle_event(&mut self, event: AppEvent) -> Result<()> {
         match event {
             AppEvent::Bluetooth(event) => {
-                let mut lck = self.ble_controller.write().await;
-                event.forward_to(&mut *lck).await
+                let selected = match &event {
+                    BluetoothEvent::SelectPeripheral(device) => {
+                        self.selected_address = Some(device.address.to_string());
+                        true
+                    }
+                    _ => false,
+                };
+                let state = match &event {
+                    BluetoothEvent::ConnectionStateChanged(state) => Some(*state),
+                    _ => None,
+                };
+                {
+                    let mut lck = self.ble_controller.write().await;
+                    event.forward_to(&mut *lck).await?;
+                }
+                match state {
+                    Some(ConnectionState::Connected) => self.schedule_auto_start().await,
+                    Some(ConnectionState::Idle) if self.sensor_lost => {
+                        self.cancel_auto_start();
+                        self.handle_reconnect_failed().await?;
+                    }
+                    Some(_) => self.cancel_auto_start(),
+                    None => {}
+                }
+                if selected {
+                    self.connect_for_auto_start().await?;
+                    self.apply_profile().await
+                } else {
+                    Ok(())
+                }
             }
             AppEvent::Measurement(event) => {
+                // only the loss of the sensor during a recording is reported
+                let recording = self.active_measurement.is_some() && !self.active_stored;
+                let disconnected = matches!(event, MeasurementEvent::MarkDisconnected) && recording;
+                let received = matches!(event, MeasurementEvent::RecordMessage(_)) && recording;
                 if let Some(measurement) = self.active_measurement.as_ref() {
                     let mut lck = measurement.write().await;
-                    event.forward_to(&mut *lck).await
-                } else {
-                    Ok(())
+                    event.forward_to(&mut *lck).await?;
+                }
+                if received {
+                    self.sensor_lost = false;
+                    self.check_hr_alert().await;
+                }
+                if disconnected {
+                    self.handle_disconnect();
                 }
+                Ok(())
             }
             AppEvent::Recording(event) => {
+                // started or stopped manually or by the countdown itself
+                self.cancel_auto_start();
+                if matches!(event, RecordingEvent::StopRecording) {
+                    self.stop_replay();
+                    self.sensor_lost = false;
+                }
                 if let Some(measurement) = self.active_measurement.as_ref() {
                     let mut lck = measurement.write().await;
                     event.clone().forward_to(&mut *lck).await?
@@ -141,20 +1080,92 @@ impl<
 
                 {
                     let mut ble_lock = self.ble_controller.write().await;
-                    event.forward_to(&mut *ble_lock).await
+                    event.clone().forward_to(&mut *ble_lock).await?
+                }
+                let auto_save_dir = self.settings.read().await.auto_save_dir.clone();
+                match (event, auto_save_dir) {
+                    (RecordingEvent::StopRecording, Some(dir)) => {
+                        self.auto_save(dir).await?;
+                        self.handle_state_events(StateChangeEvent::StoreRecording)
+                            .await
+                    }
+                    _ => Ok(()),
                 }
             }
             AppEvent::Storage(event) => {
-                let mut lck = self.acq_controller.write().await;
-                event.forward_to(&mut *lck).await
+                let loaded = matches!(
+                    event,
+                    StorageEvent::LoadFromFile(_)
+                        | StorageEvent::ImportRr(_)
+                        | StorageEvent::ImportFit(_)
+                        | StorageEvent::LoadDemo
+                        | StorageEvent::MergeMeasurements(_)
+                        | StorageEvent::SplitMeasurement(_)
+                );
+                let imported = matches!(
+                    event,
+                    StorageEvent::ImportRr(_) | StorageEvent::ImportFit(_)
+                );
+                let replay = match &event {
+                    StorageEvent::ReplayMeasurement(replay) => Some(*replay),
+                    _ => None,
+                };
+                let selected = {
+                    let mut lck = self.acq_controller.write().await;
+                    event.forward_to(&mut *lck).await?;
+                    // show the selection stored in the file or the added measurement
+                    loaded.then(|| lck.get_selected_index()).flatten()
+                };
+                if let Some(replay) = replay {
+                    return self.start_replay(replay).await;
+                }
+                if let (true, Some(idx)) = (imported, selected) {
+                    let measurement = self.acq_controller.read().await.get_measurement(idx)?;
+                    self.apply_default_template(&measurement).await?;
+                }
+                match selected {
+                    Some(idx) => {
+                        self.handle_state_events(StateChangeEvent::SelectMeasurement(idx))
+                            .await
+                    }
+                    None => Ok(()),
+                }
             }
             AppEvent::AppState(event) => self.handle_state_events(event).await,
+            AppEvent::Settings(event) => self.handle_settings_events(event).await,
+        }
+    }
+
+    /// Receives the next event from the event bus.
+    ///
+    /// Events lost because the receiver lagged behind are logged, counted and reported
+    /// to the active measurement.
+    ///
+    /// # Returns
+    /// The next event, or `None` if the event bus was closed.
+    async fn next_event(&mut self, rx: &mut Receiver<AppEvent>) -> Option<AppEvent> {
+        loop {
+            match rx.recv().await {
+                Ok(event) => return Some(event),
+                Err(RecvError::Lagged(count)) => {
+                    self.dropped_events += count;
+                    warn!(
+                        "event handler lagged behind, dropped {} events ({} in total)",
+                        count, self.dropped_events
+                    );
+                    if let Some(measurement) = self.active_measurement.as_ref() {
+                        measurement.write().await.report_dropped_events(count);
+                    }
+                }
+                Err(RecvError::Closed) => return None,
+            }
         }
     }
 
     /// Asynchronous event handler.
     ///
     /// Processes application-level events and delegates them to appropriate controllers.
+    /// Returns after a `StateChangeEvent::Shutdown` was handled.
     ///
     /// # Arguments
     /// - `gui_ctx`: The GUI context.
@@ -170,6 +1181,43 @@ impl<
             );
             tokio::time::sleep(tokio::time::Duration::from_secs(5)).await;
         }
+        let (
+            scan_interval,
+            reconnect_attempts,
+            scan_heart_rate_only,
+            repaint_interval,
+            recompute_debounce,
+            lazy_analysis,
+            analysis_threads,
+        ) = {
+            let settings = self.settings.read().await;
+            (
+                settings.scan_interval,
+                settings.reconnect_attempts,
+                settings.scan_heart_rate_only,
+                settings.repaint_interval,
+                settings.recompute_debounce,
+                settings.lazy_analysis,
+                settings.analysis_threads,
+            )
+        };
+        self.acq_controller
+            .write()
+            .await
+            .set_lazy_analysis(lazy_analysis);
+        if let Err(e) = set_analysis_threads(analysis_threads) {
+            warn!("could not limit the analysis threads: {}", e);
+        }
+        self.prune_auto_saves().await;
+        self.load_initial_file().await;
+        self.repaint.interval = Duration::from_millis(repaint_interval);
+        self.debounce.interval = Duration::from_millis(recompute_debounce);
+        {
+            let mut ble_controller = self.ble_controller.write().await;
+            ble_controller.set_scan_interval(std::time::Duration::from_secs_f64(scan_interval));
+            ble_controller.set_reconnect_attempts(reconnect_attempts);
+            ble_controller.set_scan_filter(scan_filter(scan_heart_rate_only));
+        }
         while let Err(e) = self.ble_controller.write().await.discover_adapters().await {
             error!(
                 "could not discover adapters: {}. trying again in 5 seconds",
@@ -177,16 +1225,42 @@ impl<
             );
             tokio::time::sleep(tokio::time::Duration::from_secs(5)).await;
         }
-        while let Ok(event) = event_ch_rx.recv().await {
-            if let Err(e) = self.dispatch_event(event).await {
-                error!(
-                    "error during UiEvent handling: {}\nbacktrace:\n{}",
-                    e,
-                    e.backtrace()
-                );
-            }
+        loop {
+            self.warm_session_caches(&gui_ctx).await;
+            let next = match self.debounce.deadline {
+                Some(deadline) => tokio::time::timeout_at(
+                    tokio::time::Instant::from_std(deadline),
+                    self.next_event(&mut event_ch_rx),
+                )
+                .await
+                .ok(),
+                None => Some(self.next_event(&mut event_ch_rx).await),
+            };
+            let events = match next {
+                Some(Some(event)) => self.debounce.schedule(event, Instant::now()),
+                Some(None) => break,
+                // the sliders settled
+                None => self.debounce.take(),
+            };
+            for event in events {
+                let shutdown = matches!(event, AppEvent::AppState(StateChangeEvent::Shutdown));
+                // state transitions switch the view and are shown right away
+                let immediate = matches!(event, AppEvent::AppState(_));
+                if let Err(e) = self.dispatch_event(event).await {
+                    error!(
+                        "error during UiEvent handling: {}\nbacktrace:\n{}",
+                        e,
+                        e.inner().backtrace()
+                    );
+                    self.notify_error(&e);
+                }
+                if shutdown {
+                    return;
+                }
+                self.publish_snapshot().await;
 
-            gui_ctx.request_repaint();
+                self.repaint.request(&gui_ctx, immediate);
+            }
         }
     }
 }
@@ -195,14 +1269,26 @@ impl<
 pub mod tests {
     use super::*;
     use crate::api::model::MeasurementModelApi;
+    use crate::components::bluetooth::tests::get_connectable_component;
     use crate::components::measurement::MeasurementData;
+    use crate::core::constants::HEARTRATE_SERVICE_UUID;
+    use crate::core::error::{ErrorKind, InvalidData};
     use crate::core::events::{
         BluetoothEvent, MeasurementEvent, RecordingEvent, StateChangeEvent, StorageEvent,
     };
-    use crate::model::bluetooth::{AdapterDescriptor, DeviceDescriptor, HeartrateMessage};
+    use crate::model::bluetooth::{
+        AdapterDescriptor, ConnectionState, DeviceDescriptor, HeartrateMessage,
+    };
+    use crate::model::{
+        cache::SessionCache,
+        hrv::HrvConfig,
+        import::RrUnit,
+        metrics::DfaConfig,
+        settings::{AnalysisTemplate, DeviceProfile},
+    };
     use anyhow::anyhow;
     use async_trait::async_trait;
-    use btleplug::api::BDAddr;
+    use btleplug::api::{BDAddr, ScanFilter};
     use mockall::mock;
     use mockall::predicate::{always, eq};
     use std::path::PathBuf;
@@ -217,6 +1303,8 @@ pub mod tests {
         impl RecordingApi for Bluetooth{
             async fn start_recording(&mut self) -> Result<()>;
             async fn stop_recording(&mut self) -> Result<()>;
+            async fn pause_recording(&mut self) -> Result<()>;
+            async fn resume_recording(&mut self) -> Result<()>;
         }
         impl BluetoothModelApi for Bluetooth{
             fn get_adapters(&self) -> &[AdapterDescriptor];
@@ -225,6 +1313,7 @@ pub mod tests {
             fn get_selected_device(&self) -> Option<DeviceDescriptor>;
             fn is_scanning(&self) -> bool;
             fn is_listening_to(&self) -> Option<BDAddr>;
+            fn get_connection_state(&self) -> ConnectionState;
         }
 
         #[async_trait]
@@ -236,6 +1325,11 @@ pub mod tests {
             async fn stop_scan(&mut self) -> Result<()>;
             async fn start_listening(&mut self) -> Result<()>;
             async fn stop_listening(&mut self) -> Result<()>;
+            fn set_scan_interval(&mut self, interval: std::time::Duration);
+            fn set_reconnect_attempts(&mut self, attempts: usize);
+            fn set_scan_filter(&mut self, filter: ScanFilter);
+            async fn disconnect(&mut self) -> Result<()>;
+            async fn connection_state_changed(&mut self, state: ConnectionState) -> Result<()>;
         }
     }
 
@@ -246,24 +1340,52 @@ pub mod tests {
         }
         impl StorageModelApi for Storage{
             fn get_acquisitions(&self) -> &[ModelHandle<dyn MeasurementModelApi>];
+            fn get_selected_index(&self) -> Option<usize>;
+            fn get_compare_index(&self) -> Option<usize>;
+            fn has_recording(&self) -> bool;
+            fn get_session_cache(&self, index: usize) -> Option<Arc<SessionCache>>;
         }
 
         impl StorageApi<MeasurementData> for Storage{
             fn get_measurement(& self, index:usize) -> Result<Arc<RwLock<MeasurementData>>>;
+            fn select_measurement(&mut self, index:usize) -> Result<Arc<RwLock<MeasurementData>>>;
+            fn set_compare(&mut self, index: Option<usize>) -> Result<()>;
             fn store_measurement(&mut self, measurement: Arc<RwLock<MeasurementData>>) -> Result<()>;
+            fn new_measurement(&mut self) -> Result<Arc<RwLock<MeasurementData>>>;
+            fn store_recorded_measurement(&mut self) -> Result<Arc<RwLock<MeasurementData>>>;
+            fn discard_recording(&mut self) -> Option<Arc<RwLock<MeasurementData>>>;
+            fn set_lazy_analysis(&mut self, lazy: bool);
+            fn stale_caches(&self) -> Vec<Arc<RwLock<MeasurementData>>>;
+            fn set_session_cache(&mut self, measurement: &Arc<RwLock<MeasurementData>>, cache: SessionCache);
         }
 
         #[async_trait]
         impl StorageEventApi for Storage{
             async fn clear(&mut self) -> Result<()>;
             async fn load_from_file(&mut self, path: PathBuf) -> Result<()>;
+            async fn append_from_file(&mut self, path: PathBuf) -> Result<()>;
             async fn store_to_file(&mut self, path: PathBuf) -> Result<()>;
+            async fn store_measurement_to_file(&mut self, file: (usize, PathBuf)) -> Result<()>;
+            async fn export_edf(&mut self, path: PathBuf) -> Result<()>;
+            async fn export_kubios(&mut self, path: PathBuf) -> Result<()>;
+            async fn export_correction_log(&mut self, path: PathBuf) -> Result<()>;
+            async fn export_html(&mut self, path: PathBuf) -> Result<()>;
+            async fn export_all(&mut self, dir: PathBuf) -> Result<()>;
+            async fn import_rr(&mut self, file: (PathBuf, RrUnit)) -> Result<()>;
+            async fn import_fit(&mut self, path: PathBuf) -> Result<()>;
+            async fn load_demo(&mut self) -> Result<()>;
+            async fn merge_measurements(&mut self, indices: (usize, usize)) -> Result<()>;
+            async fn split_measurement(&mut self, split: (usize, time::Duration)) -> Result<()>;
+            async fn move_measurement(&mut self, indices: (usize, usize)) -> Result<()>;
+            async fn replay_measurement(&mut self, replay: (usize, f64)) -> Result<()>;
         }
 
         #[async_trait]
         impl RecordingApi for Storage{
             async fn start_recording(&mut self) -> Result<()>;
             async fn stop_recording(&mut self) -> Result<()>;
+            async fn pause_recording(&mut self) -> Result<()>;
+            async fn resume_recording(&mut self) -> Result<()>;
         }
     }
 
@@ -285,7 +1407,11 @@ pub mod tests {
     async fn test_app_controller_recording_state() {
         let (event_bus_tx, _) = broadcast::channel(16);
         let ble_controller = MockBluetooth::new();
-        let acq_controller = MockStorage::new();
+        let mut acq_controller = MockStorage::new();
+        acq_controller
+            .expect_new_measurement()
+            .once()
+            .returning(|| Ok(Arc::new(RwLock::new(MeasurementData::default()))));
 
         let mut app_controller =
             AppController::new(ble_controller, acq_controller, event_bus_tx.clone());
@@ -306,7 +1432,7 @@ pub mod tests {
         let mock_measurement = Arc::new(RwLock::new(MeasurementData::default()));
 
         acq_controller
-            .expect_get_measurement()
+            .expect_select_measurement()
             .with(eq(0usize))
             .returning(move |_| Ok(mock_measurement.clone()));
 
@@ -340,59 +1466,861 @@ pub mod tests {
     }
 
     #[tokio::test]
-    async fn test_app_controller_measurement_event() {
+    async fn test_app_controller_error_kinds() {
+        let (event_bus_tx, _) = broadcast::channel(16);
+        let mut ble_controller = MockBluetooth::new();
+        ble_controller
+            .expect_select_adapter()
+            .returning(|_| Err(anyhow!("adapter not found")));
+        let mut acq_controller = MockStorage::new();
+        acq_controller
+            .expect_load_from_file()
+            .returning(|_| Err(std::io::Error::from(std::io::ErrorKind::NotFound).into()));
+        acq_controller
+            .expect_import_rr()
+            .returning(|_| Err(InvalidData("no RR intervals found".to_owned()).into()));
+
+        let mut app_controller = AppController::new(ble_controller, acq_controller, event_bus_tx);
+        let mut notification_rx = app_controller.notification_tx.subscribe();
+        let desc = AdapterDescriptor::new("MockAdapter".to_string());
+        let result = app_controller
+            .dispatch_event(AppEvent::Bluetooth(BluetoothEvent::SelectAdapter(desc)))
+            .await;
+        assert!(matches!(result, Err(AppError::Bluetooth(_))));
+        let result = app_controller
+            .dispatch_event(AppEvent::Storage(StorageEvent::LoadFromFile(
+                PathBuf::from("missing.json"),
+            )))
+            .await;
+        assert!(matches!(result, Err(AppError::Io(_))));
+        let result = app_controller
+            .dispatch_event(AppEvent::Storage(StorageEvent::ImportRr((
+                PathBuf::from("empty.txt"),
+                RrUnit::Auto,
+            ))))
+            .await;
+        // malformed files are reported as such, not as failure of the storage
+        assert!(matches!(result, Err(AppError::Parse(_))));
+        let result = app_controller
+            .dispatch_event(AppEvent::Settings(SettingsEvent::SetUiScale(-1.0)))
+            .await;
+        assert!(matches!(result, Err(AppError::Input(_))));
+
+        app_controller.active_measurement = Some(Arc::new(RwLock::new(MeasurementData::default())));
+        let result = app_controller
+            .dispatch_event(AppEvent::Measurement(MeasurementEvent::RecordMessage(
+                HeartrateMessage::from_values(60, None, &[1000]),
+            )))
+            .await;
+        let error = result.unwrap_err();
+        assert!(matches!(error, AppError::Analysis(_)));
+
+        // only errors the user can act on are shown
+        app_controller.notify_error(&error);
+        assert!(notification_rx.try_recv().is_err());
+        for event in [
+            MeasurementEvent::SetPnnThreshold(-1.0),
+            MeasurementEvent::AddMarker("marker".to_owned()),
+        ] {
+            let error = app_controller
+                .dispatch_event(AppEvent::Measurement(event))
+                .await
+                .unwrap_err();
+            assert!(matches!(error, AppError::Input(_)));
+            app_controller.notify_error(&error);
+            assert!(matches!(
+                notification_rx.try_recv(),
+                Ok(Notification::Error((ErrorKind::Input, _)))
+            ));
+        }
+        app_controller.notify_error(&AppError::Bluetooth(anyhow!("adapter not found")));
+        assert!(matches!(
+            notification_rx.try_recv(),
+            Ok(Notification::Error((ErrorKind::Bluetooth, msg))) if msg.contains("select it again")
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_app_controller_rescan() {
+        let (event_bus_tx, _) = broadcast::channel(16);
+        let desc = AdapterDescriptor::new("MockAdapter".to_string());
+        let mut ble_controller = MockBluetooth::new();
+        let selected = desc.clone();
+        ble_controller
+            .expect_get_selected_adapter()
+            .returning(move || Some(selected.clone()));
+        ble_controller
+            .expect_select_adapter()
+            .with(eq(desc))
+            .once()
+            .returning(|_| Ok(()));
+        let mut app_controller =
+            AppController::new(ble_controller, MockStorage::new(), event_bus_tx);
+        assert!(app_controller
+            .dispatch_event(AppEvent::AppState(StateChangeEvent::Rescan))
+            .await
+            .is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_app_controller_measurement_event() {
+        let (event_bus_tx, _) = broadcast::channel(16);
+        let ble_controller = MockBluetooth::new();
+        let acq_controller = MockStorage::new();
+
+        let mock_measurement = Arc::new(RwLock::new(MeasurementData::default()));
+
+        let mut app_controller =
+            AppController::new(ble_controller, acq_controller, event_bus_tx.clone());
+        app_controller.active_measurement = Some(mock_measurement.clone());
+
+        let event = AppEvent::Measurement(MeasurementEvent::SetStatsWindow(60));
+        let result = app_controller.dispatch_event(event).await;
+        assert!(result.is_ok());
+        assert_eq!(
+            mock_measurement.read().await.get_stats_window().unwrap(),
+            60
+        );
+    }
+
+    #[tokio::test]
+    async fn test_app_controller_start_recording_event() {
+        let (event_bus_tx, _) = broadcast::channel(16);
+        let mut ble_controller = MockBluetooth::new();
+        let mut acq_controller = MockStorage::new();
+
+        acq_controller.expect_start_recording().returning(|| Ok(()));
+        ble_controller.expect_start_recording().returning(|| Ok(()));
+
+        let mut app_controller =
+            AppController::new(ble_controller, acq_controller, event_bus_tx.clone());
+
+        let event = AppEvent::Recording(RecordingEvent::StartRecording);
+        let result = app_controller.dispatch_event(event).await;
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_app_controller_stop_recording_event() {
+        let (event_bus_tx, _) = broadcast::channel(16);
+        let mut ble_controller = MockBluetooth::new();
+        let mut acq_controller = MockStorage::new();
+
+        acq_controller.expect_stop_recording().returning(|| Ok(()));
+        ble_controller.expect_stop_recording().returning(|| Ok(()));
+
+        let mut app_controller =
+            AppController::new(ble_controller, acq_controller, event_bus_tx.clone());
+
+        let event = AppEvent::Recording(RecordingEvent::StopRecording);
+        let result = app_controller.dispatch_event(event).await;
+        assert!(result.is_ok());
+    }
+    #[tokio::test]
+    async fn test_app_controller_auto_save_on_stop() {
+        let (event_bus_tx, _) = broadcast::channel(16);
+        let mut ble_controller = MockBluetooth::new();
+        let mut acq_controller = MockStorage::new();
+        let temp_dir = tempdir::TempDir::new("test").unwrap();
+        let dir = temp_dir.path().to_path_buf();
+
+        ble_controller.expect_stop_recording().returning(|| Ok(()));
+        acq_controller
+            .expect_store_recorded_measurement()
+            .once()
+            .returning(|| Ok(Arc::new(RwLock::new(MeasurementData::default()))));
+        acq_controller
+            .expect_get_selected_index()
+            .returning(|| Some(3));
+        let expected_dir = dir.clone();
+        // only the stored recording is saved, not the whole storage
+        acq_controller.expect_store_to_file().never();
+        acq_controller
+            .expect_store_measurement_to_file()
+            .once()
+            .withf(move |(index, path)| {
+                *index == 3
+                    && path.parent() == Some(expected_dir.as_path())
+                    && path.extension().is_some_and(|ext| ext == "json")
+            })
+            .returning(|_| Ok(()));
+
+        let mut app_controller =
+            AppController::new(ble_controller, acq_controller, event_bus_tx.clone());
+        let _vm = app_controller.get_viewmanager();
+        app_controller.active_measurement = Some(Arc::new(RwLock::new(MeasurementData::default())));
+
+        assert!(app_controller
+            .dispatch_event(AppEvent::Settings(SettingsEvent::SetAutoSaveDir(Some(dir))))
+            .await
+            .is_ok());
+        assert!(app_controller
+            .dispatch_event(AppEvent::Recording(RecordingEvent::StopRecording))
+            .await
+            .is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_app_controller_settings_are_saved() {
+        let (event_bus_tx, _) = broadcast::channel(16);
+        let temp_dir = tempdir::TempDir::new("test").unwrap();
+        let path = temp_dir.path().join("settings.json");
+
+        let mut app_controller =
+            AppController::new(MockBluetooth::new(), MockStorage::new(), event_bus_tx)
+                .with_settings(AppSettings::default(), Some(path.clone()));
+        assert!(app_controller
+            .dispatch_event(AppEvent::Settings(SettingsEvent::SetUiScale(2.0)))
+            .await
+            .is_ok());
+        assert!(app_controller
+            .dispatch_event(AppEvent::Settings(SettingsEvent::SetUiScale(-1.0)))
+            .await
+            .is_err());
+        assert_eq!(AppSettings::load(&path).unwrap().ui_scale, 2.0);
+    }
+
+    #[tokio::test]
+    async fn test_app_controller_scan_interval() {
+        let (event_bus_tx, _) = broadcast::channel(16);
+        let mut ble_controller = MockBluetooth::new();
+        ble_controller
+            .expect_set_scan_interval()
+            .once()
+            .with(eq(std::time::Duration::from_secs(3)))
+            .return_const(());
+
+        let mut app_controller =
+            AppController::new(ble_controller, MockStorage::new(), event_bus_tx);
+        assert!(app_controller
+            .dispatch_event(AppEvent::Settings(SettingsEvent::SetScanInterval(3.0)))
+            .await
+            .is_ok());
+        // too short intervals are rejected
+        assert!(app_controller
+            .dispatch_event(AppEvent::Settings(SettingsEvent::SetScanInterval(0.1)))
+            .await
+            .is_err());
+        assert_eq!(app_controller.settings.read().await.scan_interval, 3.0);
+    }
+
+    #[tokio::test]
+    async fn test_app_controller_reconnect_attempts() {
+        let (event_bus_tx, _) = broadcast::channel(16);
+        let mut ble_controller = MockBluetooth::new();
+        ble_controller
+            .expect_set_reconnect_attempts()
+            .once()
+            .with(eq(5))
+            .return_const(());
+
+        let mut app_controller =
+            AppController::new(ble_controller, MockStorage::new(), event_bus_tx);
+        assert!(app_controller
+            .dispatch_event(AppEvent::Settings(SettingsEvent::SetReconnectAttempts(5)))
+            .await
+            .is_ok());
+        // the sensor is reconnected at least once
+        assert!(app_controller
+            .dispatch_event(AppEvent::Settings(SettingsEvent::SetReconnectAttempts(0)))
+            .await
+            .is_err());
+        assert_eq!(app_controller.settings.read().await.reconnect_attempts, 5);
+    }
+
+    #[tokio::test]
+    async fn test_app_controller_scan_filter() {
+        let (event_bus_tx, _) = broadcast::channel(16);
+        let mut ble_controller = MockBluetooth::new();
+        ble_controller
+            .expect_set_scan_filter()
+            .once()
+            .withf(|filter| filter.services == [HEARTRATE_SERVICE_UUID])
+            .return_const(());
+
+        let mut app_controller =
+            AppController::new(ble_controller, MockStorage::new(), event_bus_tx);
+        assert!(app_controller
+            .dispatch_event(AppEvent::Settings(SettingsEvent::SetScanHeartRateOnly(
+                true
+            )))
+            .await
+            .is_ok());
+        assert!(app_controller.settings.read().await.scan_heart_rate_only);
+    }
+
+    #[tokio::test]
+    async fn test_app_controller_reconnect_resumes_recording() {
+        let (event_bus_tx, _) = broadcast::channel(16);
+        let mut app_controller =
+            AppController::new(MockBluetooth::new(), MockStorage::new(), event_bus_tx);
+        let measurement = Arc::new(RwLock::new(MeasurementData::default()));
+        measurement.write().await.start_recording().await.unwrap();
+        app_controller.active_measurement = Some(measurement.clone());
+
+        let msg = HeartrateMessage::from_values(60, None, &[1000]);
+        let events = [
+            MeasurementEvent::RecordMessage(msg),
+            MeasurementEvent::MarkDisconnected,
+            MeasurementEvent::MarkReconnected,
+            MeasurementEvent::RecordMessage(msg),
+        ];
+        for event in events {
+            if matches!(event, MeasurementEvent::MarkReconnected) {
+                tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+            }
+            assert!(app_controller
+                .dispatch_event(AppEvent::Measurement(event))
+                .await
+                .is_ok());
+        }
+        let data = measurement.read().await;
+        assert_eq!(data.get_beat_count(), 2);
+        let gaps = data.get_gaps();
+        assert_eq!(gaps.len(), 1);
+        assert!(gaps[0].1 - gaps[0].0 >= time::Duration::milliseconds(20));
+    }
+
+    #[tokio::test]
+    async fn test_app_controller_auto_stop_on_disconnect() {
+        let (event_bus_tx, _) = broadcast::channel(16);
+        let mut ble_controller = MockBluetooth::new();
+        ble_controller
+            .expect_connection_state_changed()
+            .returning(|_| Ok(()));
+        ble_controller
+            .expect_stop_recording()
+            .once()
+            .returning(|| Ok(()));
+        let mut acq_controller = MockStorage::new();
+        let measurement = Arc::new(RwLock::new(MeasurementData::default()));
+        let recorded_measurement = measurement.clone();
+        acq_controller
+            .expect_store_recorded_measurement()
+            .once()
+            .returning(move || Ok(recorded_measurement.clone()));
+        let settings = AppSettings {
+            auto_stop_on_disconnect: true,
+            ..Default::default()
+        };
+        let mut app_controller = AppController::new(ble_controller, acq_controller, event_bus_tx)
+            .with_settings(settings, None);
+        let mut notifications = app_controller.notification_tx.subscribe();
+        let _view = app_controller.get_viewmanager();
+        measurement.write().await.start_recording().await.unwrap();
+        app_controller.active_measurement = Some(measurement.clone());
+
+        // the recording continues while the sensor reconnects
+        for event in [
+            MeasurementEvent::RecordMessage(HeartrateMessage::from_values(60, None, &[1000])),
+            MeasurementEvent::MarkDisconnected,
+            MeasurementEvent::MarkReconnected,
+            MeasurementEvent::MarkDisconnected,
+        ] {
+            assert!(app_controller
+                .dispatch_event(AppEvent::Measurement(event))
+                .await
+                .is_ok());
+        }
+        assert!(!app_controller.active_stored);
+        assert!(matches!(
+            notifications.try_recv(),
+            Ok(Notification::Warning(msg)) if msg.contains("reconnecting")
+        ));
+        // the warning is sent once per outage
+        assert!(notifications.try_recv().is_err());
+
+        // the sensor was given up
+        assert!(app_controller
+            .dispatch_event(AppEvent::Bluetooth(BluetoothEvent::ConnectionStateChanged(
+                ConnectionState::Idle
+            )))
+            .await
+            .is_ok());
+        assert!(app_controller.active_stored);
+        assert!(matches!(
+            notifications.try_recv(),
+            Ok(Notification::Warning(msg)) if msg.contains("stopped and stored")
+        ));
+        // the stopped recording ignores further messages
+        assert!(app_controller
+            .dispatch_event(AppEvent::Measurement(MeasurementEvent::RecordMessage(
+                HeartrateMessage::from_values(60, None, &[1000])
+            )))
+            .await
+            .is_err());
+        assert_eq!(measurement.read().await.get_beat_count(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_app_controller_auto_start_on_connect() {
+        let (event_bus_tx, mut event_bus_rx) = broadcast::channel(16);
+        let mut ble_controller = MockBluetooth::new();
+        ble_controller
+            .expect_select_peripheral()
+            .returning(|_| Ok(()));
+        // the selected sensor connects without recording
+        ble_controller
+            .expect_start_listening()
+            .once()
+            .returning(|| Ok(()));
+        ble_controller
+            .expect_connection_state_changed()
+            .returning(|_| Ok(()));
+        ble_controller
+            .expect_start_recording()
+            .once()
+            .returning(|| Ok(()));
+        let settings = AppSettings {
+            auto_start: Some(0.1),
+            ..Default::default()
+        };
+        let mut app_controller =
+            AppController::new(ble_controller, MockStorage::new(), event_bus_tx)
+                .with_settings(settings, None);
+        let mut notifications = app_controller.notification_tx.subscribe();
+        app_controller.active_measurement = Some(Arc::new(RwLock::new(MeasurementData::default())));
+
+        let device = DeviceDescriptor {
+            name: "TestDevice".to_string(),
+            address: BDAddr::default(),
+        };
+        assert!(app_controller
+            .dispatch_event(AppEvent::Bluetooth(BluetoothEvent::SelectPeripheral(
+                device
+            )))
+            .await
+            .is_ok());
+        let connected = AppEvent::Bluetooth(BluetoothEvent::ConnectionStateChanged(
+            ConnectionState::Connected,
+        ));
+        assert!(app_controller
+            .dispatch_event(connected.clone())
+            .await
+            .is_ok());
+        assert!(matches!(
+            notifications.try_recv(),
+            Ok(Notification::Countdown(Some(_)))
+        ));
+        let started = tokio::time::timeout(std::time::Duration::from_secs(1), event_bus_rx.recv())
+            .await
+            .expect("the recording did not start after the countdown")
+            .unwrap();
+        assert!(matches!(
+            started,
+            AppEvent::Recording(RecordingEvent::StartRecording)
+        ));
+        assert!(app_controller.dispatch_event(started).await.is_ok());
+        assert!(app_controller.auto_start.is_none());
+        assert!(matches!(
+            notifications.try_recv(),
+            Ok(Notification::Countdown(None))
+        ));
+
+        // a sensor reconnecting during the recording does not start a countdown
+        assert!(app_controller
+            .dispatch_event(connected.clone())
+            .await
+            .is_ok());
+        assert!(app_controller.auto_start.is_none());
+
+        // a cancelled countdown does not start the recording
+        app_controller.active_measurement = Some(Arc::new(RwLock::new(MeasurementData::default())));
+        assert!(app_controller.dispatch_event(connected).await.is_ok());
+        assert!(app_controller.auto_start.is_some());
+        assert!(app_controller
+            .dispatch_event(AppEvent::AppState(StateChangeEvent::CancelAutoStart))
+            .await
+            .is_ok());
+        assert!(app_controller.auto_start.is_none());
+        assert!(
+            tokio::time::timeout(std::time::Duration::from_millis(300), event_bus_rx.recv())
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_app_controller_auto_start_with_bluetooth_component() {
+        let (event_bus_tx, mut event_bus_rx) = broadcast::channel(16);
+        let ble_controller = get_connectable_component(event_bus_tx.clone());
+        let settings = AppSettings {
+            auto_start: Some(0.1),
+            ..Default::default()
+        };
+        let mut app_controller =
+            AppController::new(ble_controller, MockStorage::new(), event_bus_tx)
+                .with_settings(settings, None);
+        let measurement = Arc::new(RwLock::new(MeasurementData::default()));
+        app_controller.active_measurement = Some(measurement.clone());
+
+        let device = DeviceDescriptor {
+            name: "TestDevice".to_string(),
+            address: BDAddr::default(),
+        };
+        assert!(app_controller
+            .dispatch_event(AppEvent::Bluetooth(BluetoothEvent::SelectPeripheral(
+                device
+            )))
+            .await
+            .is_ok());
+        // the connection reported by the sensor starts the countdown, which starts the recording
+        loop {
+            let event =
+                tokio::time::timeout(std::time::Duration::from_secs(1), event_bus_rx.recv())
+                    .await
+                    .expect("the recording did not start after connecting")
+                    .unwrap();
+            let started = matches!(event, AppEvent::Recording(RecordingEvent::StartRecording));
+            assert!(app_controller.dispatch_event(event).await.is_ok());
+            if started {
+                break;
+            }
+        }
+        assert!(measurement.read().await.is_recording());
+        let ble = app_controller.ble_controller.read().await;
+        assert_eq!(ble.get_connection_state(), ConnectionState::Recording);
+        assert_eq!(ble.is_listening_to(), Some(BDAddr::default()));
+    }
+
+    #[tokio::test]
+    async fn test_app_controller_replay_measurement() {
+        let (event_bus_tx, mut event_bus_rx) = broadcast::channel(16);
+        let mut stored = MeasurementData::default();
+        stored
+            .import_messages(vec![
+                (
+                    time::Duration::milliseconds(600),
+                    HeartrateMessage::from_values(62, None, &[1000]),
+                ),
+                (
+                    time::Duration::milliseconds(200),
+                    HeartrateMessage::from_values(60, None, &[800]),
+                ),
+                (
+                    time::Duration::milliseconds(800),
+                    HeartrateMessage::from_values(64, None, &[900]),
+                ),
+            ])
+            .unwrap();
+        let stored = Arc::new(RwLock::new(stored));
+        let mut acq_controller = MockStorage::new();
+        acq_controller
+            .expect_replay_measurement()
+            .with(eq((0, 2.0)))
+            .once()
+            .returning(|_| Ok(()));
+        acq_controller
+            .expect_get_measurement()
+            .with(eq(0))
+            .returning(move |_| Ok(stored.clone()));
+        acq_controller
+            .expect_new_measurement()
+            .once()
+            .returning(|| Ok(Arc::new(RwLock::new(MeasurementData::default()))));
+        let mut app_controller =
+            AppController::new(MockBluetooth::new(), acq_controller, event_bus_tx);
+        let _view = app_controller.get_viewmanager();
+
+        let start = Instant::now();
+        assert!(app_controller
+            .dispatch_event(AppEvent::Storage(StorageEvent::ReplayMeasurement((0, 2.0))))
+            .await
+            .is_ok());
+        assert!(!app_controller.active_stored);
+
+        // the pauses of 200, 400 and 200 ms are halved
+        let mut received = Vec::new();
+        for expected in [100, 300, 400] {
+            let event =
+                tokio::time::timeout(std::time::Duration::from_secs(1), event_bus_rx.recv())
+                    .await
+                    .expect("the replay did not send the next message")
+                    .unwrap();
+            let elapsed = start.elapsed();
+            assert!(elapsed >= Duration::from_millis(expected), "{:?}", elapsed);
+            assert!(
+                elapsed < Duration::from_millis(expected + 150),
+                "{:?}",
+                elapsed
+            );
+            let AppEvent::Measurement(MeasurementEvent::RecordMessage(msg)) = event else {
+                panic!("unexpected event {:?}", event);
+            };
+            received.push(msg.get_hr());
+            assert!(app_controller
+                .dispatch_event(AppEvent::Measurement(MeasurementEvent::RecordMessage(msg)))
+                .await
+                .is_ok());
+        }
+        assert_eq!(received, [60.0, 62.0, 64.0]);
+        let active = app_controller.active_measurement.clone().unwrap();
+        assert_eq!(active.read().await.get_beat_count(), 3);
+        // the halved pauses are recorded at the replayed time
+        let (last, _) = *active.read().await.get_messages().last().unwrap();
+        assert!(last >= time::Duration::milliseconds(800), "{:?}", last);
+        assert!(last < time::Duration::milliseconds(1200), "{:?}", last);
+    }
+
+    #[tokio::test]
+    async fn test_app_controller_applies_device_profile() {
+        let (event_bus_tx, _) = broadcast::channel(16);
+        let device = DeviceDescriptor::from_address("AA:BB:CC:DD:EE:FF").unwrap();
+        let mut ble_controller = MockBluetooth::new();
+        ble_controller
+            .expect_select_peripheral()
+            .once()
+            .returning(|_| Ok(()));
+        let mut settings = AppSettings::default();
+        settings.save_profile(DeviceProfile {
+            address: device.address.to_string(),
+            name: "chest strap".to_owned(),
+            config: HrvConfig {
+                outlier_filter: 3.0,
+                ..Default::default()
+            },
+        });
+
+        let mut app_controller =
+            AppController::new(ble_controller, MockStorage::new(), event_bus_tx)
+                .with_settings(settings, None);
+        let measurement = Arc::new(RwLock::new(MeasurementData::default()));
+        app_controller.active_measurement = Some(measurement.clone());
+        assert!(app_controller
+            .dispatch_event(AppEvent::Bluetooth(BluetoothEvent::SelectPeripheral(
+                device
+            )))
+            .await
+            .is_ok());
+        assert_eq!(
+            measurement.read().await.get_hrv_config().outlier_filter,
+            3.0
+        );
+    }
+
+    #[tokio::test]
+    async fn test_app_controller_applies_template() {
         let (event_bus_tx, _) = broadcast::channel(16);
-        let ble_controller = MockBluetooth::new();
-        let acq_controller = MockStorage::new();
+        let mut app_controller =
+            AppController::new(MockBluetooth::new(), MockStorage::new(), event_bus_tx);
+        let config = HrvConfig {
+            window: Some(60),
+            outlier_filter: 3.0,
+            dfa: DfaConfig {
+                min_box: 5,
+                max_box: 12,
+            },
+            ..Default::default()
+        };
+        let template = AnalysisTemplate {
+            name: "resting".to_owned(),
+            config: config.clone(),
+        };
+        assert!(app_controller
+            .dispatch_event(AppEvent::Settings(SettingsEvent::SetDefaultTemplate(Some(
+                "resting".to_owned()
+            ))))
+            .await
+            .is_err());
+        for event in [
+            SettingsEvent::SaveTemplate(template),
+            SettingsEvent::SetDefaultTemplate(Some("resting".to_owned())),
+        ] {
+            assert!(app_controller
+                .dispatch_event(AppEvent::Settings(event))
+                .await
+                .is_ok());
+        }
 
-        let mock_measurement = Arc::new(RwLock::new(MeasurementData::default()));
+        let measurement = Arc::new(RwLock::new(MeasurementData::default()));
+        app_controller
+            .apply_default_template(&measurement)
+            .await
+            .unwrap();
+        let applied = measurement.read().await.get_hrv_config();
+        assert_eq!(applied.window, Some(60));
+        assert_eq!(applied.outlier_filter, 3.0);
+        assert_eq!(applied.dfa, config.dfa);
 
-        let mut app_controller =
-            AppController::new(ble_controller, acq_controller, event_bus_tx.clone());
-        app_controller.active_measurement = Some(mock_measurement.clone());
+        // without a default template new measurements keep their parameters
+        assert!(app_controller
+            .dispatch_event(AppEvent::Settings(SettingsEvent::DeleteTemplate(
+                "resting".to_owned()
+            )))
+            .await
+            .is_ok());
+        let measurement = Arc::new(RwLock::new(MeasurementData::default()));
+        app_controller
+            .apply_default_template(&measurement)
+            .await
+            .unwrap();
+        assert_eq!(
+            measurement.read().await.get_hrv_config(),
+            MeasurementData::default().get_hrv_config()
+        );
+    }
 
-        let event = AppEvent::Measurement(MeasurementEvent::SetStatsWindow(60));
-        let result = app_controller.dispatch_event(event).await;
-        assert!(result.is_ok());
+    #[test]
+    fn test_repaint_throttle() {
+        let mut throttle = RepaintThrottle {
+            interval: Duration::from_millis(50),
+            last: None,
+        };
+        let start = Instant::now();
+        // a burst of 100 events within 10 ms repaints once right away, the rest is delayed
+        let immediate = (0..100)
+            .map(|idx| throttle.delay(start + Duration::from_micros(idx * 100), false))
+            .filter(|delay| delay.is_zero())
+            .count();
+        assert_eq!(immediate, 1);
         assert_eq!(
-            mock_measurement.read().await.get_stats_window().unwrap(),
-            60
+            throttle.delay(start + Duration::from_millis(20), false),
+            Duration::from_millis(30)
         );
+        // state transitions are not delayed
+        assert!(throttle
+            .delay(start + Duration::from_millis(21), true)
+            .is_zero());
+        assert!(throttle
+            .delay(start + Duration::from_millis(71), false)
+            .is_zero());
     }
 
     #[tokio::test]
-    async fn test_app_controller_start_recording_event() {
-        let (event_bus_tx, _) = broadcast::channel(16);
-        let mut ble_controller = MockBluetooth::new();
-        let mut acq_controller = MockStorage::new();
+    async fn test_recompute_debounce() {
+        let mut debounce = RecomputeDebounce {
+            interval: Duration::from_millis(150),
+            ..Default::default()
+        };
+        let start = Instant::now();
+        // a slider drag sends a change on every frame
+        for (idx, window) in (30..60).enumerate() {
+            let now = start + Duration::from_millis(idx as u64 * 16);
+            let event = AppEvent::Measurement(MeasurementEvent::SetStatsWindow(window));
+            assert!(debounce.schedule(event, now).is_empty());
+        }
+        assert_eq!(
+            debounce.deadline,
+            Some(start + Duration::from_millis(29 * 16 + 150))
+        );
+        let mut measurement = MeasurementData::default();
+        let revision = measurement.get_revision();
+        let events = debounce.take();
+        assert_eq!(events.len(), 1);
+        assert!(debounce.deadline.is_none());
+        for event in events {
+            let AppEvent::Measurement(event) = event else {
+                panic!("unexpected event");
+            };
+            event.forward_to(&mut measurement).await.unwrap();
+        }
+        assert_eq!(measurement.get_revision(), revision + 1);
+        assert_eq!(measurement.get_stats_window(), Some(59));
+
+        // events of other components apply the pending changes first
+        let event = AppEvent::Measurement(MeasurementEvent::SetStatsWindow(40));
+        assert!(debounce.schedule(event, start).is_empty());
+        let events = debounce.schedule(
+            AppEvent::AppState(StateChangeEvent::SelectMeasurement(0)),
+            start,
+        );
+        assert!(matches!(
+            events.as_slice(),
+            [
+                AppEvent::Measurement(MeasurementEvent::SetStatsWindow(40)),
+                AppEvent::AppState(StateChangeEvent::SelectMeasurement(0))
+            ]
+        ));
+
+        // as do other measurement events, so a stale change does not override them
+        let event = AppEvent::Measurement(MeasurementEvent::SetStatsWindow(45));
+        assert!(debounce.schedule(event, start).is_empty());
+        let event = AppEvent::Measurement(MeasurementEvent::SetHrvConfig(HrvConfig::default()));
+        let events = debounce.schedule(event, start);
+        assert!(matches!(
+            events.as_slice(),
+            [
+                AppEvent::Measurement(MeasurementEvent::SetStatsWindow(45)),
+                AppEvent::Measurement(MeasurementEvent::SetHrvConfig(_))
+            ]
+        ));
+        assert!(debounce.deadline.is_none());
+
+        // a zero interval applies every change
+        debounce.interval = Duration::ZERO;
+        let event = AppEvent::Measurement(MeasurementEvent::SetStatsWindow(50));
+        assert_eq!(debounce.schedule(event, start).len(), 1);
+    }
 
-        acq_controller.expect_start_recording().returning(|| Ok(()));
-        ble_controller.expect_start_recording().returning(|| Ok(()));
+    #[tokio::test]
+    async fn test_app_controller_lagged_events_are_reported() {
+        let (event_bus_tx, _) = broadcast::channel(2);
+        let ble_controller = MockBluetooth::new();
+        let acq_controller = MockStorage::new();
+        let measurement = Arc::new(RwLock::new(MeasurementData::default()));
+        measurement.write().await.start_recording().await.unwrap();
 
         let mut app_controller =
             AppController::new(ble_controller, acq_controller, event_bus_tx.clone());
-
-        let event = AppEvent::Recording(RecordingEvent::StartRecording);
-        let result = app_controller.dispatch_event(event).await;
-        assert!(result.is_ok());
+        app_controller.active_measurement = Some(measurement.clone());
+
+        let mut rx = event_bus_tx.subscribe();
+        for _ in 0..5 {
+            event_bus_tx
+                .send(AppEvent::Measurement(MeasurementEvent::RecordMessage(
+                    HeartrateMessage::from_values(60, None, &[1000]),
+                )))
+                .unwrap();
+        }
+        // the two most recent events are still delivered
+        for _ in 0..2 {
+            assert!(matches!(
+                app_controller.next_event(&mut rx).await,
+                Some(AppEvent::Measurement(MeasurementEvent::RecordMessage(_)))
+            ));
+        }
+        assert_eq!(app_controller.dropped_events, 3);
+        assert_eq!(measurement.read().await.get_dropped_events(), 3);
     }
 
     #[tokio::test]
-    async fn test_app_controller_stop_recording_event() {
+    async fn test_app_controller_pause_resume_recording_event() {
         let (event_bus_tx, _) = broadcast::channel(16);
         let mut ble_controller = MockBluetooth::new();
-        let mut acq_controller = MockStorage::new();
+        let acq_controller = MockStorage::new();
 
-        acq_controller.expect_stop_recording().returning(|| Ok(()));
-        ble_controller.expect_stop_recording().returning(|| Ok(()));
+        ble_controller.expect_start_recording().returning(|| Ok(()));
+        ble_controller
+            .expect_pause_recording()
+            .once()
+            .returning(|| Ok(()));
+        ble_controller
+            .expect_resume_recording()
+            .once()
+            .returning(|| Ok(()));
 
+        let measurement = Arc::new(RwLock::new(MeasurementData::default()));
         let mut app_controller =
             AppController::new(ble_controller, acq_controller, event_bus_tx.clone());
-
-        let event = AppEvent::Recording(RecordingEvent::StopRecording);
-        let result = app_controller.dispatch_event(event).await;
-        assert!(result.is_ok());
+        app_controller.active_measurement = Some(measurement.clone());
+
+        for event in [
+            RecordingEvent::StartRecording,
+            RecordingEvent::PauseRecording,
+        ] {
+            assert!(app_controller
+                .dispatch_event(AppEvent::Recording(event))
+                .await
+                .is_ok());
+        }
+        assert!(measurement.read().await.is_paused());
+        assert!(app_controller
+            .dispatch_event(AppEvent::Recording(RecordingEvent::ResumeRecording))
+            .await
+            .is_ok());
+        assert!(!measurement.read().await.is_paused());
     }
+
     #[tokio::test]
     async fn test_app_controller_storage_event() {
         let (event_bus_tx, _) = broadcast::channel(16);
@@ -441,23 +2369,30 @@ pub mod tests {
             .once()
             .returning(|| Ok(()));
         let measurement = Arc::new(RwLock::new(MeasurementData::default()));
+        let new_measurement = measurement.clone();
         acq_controller
-            .expect_store_measurement()
+            .expect_new_measurement()
+            .once()
+            .returning(move || Ok(new_measurement.clone()));
+        let recorded_measurement = measurement.clone();
+        acq_controller
+            .expect_store_recorded_measurement()
             .once()
-            .returning(move |_| Ok(()));
+            .returning(move || Ok(recorded_measurement.clone()));
 
         let mut app_controller =
             AppController::new(ble_controller, acq_controller, event_bus_tx.clone());
         // needed to have an open view channel
         let _view = app_controller.get_viewmanager();
 
-        app_controller.active_measurement = Some(measurement);
-
         assert!(app_controller
             .handle_state_events(StateChangeEvent::ToRecordingState)
             .await
             .is_ok());
-        assert!(app_controller.active_measurement.is_some());
+        assert!(app_controller
+            .active_measurement
+            .as_ref()
+            .is_some_and(|m| Arc::ptr_eq(m, &measurement)));
         assert!(app_controller
             .dispatch_event(AppEvent::Recording(RecordingEvent::StartRecording))
             .await
@@ -478,20 +2413,344 @@ pub mod tests {
             .is_ok());
     }
 
+    #[tokio::test]
+    async fn test_app_controller_store_and_continue() {
+        let (event_bus_tx, _) = broadcast::channel(16);
+        let mut ble_controller = MockBluetooth::new();
+        let mut acq_controller = MockStorage::new();
+        ble_controller
+            .expect_start_recording()
+            .once()
+            .returning(|| Ok(()));
+        // the sensor connection is retained
+        ble_controller.expect_stop_recording().never();
+        ble_controller.expect_stop_listening().never();
+        acq_controller
+            .expect_new_measurement()
+            .times(2)
+            .returning(|| Ok(Arc::new(RwLock::new(MeasurementData::default()))));
+        acq_controller
+            .expect_store_recorded_measurement()
+            .once()
+            .returning(|| Ok(Arc::new(RwLock::new(MeasurementData::default()))));
+
+        let mut app_controller =
+            AppController::new(ble_controller, acq_controller, event_bus_tx.clone());
+        let _view = app_controller.get_viewmanager();
+
+        assert!(app_controller
+            .handle_state_events(StateChangeEvent::ToRecordingState)
+            .await
+            .is_ok());
+        let first = app_controller.active_measurement.clone().unwrap();
+        assert!(app_controller
+            .dispatch_event(AppEvent::Recording(RecordingEvent::StartRecording))
+            .await
+            .is_ok());
+        assert!(app_controller
+            .handle_state_events(StateChangeEvent::StoreAndContinue)
+            .await
+            .is_ok());
+        let second = app_controller.active_measurement.clone().unwrap();
+        assert!(!Arc::ptr_eq(&first, &second));
+        assert!(!app_controller.active_stored);
+        // the new measurement records right away, the stored one does not
+        let msg = HeartrateMessage::from_values(60, None, &[1000]);
+        assert!(app_controller
+            .dispatch_event(AppEvent::Measurement(MeasurementEvent::RecordMessage(msg)))
+            .await
+            .is_ok());
+        assert!(first.write().await.record_message(msg).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_app_controller_snapshot_does_not_block() {
+        let (event_bus_tx, _) = broadcast::channel(16);
+        let mut acq_controller = MockStorage::new();
+        let measurement = Arc::new(RwLock::new(MeasurementData::default()));
+        let new_measurement = measurement.clone();
+        acq_controller
+            .expect_new_measurement()
+            .returning(move || Ok(new_measurement.clone()));
+
+        let mut app_controller =
+            AppController::new(MockBluetooth::new(), acq_controller, event_bus_tx);
+        let _view = app_controller.get_viewmanager();
+        assert!(app_controller
+            .handle_state_events(StateChangeEvent::ToRecordingState)
+            .await
+            .is_ok());
+        let snapshot = app_controller.snapshot_tx.subscribe();
+
+        {
+            // a long running analysis holds the lock of the measurement
+            let _analysis = measurement.write().await;
+            let reader = snapshot.clone();
+            let read = tokio::time::timeout(
+                std::time::Duration::from_millis(500),
+                tokio::task::spawn_blocking(move || reader.borrow().get_beat_count()),
+            )
+            .await;
+            assert_eq!(read.unwrap().unwrap(), 0);
+        }
+
+        {
+            let mut lck = measurement.write().await;
+            lck.start_recording().await.unwrap();
+            lck.record_message(HeartrateMessage::from_values(60, None, &[1000]))
+                .await
+                .unwrap();
+        }
+        app_controller.publish_snapshot().await;
+        assert_eq!(snapshot.borrow().get_beat_count(), 1);
+
+        // an unchanged measurement keeps the published snapshot
+        let published = snapshot.borrow().clone();
+        app_controller.publish_snapshot().await;
+        assert!(Arc::ptr_eq(&published, &snapshot.borrow()));
+        measurement.write().await.pause_recording().await.unwrap();
+        app_controller.publish_snapshot().await;
+        assert!(!Arc::ptr_eq(&published, &snapshot.borrow()));
+    }
+
+    #[tokio::test]
+    async fn test_app_controller_snapshot_shows_excluded() {
+        let (event_bus_tx, _) = broadcast::channel(16);
+        let mut app_controller =
+            AppController::new(MockBluetooth::new(), MockStorage::new(), event_bus_tx);
+        app_controller.active_measurement = Some(Arc::new(RwLock::new(MeasurementData::default())));
+        let snapshot = app_controller.publish_snapshot().await;
+        assert!(!snapshot.borrow().is_excluded());
+
+        let published = snapshot.borrow().clone();
+        assert!(app_controller
+            .dispatch_event(AppEvent::Measurement(MeasurementEvent::SetExcluded(true)))
+            .await
+            .is_ok());
+        app_controller.publish_snapshot().await;
+        assert!(!Arc::ptr_eq(&published, &snapshot.borrow()));
+        assert!(snapshot.borrow().is_excluded());
+    }
+
+    #[tokio::test]
+    async fn test_app_controller_load_initial_file() {
+        let (event_bus_tx, _) = broadcast::channel(16);
+        let path = PathBuf::from("recording.json");
+        let mut acq_controller = MockStorage::new();
+        acq_controller
+            .expect_load_from_file()
+            .once()
+            .with(eq(path.clone()))
+            .returning(|_| Ok(()));
+        acq_controller
+            .expect_get_selected_index()
+            .return_const(None);
+
+        let mut app_controller =
+            AppController::new(MockBluetooth::new(), acq_controller, event_bus_tx)
+                .with_initial_file(Some(path));
+        app_controller.load_initial_file().await;
+        // the file is only loaded once
+        app_controller.load_initial_file().await;
+    }
+
+    #[tokio::test]
+    async fn test_app_controller_load_restores_selection() {
+        let (event_bus_tx, _) = broadcast::channel(16);
+        let measurement = Arc::new(RwLock::new(MeasurementData::default()));
+        let mut acq_controller = MockStorage::new();
+        acq_controller
+            .expect_load_from_file()
+            .once()
+            .returning(|_| Ok(()));
+        acq_controller
+            .expect_get_selected_index()
+            .return_const(Some(1));
+        let selected = measurement.clone();
+        acq_controller
+            .expect_select_measurement()
+            .once()
+            .with(eq(1))
+            .returning(move |_| Ok(selected.clone()));
+
+        let mut app_controller =
+            AppController::new(MockBluetooth::new(), acq_controller, event_bus_tx);
+        let mut view_rx = app_controller.view_tx.subscribe();
+        assert!(app_controller
+            .dispatch_event(AppEvent::Storage(StorageEvent::LoadFromFile(
+                PathBuf::from("selection.json")
+            )))
+            .await
+            .is_ok());
+        assert!(Arc::ptr_eq(
+            app_controller.active_measurement.as_ref().unwrap(),
+            &measurement
+        ));
+        assert!(matches!(
+            view_rx.try_recv(),
+            Ok(ViewState::Overview((_, Some(_))))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_app_controller_load_initial_file_error() {
+        let (event_bus_tx, _) = broadcast::channel(16);
+        let mut acq_controller = MockStorage::new();
+        acq_controller
+            .expect_load_from_file()
+            .returning(|_| Err(anyhow!("invalid file")));
+
+        let mut app_controller =
+            AppController::new(MockBluetooth::new(), acq_controller, event_bus_tx)
+                .with_initial_file(Some(PathBuf::from("invalid.json")));
+        let mut notification_rx = app_controller.notification_tx.subscribe();
+        app_controller.load_initial_file().await;
+        assert!(matches!(
+            notification_rx.try_recv(),
+            Ok(Notification::Error((_, msg))) if msg.contains("invalid file")
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_app_controller_shutdown() {
+        let (event_bus_tx, _) = broadcast::channel(16);
+        let mut ble_controller = MockBluetooth::new();
+        let mut seq = mockall::Sequence::new();
+        ble_controller
+            .expect_stop_listening()
+            .once()
+            .in_sequence(&mut seq)
+            .returning(|| Ok(()));
+        ble_controller
+            .expect_disconnect()
+            .once()
+            .in_sequence(&mut seq)
+            .returning(|| Ok(()));
+        let measurement = Arc::new(RwLock::new(MeasurementData::default()));
+        measurement.write().await.start_recording().await.unwrap();
+
+        let mut app_controller =
+            AppController::new(ble_controller, MockStorage::new(), event_bus_tx.clone());
+        app_controller.active_measurement = Some(measurement.clone());
+
+        assert!(app_controller
+            .dispatch_event(AppEvent::AppState(StateChangeEvent::Shutdown))
+            .await
+            .is_ok());
+        // the recording was stopped
+        assert!(measurement
+            .write()
+            .await
+            .record_message(HeartrateMessage::from_values(60, None, &[1000]))
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_app_controller_shutdown_skips_saved_recordings() {
+        let (event_bus_tx, _) = broadcast::channel(16);
+        let temp_dir = tempdir::TempDir::new("test").unwrap();
+        let mut ble_controller = MockBluetooth::new();
+        ble_controller.expect_stop_listening().returning(|| Ok(()));
+        ble_controller.expect_disconnect().returning(|| Ok(()));
+        let mut acq_controller = MockStorage::new();
+        acq_controller
+            .expect_new_measurement()
+            .once()
+            .returning(|| Ok(Arc::new(RwLock::new(MeasurementData::default()))));
+        acq_controller.expect_discard_recording().returning(|| None);
+        // neither the stored nor the discarded recording is saved again
+        acq_controller.expect_store_recorded_measurement().never();
+        acq_controller.expect_store_measurement_to_file().never();
+
+        let mut app_controller =
+            AppController::new(ble_controller, acq_controller, event_bus_tx.clone());
+        let _vm = app_controller.get_viewmanager();
+        assert!(app_controller
+            .dispatch_event(AppEvent::Settings(SettingsEvent::SetAutoSaveDir(Some(
+                temp_dir.path().to_path_buf()
+            ))))
+            .await
+            .is_ok());
+
+        // a stored measurement is active
+        app_controller.active_measurement = Some(Arc::new(RwLock::new(MeasurementData::default())));
+        app_controller.active_stored = true;
+        assert!(app_controller.shutdown().await.is_ok());
+
+        assert!(app_controller
+            .handle_state_events(StateChangeEvent::ToRecordingState)
+            .await
+            .is_ok());
+        assert!(app_controller
+            .handle_state_events(StateChangeEvent::DiscardRecording)
+            .await
+            .is_ok());
+        assert!(app_controller.shutdown().await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_app_controller_event_handler_returns_on_shutdown() {
+        let (event_bus_tx, _) = broadcast::channel(16);
+        let mut ble_controller = MockBluetooth::new();
+        ble_controller
+            .expect_discover_adapters()
+            .returning(|| Ok(()));
+        ble_controller.expect_set_scan_interval().return_const(());
+        ble_controller
+            .expect_set_reconnect_attempts()
+            .return_const(());
+        ble_controller.expect_set_scan_filter().return_const(());
+        ble_controller.expect_stop_listening().returning(|| Ok(()));
+        ble_controller
+            .expect_disconnect()
+            .once()
+            .returning(|| Ok(()));
+        let mut acq_controller = MockStorage::new();
+        acq_controller
+            .expect_set_lazy_analysis()
+            .with(eq(false))
+            .once()
+            .return_const(());
+        acq_controller.expect_stale_caches().returning(Vec::new);
+
+        let app_controller =
+            AppController::new(ble_controller, acq_controller, event_bus_tx.clone());
+        let _vm = app_controller.get_viewmanager();
+        let handle = tokio::spawn(app_controller.event_handler(egui::Context::default()));
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        event_bus_tx
+            .send(AppEvent::AppState(StateChangeEvent::Shutdown))
+            .unwrap();
+        assert!(
+            tokio::time::timeout(std::time::Duration::from_secs(1), handle)
+                .await
+                .is_ok()
+        );
+    }
+
     #[tokio::test]
     async fn test_app_controller_discard_recording() {
         // Covers discarding a measurement if active_measurement is Some
         let (event_bus_tx, _) = broadcast::channel(16);
         let ble_controller = MockBluetooth::new();
-        let acq_controller = MockStorage::new();
+        let mut acq_controller = MockStorage::new();
+        acq_controller
+            .expect_new_measurement()
+            .once()
+            .returning(|| Ok(Arc::new(RwLock::new(MeasurementData::default()))));
+        acq_controller.expect_store_recorded_measurement().never();
+        // the recording is dropped from the storage as well
+        acq_controller
+            .expect_discard_recording()
+            .once()
+            .returning(|| None);
 
         let mut app_controller =
             AppController::new(ble_controller, acq_controller, event_bus_tx.clone());
         // needed to have an open view channel
         let _view = app_controller.get_viewmanager();
 
-        app_controller.active_measurement = Some(Arc::new(RwLock::new(MeasurementData::default())));
-
         assert!(app_controller
             .handle_state_events(StateChangeEvent::ToRecordingState)
             .await
@@ -583,13 +2842,13 @@ pub mod tests {
 
     #[tokio::test]
     async fn test_app_controller_select_measurement_error() {
-        // Covers lines where get_measurement returns an error
+        // Covers lines where select_measurement returns an error
         let (event_bus_tx, _) = broadcast::channel(16);
         let ble_controller = MockBluetooth::new();
         let mut acq_controller = MockStorage::new();
 
         acq_controller
-            .expect_get_measurement()
+            .expect_select_measurement()
             .with(always())
             .returning(move |_| Err(anyhow!("Mock get measurement error")));
 
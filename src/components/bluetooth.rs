@@ -4,22 +4,28 @@
 //! It handles device discovery, connection management, and data acquisition from BLE heart rate monitors.
 use crate::api::controller::{AdapterDiscovery, BluetoothApi, DisplayName, RecordingApi};
 use crate::api::model::BluetoothModelApi;
-use crate::core::constants::HEARTRATE_MEASUREMENT_UUID;
-use crate::core::events::{AppEvent, MeasurementEvent};
+use crate::core::constants::{HEARTRATE_MEASUREMENT_UUID, RECONNECT_DELAY};
+use crate::core::events::{AppEvent, BluetoothEvent, MeasurementEvent};
 use crate::model::bluetooth::AdapterDescriptor;
-use crate::model::bluetooth::{DeviceDescriptor, HeartrateMessage};
+use crate::model::bluetooth::{ConnectionState, DeviceDescriptor, HeartrateMessage};
+use crate::model::settings::{
+    DEFAULT_RECONNECT_ATTEMPTS, DEFAULT_SCAN_INTERVAL, RECONNECT_ATTEMPTS_RANGE,
+    SCAN_INTERVAL_RANGE,
+};
 use anyhow::{anyhow, Result};
 
 use btleplug::{
-    api::{BDAddr, Central, Manager as _, Peripheral},
+    api::{BDAddr, Central, Manager as _, Peripheral, ScanFilter, ValueNotification},
     platform::{Adapter, Manager},
 };
 
-use futures::StreamExt;
+use futures::{Stream, StreamExt};
 use log::{trace, warn};
 use std::collections::HashMap;
 use std::fmt::Debug;
+use std::pin::Pin;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::broadcast::Sender;
 use tokio::sync::RwLock;
 use tokio::task::JoinHandle;
@@ -43,6 +49,11 @@ use async_trait::async_trait;
 /// - `devices`: Thread-safe list of discovered devices
 /// - `scanning`: Indicates if device scanning is active
 /// - `listening`: Address of device currently being monitored
+/// - `scan_interval`: Time between two refreshes of the device list
+/// - `reconnect_attempts`: Number of attempts to reconnect a lost sensor
+/// - `scan_filter`: Filter of the devices the adapter scans for
+/// - `state`: State of the connection to the sensor, broadcast on transitions
+/// - `recording`: Indicates if the notifications are recorded
 #[derive(Debug)]
 pub struct BluetoothComponent<A: Central + DisplayName + AdapterDiscovery<A> + 'static>
 where
@@ -58,6 +69,11 @@ where
     devices: Arc<RwLock<Vec<DeviceDescriptor>>>,
     scanning: bool,
     listening: Option<BDAddr>,
+    scan_interval: Duration,
+    reconnect_attempts: usize,
+    scan_filter: ScanFilter,
+    state: ConnectionState,
+    recording: bool,
 }
 
 impl<A: DisplayName + Central + AdapterDiscovery<A>> Drop for BluetoothComponent<A>
@@ -99,13 +115,50 @@ where
             devices: Arc::new(RwLock::new(Vec::new())),
             scanning: false,
             listening: None,
+            scan_interval: Duration::from_secs_f64(DEFAULT_SCAN_INTERVAL),
+            reconnect_attempts: DEFAULT_RECONNECT_ATTEMPTS,
+            scan_filter: ScanFilter::default(),
+            state: ConnectionState::Idle,
+            recording: false,
         }
     }
 
-    pub async fn peripheral_listener<T: Peripheral>(
-        cheststrap: T,
-        tx: Sender<AppEvent>,
-    ) -> Result<()> {
+    /// Broadcasts a connection state on the event bus.
+    ///
+    /// # Returns
+    /// An error if nobody receives the events anymore.
+    fn send_state(tx: &Sender<AppEvent>, state: ConnectionState) -> Result<()> {
+        tx.send(AppEvent::Bluetooth(BluetoothEvent::ConnectionStateChanged(
+            state,
+        )))
+        .map(|_| ())
+        .map_err(|_| anyhow!("listener terminated"))
+    }
+
+    /// Changes the connection state and broadcasts the transition.
+    fn set_state(&mut self, state: ConnectionState) {
+        if self.state != state {
+            trace!("connection state: {} -> {}", self.state, state);
+            self.state = state;
+            if Self::send_state(&self.event_bus, state).is_err() {
+                warn!("no receiver for the connection state");
+            }
+        }
+    }
+
+    /// Returns the state without a connected sensor.
+    fn disconnected_state(&self) -> ConnectionState {
+        if self.peri_updater_handle.is_some() {
+            ConnectionState::Scanning
+        } else {
+            ConnectionState::Idle
+        }
+    }
+
+    /// Connects to the peripheral and subscribes to its heart rate notifications.
+    async fn subscribe<T: Peripheral>(
+        cheststrap: &T,
+    ) -> Result<Pin<Box<dyn Stream<Item = ValueNotification> + Send>>> {
         cheststrap.connect().await?;
 
         cheststrap.discover_services().await?;
@@ -119,26 +172,114 @@ where
 
         cheststrap.subscribe(&char).await?;
 
-        let mut notification_stream = cheststrap.notifications().await?;
-        while let Some(data) = notification_stream.next().await {
-            if data.value.len() < 2
-                || tx
+        Ok(cheststrap.notifications().await?)
+    }
+
+    /// Forwards the heart rate notifications of the peripheral to the event bus.
+    ///
+    /// If the connection is lost, the peripheral is reconnected and the notifications continue
+    /// into the same recording. The lost connection is reported so the recording marks a gap.
+    /// Malformed notifications are dropped without ending the connection.
+    ///
+    /// # Arguments
+    /// - `cheststrap`: The peripheral to listen to.
+    /// - `tx`: The event bus.
+    /// - `reconnect_delay`: Time between two reconnect attempts.
+    /// - `reconnect_attempts`: Number of reconnect attempts before the sensor is given up.
+    ///
+    /// # Returns
+    /// An error if the first connection fails, the sensor does not reconnect or nobody
+    /// receives the events anymore.
+    pub async fn peripheral_listener<T: Peripheral>(
+        cheststrap: T,
+        tx: Sender<AppEvent>,
+        reconnect_delay: Duration,
+        reconnect_attempts: usize,
+    ) -> Result<()> {
+        let mut notification_stream = match Self::subscribe(&cheststrap).await {
+            Ok(stream) => stream,
+            Err(e) => {
+                let _ = Self::send_state(&tx, ConnectionState::Idle);
+                return Err(e);
+            }
+        };
+        Self::send_state(&tx, ConnectionState::Connected)?;
+        let mut malformed = 0;
+        loop {
+            while let Some(data) = notification_stream.next().await {
+                if data.value.len() < 2 {
+                    malformed += 1;
+                    warn!(
+                        "dropped a malformed notification of {} bytes ({} in total)",
+                        data.value.len(),
+                        malformed
+                    );
+                    continue;
+                }
+                if tx
                     .send(AppEvent::Measurement(MeasurementEvent::RecordMessage(
                         HeartrateMessage::new(&data.value),
                     )))
                     .is_err()
+                {
+                    warn!("BT transceiver terminated");
+                    return Err(anyhow!("listener terminated"));
+                }
+            }
+            warn!("connection to the sensor lost, reconnecting");
+            if tx
+                .send(AppEvent::Measurement(MeasurementEvent::MarkDisconnected))
+                .is_err()
             {
-                break;
+                return Err(anyhow!("listener terminated"));
+            }
+            Self::send_state(&tx, ConnectionState::Connecting)?;
+            let Some(stream) =
+                Self::reconnect(&cheststrap, reconnect_delay, reconnect_attempts).await
+            else {
+                warn!(
+                    "the sensor did not reconnect after {} attempts",
+                    reconnect_attempts
+                );
+                Self::send_state(&tx, ConnectionState::Idle)?;
+                return Err(anyhow!("sensor did not reconnect"));
+            };
+            notification_stream = stream;
+            trace!("sensor reconnected");
+            Self::send_state(&tx, ConnectionState::Connected)?;
+            if tx
+                .send(AppEvent::Measurement(MeasurementEvent::MarkReconnected))
+                .is_err()
+            {
+                return Err(anyhow!("listener terminated"));
             }
         }
-        warn!("BT transceiver terminated");
-        Err(anyhow!("listener terminated"))
+    }
+
+    /// Tries to reconnect a lost peripheral.
+    ///
+    /// # Returns
+    /// The notifications of the reconnected peripheral, `None` if all attempts failed.
+    async fn reconnect<T: Peripheral>(
+        cheststrap: &T,
+        delay: Duration,
+        attempts: usize,
+    ) -> Option<Pin<Box<dyn Stream<Item = ValueNotification> + Send>>> {
+        for attempt in 1..=attempts {
+            tokio::time::sleep(delay).await;
+            match Self::subscribe(cheststrap).await {
+                Ok(stream) => return Some(stream),
+                Err(e) => trace!("reconnect attempt {} failed: {}", attempt, e),
+            }
+        }
+        None
     }
 
     pub async fn listen_to_peripheral(
         adapter: A,
         peripheral_address: BDAddr,
         tx: Sender<AppEvent>,
+        reconnect_attempts: usize,
     ) -> Result<JoinHandle<Result<()>>> {
         let peripherals = adapter.peripherals().await?;
         let cheststrap = peripherals
@@ -146,13 +287,25 @@ where
             .find(|p| p.address() == peripheral_address)
             .ok_or(anyhow!("Peripheral not found"))?;
 
-        let fut = tokio::spawn(Self::peripheral_listener(cheststrap, tx));
+        let fut = tokio::spawn(Self::peripheral_listener(
+            cheststrap,
+            tx,
+            RECONNECT_DELAY,
+            reconnect_attempts,
+        ));
         Ok(fut)
     }
 
+    /// Refreshes the list of discovered devices, starting immediately.
+    ///
+    /// # Arguments
+    /// - `adapter`: The adapter to query.
+    /// - `devices`: The list of devices to update.
+    /// - `interval`: Time between two refreshes.
     pub async fn adapter_updater(
         adapter: A,
         devices: Arc<RwLock<Vec<DeviceDescriptor>>>,
+        interval: Duration,
     ) -> Result<()> {
         loop {
             let peripherals = adapter.peripherals().await?;
@@ -166,7 +319,7 @@ where
             // TODO: Send events when an error arises
             descriptors.sort();
             *devices.write().await = descriptors;
-            tokio::time::sleep(tokio::time::Duration::from_secs(5)).await;
+            tokio::time::sleep(interval).await;
         }
     }
 }
@@ -178,14 +331,23 @@ where
     A::Peripheral: DisplayName,
 {
     async fn discover_adapters(&mut self) -> Result<()> {
-        for adapter in A::discover_adapters().await? {
+        for (index, adapter) in A::discover_adapters().await?.into_iter().enumerate() {
             let name = adapter.get_name().await?;
-            let desc = AdapterDescriptor::new(name);
+            // adapters of the same model may report the same name
+            let occurrence = self
+                .adapter_descriptors
+                .iter()
+                .filter(|desc| desc.get_name() == name)
+                .count();
+            let desc = AdapterDescriptor::new(name)
+                .with_index(index)
+                .with_occurrence(occurrence);
             self.adapter_descriptors.push(desc.clone());
             self.adapters.insert(*desc.get_uuid(), adapter);
         }
+        // the discovery order is kept, so the index shown with the name stays stable
         self.adapter_descriptors
-            .sort_by(|a, b| a.get_uuid().cmp(b.get_uuid()));
+            .sort_by_key(AdapterDescriptor::get_index);
 
         Ok(())
     }
@@ -205,7 +367,12 @@ where
             .iter()
             .find(|d| d.get_uuid() == uuid)
             .ok_or(anyhow!("Adapter not found"))?;
-        self.selected_adapter = Some((desc.clone(), handle.clone()));
+        let selected = (desc.clone(), handle.clone());
+        if self.scanning {
+            // the previous adapter stops scanning before the new one starts
+            self.stop_scan().await?;
+        }
+        self.selected_adapter = Some(selected);
         self.start_scan().await
     }
 
@@ -213,16 +380,25 @@ where
         if self.scanning {
             return Err(anyhow!("Already scanning"));
         }
-        let adapter = self
+        let (_, adapter) = self
             .selected_adapter
             .as_ref()
-            .ok_or(anyhow!("no selected adapter!"))?
-            .1
-            .clone();
+            .ok_or(anyhow!("no selected adapter!"))?;
+        // devices that only answer active scans are not discovered passively
+        adapter.start_scan(self.scan_filter.clone()).await?;
+        let adapter = adapter.clone();
+        self.scanning = true;
         trace!("Scanning started on adapter {}.", adapter.get_name().await?);
         let devices = self.devices.clone();
         if self.peri_updater_handle.is_none() {
-            self.peri_updater_handle = Some(tokio::spawn(Self::adapter_updater(adapter, devices)));
+            self.peri_updater_handle = Some(tokio::spawn(Self::adapter_updater(
+                adapter,
+                devices,
+                self.scan_interval,
+            )));
+        }
+        if self.state == ConnectionState::Idle {
+            self.set_state(ConnectionState::Scanning);
         }
         Ok(())
     }
@@ -241,6 +417,9 @@ where
             updater_handle.abort();
         }
         self.scanning = false;
+        if self.state == ConnectionState::Scanning {
+            self.set_state(ConnectionState::Idle);
+        }
         Ok(())
     }
 
@@ -258,14 +437,22 @@ where
             .as_ref()
             .ok_or(anyhow!("no selected device!"))?
             .clone();
-        self.listener_handle = Some(
-            BluetoothComponent::listen_to_peripheral(
-                adapter.clone(),
-                desc.address,
-                self.event_bus.clone(),
-            )
-            .await?,
-        );
+        let adapter = adapter.clone();
+        self.set_state(ConnectionState::Connecting);
+        let listener = BluetoothComponent::listen_to_peripheral(
+            adapter,
+            desc.address,
+            self.event_bus.clone(),
+            self.reconnect_attempts,
+        )
+        .await;
+        match listener {
+            Ok(handle) => self.listener_handle = Some(handle),
+            Err(e) => {
+                self.set_state(self.disconnected_state());
+                return Err(e);
+            }
+        }
         self.listening = Some(desc.address);
         Ok(())
     }
@@ -274,9 +461,60 @@ where
         if let Some(handle) = &self.listener_handle {
             handle.abort();
             self.listening = None;
+            self.set_state(self.disconnected_state());
+        }
+        Ok(())
+    }
+
+    fn set_scan_interval(&mut self, interval: Duration) {
+        // refreshing too often only loads the adapter
+        self.scan_interval = interval.max(Duration::from_secs_f64(*SCAN_INTERVAL_RANGE.start()));
+    }
+
+    fn set_reconnect_attempts(&mut self, attempts: usize) {
+        self.reconnect_attempts = attempts.clamp(
+            *RECONNECT_ATTEMPTS_RANGE.start(),
+            *RECONNECT_ATTEMPTS_RANGE.end(),
+        );
+    }
+
+    fn set_scan_filter(&mut self, filter: ScanFilter) {
+        self.scan_filter = filter;
+    }
+
+    async fn disconnect(&mut self) -> Result<()> {
+        let (Some((_, adapter)), Some(desc)) = (&self.selected_adapter, &self.selected_device)
+        else {
+            return Ok(());
+        };
+        for peripheral in adapter.peripherals().await? {
+            if peripheral.address() == desc.address && peripheral.is_connected().await? {
+                peripheral.disconnect().await?;
+                trace!("Disconnected from {}.", desc.address);
+            }
+        }
+        if self.listening.is_none() {
+            self.set_state(self.disconnected_state());
         }
         Ok(())
     }
+
+    async fn connection_state_changed(&mut self, state: ConnectionState) -> Result<()> {
+        if self.listening.is_none() {
+            // a late report of a listener that was stopped
+            return Ok(());
+        }
+        self.state = match state {
+            ConnectionState::Connected if self.recording => ConnectionState::Recording,
+            // the first connection failed or the sensor did not reconnect
+            ConnectionState::Idle => {
+                self.listening = None;
+                self.disconnected_state()
+            }
+            state => state,
+        };
+        Ok(())
+    }
 }
 
 impl<A: Central + DisplayName + AdapterDiscovery<A> + Debug + 'static> BluetoothModelApi
@@ -310,6 +548,10 @@ where
     fn is_listening_to(&self) -> Option<BDAddr> {
         self.listening
     }
+
+    fn get_connection_state(&self) -> ConnectionState {
+        self.state
+    }
 }
 
 #[async_trait]
@@ -351,15 +593,34 @@ where
     A::Peripheral: DisplayName,
 {
     async fn start_recording(&mut self) -> Result<()> {
+        self.recording = true;
+        let selected = self.selected_device.as_ref().map(|d| d.address);
+        if self.listening.is_some() && self.listening == selected {
+            // connected ahead of the recording, e.g. for its automatic start
+            if self.state == ConnectionState::Connected {
+                self.set_state(ConnectionState::Recording);
+            }
+            return Ok(());
+        }
         self.start_listening().await
     }
     async fn stop_recording(&mut self) -> Result<()> {
+        self.recording = false;
         self.stop_listening().await
     }
+    async fn pause_recording(&mut self) -> Result<()> {
+        // keep the connection alive, the measurement discards messages while paused
+        Ok(())
+    }
+    async fn resume_recording(&mut self) -> Result<()> {
+        Ok(())
+    }
 }
 #[cfg(test)]
-mod tests {
+pub mod tests {
     use super::*;
+    use crate::core::constants::HEARTRATE_SERVICE_UUID;
+    use crate::model::bluetooth::scan_filter;
     use anyhow::Result;
     use btleplug::{
         api::{
@@ -375,7 +636,7 @@ mod tests {
     use tokio::sync::broadcast;
 
     mock! {
-        Peripheral{}
+        pub Peripheral{}
 
         impl Clone for Peripheral {
             fn clone(&self) -> Self;
@@ -421,7 +682,7 @@ mod tests {
     }
 
     mock! {
-        Adapter{}
+        pub Adapter{}
         impl Clone for Adapter {
             fn clone(&self) -> Self;
         }
@@ -480,19 +741,28 @@ mod tests {
         let mut component = BluetoothComponent::<MockAdapter>::new(tx);
         let _da_ctx = MockAdapter::discover_adapters_context();
         _da_ctx.expect().times(1).returning(|| {
-            let mut adapter = MockAdapter::default();
-            adapter
-                .expect_get_name()
-                .once()
-                .returning(|| Ok("MockAdapter".to_string()));
-            Ok(vec![adapter])
+            // two adapters of the same model
+            let adapters = (0..2)
+                .map(|_| {
+                    let mut adapter = MockAdapter::default();
+                    adapter
+                        .expect_get_name()
+                        .once()
+                        .returning(|| Ok("MockAdapter".to_string()));
+                    adapter
+                })
+                .collect();
+            Ok(adapters)
         });
         assert!(component.discover_adapters().await.is_ok());
-        assert!(!component.adapter_descriptors.is_empty());
-        assert_eq!(
-            component.adapter_descriptors.first().unwrap().get_name(),
-            "MockAdapter"
-        );
+        assert_eq!(component.adapter_descriptors.len(), 2);
+        let [first, second] = &component.adapter_descriptors[..] else {
+            panic!("expected two adapters");
+        };
+        assert_eq!(first.get_name(), "MockAdapter");
+        assert_eq!(second.get_name(), "MockAdapter");
+        // identically named adapters keep separate aliases
+        assert_ne!(first.get_key(), second.get_key());
     }
 
     #[tokio::test]
@@ -504,9 +774,9 @@ mod tests {
             .push(AdapterDescriptor::new("MockAdapter".to_string()));
         let mut adapter = MockAdapter::default();
         let _ = adapter.expect_get_name();
-        let _ = adapter.expect_start_scan().returning(|_| Ok(()));
         let _ = adapter.expect_clone().returning(|| {
             let mut adapter = MockAdapter::default();
+            adapter.expect_start_scan().once().returning(|_| Ok(()));
             adapter.expect_clone().returning(|| {
                 let mut adapter = MockAdapter::default();
                 adapter
@@ -530,6 +800,43 @@ mod tests {
             .await
             .is_ok());
         assert!(component.get_selected_adapter().is_some());
+        assert!(component.is_scanning());
+    }
+
+    #[tokio::test]
+    async fn test_start_and_stop_scan() {
+        let (tx, _rx) = broadcast::channel(16);
+        let mut component = BluetoothComponent::<MockAdapter>::new(tx);
+        assert!(component.start_scan().await.is_err());
+
+        let mut adapter = MockAdapter::default();
+        adapter
+            .expect_start_scan()
+            .once()
+            .withf(|filter| filter.services == [HEARTRATE_SERVICE_UUID])
+            .returning(|_| Ok(()));
+        adapter.expect_stop_scan().once().returning(|| Ok(()));
+        adapter
+            .expect_get_name()
+            .returning(|| Ok("MockAdapter".to_string()));
+        adapter.expect_clone().returning(|| {
+            let mut adapter = MockAdapter::default();
+            adapter.expect_peripherals().returning(|| Ok(vec![]));
+            adapter
+        });
+        component.selected_adapter =
+            Some((AdapterDescriptor::new("MockAdapter".to_string()), adapter));
+        component.set_scan_filter(scan_filter(true));
+
+        assert!(component.start_scan().await.is_ok());
+        assert!(component.is_scanning());
+        assert_eq!(component.get_connection_state(), ConnectionState::Scanning);
+        assert!(component.start_scan().await.is_err());
+
+        assert!(component.stop_scan().await.is_ok());
+        assert!(!component.is_scanning());
+        assert_eq!(component.get_connection_state(), ConnectionState::Idle);
+        assert!(component.stop_scan().await.is_err());
     }
     #[tokio::test]
     async fn test_select_peripheral() {
@@ -591,6 +898,74 @@ mod tests {
         assert!(component.listening.is_some());
     }
 
+    #[tokio::test]
+    async fn test_connection_state_transitions() {
+        let (tx, mut rx) = broadcast::channel(16);
+        let mut component = BluetoothComponent::<MockAdapter>::new(tx);
+        assert_eq!(component.get_connection_state(), ConnectionState::Idle);
+
+        let mut adapter = MockAdapter::default();
+        adapter.expect_clone().returning(|| {
+            let mut adapter = MockAdapter::default();
+            adapter.expect_peripherals().returning(|| {
+                let mut peripheral = get_streaming_peripheral();
+                peripheral.expect_address().returning(BDAddr::default);
+                Ok(vec![peripheral])
+            });
+            adapter
+        });
+        component.selected_adapter =
+            Some((AdapterDescriptor::new("MockAdapter".to_string()), adapter));
+        let device = DeviceDescriptor {
+            name: "TestDevice".to_string(),
+            address: BDAddr::default(),
+        };
+        assert!(component.select_peripheral(device).await.is_ok());
+        assert!(component.start_listening().await.is_ok());
+        assert_eq!(
+            component.get_connection_state(),
+            ConnectionState::Connecting
+        );
+
+        // the transitions are broadcast, the listener reports the connection
+        let mut states = Vec::new();
+        while states.len() < 2 {
+            if let AppEvent::Bluetooth(BluetoothEvent::ConnectionStateChanged(state)) =
+                rx.recv().await.unwrap()
+            {
+                states.push(state);
+            }
+        }
+        assert_eq!(
+            states,
+            vec![ConnectionState::Connecting, ConnectionState::Connected]
+        );
+        assert!(component
+            .connection_state_changed(ConnectionState::Connected)
+            .await
+            .is_ok());
+        assert_eq!(component.get_connection_state(), ConnectionState::Connected);
+
+        assert!(component.stop_listening().await.is_ok());
+        assert_eq!(component.get_connection_state(), ConnectionState::Idle);
+        // reports of the stopped listener are ignored
+        assert!(component
+            .connection_state_changed(ConnectionState::Connected)
+            .await
+            .is_ok());
+        assert_eq!(component.get_connection_state(), ConnectionState::Idle);
+
+        // a sensor that did not reconnect is not listened to anymore
+        component.listening = Some(BDAddr::default());
+        component.state = ConnectionState::Connecting;
+        assert!(component
+            .connection_state_changed(ConnectionState::Idle)
+            .await
+            .is_ok());
+        assert_eq!(component.get_connection_state(), ConnectionState::Idle);
+        assert!(component.is_listening_to().is_none());
+    }
+
     #[tokio::test]
     async fn test_stop_listening() {
         let (tx, _rx) = broadcast::channel(16);
@@ -605,13 +980,38 @@ mod tests {
         assert!(component.listening.is_none());
     }
 
+    #[tokio::test]
+    async fn test_disconnect() {
+        let (tx, _rx) = broadcast::channel(16);
+        let mut component = BluetoothComponent::<MockAdapter>::new(tx);
+        // nothing selected, nothing to disconnect
+        assert!(component.disconnect().await.is_ok());
+
+        let mut adapter = MockAdapter::default();
+        adapter.expect_peripherals().once().returning(|| {
+            let mut peripheral = MockPeripheral::default();
+            peripheral.expect_address().returning(BDAddr::default);
+            peripheral.expect_is_connected().returning(|| Ok(true));
+            peripheral.expect_disconnect().once().returning(|| Ok(()));
+            Ok(vec![peripheral])
+        });
+        component.selected_adapter =
+            Some((AdapterDescriptor::new("MockAdapter".to_string()), adapter));
+        component.selected_device = Some(DeviceDescriptor {
+            name: "TestDevice".to_string(),
+            address: BDAddr::default(),
+        });
+
+        assert!(component.disconnect().await.is_ok());
+    }
+
     #[tokio::test]
     async fn test_stop_scan() {
         let (tx, _rx) = broadcast::channel(16);
         let mut component = BluetoothComponent::<MockAdapter>::new(tx);
 
         let mut adapter = MockAdapter::default();
-        adapter.expect_stop_scan().returning(|| Ok(()));
+        adapter.expect_stop_scan().once().returning(|| Ok(()));
         adapter
             .expect_get_name()
             .returning(|| Ok("MockAdapter".to_string()));
@@ -623,9 +1023,8 @@ mod tests {
         assert!(component.stop_scan().await.is_ok());
         assert!(!component.scanning);
     }
-    #[tokio::test]
-    async fn test_peripheral_listener() {
-        let (tx, _rx) = broadcast::channel(16);
+    /// Creates a peripheral that accepts connections and heart rate subscriptions.
+    fn get_subscribable_peripheral() -> MockPeripheral {
         let mut peripheral = MockPeripheral::default();
 
         // Set up expectations for peripheral
@@ -642,6 +1041,12 @@ mod tests {
             chars
         });
         peripheral.expect_subscribe().returning(|_| Ok(()));
+        peripheral
+    }
+
+    /// Creates a peripheral whose connections each stream one heartrate message.
+    fn get_streaming_peripheral() -> MockPeripheral {
+        let mut peripheral = get_subscribable_peripheral();
 
         // Create notification stream that sends one heartrate message
         peripheral.expect_notifications().returning(|| {
@@ -652,9 +1057,202 @@ mod tests {
                 }
             })))
         });
+        peripheral
+    }
+
+    /// Creates a component with a selected adapter whose peripheral stays connected.
+    ///
+    /// # Arguments
+    /// - `tx`: The event bus the component broadcasts to.
+    pub fn get_connectable_component(tx: Sender<AppEvent>) -> BluetoothComponent<MockAdapter> {
+        let mut component = BluetoothComponent::<MockAdapter>::new(tx);
+        let mut adapter = MockAdapter::default();
+        adapter.expect_clone().returning(|| {
+            let mut adapter = MockAdapter::default();
+            adapter.expect_peripherals().returning(|| {
+                let mut peripheral = get_subscribable_peripheral();
+                peripheral.expect_address().returning(BDAddr::default);
+                peripheral
+                    .expect_notifications()
+                    .returning(|| Ok(Box::pin(futures::stream::pending())));
+                Ok(vec![peripheral])
+            });
+            adapter
+        });
+        component.selected_adapter =
+            Some((AdapterDescriptor::new("MockAdapter".to_string()), adapter));
+        component
+    }
+
+    #[tokio::test]
+    async fn test_start_recording_keeps_connection() {
+        let (tx, _rx) = broadcast::channel(16);
+        let mut component = get_connectable_component(tx);
+        let device = DeviceDescriptor {
+            name: "TestDevice".to_string(),
+            address: BDAddr::default(),
+        };
+        assert!(component.select_peripheral(device).await.is_ok());
+        assert!(component.start_listening().await.is_ok());
+        assert!(component
+            .connection_state_changed(ConnectionState::Connected)
+            .await
+            .is_ok());
+        assert_eq!(component.get_connection_state(), ConnectionState::Connected);
+        let listener = component.listener_handle.as_ref().unwrap().abort_handle();
+
+        // the recording continues on the established connection
+        assert!(component.start_recording().await.is_ok());
+        assert_eq!(component.get_connection_state(), ConnectionState::Recording);
+        tokio::task::yield_now().await;
+        assert!(!listener.is_finished());
+        assert!(component
+            .connection_state_changed(ConnectionState::Connected)
+            .await
+            .is_ok());
+        assert_eq!(component.get_connection_state(), ConnectionState::Recording);
+    }
+
+    #[tokio::test]
+    async fn test_peripheral_listener() {
+        let (tx, rx) = broadcast::channel(16);
+        drop(rx);
+        let result = BluetoothComponent::<MockAdapter>::peripheral_listener(
+            get_streaming_peripheral(),
+            tx,
+            Duration::ZERO,
+            1,
+        )
+        .await;
+        assert!(result.is_err()); // Should error when nobody receives the events
+    }
+
+    #[tokio::test]
+    async fn test_peripheral_listener_reconnects() {
+        let (tx, mut rx) = broadcast::channel(16);
+        let handle = tokio::spawn(BluetoothComponent::<MockAdapter>::peripheral_listener(
+            get_streaming_peripheral(),
+            tx,
+            Duration::from_millis(10),
+            3,
+        ));
+        let mut events = Vec::new();
+        for _ in 0..7 {
+            events.push(rx.recv().await.unwrap());
+        }
+        assert!(matches!(
+            events.as_slice(),
+            [
+                AppEvent::Bluetooth(BluetoothEvent::ConnectionStateChanged(
+                    ConnectionState::Connected
+                )),
+                AppEvent::Measurement(MeasurementEvent::RecordMessage(_)),
+                AppEvent::Measurement(MeasurementEvent::MarkDisconnected),
+                AppEvent::Bluetooth(BluetoothEvent::ConnectionStateChanged(
+                    ConnectionState::Connecting
+                )),
+                AppEvent::Bluetooth(BluetoothEvent::ConnectionStateChanged(
+                    ConnectionState::Connected
+                )),
+                AppEvent::Measurement(MeasurementEvent::MarkReconnected),
+                AppEvent::Measurement(MeasurementEvent::RecordMessage(_)),
+            ]
+        ));
+        // the listener stops once nobody receives the events
+        drop(rx);
+        let result = tokio::time::timeout(Duration::from_secs(1), handle).await;
+        assert!(result.unwrap().unwrap().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_peripheral_listener_drops_malformed_notifications() {
+        let (tx, mut rx) = broadcast::channel(16);
+        let mut peripheral = get_subscribable_peripheral();
+        peripheral.expect_notifications().returning(|| {
+            let notifications = [vec![0], vec![0, 60]].map(|value| ValueNotification {
+                uuid: HEARTRATE_MEASUREMENT_UUID,
+                value,
+            });
+            Ok(Box::pin(
+                futures::stream::iter(notifications).chain(futures::stream::pending()),
+            ))
+        });
+        let handle = tokio::spawn(BluetoothComponent::<MockAdapter>::peripheral_listener(
+            peripheral,
+            tx,
+            Duration::from_millis(10),
+            3,
+        ));
+        assert!(matches!(
+            rx.recv().await.unwrap(),
+            AppEvent::Bluetooth(BluetoothEvent::ConnectionStateChanged(
+                ConnectionState::Connected
+            ))
+        ));
+        assert!(matches!(
+            rx.recv().await.unwrap(),
+            AppEvent::Measurement(MeasurementEvent::RecordMessage(_))
+        ));
+        // the connection is kept
+        assert!(tokio::time::timeout(Duration::from_millis(100), rx.recv())
+            .await
+            .is_err());
+        handle.abort();
+    }
 
-        let result = BluetoothComponent::<MockAdapter>::peripheral_listener(peripheral, tx).await;
-        assert!(result.is_err()); // Should error when stream ends
+    #[tokio::test]
+    async fn test_peripheral_listener_gives_up_reconnecting() {
+        let (tx, mut rx) = broadcast::channel(16);
+        let mut peripheral = MockPeripheral::default();
+        let mut connections = 0;
+        peripheral.expect_connect().returning(move || {
+            connections += 1;
+            if connections == 1 {
+                Ok(())
+            } else {
+                Err(btleplug::Error::NotConnected)
+            }
+        });
+        peripheral.expect_discover_services().returning(|| Ok(()));
+        peripheral.expect_characteristics().returning(|| {
+            BTreeSet::from([Characteristic {
+                uuid: HEARTRATE_MEASUREMENT_UUID,
+                service_uuid: Uuid::nil(),
+                descriptors: BTreeSet::new(),
+                properties: Default::default(),
+            }])
+        });
+        peripheral.expect_subscribe().returning(|_| Ok(()));
+        peripheral
+            .expect_notifications()
+            .returning(|| Ok(Box::pin(futures::stream::empty())));
+        let result = BluetoothComponent::<MockAdapter>::peripheral_listener(
+            peripheral,
+            tx,
+            Duration::from_millis(1),
+            2,
+        )
+        .await;
+        assert!(result.is_err());
+        let mut events = Vec::new();
+        while let Ok(event) = rx.try_recv() {
+            events.push(event);
+        }
+        assert!(matches!(
+            events.as_slice(),
+            [
+                AppEvent::Bluetooth(BluetoothEvent::ConnectionStateChanged(
+                    ConnectionState::Connected
+                )),
+                AppEvent::Measurement(MeasurementEvent::MarkDisconnected),
+                AppEvent::Bluetooth(BluetoothEvent::ConnectionStateChanged(
+                    ConnectionState::Connecting
+                )),
+                AppEvent::Bluetooth(BluetoothEvent::ConnectionStateChanged(
+                    ConnectionState::Idle
+                )),
+            ]
+        ));
     }
 
     #[tokio::test]
@@ -676,7 +1274,9 @@ mod tests {
 
         // Run adapter_updater (will be cancelled by handle)
         let hnd2 = tokio::spawn(BluetoothComponent::<MockAdapter>::adapter_updater(
-            adapter, devices,
+            adapter,
+            devices,
+            Duration::from_secs(2),
         ));
         let handle = tokio::spawn(async move {
             // Give adapter_updater time to run one iteration
@@ -693,4 +1293,61 @@ mod tests {
         handle.await.unwrap();
         hnd2.abort();
     }
+
+    #[tokio::test]
+    async fn test_adapter_updater_refreshes_immediately() {
+        let devices = Arc::new(RwLock::new(Vec::<DeviceDescriptor>::new()));
+        let mut adapter = MockAdapter::default();
+        adapter.expect_peripherals().once().returning(|| {
+            let mut peripheral = MockPeripheral::default();
+            peripheral.expect_address().returning(BDAddr::default);
+            peripheral
+                .expect_get_name()
+                .returning(|| Ok("TestDevice".to_string()));
+            Ok(vec![peripheral])
+        });
+
+        // the first refresh does not wait for the interval
+        let updater = tokio::spawn(BluetoothComponent::<MockAdapter>::adapter_updater(
+            adapter,
+            devices.clone(),
+            Duration::from_secs(60),
+        ));
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert_eq!(devices.read().await.len(), 1);
+        updater.abort();
+    }
+
+    #[test]
+    fn test_set_scan_interval() {
+        let (tx, _rx) = broadcast::channel(16);
+        let mut component = BluetoothComponent::<MockAdapter>::new(tx);
+        assert_eq!(
+            component.scan_interval,
+            Duration::from_secs_f64(DEFAULT_SCAN_INTERVAL)
+        );
+        component.set_scan_interval(Duration::from_secs(10));
+        assert_eq!(component.scan_interval, Duration::from_secs(10));
+        // too short intervals are clamped
+        component.set_scan_interval(Duration::ZERO);
+        assert_eq!(
+            component.scan_interval,
+            Duration::from_secs_f64(*SCAN_INTERVAL_RANGE.start())
+        );
+    }
+
+    #[test]
+    fn test_set_reconnect_attempts() {
+        let (tx, _rx) = broadcast::channel(16);
+        let mut component = BluetoothComponent::<MockAdapter>::new(tx);
+        assert_eq!(component.reconnect_attempts, DEFAULT_RECONNECT_ATTEMPTS);
+        component.set_reconnect_attempts(5);
+        assert_eq!(component.reconnect_attempts, 5);
+        // at least one attempt is made
+        component.set_reconnect_attempts(0);
+        assert_eq!(
+            component.reconnect_attempts,
+            *RECONNECT_ATTEMPTS_RANGE.start()
+        );
+    }
 }
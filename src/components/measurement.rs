@@ -3,15 +3,61 @@ use crate::{
         controller::{MeasurementApi, OutlierFilter, RecordingApi},
         model::MeasurementModelApi,
     },
-    model::{bluetooth::HeartrateMessage, hrv::HrvAnalysisData},
+    model::{
+        bluetooth::HeartrateMessage,
+        hrv::{
+            tune_outlier_scale, AnalysisState, CorrectionEntry, HrvAnalysisData, HrvConfig,
+            HrvSummary, PoincareEllipse, SettlingFilter, OUTLIER_SCALE_RANGE,
+        },
+        metadata::MeasurementMetadata,
+        metrics::{
+            pnnx_name, DetrendOption, DfaConfig, DifferenceMode, MetricSeries, Pnnx,
+            PNN_DEFAULT_THRESHOLD, PNN_THRESHOLD_RANGE,
+        },
+        settings::MarkerInterval,
+    },
 };
 use anyhow::Result;
 use async_trait::async_trait;
 use log::warn;
 use serde::{Deserialize, Deserializer, Serialize};
 use std::fmt::Debug;
+use std::sync::{Arc, OnceLock};
 use time::{Duration, OffsetDateTime};
 
+/// Sums the energy expended reported by the sensor over a recording.
+///
+/// The sensor reports the energy expended since its counter was last reset, a decreasing value
+/// indicates a reset. The counter saturates at its maximum value.
+#[derive(Debug, Clone, Default, PartialEq)]
+struct EnergyAccumulator {
+    /// Most recently reported counter value.
+    last: Option<u16>,
+    /// Energy expended since the first reported value in kJ.
+    total: f64,
+}
+
+impl EnergyAccumulator {
+    /// Adds the counter value of a message, messages without energy expended are ignored.
+    fn add(&mut self, msg: &HeartrateMessage) {
+        if !msg.has_energy_exp() {
+            return;
+        }
+        let value = msg.get_energy_exp() as u16;
+        if let Some(last) = self.last {
+            // after a reset the counter starts at zero again
+            let delta = if value >= last { value - last } else { value };
+            self.total += f64::from(delta);
+        }
+        self.last = Some(value);
+    }
+
+    /// Returns the accumulated energy in kJ, `None` if no message reported energy expended.
+    fn total(&self) -> Option<f64> {
+        self.last.map(|_| self.total)
+    }
+}
+
 /// Represents the acquisition model, managing HRV-related data and operations.
 #[derive(Serialize, Debug, Clone)]
 pub struct MeasurementData {
@@ -23,11 +69,63 @@ pub struct MeasurementData {
     window: Option<usize>,
     /// Outlier filter threshold.
     outlier_filter: f64,
-    /// Processed session data.
+    /// Box sizes of the DFA 1 alpha.
+    dfa: DfaConfig,
+    /// Optional `(start, end)` elapsed time range restricting the analysis.
+    range: Option<(Duration, Duration)>,
+    /// `(start, end)` elapsed times of the pauses in the recording.
+    gaps: Vec<(Duration, Duration)>,
+    /// Settling period at the start of the recording excluded from the analysis.
+    skip_initial: Duration,
+    /// Number of RR intervals discarded after every gap.
+    blank_after_gap: usize,
+    /// Number of successive identical RR intervals that are kept, `None` keeps all.
+    max_identical_run: Option<usize>,
+    /// Threshold of the pNNx in ms.
+    pnn_threshold: f64,
+    /// Trend removed from the RR intervals before the statistics are calculated.
+    detrend: DetrendOption,
+    /// Pairing of the inlier RR intervals for the successive differences.
+    differences: DifferenceMode,
+    /// Separate outlier filter scale of the settling phase, `None` uses one scale throughout.
+    settling_filter: Option<SettlingFilter>,
+    /// Number of events lost while recording.
+    dropped_events: u64,
+    /// `(elapsed time, label)` of the markers added during the recording.
+    markers: Vec<(Duration, String)>,
+    /// Subject information, `None` if not entered.
+    metadata: Option<MeasurementMetadata>,
+    /// Whether the measurement is left out of the aggregates of the stored measurements.
+    excluded: bool,
+    /// Energy expended during the recording.
+    #[serde(skip)]
+    energy: EnergyAccumulator,
+    /// Processed session data, calculated on first access after loading.
     #[serde(skip)]
-    sessiondata: HrvAnalysisData,
+    sessiondata: OnceLock<HrvAnalysisData>,
+    /// Number of changes of the measurement.
+    #[serde(skip)]
+    revision: u64,
     #[serde(skip)]
     is_recording: bool,
+    /// Elapsed time at which the recording was paused, if paused.
+    #[serde(skip)]
+    paused_at: Option<Duration>,
+    /// Elapsed time at which the connection to the sensor was lost, if disconnected.
+    #[serde(skip)]
+    disconnected_at: Option<Duration>,
+    /// Interval of the markers inserted automatically while recording.
+    #[serde(skip)]
+    auto_markers: Option<MarkerInterval>,
+    /// Number of markers inserted automatically.
+    #[serde(skip)]
+    auto_marker_count: usize,
+    /// Number of RR intervals of the recording, counted for the beat based markers.
+    #[serde(skip)]
+    recorded_beats: usize,
+    /// Speed the messages arrive at as multiple of real time, above 1 for fast replays.
+    #[serde(skip)]
+    replay_speed: f64,
 }
 
 impl MeasurementData {
@@ -36,18 +134,113 @@ impl MeasurementData {
     /// # Returns
     /// A result indicating success or failure.
     fn update(&mut self) -> Result<()> {
-        match HrvAnalysisData::from_acquisition(
-            &self.measurements,
-            self.window,
-            self.outlier_filter,
-        ) {
-            Ok(data) => self.sessiondata = data,
+        self.revision += 1;
+        match self.analyse() {
+            Ok(data) => self.sessiondata = OnceLock::from(data),
             Err(e) => {
                 warn!("could not calculate session data: {}", e);
             }
         }
         Ok(())
     }
+
+    /// Records the time the sensor was disconnected as gap, if it was disconnected.
+    fn close_disconnect_gap(&mut self) {
+        if let Some(disconnected_at) = self.disconnected_at.take() {
+            self.gaps.push((disconnected_at, self.now_elapsed()));
+            // events may have been dropped while the sensor was disconnected
+            self.gaps.sort_by_key(|gap| gap.0);
+            self.session_mut().mark_gap();
+        }
+    }
+
+    /// Returns the session data, analysing the measurements on first access.
+    fn session(&self) -> &HrvAnalysisData {
+        self.sessiondata.get_or_init(|| {
+            self.analyse().unwrap_or_else(|e| {
+                warn!("could not calculate session data: {}", e);
+                HrvAnalysisData::default()
+            })
+        })
+    }
+
+    /// Returns the session data for updating, analysing the measurements on first access.
+    fn session_mut(&mut self) -> &mut HrvAnalysisData {
+        self.revision += 1;
+        self.session();
+        self.sessiondata
+            .get_mut()
+            .expect("session data is initialized")
+    }
+
+    /// Analyses the measurements with the current parameters.
+    fn analyse(&self) -> Result<HrvAnalysisData> {
+        let mut data = HrvAnalysisData::from_acquisition(
+            &self.measurements,
+            &self.gaps,
+            &self.get_hrv_config(),
+            self.range,
+            self.skip_initial,
+        )?;
+        if self.pnn_threshold != PNN_DEFAULT_THRESHOLD {
+            data.swap_metric(
+                &pnnx_name(PNN_DEFAULT_THRESHOLD),
+                Arc::new(Pnnx::new(self.pnn_threshold)),
+                self.window.unwrap_or(usize::MAX),
+            )?;
+        }
+        if self.detrend != DetrendOption::None {
+            data.set_detrend(self.detrend, self.window.unwrap_or(usize::MAX))?;
+        }
+        if self.differences != DifferenceMode::AcrossOutliers {
+            data.set_differences(self.differences, self.window.unwrap_or(usize::MAX))?;
+        }
+        if self.settling_filter.is_some() {
+            data.set_settling_filter(self.settling_filter, self.window.unwrap_or(usize::MAX))?;
+        }
+        Ok(data)
+    }
+
+    /// Returns the time elapsed since the start of the recording, scaled by the replay speed.
+    fn now_elapsed(&self) -> Duration {
+        (OffsetDateTime::now_utc() - self.start_time) * self.replay_speed
+    }
+
+    /// Inserts the automatic markers due up to `elapsed`.
+    ///
+    /// Time based markers are placed at the multiples of their period, beat based markers
+    /// at the message completing their number of beats.
+    fn add_auto_markers(&mut self, elapsed: Duration) {
+        let Some(interval) = self.auto_markers else {
+            return;
+        };
+        loop {
+            let next = self.auto_marker_count + 1;
+            let at = match interval {
+                MarkerInterval::Seconds(period) => {
+                    let at = Duration::seconds((period as usize * next) as i64);
+                    if at > elapsed {
+                        return;
+                    }
+                    at
+                }
+                MarkerInterval::Beats(beats) => {
+                    if self.recorded_beats < beats * next {
+                        return;
+                    }
+                    elapsed
+                }
+            };
+            self.markers.push((at, format!("auto {}", next)));
+            self.auto_marker_count = next;
+        }
+    }
+
+    /// Checks if a measurement received at `elapsed` lies within the analysis range.
+    fn is_in_range(&self, elapsed: &Duration) -> bool {
+        self.range
+            .is_none_or(|(start, end)| (start..=end).contains(elapsed))
+    }
 }
 
 impl Default for MeasurementData {
@@ -56,13 +249,40 @@ impl Default for MeasurementData {
             start_time: OffsetDateTime::now_utc(),
             measurements: Vec::new(),
             window: None,
-            outlier_filter: 5.0,
-            sessiondata: Default::default(),
+            outlier_filter: HrvConfig::default().outlier_filter,
+            dfa: DfaConfig::default(),
+            range: None,
+            gaps: Vec::new(),
+            skip_initial: Duration::ZERO,
+            blank_after_gap: 0,
+            max_identical_run: None,
+            pnn_threshold: PNN_DEFAULT_THRESHOLD,
+            detrend: DetrendOption::None,
+            differences: DifferenceMode::AcrossOutliers,
+            settling_filter: None,
+            dropped_events: 0,
+            markers: Vec::new(),
+            metadata: None,
+            excluded: false,
+            energy: EnergyAccumulator::default(),
+            sessiondata: OnceLock::from(HrvAnalysisData::default()),
+            revision: 0,
             is_recording: false,
+            paused_at: None,
+            disconnected_at: None,
+            auto_markers: None,
+            auto_marker_count: 0,
+            recorded_beats: 0,
+            replay_speed: 1.0,
         }
     }
 }
 
+/// Threshold of the pNNx of measurements stored without one.
+fn default_pnn_threshold() -> f64 {
+    PNN_DEFAULT_THRESHOLD
+}
+
 impl<'de> Deserialize<'de> for MeasurementData {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
@@ -74,25 +294,72 @@ impl<'de> Deserialize<'de> for MeasurementData {
             measurements: Vec<(Duration, HeartrateMessage)>,
             window: Option<usize>,
             outlier_filter: f64,
+            #[serde(default)]
+            dfa: DfaConfig,
+            #[serde(default)]
+            range: Option<(Duration, Duration)>,
+            #[serde(default)]
+            gaps: Vec<(Duration, Duration)>,
+            #[serde(default)]
+            skip_initial: Duration,
+            #[serde(default)]
+            blank_after_gap: usize,
+            #[serde(default)]
+            max_identical_run: Option<usize>,
+            #[serde(default = "default_pnn_threshold")]
+            pnn_threshold: f64,
+            #[serde(default)]
+            detrend: DetrendOption,
+            #[serde(default)]
+            differences: DifferenceMode,
+            #[serde(default)]
+            settling_filter: Option<SettlingFilter>,
+            #[serde(default)]
+            dropped_events: u64,
+            #[serde(default)]
+            markers: Vec<(Duration, String)>,
+            #[serde(default)]
+            metadata: Option<MeasurementMetadata>,
+            #[serde(default)]
+            excluded: bool,
         }
-        // Deserialize all fields except `sessiondata`
+        // Deserialize all fields except `sessiondata`, which is calculated on first access
         let helper = AcquisitionModelHelper::deserialize(deserializer)?;
 
-        // Reconstruct `sessiondata` from the `measurements`
-        let sessiondata = HrvAnalysisData::from_acquisition(
-            &helper.measurements,
-            helper.window,
-            helper.outlier_filter,
-        )
-        .map_err(serde::de::Error::custom)?;
+        let mut energy = EnergyAccumulator::default();
+        for (_, msg) in &helper.measurements {
+            energy.add(msg);
+        }
 
         Ok(MeasurementData {
             start_time: helper.start_time,
             measurements: helper.measurements,
             window: helper.window,
             outlier_filter: helper.outlier_filter,
-            sessiondata,
+            dfa: helper.dfa,
+            range: helper.range,
+            gaps: helper.gaps,
+            skip_initial: helper.skip_initial,
+            blank_after_gap: helper.blank_after_gap,
+            max_identical_run: helper.max_identical_run,
+            pnn_threshold: helper.pnn_threshold,
+            detrend: helper.detrend,
+            differences: helper.differences,
+            settling_filter: helper.settling_filter,
+            dropped_events: helper.dropped_events,
+            markers: helper.markers,
+            metadata: helper.metadata,
+            excluded: helper.excluded,
+            energy,
+            sessiondata: OnceLock::new(),
+            revision: 0,
             is_recording: false,
+            paused_at: None,
+            disconnected_at: None,
+            auto_markers: None,
+            auto_marker_count: 0,
+            recorded_beats: 0,
+            replay_speed: 1.0,
         })
     }
 }
@@ -109,23 +376,251 @@ impl MeasurementApi for MeasurementData {
                 parameter,
                 _window: _,
             } => {
+                if !OUTLIER_SCALE_RANGE.contains(&parameter) {
+                    return Err(anyhow::anyhow!(
+                        "invalid outlier filter scale: {}",
+                        parameter
+                    ));
+                }
                 self.outlier_filter = parameter;
             }
         }
         self.update()
     }
+    async fn set_analysis_range(&mut self, range: Option<(Duration, Duration)>) -> Result<()> {
+        if let Some((start, end)) = range {
+            if start.is_negative() || start >= end {
+                return Err(anyhow::anyhow!(
+                    "invalid analysis range: {} - {}",
+                    start,
+                    end
+                ));
+            }
+        }
+        self.range = range;
+        self.update()
+    }
+    async fn set_hrv_config(&mut self, config: HrvConfig) -> Result<()> {
+        config.validate()?;
+        self.window = config.window;
+        self.outlier_filter = config.outlier_filter;
+        self.dfa = config.dfa;
+        self.blank_after_gap = config.blank_after_gap;
+        self.max_identical_run = config.max_identical_run;
+        self.update()
+    }
+    async fn set_skip_initial(&mut self, skip: Duration) -> Result<()> {
+        if skip.is_negative() {
+            return Err(anyhow::anyhow!("invalid settling period: {}", skip));
+        }
+        self.skip_initial = skip;
+        self.update()
+    }
+    async fn set_pnn_threshold(&mut self, threshold: f64) -> Result<()> {
+        if !PNN_THRESHOLD_RANGE.contains(&threshold) {
+            return Err(anyhow::anyhow!("invalid pNNx threshold: {}", threshold));
+        }
+        self.pnn_threshold = threshold;
+        self.update()
+    }
+    async fn set_detrend(&mut self, detrend: DetrendOption) -> Result<()> {
+        detrend.validate()?;
+        self.detrend = detrend;
+        self.update()
+    }
+    async fn set_differences(&mut self, differences: DifferenceMode) -> Result<()> {
+        self.differences = differences;
+        self.update()
+    }
+    async fn set_settling_filter(&mut self, settling: Option<SettlingFilter>) -> Result<()> {
+        if let Some(settling) = settling {
+            settling.validate()?;
+        }
+        self.settling_filter = settling;
+        self.update()
+    }
     async fn record_message(&mut self, msg: HeartrateMessage) -> Result<()> {
         if self.is_recording {
-            let elapsed = OffsetDateTime::now_utc() - self.start_time;
+            if self.paused_at.is_some() {
+                // the sensor keeps streaming while paused, drop the messages
+                return Ok(());
+            }
+            // data only arrives after a reconnect, even if its notification was lost
+            self.close_disconnect_gap();
+            let elapsed = self.now_elapsed();
+            self.energy.add(&msg);
             self.measurements.push((elapsed, msg));
-            self.sessiondata
-                .add_measurement(&msg, self.window.unwrap_or(usize::MAX))
+            self.recorded_beats += msg.get_rr_intervals().len();
+            self.revision += 1;
+            self.add_auto_markers(elapsed);
+            if !self.is_in_range(&elapsed) {
+                return Ok(());
+            }
+            let window = self.window.unwrap_or(usize::MAX);
+            self.session_mut().add_measurement(elapsed, &msg, window)
         } else {
             Err(anyhow::anyhow!(
                 "RecordMessage event received while not recording"
             ))
         }
     }
+    async fn auto_tune_outlier_scale(&mut self, target_ratio: f64) -> Result<()> {
+        // the preprocessing does not depend on the scale, so the analysed series is reused
+        let rr = self.session().get_filter_input().to_vec();
+        self.outlier_filter = tune_outlier_scale(&rr, target_ratio)?;
+        self.update()
+    }
+    fn prepare_analysis(&self) {
+        self.session();
+    }
+    async fn add_marker(&mut self, label: String) -> Result<()> {
+        if !self.is_recording {
+            return Err(anyhow::anyhow!("markers can only be added while recording"));
+        }
+        let elapsed = self.now_elapsed();
+        self.markers.push((elapsed, label));
+        self.revision += 1;
+        Ok(())
+    }
+    async fn set_metadata(&mut self, metadata: Option<MeasurementMetadata>) -> Result<()> {
+        if let Some(metadata) = metadata.as_ref() {
+            metadata.validate()?;
+        }
+        self.metadata = metadata.filter(|metadata| !metadata.is_empty());
+        self.revision += 1;
+        Ok(())
+    }
+    async fn set_excluded(&mut self, excluded: bool) -> Result<()> {
+        self.excluded = excluded;
+        self.revision += 1;
+        Ok(())
+    }
+    async fn mark_disconnected(&mut self) -> Result<()> {
+        if self.is_recording && self.paused_at.is_none() && self.disconnected_at.is_none() {
+            self.disconnected_at = Some(self.now_elapsed());
+            self.revision += 1;
+        }
+        Ok(())
+    }
+    async fn mark_reconnected(&mut self) -> Result<()> {
+        self.close_disconnect_gap();
+        self.revision += 1;
+        Ok(())
+    }
+    fn report_dropped_events(&mut self, count: u64) {
+        if !self.is_recording || self.paused_at.is_some() {
+            return;
+        }
+        self.dropped_events += count;
+        let now = self.now_elapsed();
+        self.gaps.push((now, now));
+        self.session_mut().mark_gap();
+    }
+    fn set_auto_markers(&mut self, interval: Option<MarkerInterval>) {
+        self.auto_markers = interval;
+    }
+    fn set_replay_speed(&mut self, speed: f64) {
+        self.replay_speed = speed;
+    }
+    fn import_messages(&mut self, messages: Vec<(Duration, HeartrateMessage)>) -> Result<()> {
+        if self.is_recording {
+            return Err(anyhow::anyhow!("cannot import into an active recording"));
+        }
+        self.energy = EnergyAccumulator::default();
+        for (_, msg) in &messages {
+            self.energy.add(msg);
+        }
+        self.measurements = messages;
+        self.gaps.clear();
+        self.dropped_events = 0;
+        self.update()
+    }
+    fn append_measurement(&mut self, other: &Self) -> Result<()> {
+        if self.is_recording || other.is_recording {
+            return Err(anyhow::anyhow!("cannot merge an active recording"));
+        }
+        let end = self.get_elapsed_time();
+        let offset = other.start_time - self.start_time;
+        if offset < end {
+            return Err(anyhow::anyhow!("measurements overlap"));
+        }
+        for (_, msg) in &other.measurements {
+            self.energy.add(msg);
+        }
+        self.measurements.extend(
+            other
+                .measurements
+                .iter()
+                .map(|(elapsed, msg)| (*elapsed + offset, *msg)),
+        );
+        self.gaps.push((end, offset));
+        self.gaps.extend(
+            other
+                .gaps
+                .iter()
+                .map(|(start, gap_end)| (*start + offset, *gap_end + offset)),
+        );
+        self.dropped_events += other.dropped_events;
+        self.markers.extend(
+            other
+                .markers
+                .iter()
+                .map(|(elapsed, label)| (*elapsed + offset, label.clone())),
+        );
+        self.update()
+    }
+    fn split_off(&mut self, at: Duration) -> Result<Self> {
+        if self.is_recording {
+            return Err(anyhow::anyhow!("cannot split an active recording"));
+        }
+        let idx = self
+            .measurements
+            .partition_point(|(elapsed, _)| *elapsed <= at);
+        if idx == 0 || idx == self.measurements.len() {
+            return Err(anyhow::anyhow!("split point outside the recording: {}", at));
+        }
+        let mut later = self.clone();
+        later.start_time = self.start_time + at;
+        later.measurements = self
+            .measurements
+            .split_off(idx)
+            .into_iter()
+            .map(|(elapsed, msg)| (elapsed - at, msg))
+            .collect();
+        // a gap containing the split point ends the first part and starts the later one
+        later.gaps = self
+            .gaps
+            .iter()
+            .filter(|(_, end)| *end > at)
+            .map(|(start, end)| ((*start - at).max(Duration::ZERO), *end - at))
+            .collect();
+        self.gaps.retain(|(start, _)| *start < at);
+        for (_, end) in self.gaps.iter_mut() {
+            *end = (*end).min(at);
+        }
+        later.markers = self
+            .markers
+            .iter()
+            .filter(|(elapsed, _)| *elapsed > at)
+            .map(|(elapsed, label)| (*elapsed - at, label.clone()))
+            .collect();
+        self.markers.retain(|(elapsed, _)| *elapsed <= at);
+        // the settling period and the analysis range refer to the original recording
+        later.skip_initial = Duration::ZERO;
+        later.range = None;
+        self.range = None;
+        // the lost events are not timed, they stay with the first part
+        later.dropped_events = 0;
+        for part in [&mut *self, &mut later] {
+            part.energy = EnergyAccumulator::default();
+            for (_, msg) in &part.measurements {
+                part.energy.add(msg);
+            }
+        }
+        later.update()?;
+        self.update()?;
+        Ok(later)
+    }
 }
 
 impl MeasurementModelApi for MeasurementData {
@@ -143,51 +638,172 @@ impl MeasurementModelApi for MeasurementData {
     fn get_outlier_filter_value(&self) -> f64 {
         self.outlier_filter
     }
+    fn get_artifact_ratio(&self) -> f64 {
+        self.session().get_artifact_ratio()
+    }
+    fn get_completeness(&self) -> Option<f64> {
+        self.session().get_completeness()
+    }
+    fn get_inlier_count(&self) -> usize {
+        self.session().get_inlier_count()
+    }
+    fn get_rr_differences(&self) -> Vec<f64> {
+        self.session()
+            .get_successive_differences(self.window)
+            .unwrap_or_default()
+    }
     fn get_poincare_points(&self) -> Result<(Vec<[f64; 2]>, Vec<[f64; 2]>)> {
-        self.sessiondata.get_poincare(self.window)
+        self.session().get_poincare(self.window)
+    }
+    fn get_poincare_ellipse(&self) -> Result<PoincareEllipse> {
+        self.session().get_poincare_ellipse(self.window)
     }
 
     fn get_start_time(&self) -> &OffsetDateTime {
         &self.start_time
     }
+    fn get_hrv_config(&self) -> HrvConfig {
+        HrvConfig {
+            window: self.window,
+            outlier_filter: self.outlier_filter,
+            dfa: self.dfa.clone(),
+            blank_after_gap: self.blank_after_gap,
+            max_identical_run: self.max_identical_run,
+        }
+    }
+    fn get_markers(&self) -> &[(Duration, String)] {
+        &self.markers
+    }
+    fn get_messages(&self) -> &[(Duration, HeartrateMessage)] {
+        &self.measurements
+    }
+    fn get_metadata(&self) -> Option<&MeasurementMetadata> {
+        self.metadata.as_ref()
+    }
+    fn is_excluded(&self) -> bool {
+        self.excluded
+    }
+    fn get_gaps(&self) -> &[(Duration, Duration)] {
+        &self.gaps
+    }
     fn get_stats_window(&self) -> Option<usize> {
         self.window
     }
+    fn get_analysis_range(&self) -> Option<(Duration, Duration)> {
+        self.range
+    }
+    fn get_skip_initial(&self) -> Duration {
+        self.skip_initial
+    }
+    fn get_revision(&self) -> u64 {
+        self.revision
+    }
+    fn get_pnn_threshold(&self) -> f64 {
+        self.pnn_threshold
+    }
+    fn get_detrend(&self) -> DetrendOption {
+        self.detrend
+    }
+    fn get_differences(&self) -> DifferenceMode {
+        self.differences
+    }
+    fn get_settling_filter(&self) -> Option<SettlingFilter> {
+        self.settling_filter
+    }
+    fn is_paused(&self) -> bool {
+        self.paused_at.is_some()
+    }
+    fn is_recording(&self) -> bool {
+        self.is_recording
+    }
+    fn get_dropped_events(&self) -> u64 {
+        self.dropped_events
+    }
+    fn is_rr_available(&self) -> bool {
+        self.measurements.is_empty()
+            || self
+                .measurements
+                .iter()
+                .any(|(_, msg)| msg.has_rr_interval())
+    }
+    fn get_sensor_hr_ts(&self) -> Vec<[f64; 2]> {
+        self.measurements
+            .iter()
+            .filter(|(_, msg)| msg.has_valid_hr())
+            .map(|(elapsed, msg)| [elapsed.as_seconds_f64(), msg.get_hr()])
+            .collect()
+    }
+    fn get_total_energy(&self) -> Option<f64> {
+        self.energy.total()
+    }
+    fn get_beat_count(&self) -> usize {
+        self.measurements
+            .iter()
+            .map(|(_, msg)| msg.get_rr_intervals().len())
+            .sum()
+    }
     fn get_dfa1a(&self) -> Option<f64> {
-        self.sessiondata.get_dfa_alpha()
+        if self.get_dfa_warmup().is_some() {
+            return None;
+        }
+        self.session().get_dfa_alpha()
     }
-    fn get_dfa1a_ts(&self) -> Vec<[f64; 2]> {
-        self.sessiondata.get_dfa_alpha_ts().to_owned()
+    fn get_dfa_warmup(&self) -> Option<(usize, usize)> {
+        // outliers and intervals outside the analysis do not contribute to the DFA
+        let beats = self.session().get_inlier_count();
+        let required = self.dfa.min_beats();
+        (beats < required).then_some((beats, required))
     }
     fn get_hr(&self) -> Option<f64> {
-        self.sessiondata.get_hr()
+        self.session().get_hr()
     }
     fn get_hr_ts(&self) -> Vec<[f64; 2]> {
-        self.sessiondata.get_hr_ts().to_owned()
+        self.session().get_hr_ts().to_owned()
+    }
+    fn get_recent_rr(&self, count: usize) -> Vec<(f64, bool)> {
+        self.session().get_recent_rr(count)
+    }
+    fn get_contact_track(&self) -> Vec<(f64, bool)> {
+        self.session().get_contact_track()
+    }
+    fn get_correction_log(&self) -> Vec<CorrectionEntry> {
+        self.session().get_correction_log()
     }
-    fn get_rmssd(&self) -> Option<f64> {
-        self.sessiondata.get_rmssd()
+    fn get_rr_ts(&self) -> Vec<[f64; 2]> {
+        self.session().get_rr_ts()
     }
     fn get_rmssd_ts(&self) -> Vec<[f64; 2]> {
-        self.sessiondata.get_rmssd_ts().to_owned()
+        self.session().get_rmssd_ts().to_owned()
     }
-    fn get_sd1(&self) -> Option<f64> {
-        self.sessiondata.get_sd1()
+    fn get_analysis_state(&self) -> AnalysisState {
+        self.session().state()
     }
-    fn get_sd1_ts(&self) -> Vec<[f64; 2]> {
-        self.sessiondata.get_sd1_ts().to_owned()
+    fn get_final_rmssd(&self) -> Option<f64> {
+        self.session().get_final_rmssd()
     }
-    fn get_sd2(&self) -> Option<f64> {
-        self.sessiondata.get_sd2()
+    fn get_instant_sd1(&self) -> Option<f64> {
+        self.session().get_instant_sd1()
     }
-    fn get_sd2_ts(&self) -> Vec<[f64; 2]> {
-        self.sessiondata.get_sd2_ts().to_owned()
+    fn get_beats_since_outlier(&self) -> usize {
+        self.session().get_beats_since_outlier()
     }
-    fn get_sdrr(&self) -> Option<f64> {
-        self.sessiondata.get_sdrr()
+    fn get_recent_artifact_rate(&self) -> Option<f64> {
+        self.session().get_recent_artifact_rate()
     }
-    fn get_sdrr_ts(&self) -> Vec<[f64; 2]> {
-        self.sessiondata.get_sdrr_ts().to_owned()
+    fn get_summary(&self) -> HrvSummary {
+        HrvSummary {
+            dropped_events: self.dropped_events,
+            ..self.session().summary()
+        }
+    }
+    fn get_metrics(&self) -> Vec<MetricSeries> {
+        self.session().get_metrics()
+    }
+    fn get_sdann(&self) -> Option<f64> {
+        self.session().get_sdann()
+    }
+    fn get_sdnn_index(&self) -> Option<f64> {
+        self.session().get_sdnn_index()
     }
 }
 
@@ -195,11 +811,38 @@ impl MeasurementModelApi for MeasurementData {
 impl RecordingApi for MeasurementData {
     async fn start_recording(&mut self) -> Result<()> {
         self.is_recording = true;
+        self.recorded_beats = self.get_beat_count();
+        self.revision += 1;
         Ok(())
     }
 
     async fn stop_recording(&mut self) -> Result<()> {
+        // a disconnection lasting until the end is recorded as trailing gap
+        self.close_disconnect_gap();
         self.is_recording = false;
+        self.paused_at = None;
+        self.revision += 1;
+        Ok(())
+    }
+
+    async fn pause_recording(&mut self) -> Result<()> {
+        if !self.is_recording {
+            return Err(anyhow::anyhow!("pause requested while not recording"));
+        }
+        if self.paused_at.is_none() {
+            self.paused_at = Some(self.now_elapsed());
+            self.revision += 1;
+        }
+        Ok(())
+    }
+
+    async fn resume_recording(&mut self) -> Result<()> {
+        let paused_at = self
+            .paused_at
+            .take()
+            .ok_or(anyhow::anyhow!("resume requested while not paused"))?;
+        self.gaps.push((paused_at, self.now_elapsed()));
+        self.session_mut().mark_gap();
         Ok(())
     }
 }
@@ -210,7 +853,10 @@ mod tests {
 
     use super::*;
     use crate::model::bluetooth::HeartrateMessage;
-    use crate::model::hrv::tests::get_data;
+    use crate::model::metadata::{Posture, Sex};
+    use crate::model::metrics::{DFA_ALPHA, SD1, SD2, SDRR};
+    use crate::model::synthetic::{self, SyntheticConfig};
+    use crate::model::testdata::get_data;
 
     #[test]
     fn test_default_measurement_data() {
@@ -249,6 +895,27 @@ mod tests {
         assert_eq!(data.outlier_filter, 100.0);
     }
 
+    #[test]
+    fn test_deserialize_analyses_on_demand() {
+        let mut data = MeasurementData {
+            measurements: get_data(200),
+            ..Default::default()
+        };
+        data.update().unwrap();
+        let json = serde_json::to_string(&data).unwrap();
+
+        let restored: MeasurementData = serde_json::from_str(&json).unwrap();
+        assert!(restored.sessiondata.get().is_none());
+        // the first getter analyses the measurements
+        assert_eq!(restored.get_summary().rmssd, data.get_summary().rmssd);
+        assert!(restored.sessiondata.get().is_some());
+
+        let restored: MeasurementData = serde_json::from_str(&json).unwrap();
+        restored.prepare_analysis();
+        assert!(restored.sessiondata.get().is_some());
+        assert_eq!(restored.get_summary(), data.get_summary());
+    }
+
     #[tokio::test]
     async fn test_set_stats_window() {
         let mut data = MeasurementData::default();
@@ -261,11 +928,76 @@ mod tests {
     async fn test_set_outlier_filter() {
         let mut data = MeasurementData::default();
         let filter = OutlierFilter::MovingMAD {
-            parameter: 50.0,
+            parameter: 3.0,
             _window: 5,
         };
         assert!(data.set_outlier_filter(filter).await.is_ok());
-        assert_eq!(data.outlier_filter, 50.0);
+        assert_eq!(data.outlier_filter, 3.0);
+        // the filter degenerates for extreme scales
+        let filter = OutlierFilter::MovingMAD {
+            parameter: 50.0,
+            _window: 5,
+        };
+        assert!(data.set_outlier_filter(filter).await.is_err());
+        assert_eq!(data.outlier_filter, 3.0);
+    }
+
+    #[tokio::test]
+    async fn test_auto_tune_outlier_scale() {
+        let config = SyntheticConfig {
+            ectopic_rate: 0.01,
+            ..Default::default()
+        };
+        let mut data = MeasurementData {
+            measurements: synthetic::generate(&config),
+            ..Default::default()
+        };
+        data.update().unwrap();
+        assert!(data.auto_tune_outlier_scale(0.02).await.is_ok());
+        assert!((data.get_summary().artifact_ratio - 0.02).abs() < 0.01);
+        assert!(OUTLIER_SCALE_RANGE.contains(&data.outlier_filter));
+    }
+
+    #[tokio::test]
+    async fn test_auto_tune_analysed_range() {
+        let range = Some((Duration::ZERO, Duration::seconds(250)));
+        let mut clean = MeasurementData {
+            measurements: synthetic::generate(&SyntheticConfig::default()),
+            ..Default::default()
+        };
+        let mut data = clean.clone();
+        // artifacts outside of the analysis range do not affect the scale
+        for (_, msg) in data.measurements[300..].iter_mut().step_by(3) {
+            *msg = HeartrateMessage::from_values(60, None, &[2000]);
+        }
+        for data in [&mut clean, &mut data] {
+            data.set_analysis_range(range).await.unwrap();
+            data.auto_tune_outlier_scale(0.02).await.unwrap();
+        }
+        assert_eq!(data.outlier_filter, clean.outlier_filter);
+    }
+
+    #[tokio::test]
+    async fn test_set_hrv_config() {
+        let mut data = MeasurementData::default();
+        let config = HrvConfig {
+            window: Some(60),
+            outlier_filter: 3.0,
+            dfa: DfaConfig {
+                min_box: 4,
+                max_box: 12,
+            },
+            blank_after_gap: 2,
+            max_identical_run: Some(5),
+        };
+        assert!(data.set_hrv_config(config.clone()).await.is_ok());
+        assert_eq!(data.get_hrv_config(), config);
+        let invalid = HrvConfig {
+            outlier_filter: 0.0,
+            ..Default::default()
+        };
+        assert!(data.set_hrv_config(invalid).await.is_err());
+        assert_eq!(data.get_hrv_config(), config);
     }
 
     #[tokio::test]
@@ -280,6 +1012,307 @@ mod tests {
         assert_eq!(data.measurements[0].1.get_hr(), 80.0);
     }
 
+    #[tokio::test]
+    async fn test_pause_resume_recording() {
+        let mut data = MeasurementData::default();
+        let msgs = get_data(10);
+        assert!(data.pause_recording().await.is_err());
+        assert!(data.start_recording().await.is_ok());
+        for (_, msg) in &msgs[..5] {
+            data.record_message(*msg).await.unwrap();
+        }
+        assert!(data.pause_recording().await.is_ok());
+        assert!(data.is_paused());
+        for (_, msg) in &msgs[5..] {
+            assert!(data.record_message(*msg).await.is_ok());
+        }
+        // messages during the pause are ignored
+        assert_eq!(data.measurements.len(), 5);
+        assert!(data.resume_recording().await.is_ok());
+        assert!(!data.is_paused());
+        assert!(data.resume_recording().await.is_err());
+        // the pause is marked as a gap
+        assert_eq!(data.gaps.len(), 1);
+        assert!(data.gaps[0].0 <= data.gaps[0].1);
+        assert!(data.gaps[0].0 >= data.measurements[4].0);
+        data.record_message(msgs[5].1).await.unwrap();
+        assert_eq!(data.measurements.len(), 6);
+    }
+
+    #[tokio::test]
+    async fn test_add_marker() {
+        let mut data = MeasurementData::default();
+        assert!(data.add_marker("stood up".to_owned()).await.is_err());
+        assert!(data.start_recording().await.is_ok());
+        data.start_time -= Duration::seconds(30);
+        assert!(data.add_marker("stood up".to_owned()).await.is_ok());
+        let markers = data.get_markers();
+        assert_eq!(markers.len(), 1);
+        assert_eq!(markers[0].1, "stood up");
+        assert!((markers[0].0 - Duration::seconds(30)).abs() < Duration::seconds(1));
+
+        let json = serde_json::to_string(&data).unwrap();
+        let restored: MeasurementData = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.get_markers(), data.get_markers());
+    }
+
+    #[tokio::test]
+    async fn test_replay_speed() {
+        let mut data = MeasurementData::default();
+        data.set_replay_speed(4.0);
+        data.start_recording().await.unwrap();
+        data.start_time -= Duration::seconds(2);
+        data.record_message(HeartrateMessage::from_values(60, None, &[1000]))
+            .await
+            .unwrap();
+        // two seconds of a fourfold replay are eight recorded seconds
+        let (elapsed, _) = data.get_messages()[0];
+        assert!((elapsed - Duration::seconds(8)).abs() < Duration::seconds(1));
+    }
+
+    #[tokio::test]
+    async fn test_auto_markers() {
+        let mut data = MeasurementData::default();
+        data.set_auto_markers(Some(MarkerInterval::Seconds(60)));
+        data.start_recording().await.unwrap();
+        // a message every second over three minutes
+        for _ in 0..180 {
+            data.start_time -= Duration::seconds(1);
+            data.record_message(HeartrateMessage::from_values(60, None, &[1000]))
+                .await
+                .unwrap();
+        }
+        let markers = data.get_markers();
+        assert_eq!(
+            markers,
+            [
+                (Duration::seconds(60), "auto 1".to_owned()),
+                (Duration::seconds(120), "auto 2".to_owned()),
+                (Duration::seconds(180), "auto 3".to_owned()),
+            ]
+        );
+
+        let mut data = MeasurementData::default();
+        data.set_auto_markers(Some(MarkerInterval::Beats(10)));
+        data.start_recording().await.unwrap();
+        for _ in 0..12 {
+            data.record_message(HeartrateMessage::from_values(60, None, &[500, 500]))
+                .await
+                .unwrap();
+        }
+        let labels: Vec<&str> = data.get_markers().iter().map(|m| m.1.as_str()).collect();
+        assert_eq!(labels, ["auto 1", "auto 2"]);
+        assert_eq!(data.recorded_beats, data.get_beat_count());
+    }
+
+    #[tokio::test]
+    async fn test_set_metadata() {
+        let mut data = MeasurementData::default();
+        assert!(data.get_metadata().is_none());
+        let metadata = MeasurementMetadata {
+            subject_id: "S-017".to_owned(),
+            age: Some(34),
+            sex: Some(Sex::Female),
+            posture: Some(Posture::Supine),
+        };
+        data.set_metadata(Some(metadata.clone())).await.unwrap();
+        let json = serde_json::to_string(&data).unwrap();
+        let mut restored: MeasurementData = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.get_metadata(), Some(&metadata));
+
+        let invalid = MeasurementMetadata {
+            age: Some(500),
+            ..Default::default()
+        };
+        assert!(restored.set_metadata(Some(invalid)).await.is_err());
+        assert_eq!(restored.get_metadata(), Some(&metadata));
+        // empty metadata is not stored
+        restored
+            .set_metadata(Some(MeasurementMetadata::default()))
+            .await
+            .unwrap();
+        assert!(restored.get_metadata().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_total_energy() {
+        let mut data = MeasurementData::default();
+        data.start_recording().await.unwrap();
+        assert!(data.get_total_energy().is_none());
+        // the counter is reset after 12 kJ and saturates at its maximum
+        for energy in [
+            Some(100),
+            None,
+            Some(105),
+            Some(112),
+            Some(3),
+            Some(65530),
+            Some(65535),
+            Some(65535),
+        ] {
+            data.record_message(HeartrateMessage::from_values(60, energy, &[1000]))
+                .await
+                .unwrap();
+        }
+        assert_eq!(data.get_total_energy(), Some(12.0 + 3.0 + 65527.0 + 5.0));
+
+        // the total is restored from the stored messages
+        let restored: MeasurementData =
+            serde_json::from_str(&serde_json::to_string(&data).unwrap()).unwrap();
+        assert_eq!(restored.get_total_energy(), data.get_total_energy());
+    }
+
+    #[tokio::test]
+    async fn test_hr_only_messages() {
+        let mut data = MeasurementData::default();
+        assert!(data.is_rr_available());
+        data.start_recording().await.unwrap();
+        for hr in [70, 72, 0, 71] {
+            data.record_message(HeartrateMessage::from_values(hr, None, &[]))
+                .await
+                .unwrap();
+        }
+        assert!(!data.is_rr_available());
+        let hr: Vec<f64> = data.get_sensor_hr_ts().iter().map(|p| p[1]).collect();
+        assert_eq!(hr, vec![70.0, 72.0, 71.0]);
+        assert_eq!(data.get_analysis_state(), AnalysisState::NoData);
+        assert!(data.get_summary().rmssd.is_none());
+        assert!(data.get_hr().is_none());
+    }
+
+    #[test]
+    fn test_dfa_warmup() {
+        let mut data = MeasurementData::default();
+        let required = DfaConfig::default().min_beats();
+        let beats = |count: usize| -> Vec<(Duration, HeartrateMessage)> {
+            (0..count)
+                .map(|idx| {
+                    let rr = if idx % 2 == 0 { 800 } else { 840 };
+                    (
+                        Duration::seconds(idx as i64),
+                        HeartrateMessage::from_values(60, None, &[rr]),
+                    )
+                })
+                .collect()
+        };
+        data.measurements = beats(required - 1);
+        data.update().unwrap();
+        assert_eq!(data.get_dfa_warmup(), Some((required - 1, required)));
+        assert!(data.get_dfa1a().is_none());
+        assert!(data.get_summary().dfa_alpha.is_none());
+
+        data.measurements = beats(required);
+        data.update().unwrap();
+        assert!(data.get_dfa_warmup().is_none());
+        assert!(data.get_dfa1a().is_some());
+
+        // outliers are not counted
+        data.measurements[required / 2].1 = HeartrateMessage::from_values(60, None, &[20000]);
+        data.update().unwrap();
+        assert_eq!(data.get_beat_count(), required);
+        let (beats, min_beats) = data.get_dfa_warmup().unwrap();
+        assert!(beats < required);
+        assert_eq!(min_beats, required);
+    }
+
+    #[test]
+    fn test_beat_count() {
+        let mut data = MeasurementData::default();
+        assert_eq!(data.get_beat_count(), 0);
+        data.measurements.push((
+            Duration::seconds(1),
+            HeartrateMessage::from_values(60, None, &[1000, 980]),
+        ));
+        data.measurements.push((
+            Duration::seconds(2),
+            HeartrateMessage::from_values(60, None, &[]),
+        ));
+        assert_eq!(data.get_beat_count(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_report_dropped_events() {
+        let mut data = MeasurementData::default();
+        // events lost outside of a recording are not counted
+        data.report_dropped_events(3);
+        assert_eq!(data.get_dropped_events(), 0);
+        assert!(data.start_recording().await.is_ok());
+        data.report_dropped_events(3);
+        assert_eq!(data.get_dropped_events(), 3);
+        assert_eq!(data.get_summary().dropped_events, 3);
+        // the lost events split the RR series
+        assert_eq!(data.gaps.len(), 1);
+
+        let json = serde_json::to_string(&data).unwrap();
+        let data: MeasurementData = serde_json::from_str(&json).unwrap();
+        assert_eq!(data.get_dropped_events(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_trailing_disconnect() {
+        let mut data = MeasurementData::default();
+        data.start_recording().await.unwrap();
+        let revision = data.get_revision();
+        data.mark_disconnected().await.unwrap();
+        assert!(data.get_revision() > revision);
+        assert!(data.get_gaps().is_empty());
+        // the sensor does not come back before the recording is stopped
+        data.stop_recording().await.unwrap();
+        assert_eq!(data.get_gaps().len(), 1);
+        assert!(data.disconnected_at.is_none());
+    }
+
+    #[test]
+    fn test_deserialize_gaps() {
+        let mut data = MeasurementData::default();
+        for msg in get_data(40) {
+            data.measurements.push(msg);
+        }
+        data.gaps
+            .push((Duration::seconds(20), Duration::seconds(25)));
+        let json = serde_json::to_string(&data).unwrap();
+        let data: MeasurementData = serde_json::from_str(&json).unwrap();
+        assert_eq!(
+            data.gaps,
+            vec![(Duration::seconds(20), Duration::seconds(25))]
+        );
+    }
+
+    #[test]
+    fn test_split_off_gaps() {
+        let mut data = MeasurementData::default();
+        data.import_messages(get_data(120)).unwrap();
+        let at = data.measurements[59].0;
+        let second = Duration::seconds(1);
+        data.gaps = vec![
+            (Duration::seconds(5), Duration::seconds(6)),
+            (at - second, at + second),
+            (at + 5 * second, at + 6 * second),
+        ];
+        data.dropped_events = 3;
+
+        let later = data.split_off(at).unwrap();
+        // the gap containing the split point is divided between both parts
+        assert_eq!(
+            data.gaps,
+            vec![
+                (Duration::seconds(5), Duration::seconds(6)),
+                (at - second, at)
+            ]
+        );
+        assert!(data
+            .gaps
+            .iter()
+            .all(|(_, end)| *end <= data.get_elapsed_time()));
+        assert_eq!(
+            later.gaps,
+            vec![(Duration::ZERO, second), (5 * second, 6 * second)]
+        );
+        // the lost events stay with the first part
+        assert_eq!(data.get_dropped_events(), 3);
+        assert_eq!(later.get_dropped_events(), 0);
+    }
+
     #[test]
     fn test_get_elapsed_time() {
         let mut data = MeasurementData::default();
@@ -296,6 +1329,21 @@ mod tests {
         assert_eq!(data.get_last_msg(), Some(&hr_msg));
     }
 
+    #[tokio::test]
+    async fn test_get_recent_rr() {
+        let mut data = MeasurementData::default();
+        data.start_recording().await.unwrap();
+        for (_, msg) in get_data(30) {
+            data.record_message(msg).await.unwrap();
+        }
+        let recent = data.get_recent_rr(20);
+        assert_eq!(recent.len(), 20);
+        assert_eq!(
+            recent.last().map(|(rr, _)| *rr),
+            data.get_last_msg().map(|msg| msg.get_rr_intervals()[0])
+        );
+    }
+
     #[test]
     fn test_get_outlier_filter_value() {
         let data = MeasurementData::default();
@@ -329,6 +1377,166 @@ mod tests {
         assert_eq!(data.get_stats_window().unwrap(), 60);
     }
 
+    #[tokio::test]
+    async fn test_set_analysis_range() {
+        let mut data = MeasurementData::default();
+        for msg in get_data(300) {
+            data.measurements.push(msg);
+        }
+        data.update().unwrap();
+        let full_rmssd = data.get_summary().rmssd;
+        let range = Some((Duration::seconds(60), Duration::seconds(240)));
+        assert!(data.set_analysis_range(range).await.is_ok());
+        assert_eq!(data.get_analysis_range(), range);
+        assert_ne!(data.get_summary().rmssd, full_rmssd);
+        // cropping is non-destructive
+        assert_eq!(data.measurements.len(), 300);
+        assert!(data.set_analysis_range(None).await.is_ok());
+        assert_eq!(data.get_summary().rmssd, full_rmssd);
+    }
+
+    #[tokio::test]
+    async fn test_set_skip_initial() {
+        let mut data = MeasurementData::default();
+        for msg in get_data(120) {
+            data.measurements.push(msg);
+        }
+        data.update().unwrap();
+        let full_rmssd = data.get_summary().rmssd;
+        assert!(data.set_skip_initial(Duration::seconds(30)).await.is_ok());
+        assert_eq!(data.get_skip_initial(), Duration::seconds(30));
+        assert_ne!(data.get_summary().rmssd, full_rmssd);
+        assert!(data.set_skip_initial(Duration::seconds(-1)).await.is_err());
+        assert!(data.set_skip_initial(Duration::ZERO).await.is_ok());
+        assert_eq!(data.get_summary().rmssd, full_rmssd);
+    }
+
+    #[tokio::test]
+    async fn test_set_pnn_threshold() {
+        let mut data = MeasurementData::default();
+        for msg in get_data(120) {
+            data.measurements.push(msg);
+        }
+        data.update().unwrap();
+        let pnn = |data: &MeasurementData, name: &str| {
+            data.get_metrics()
+                .into_iter()
+                .find(|metric| metric.name == name)
+                .and_then(|metric| metric.last())
+        };
+        let pnn50 = pnn(&data, "pNN50").unwrap();
+        assert!(data.set_pnn_threshold(20.0).await.is_ok());
+        assert_eq!(data.get_pnn_threshold(), 20.0);
+        assert_eq!(pnn(&data, "pNN50"), None);
+        assert!(pnn(&data, "pNN20").unwrap() >= pnn50);
+        assert!(data.set_pnn_threshold(0.0).await.is_err());
+        assert_eq!(data.get_pnn_threshold(), 20.0);
+
+        let json = serde_json::to_string(&data).unwrap();
+        let restored: MeasurementData = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.get_pnn_threshold(), 20.0);
+    }
+
+    #[tokio::test]
+    async fn test_set_detrend() {
+        let mut data = MeasurementData::default();
+        for msg in get_data(120) {
+            data.measurements.push(msg);
+        }
+        data.update().unwrap();
+        let (sdrr, rr_ts) = (data.get_summary().sdrr, data.get_rr_ts());
+        assert!(data.set_detrend(DetrendOption::Polynomial(2)).await.is_ok());
+        assert_eq!(data.get_detrend(), DetrendOption::Polynomial(2));
+        assert_ne!(data.get_summary().sdrr, sdrr);
+        assert_eq!(data.get_rr_ts(), rr_ts);
+        assert!(data
+            .set_detrend(DetrendOption::Polynomial(0))
+            .await
+            .is_err());
+        assert_eq!(data.get_detrend(), DetrendOption::Polynomial(2));
+
+        let json = serde_json::to_string(&data).unwrap();
+        let restored: MeasurementData = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.get_detrend(), DetrendOption::Polynomial(2));
+        assert_eq!(restored.get_summary().sdrr, data.get_summary().sdrr);
+    }
+
+    #[tokio::test]
+    async fn test_set_differences() {
+        let mut data = MeasurementData::default();
+        data.measurements = get_data(120);
+        data.measurements[60].1 = HeartrateMessage::from_values(60, None, &[20000]);
+        data.update().unwrap();
+        let rmssd = data.get_summary().rmssd;
+        assert!(data
+            .set_differences(DifferenceMode::WithinRuns)
+            .await
+            .is_ok());
+        assert_eq!(data.get_differences(), DifferenceMode::WithinRuns);
+        assert_ne!(data.get_summary().rmssd, rmssd);
+
+        let json = serde_json::to_string(&data).unwrap();
+        let restored: MeasurementData = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.get_differences(), DifferenceMode::WithinRuns);
+        assert_eq!(restored.get_summary().rmssd, data.get_summary().rmssd);
+    }
+
+    #[tokio::test]
+    async fn test_set_settling_filter() {
+        let mut data = MeasurementData::default();
+        data.measurements = get_data(120);
+        data.outlier_filter = 1.0;
+        data.update().unwrap();
+        let inliers = data.get_inlier_count();
+        let settling = SettlingFilter {
+            period: Duration::seconds(60),
+            scale: 10.0,
+        };
+        assert!(data.set_settling_filter(Some(settling)).await.is_ok());
+        assert_eq!(data.get_settling_filter(), Some(settling));
+        // the looser scale of the settling phase flags fewer beats
+        assert!(data.get_inlier_count() >= inliers);
+        assert!(data
+            .set_settling_filter(Some(SettlingFilter {
+                scale: 20.0,
+                ..settling
+            }))
+            .await
+            .is_err());
+        assert_eq!(data.get_settling_filter(), Some(settling));
+
+        let json = serde_json::to_string(&data).unwrap();
+        let restored: MeasurementData = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.get_settling_filter(), Some(settling));
+        assert_eq!(restored.get_inlier_count(), data.get_inlier_count());
+
+        assert!(data.set_settling_filter(None).await.is_ok());
+        assert_eq!(data.get_inlier_count(), inliers);
+    }
+
+    #[tokio::test]
+    async fn test_set_invalid_analysis_range() {
+        let mut data = MeasurementData::default();
+        let range = Some((Duration::seconds(60), Duration::seconds(30)));
+        assert!(data.set_analysis_range(range).await.is_err());
+        assert!(data.get_analysis_range().is_none());
+    }
+
+    #[test]
+    fn test_deserialize_analysis_range() {
+        let mut data = MeasurementData::default();
+        for msg in get_data(100) {
+            data.measurements.push(msg);
+        }
+        data.range = Some((Duration::seconds(10), Duration::seconds(50)));
+        let json = serde_json::to_string(&data).unwrap();
+        let data: MeasurementData = serde_json::from_str(&json).unwrap();
+        assert_eq!(
+            data.get_analysis_range(),
+            Some((Duration::seconds(10), Duration::seconds(50)))
+        );
+    }
+
     #[test]
     fn get_elapsed_time_none() {
         let data = MeasurementData::default();
@@ -344,16 +1552,19 @@ mod tests {
         }
         data.update().unwrap();
         assert!(data.get_dfa1a().is_some());
-        assert!(!data.get_dfa1a_ts().is_empty());
         assert!(data.get_hr().is_some());
         assert!(!data.get_hr_ts().is_empty());
-        assert!(data.get_rmssd().is_some());
         assert!(!data.get_rmssd_ts().is_empty());
-        assert!(data.get_sd1().is_some());
-        assert!(!data.get_sd1_ts().is_empty());
-        assert!(data.get_sd2().is_some());
-        assert!(!data.get_sd2_ts().is_empty());
-        assert!(data.get_sdrr().is_some());
-        assert!(!data.get_sdrr_ts().is_empty());
+        let summary = data.get_summary();
+        assert!(summary.rmssd.is_some());
+        assert!(summary.sd1.is_some());
+        assert!(summary.sd2.is_some());
+        assert!(summary.sdrr.is_some());
+        let metrics = data.get_metrics();
+        for name in [DFA_ALPHA, SD1, SD2, SDRR] {
+            assert!(metrics
+                .iter()
+                .any(|metric| metric.name == name && !metric.time_series.is_empty()));
+        }
     }
 }
@@ -3,19 +3,163 @@
 //! This module defines the controller responsible for managing data acquisition from BLE devices.
 //! It interacts with the acquisition model and coordinates data flow during HRV analysis.
 
-use std::{path::PathBuf, sync::Arc};
+use std::{
+    collections::HashSet,
+    io::{Read, Write},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 
-use crate::api::{
-    controller::{MeasurementApi, StorageApi, StorageEventApi},
-    model::{MeasurementModelApi, ModelHandle, StorageModelApi},
+use crate::{
+    api::{
+        controller::{MeasurementApi, StorageApi, StorageEventApi},
+        model::{MeasurementModelApi, ModelHandle, StorageModelApi},
+    },
+    model::{
+        bluetooth::HeartrateMessage,
+        cache::SessionCache,
+        edf,
+        hrv::{correction_log_csv, HrvSummary},
+        import::{parse_fit, parse_rr, RrUnit},
+        kubios,
+        metadata::MeasurementMetadata,
+        operation::FileOperation,
+        replay,
+        report::{html_report, render_plot_png, PLOT_SIZE},
+        synthetic::{self, SyntheticConfig},
+    },
 };
 use anyhow::{anyhow, Result};
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
 
-use serde::{de::DeserializeOwned, Serialize};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use time::{
+    format_description::well_known::Rfc3339, macros::format_description, Duration, OffsetDateTime,
+};
 use tokio::{fs, sync::RwLock};
 
 use async_trait::async_trait;
 
+/// Content of a storage file.
+///
+/// Besides the measurements, the file keeps the selection of the overview so reopening it
+/// restores the previous view.
+#[derive(Serialize, Deserialize)]
+struct StorageFile<T> {
+    measurements: Vec<T>,
+    /// Index of the selected measurement.
+    #[serde(default)]
+    selected: Option<usize>,
+    /// Index of the measurement the selected one is compared to.
+    #[serde(default)]
+    compare: Option<usize>,
+}
+
+/// Storage file formats that can be loaded.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StorageContent<T> {
+    File(StorageFile<T>),
+    /// Files written before the selection was stored only hold the measurements.
+    Measurements(Vec<T>),
+}
+
+/// Writes the content of a storage file from the serialized measurements.
+///
+/// The measurements are inserted as they are, so unchanged measurements keep the bytes of
+/// the previous save. The result matches the serialization of a `StorageFile`.
+fn storage_json(
+    measurements: &[Arc<str>],
+    selected: Option<usize>,
+    compare: Option<usize>,
+) -> Result<String> {
+    Ok(format!(
+        "{{\"measurements\":[{}],\"selected\":{},\"compare\":{}}}",
+        measurements.join(","),
+        serde_json::to_string(&selected)?,
+        serde_json::to_string(&compare)?
+    ))
+}
+
+/// Leading bytes of gzip compressed data.
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// Checks if a storage file is written gzip compressed, which is selected by a `.gz` extension.
+fn is_compressed(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("gz"))
+}
+
+/// Decodes the content of a storage file.
+///
+/// Compressed files are detected by their magic bytes, independent of the file name.
+fn decode_storage(bytes: Vec<u8>) -> Result<String> {
+    if bytes.starts_with(&GZIP_MAGIC) {
+        let mut json = String::new();
+        GzDecoder::new(bytes.as_slice()).read_to_string(&mut json)?;
+        Ok(json)
+    } else {
+        Ok(String::from_utf8(bytes)?)
+    }
+}
+
+/// Compresses the content of a storage file with gzip.
+fn compress_storage(json: &str) -> Result<Vec<u8>> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(json.as_bytes())?;
+    Ok(encoder.finish()?)
+}
+
+/// Name of the combined summary written by the batch export.
+const SUMMARY_FILE: &str = "summary.csv";
+
+/// Returns a file name for the measurement started at `start` that is not in `used`.
+///
+/// The name is the start time, measurements started within the same second are told apart by
+/// an appended counter.
+fn unique_file_name(start: &OffsetDateTime, used: &mut HashSet<String>) -> Result<String> {
+    let stem = start.format(format_description!(
+        "[year]-[month]-[day]_[hour]-[minute]-[second]"
+    ))?;
+    let name = (1..)
+        .map(|count| match count {
+            1 => format!("{}.csv", stem),
+            count => format!("{}_{}.csv", stem, count),
+        })
+        .find(|name| !used.contains(name))
+        .expect("unbounded candidates");
+    used.insert(name.clone());
+    Ok(name)
+}
+
+/// Encodes the RR intervals of a measurement as CSV with a header row.
+///
+/// The subject is noted in comment lines preceding the header.
+fn rr_csv(measurement: &dyn MeasurementModelApi) -> String {
+    let mut csv = measurement
+        .get_metadata()
+        .map(MeasurementMetadata::csv_comment)
+        .unwrap_or_default();
+    csv.push_str("time_s,rr_ms\n");
+    for [time, rr] in measurement.get_rr_ts() {
+        csv.push_str(&format!("{:.3},{}\n", time, rr));
+    }
+    csv
+}
+
+/// Returns the index of the entry at `index` after moving the entry at `from` to `to`.
+fn moved_index(index: usize, from: usize, to: usize) -> usize {
+    if index == from {
+        to
+    } else if from < index && index <= to {
+        index - 1
+    } else if to <= index && index < from {
+        index + 1
+    } else {
+        index
+    }
+}
+
 /// The `AcquisitionController` struct implements the `DataAcquisitionApi` trait and manages
 /// data acquisition sessions through an associated model.
 ///
@@ -27,6 +171,93 @@ pub struct StorageComponent<
 > {
     measurements: Vec<Arc<RwLock<MT>>>,
     handles: Vec<ModelHandle<dyn MeasurementModelApi>>,
+    /// The measurement currently being recorded, not yet part of the storage.
+    recording: Option<Arc<RwLock<MT>>>,
+    /// The selected stored measurement.
+    selected: Option<Arc<RwLock<MT>>>,
+    /// Index of the selected stored measurement.
+    selected_index: Option<usize>,
+    /// Index of the stored measurement the selected one is compared to.
+    compare: Option<usize>,
+    /// The running file operation, shared with the views to cancel it.
+    operation: Arc<FileOperation>,
+    /// Whether loaded measurements are analysed on first access.
+    lazy_analysis: bool,
+    /// Serialized measurements of the last save, `None` if changed since.
+    ///
+    /// Stored measurements are only changed through the selection, so the selected
+    /// measurement is serialized on every save.
+    serialized: Vec<Option<Arc<str>>>,
+    /// Computed results of the stored measurements, `None` until first computed.
+    ///
+    /// The results are computed outside the storage lock, see `stale_caches`.
+    cache: Vec<Option<Arc<SessionCache>>>,
+}
+
+impl<
+        MT: MeasurementApi + DeserializeOwned + Serialize + Default + Send + Sync + Clone + 'static,
+    > StorageComponent<MT>
+{
+    /// Shares the state of the file operations with the views.
+    ///
+    /// # Arguments
+    /// * `operation` - The `FileOperation` the views can cancel.
+    ///
+    /// # Returns
+    /// The `StorageComponent` reporting its file operations to `operation`.
+    pub fn with_operation(mut self, operation: Arc<FileOperation>) -> Self {
+        self.operation = operation;
+        self
+    }
+
+    /// Reads the measurements and the selection of a storage file.
+    ///
+    /// The file is converted on a blocking task and can be cancelled through the file
+    /// operation. Loaded measurements are analysed unless the analysis is lazy.
+    async fn read_file(&self, path: &Path) -> Result<StorageFile<MT>> {
+        let operation = self.operation.clone();
+        let bytes = fs::read(path).await?;
+        operation.check()?;
+        let lazy = self.lazy_analysis;
+        let file = tokio::task::spawn_blocking(move || -> Result<StorageFile<MT>> {
+            let json = decode_storage(bytes)?;
+            let content: StorageContent<serde_json::Value> = serde_json::from_str(json.as_str())?;
+            let file = match content {
+                StorageContent::File(file) => file,
+                StorageContent::Measurements(measurements) => StorageFile {
+                    measurements,
+                    selected: None,
+                    compare: None,
+                },
+            };
+            // convert one measurement at a time to allow cancelling large files
+            let mut measurements = Vec::with_capacity(file.measurements.len());
+            for value in file.measurements {
+                operation.check()?;
+                let measurement: MT = serde_json::from_value(value)?;
+                if !lazy {
+                    measurement.prepare_analysis();
+                }
+                measurements.push(measurement);
+            }
+            Ok(StorageFile {
+                measurements,
+                selected: file.selected,
+                compare: file.compare,
+            })
+        })
+        .await??;
+        Ok(file)
+    }
+
+    /// Stores a new measurement holding `messages` and selects it.
+    fn add_messages(&mut self, messages: Vec<(Duration, HeartrateMessage)>) -> Result<()> {
+        let mut measurement = MT::default();
+        measurement.import_messages(messages)?;
+        self.store_measurement(Arc::new(RwLock::new(measurement)))?;
+        self.select_measurement(self.measurements.len() - 1)?;
+        Ok(())
+    }
 }
 
 #[async_trait]
@@ -37,21 +268,28 @@ impl<
     async fn clear(&mut self) -> Result<()> {
         self.measurements.clear();
         self.handles.clear();
+        self.serialized.clear();
+        self.cache.clear();
+        self.selected = None;
+        self.selected_index = None;
+        self.compare = None;
         Ok(())
     }
 
     async fn load_from_file(&mut self, path: PathBuf) -> Result<()> {
-        let json = fs::read_to_string(&path).await?;
-        let measurements = tokio::task::spawn_blocking(move || {
-            let serde_result: Result<Vec<MT>, serde_json::Error> =
-                serde_json::from_str(json.as_str());
-            serde_result
-        })
-        .await??;
+        let operation = self.operation.clone();
+        let _running = operation.start(format!("Loading {}", path.display()));
+        let file = self.read_file(&path).await?;
+        // the storage is only replaced by a completely loaded file
+        operation.check()?;
+        let measurements = file.measurements;
+        let count = measurements.len();
         self.measurements = measurements
             .into_iter()
             .map(|measurement| Arc::new(RwLock::new(measurement)))
             .collect();
+        self.serialized = vec![None; count];
+        self.cache = vec![None; count];
 
         self.handles = self
             .measurements
@@ -62,18 +300,289 @@ impl<
                 mh
             })
             .collect();
+        // indices of another file version may be out of bounds
+        self.selected_index = file.selected.filter(|idx| *idx < count);
+        self.selected = self
+            .selected_index
+            .map(|idx| self.measurements[idx].clone());
+        self.compare = file.compare.filter(|idx| *idx < count);
+        Ok(())
+    }
+
+    async fn append_from_file(&mut self, path: PathBuf) -> Result<()> {
+        let operation = self.operation.clone();
+        let _running = operation.start(format!("Adding {}", path.display()));
+        let file = self.read_file(&path).await?;
+        operation.check()?;
+        // measurements with the start time and beat count of a stored one are duplicates
+        let mut stored = Vec::with_capacity(self.measurements.len());
+        for measurement in &self.measurements {
+            let lck = measurement.read().await;
+            stored.push((*lck.get_start_time(), lck.get_beat_count()));
+        }
+        for measurement in file.measurements {
+            let key = (*measurement.get_start_time(), measurement.get_beat_count());
+            if stored.contains(&key) {
+                log::info!("skipped measurement of {} already in the workspace", key.0);
+                continue;
+            }
+            stored.push(key);
+            self.store_measurement(Arc::new(RwLock::new(measurement)))?;
+        }
         Ok(())
     }
 
     async fn store_to_file(&mut self, path: PathBuf) -> Result<()> {
-        let measurements = self.measurements.clone();
-        let json = tokio::task::spawn_blocking(move || {
-            let guards: Vec<_> = measurements.iter().map(|m| m.blocking_read()).collect();
-            let mr: Vec<&MT> = guards.iter().map(|g| &**g).collect();
-            serde_json::to_string(&mr)
+        let operation = self.operation.clone();
+        let _running = operation.start(format!("Saving {}", path.display()));
+        let (selected, compare) = (self.selected_index, self.compare);
+        let entries: Vec<(Arc<RwLock<MT>>, Option<Arc<str>>)> = self
+            .measurements
+            .iter()
+            .zip(self.serialized.iter())
+            .enumerate()
+            .map(|(idx, (measurement, serialized))| {
+                let serialized = serialized.clone().filter(|_| Some(idx) != selected);
+                (measurement.clone(), serialized)
+            })
+            .collect();
+        let task_operation = operation.clone();
+        let compressed = is_compressed(&path);
+        let (serialized, content) =
+            tokio::task::spawn_blocking(move || -> Result<(Vec<Arc<str>>, Vec<u8>)> {
+                // serialize one measurement at a time to allow cancelling large storages
+                let mut serialized = Vec::with_capacity(entries.len());
+                for (measurement, cached) in entries {
+                    task_operation.check()?;
+                    serialized.push(match cached {
+                        Some(cached) => cached,
+                        None => serde_json::to_string(&*measurement.blocking_read())?.into(),
+                    });
+                }
+                let json = storage_json(&serialized, selected, compare)?;
+                let content = if compressed {
+                    compress_storage(&json)?
+                } else {
+                    json.into_bytes()
+                };
+                Ok((serialized, content))
+            })
+            .await??;
+        // a cancelled save leaves an existing file untouched
+        operation.check()?;
+        fs::write(&path, content).await?;
+        self.serialized = serialized.into_iter().map(Some).collect();
+        Ok(())
+    }
+
+    async fn store_measurement_to_file(&mut self, file: (usize, PathBuf)) -> Result<()> {
+        let (index, path) = file;
+        let measurement = self.get_measurement(index)?;
+        let compressed = is_compressed(&path);
+        let content = tokio::task::spawn_blocking(move || -> Result<Vec<u8>> {
+            let serialized: Arc<str> = serde_json::to_string(&*measurement.blocking_read())?.into();
+            let json = storage_json(&[serialized], Some(0), None)?;
+            if compressed {
+                compress_storage(&json)
+            } else {
+                Ok(json.into_bytes())
+            }
         })
         .await??;
-        fs::write(&path, json).await.map_err(|e| anyhow!(e))
+        fs::write(&path, content).await?;
+        Ok(())
+    }
+
+    async fn export_edf(&mut self, path: PathBuf) -> Result<()> {
+        let measurement = self
+            .selected
+            .clone()
+            .ok_or_else(|| anyhow!("No measurement selected"))?;
+        let operation = self.operation.clone();
+        let _running = operation.start(format!("Exporting {}", path.display()));
+        let content = {
+            let lck = measurement.read().await;
+            edf::encode_rr_annotations(lck.get_start_time(), &lck.get_rr_ts())?
+        };
+        operation.check()?;
+        fs::write(&path, content).await.map_err(|e| anyhow!(e))
+    }
+
+    async fn export_kubios(&mut self, path: PathBuf) -> Result<()> {
+        let measurement = self
+            .selected
+            .clone()
+            .ok_or_else(|| anyhow!("No measurement selected"))?;
+        let operation = self.operation.clone();
+        let _running = operation.start(format!("Exporting {}", path.display()));
+        let content = {
+            let lck = measurement.read().await;
+            kubios::encode_rr(&lck.get_rr_ts())
+        };
+        operation.check()?;
+        fs::write(&path, content).await.map_err(|e| anyhow!(e))
+    }
+
+    async fn export_correction_log(&mut self, path: PathBuf) -> Result<()> {
+        let measurement = self
+            .selected
+            .clone()
+            .ok_or_else(|| anyhow!("No measurement selected"))?;
+        let operation = self.operation.clone();
+        let _running = operation.start(format!("Exporting {}", path.display()));
+        let content = {
+            let lck = measurement.read().await;
+            // the subject is noted in comment lines preceding the header
+            let mut csv = lck
+                .get_metadata()
+                .map(MeasurementMetadata::csv_comment)
+                .unwrap_or_default();
+            csv.push_str(&correction_log_csv(&lck.get_correction_log()));
+            csv
+        };
+        operation.check()?;
+        fs::write(&path, content).await.map_err(|e| anyhow!(e))
+    }
+
+    async fn export_html(&mut self, path: PathBuf) -> Result<()> {
+        let measurement = self
+            .selected
+            .clone()
+            .ok_or_else(|| anyhow!("No measurement selected"))?;
+        let operation = self.operation.clone();
+        let _running = operation.start(format!("Exporting {}", path.display()));
+        let content = {
+            let lck = measurement.read().await;
+            // series without enough data are left out of the report
+            let plots: Vec<(&str, Vec<u8>)> = [
+                ("RR intervals [ms]", lck.get_rr_ts()),
+                ("Heart rate [1/min]", lck.get_hr_ts()),
+                ("RMSSD [ms]", lck.get_rmssd_ts()),
+            ]
+            .into_iter()
+            .filter_map(|(title, series)| {
+                render_plot_png(&series, PLOT_SIZE)
+                    .ok()
+                    .map(|png| (title, png))
+            })
+            .collect();
+            html_report(
+                lck.get_start_time(),
+                &lck.get_summary(),
+                lck.get_metadata(),
+                &plots,
+            )?
+        };
+        operation.check()?;
+        fs::write(&path, content).await.map_err(|e| anyhow!(e))
+    }
+
+    async fn export_all(&mut self, dir: PathBuf) -> Result<()> {
+        if self.measurements.is_empty() {
+            return Err(anyhow!("No measurements to export"));
+        }
+        let operation = self.operation.clone();
+        let _running = operation.start(format!("Exporting to {}", dir.display()));
+        fs::create_dir_all(&dir).await?;
+        let mut used = HashSet::from([SUMMARY_FILE.to_owned()]);
+        // the summary columns match the tab separated summary of a single measurement
+        let mut summary = format!(
+            "file,start_time,{}\n",
+            HrvSummary::TSV_HEADER.replace('\t', ",")
+        );
+        for measurement in &self.measurements {
+            operation.check()?;
+            let (name, content) = {
+                let lck = measurement.read().await;
+                if lck.is_excluded() {
+                    continue;
+                }
+                let start = *lck.get_start_time();
+                let name = unique_file_name(&start, &mut used)?;
+                summary.push_str(&format!(
+                    "{},{},{}\n",
+                    name,
+                    start.format(&Rfc3339)?,
+                    lck.get_summary().to_tsv().replace('\t', ",")
+                ));
+                (name, rr_csv(&*lck))
+            };
+            fs::write(dir.join(name), content).await?;
+        }
+        fs::write(dir.join(SUMMARY_FILE), summary)
+            .await
+            .map_err(|e| anyhow!(e))
+    }
+
+    async fn import_rr(&mut self, file: (PathBuf, RrUnit)) -> Result<()> {
+        let (path, unit) = file;
+        let text = fs::read_to_string(&path).await?;
+        self.add_messages(parse_rr(&text, unit)?)
+    }
+
+    async fn import_fit(&mut self, path: PathBuf) -> Result<()> {
+        let bytes = fs::read(&path).await?;
+        self.add_messages(parse_fit(&bytes)?)
+    }
+
+    async fn load_demo(&mut self) -> Result<()> {
+        self.add_messages(synthetic::generate(&SyntheticConfig::default()))
+    }
+
+    async fn merge_measurements(&mut self, indices: (usize, usize)) -> Result<()> {
+        let (first, second) = indices;
+        if first == second {
+            return Err(anyhow!("cannot merge a measurement with itself"));
+        }
+        let (first, second) = (self.get_measurement(first)?, self.get_measurement(second)?);
+        let (first, second) = (first.read().await, second.read().await);
+        let (earlier, later) = if first.get_start_time() <= second.get_start_time() {
+            (&*first, &*second)
+        } else {
+            (&*second, &*first)
+        };
+        let mut merged = earlier.clone();
+        merged.append_measurement(later)?;
+        drop((first, second));
+        self.store_measurement(Arc::new(RwLock::new(merged)))?;
+        self.select_measurement(self.measurements.len() - 1)?;
+        Ok(())
+    }
+
+    async fn split_measurement(&mut self, split: (usize, Duration)) -> Result<()> {
+        let (idx, at) = split;
+        let mut first = self.get_measurement(idx)?.read().await.clone();
+        let second = first.split_off(at)?;
+        self.store_measurement(Arc::new(RwLock::new(first)))?;
+        self.store_measurement(Arc::new(RwLock::new(second)))?;
+        self.select_measurement(self.measurements.len() - 2)?;
+        Ok(())
+    }
+
+    async fn move_measurement(&mut self, indices: (usize, usize)) -> Result<()> {
+        let (from, to) = indices;
+        if from >= self.measurements.len() || to >= self.measurements.len() {
+            return Err(anyhow!("Index out of bounds"));
+        }
+        // the parallel vectors are reordered alike
+        let measurement = self.measurements.remove(from);
+        self.measurements.insert(to, measurement);
+        let handle = self.handles.remove(from);
+        self.handles.insert(to, handle);
+        let serialized = self.serialized.remove(from);
+        self.serialized.insert(to, serialized);
+        let cache = self.cache.remove(from);
+        self.cache.insert(to, cache);
+        self.selected_index = self.selected_index.map(|idx| moved_index(idx, from, to));
+        self.compare = self.compare.map(|idx| moved_index(idx, from, to));
+        Ok(())
+    }
+
+    async fn replay_measurement(&mut self, replay: (usize, f64)) -> Result<()> {
+        let (index, speed) = replay;
+        let measurement = self.get_measurement(index)?;
+        let lck = measurement.read().await;
+        replay::validate(lck.get_messages(), speed)
     }
 }
 
@@ -87,12 +596,85 @@ impl<MT: MeasurementApi + Serialize + DeserializeOwned + Clone + Default> Storag
             Err(anyhow!("Index out of bounds"))
         }
     }
+    fn select_measurement(&mut self, index: usize) -> Result<Arc<RwLock<MT>>> {
+        let measurement = self.get_measurement(index)?;
+        // the previous selection may have changed since the last save
+        if let Some(previous) = self.selected_index {
+            self.serialized[previous] = None;
+        }
+        self.selected = Some(measurement.clone());
+        self.selected_index = Some(index);
+        Ok(measurement)
+    }
+    fn set_compare(&mut self, index: Option<usize>) -> Result<()> {
+        if let Some(index) = index {
+            self.get_measurement(index)?;
+        }
+        self.compare = index;
+        Ok(())
+    }
     fn store_measurement(&mut self, measurement: Arc<RwLock<MT>>) -> Result<()> {
         self.measurements.push(measurement.clone());
+        self.serialized.push(None);
+        self.cache.push(None);
         let mh: ModelHandle<dyn MeasurementModelApi> = ModelHandle::from(measurement.clone());
         self.handles.push(mh);
         Ok(())
     }
+    fn new_measurement(&mut self) -> Result<Arc<RwLock<MT>>> {
+        let measurement = Arc::new(RwLock::new(MT::default()));
+        self.recording = Some(measurement.clone());
+        Ok(measurement)
+    }
+    fn store_recorded_measurement(&mut self) -> Result<Arc<RwLock<MT>>> {
+        let measurement = self
+            .recording
+            .take()
+            .ok_or_else(|| anyhow!("No active recording"))?;
+        if let Some(previous) = self.selected_index {
+            self.serialized[previous] = None;
+        }
+        self.store_measurement(measurement.clone())?;
+        self.selected = Some(measurement.clone());
+        self.selected_index = Some(self.measurements.len() - 1);
+        Ok(measurement)
+    }
+    fn discard_recording(&mut self) -> Option<Arc<RwLock<MT>>> {
+        self.recording.take()
+    }
+    fn set_lazy_analysis(&mut self, lazy: bool) {
+        self.lazy_analysis = lazy;
+    }
+    fn stale_caches(&self) -> Vec<Arc<RwLock<MT>>> {
+        self.measurements
+            .iter()
+            .zip(&self.cache)
+            .filter(|(measurement, cache)| {
+                measurement.try_read().is_ok_and(|measurement| {
+                    !cache
+                        .as_ref()
+                        .is_some_and(|cache| cache.is_valid_for(&*measurement))
+                })
+            })
+            .map(|(measurement, _)| measurement.clone())
+            .collect()
+    }
+    fn set_session_cache(&mut self, measurement: &Arc<RwLock<MT>>, cache: SessionCache) {
+        let Some(index) = self
+            .measurements
+            .iter()
+            .position(|stored| Arc::ptr_eq(stored, measurement))
+        else {
+            return;
+        };
+        let Ok(stored) = self.measurements[index].try_read() else {
+            return;
+        };
+        if cache.is_valid_for(&*stored) {
+            drop(stored);
+            self.cache[index] = Some(Arc::new(cache));
+        }
+    }
 }
 
 impl<
@@ -102,13 +684,28 @@ impl<
     fn get_acquisitions(&self) -> &[ModelHandle<dyn MeasurementModelApi>] {
         self.handles.as_slice()
     }
+    fn get_selected_index(&self) -> Option<usize> {
+        self.selected_index
+    }
+    fn get_compare_index(&self) -> Option<usize> {
+        self.compare
+    }
+    fn has_recording(&self) -> bool {
+        self.recording.is_some()
+    }
+    fn get_session_cache(&self, index: usize) -> Option<Arc<SessionCache>> {
+        self.cache.get(index).cloned().flatten()
+    }
 }
 
 #[cfg(test)]
 mod tests {
 
     use crate::api::controller::RecordingApi;
-    use crate::{components::measurement::MeasurementData, model::hrv::tests::get_data};
+    use crate::{
+        components::measurement::MeasurementData,
+        model::{bluetooth::HeartrateMessage, metadata::Posture, testdata::get_data},
+    };
 
     use super::*;
 
@@ -167,6 +764,385 @@ mod tests {
         let mut new_storage = StorageComponent::<MeasurementData>::default();
         assert!(new_storage.load_from_file(path.clone()).await.is_ok());
         assert_eq!(new_storage.get_acquisitions().len(), 1);
+        assert_eq!(new_storage.get_selected_index(), None);
+    }
+
+    #[tokio::test]
+    async fn test_store_and_load_compressed() {
+        let temp_dir = tempdir::TempDir::new("test").unwrap();
+        let (plain, compressed) = (
+            temp_dir.path().join("measurements.json"),
+            temp_dir.path().join("measurements.json.gz"),
+        );
+        let mut storage = StorageComponent::<MeasurementData>::default();
+        let mut measurement = MeasurementData::default();
+        measurement.import_messages(get_data(120)).unwrap();
+        let beats = measurement.get_beat_count();
+        assert!(storage
+            .store_measurement(Arc::new(RwLock::new(measurement)))
+            .is_ok());
+        assert!(storage.select_measurement(0).is_ok());
+        assert!(storage.store_to_file(plain.clone()).await.is_ok());
+        assert!(storage.store_to_file(compressed.clone()).await.is_ok());
+
+        let bytes = std::fs::read(&compressed).unwrap();
+        assert!(bytes.starts_with(&GZIP_MAGIC));
+        assert!(bytes.len() < std::fs::metadata(&plain).unwrap().len() as usize);
+        assert_eq!(
+            decode_storage(bytes).unwrap(),
+            std::fs::read_to_string(&plain).unwrap()
+        );
+
+        // compressed content is detected without the extension
+        let renamed = temp_dir.path().join("renamed.json");
+        std::fs::rename(&compressed, &renamed).unwrap();
+        let mut new_storage = StorageComponent::<MeasurementData>::default();
+        assert!(new_storage.load_from_file(renamed).await.is_ok());
+        assert_eq!(new_storage.get_acquisitions().len(), 1);
+        assert_eq!(new_storage.get_selected_index(), Some(0));
+        let loaded = new_storage.get_measurement(0).unwrap();
+        assert_eq!(loaded.read().await.get_beat_count(), beats);
+    }
+
+    #[tokio::test]
+    async fn test_store_single_measurement() {
+        let temp_dir = tempdir::TempDir::new("test").unwrap();
+        let path = temp_dir.path().join("single.json");
+        let mut storage = StorageComponent::<MeasurementData>::default();
+        assert!(storage.add_messages(get_data(120)).is_ok());
+        assert!(storage.add_messages(get_data(60)).is_ok());
+        assert!(storage
+            .store_measurement_to_file((2, path.clone()))
+            .await
+            .is_err());
+        assert!(storage
+            .store_measurement_to_file((1, path.clone()))
+            .await
+            .is_ok());
+
+        let mut new_storage = StorageComponent::<MeasurementData>::default();
+        assert!(new_storage.load_from_file(path).await.is_ok());
+        assert_eq!(new_storage.get_acquisitions().len(), 1);
+        assert_eq!(new_storage.get_selected_index(), Some(0));
+        let loaded = new_storage.get_measurement(0).unwrap();
+        let stored = storage.get_measurement(1).unwrap();
+        assert_eq!(
+            loaded.read().await.get_beat_count(),
+            stored.read().await.get_beat_count()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_store_unchanged_measurements() {
+        let temp_dir = tempdir::TempDir::new("test").unwrap();
+        let (first_path, second_path) = (
+            temp_dir.path().join("first.json"),
+            temp_dir.path().join("second.json"),
+        );
+        let mut storage = StorageComponent::<MeasurementData>::default();
+        assert!(storage.add_messages(get_data(120)).is_ok());
+        assert!(storage.store_to_file(first_path.clone()).await.is_ok());
+        let first = fs::read_to_string(&first_path).await.unwrap();
+
+        assert!(storage.add_messages(get_data(60)).is_ok());
+        assert!(storage.store_to_file(second_path.clone()).await.is_ok());
+        let second = fs::read_to_string(&second_path).await.unwrap();
+        let (prefix, _) = first.split_once("],\"selected\"").unwrap();
+        assert!(second.starts_with(&format!("{},", prefix)));
+
+        // changes of the selected measurement are saved
+        let measurement = storage.select_measurement(0).unwrap();
+        measurement
+            .write()
+            .await
+            .set_stats_window(20)
+            .await
+            .unwrap();
+        assert!(storage.select_measurement(1).is_ok());
+        assert!(storage.store_to_file(second_path.clone()).await.is_ok());
+        let mut new_storage = StorageComponent::<MeasurementData>::default();
+        assert!(new_storage.load_from_file(second_path).await.is_ok());
+        assert_eq!(new_storage.get_acquisitions().len(), 2);
+        assert_eq!(new_storage.get_selected_index(), Some(1));
+        let loaded = new_storage.get_measurement(0).unwrap();
+        assert_eq!(loaded.read().await.get_stats_window(), Some(20));
+    }
+
+    #[tokio::test]
+    async fn test_append_from_file() {
+        let temp_dir = tempdir::TempDir::new("test").unwrap();
+        let path = temp_dir.path().join("other.json");
+        let mut other = StorageComponent::<MeasurementData>::default();
+        assert!(other.add_messages(get_data(60)).is_ok());
+        assert!(other.store_to_file(path.clone()).await.is_ok());
+
+        let mut storage = StorageComponent::<MeasurementData>::default();
+        assert!(storage.load_demo().await.is_ok());
+        assert!(storage.add_messages(get_data(90)).is_ok());
+        let existing = storage.get_measurement(0).unwrap();
+        assert!(storage.select_measurement(1).is_ok());
+
+        assert!(storage.append_from_file(path.clone()).await.is_ok());
+        assert_eq!(storage.get_acquisitions().len(), 3);
+        assert!(Arc::ptr_eq(&existing, &storage.get_measurement(0).unwrap()));
+        assert_eq!(storage.get_selected_index(), Some(1));
+        // appending the same file again does not duplicate its measurements
+        assert!(storage.append_from_file(path.clone()).await.is_ok());
+        assert_eq!(storage.get_acquisitions().len(), 3);
+        assert!(storage
+            .append_from_file(temp_dir.path().join("missing.json"))
+            .await
+            .is_err());
+        assert_eq!(storage.get_acquisitions().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_selection_is_restored() {
+        let temp_dir = tempdir::TempDir::new("test").unwrap();
+        let path = temp_dir.path().join("selection.json");
+        let mut storage = StorageComponent::<MeasurementData>::default();
+        for _ in 0..2 {
+            let measurement = Arc::new(RwLock::new(MeasurementData::default()));
+            assert!(storage.store_measurement(measurement).is_ok());
+        }
+        assert!(storage.select_measurement(1).is_ok());
+        assert!(storage.set_compare(Some(0)).is_ok());
+        assert!(storage.set_compare(Some(2)).is_err());
+        assert!(storage.store_to_file(path.clone()).await.is_ok());
+
+        let mut new_storage = StorageComponent::<MeasurementData>::default();
+        assert!(new_storage.load_from_file(path).await.is_ok());
+        assert_eq!(new_storage.get_selected_index(), Some(1));
+        assert_eq!(new_storage.get_compare_index(), Some(0));
+        assert!(new_storage
+            .export_edf(temp_dir.path().join("selected.edf"))
+            .await
+            .is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_load_measurement_list() {
+        let temp_dir = tempdir::TempDir::new("test").unwrap();
+        let path = temp_dir.path().join("measurements.json");
+        let json = serde_json::to_string(&vec![MeasurementData::default()]).unwrap();
+        fs::write(&path, json).await.unwrap();
+
+        let mut storage = StorageComponent::<MeasurementData>::default();
+        assert!(storage.load_from_file(path).await.is_ok());
+        assert_eq!(storage.get_acquisitions().len(), 1);
+        assert_eq!(storage.get_selected_index(), None);
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_load_keeps_measurements() {
+        let temp_dir = tempdir::TempDir::new("test").unwrap();
+        let path = temp_dir.path().join("measurements.json");
+        // large enough that the conversion is still running when the load is cancelled
+        let count = 10_000;
+        let json = serde_json::to_string(&vec![MeasurementData::default(); count]).unwrap();
+        fs::write(&path, json).await.unwrap();
+
+        let operation = Arc::new(FileOperation::default());
+        let mut storage =
+            StorageComponent::<MeasurementData>::default().with_operation(operation.clone());
+        let measurement = Arc::new(RwLock::new(MeasurementData::default()));
+        assert!(storage.store_measurement(measurement).is_ok());
+        assert!(storage.select_measurement(0).is_ok());
+
+        // cancel as soon as the load is running
+        let canceller = tokio::spawn({
+            let operation = operation.clone();
+            async move {
+                while operation.running().is_none() {
+                    tokio::task::yield_now().await;
+                }
+                operation.cancel();
+            }
+        });
+        assert!(storage.load_from_file(path.clone()).await.is_err());
+        canceller.await.unwrap();
+        assert_eq!(storage.get_acquisitions().len(), 1);
+        assert_eq!(storage.get_selected_index(), Some(0));
+        assert_eq!(operation.running(), None);
+
+        // the next operation is not cancelled
+        assert!(storage.load_from_file(path).await.is_ok());
+        assert_eq!(storage.get_acquisitions().len(), count);
+    }
+
+    #[tokio::test]
+    async fn test_import_rr_and_demo() {
+        let temp_dir = tempdir::TempDir::new("test").unwrap();
+        let path = temp_dir.path().join("rr.txt");
+        fs::write(&path, "# rr [ms]\n1000\n950\n1010\n")
+            .await
+            .unwrap();
+
+        let mut storage = StorageComponent::<MeasurementData>::default();
+        assert!(storage.import_rr((path, RrUnit::Auto)).await.is_ok());
+        assert_eq!(storage.get_selected_index(), Some(0));
+        let imported = storage.get_measurement(0).unwrap();
+        assert_eq!(imported.read().await.get_beat_count(), 3);
+
+        assert!(storage
+            .import_rr((temp_dir.path().join("missing.txt"), RrUnit::Auto))
+            .await
+            .is_err());
+        assert!(storage.load_demo().await.is_ok());
+        assert_eq!(storage.get_acquisitions().len(), 2);
+        assert_eq!(storage.get_selected_index(), Some(1));
+    }
+
+    #[tokio::test]
+    async fn test_export_html() {
+        let temp_dir = tempdir::TempDir::new("test").unwrap();
+        let path = temp_dir.path().join(PathBuf::from("report.html"));
+        let mut storage = StorageComponent::<MeasurementData>::default();
+        assert!(storage.export_html(path.clone()).await.is_err());
+
+        let mut measurement = MeasurementData::default();
+        measurement.import_messages(get_data(120)).unwrap();
+        let summary = measurement.get_summary();
+        assert!(storage
+            .store_measurement(Arc::new(RwLock::new(measurement)))
+            .is_ok());
+        assert!(storage.select_measurement(0).is_ok());
+        assert!(storage.export_html(path.clone()).await.is_ok());
+        let html = fs::read_to_string(&path).await.unwrap();
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains(&format!("{:.2} ms", summary.rmssd.unwrap())));
+        assert!(html.contains(&format!("{:.2} 1/min", summary.hr.unwrap())));
+        assert!(html.contains(&format!("<td>{}</td>", summary.rr_count)));
+        assert!(html.contains("src=\"data:image/png;base64,iVBORw0KGgo"));
+    }
+
+    #[tokio::test]
+    async fn test_merge_measurements() {
+        let mut storage = StorageComponent::<MeasurementData>::default();
+        let data = get_data(120);
+        let (head, tail) = data.split_at(60);
+        let mut first = MeasurementData::default();
+        first.import_messages(head.to_vec()).unwrap();
+        // the second recording starts a minute after the first one ended
+        let offset = first.get_elapsed_time() + Duration::minutes(1);
+        let tail: Vec<_> = tail
+            .iter()
+            .map(|(elapsed, msg)| (*elapsed - tail[0].0, *msg))
+            .collect();
+        let mut value = serde_json::to_value(&first).unwrap();
+        value["start_time"] = serde_json::to_value(*first.get_start_time() + offset).unwrap();
+        value["measurements"] = serde_json::to_value(&tail).unwrap();
+        let second: MeasurementData = serde_json::from_value(value).unwrap();
+        let beats = first.get_beat_count() + second.get_beat_count();
+        // the later measurement is stored first
+        assert!(storage
+            .store_measurement(Arc::new(RwLock::new(second)))
+            .is_ok());
+        assert!(storage
+            .store_measurement(Arc::new(RwLock::new(first)))
+            .is_ok());
+
+        assert!(storage.merge_measurements((0, 0)).await.is_err());
+        assert!(storage.merge_measurements((0, 2)).await.is_err());
+        assert!(storage.merge_measurements((0, 1)).await.is_ok());
+        assert_eq!(storage.get_acquisitions().len(), 3);
+        assert_eq!(storage.get_selected_index(), Some(2));
+        let merged = storage.get_measurement(2).unwrap();
+        let merged = merged.read().await;
+        assert_eq!(merged.get_beat_count(), beats);
+        assert_eq!(
+            merged.get_gaps(),
+            &[(offset - Duration::minutes(1), offset)]
+        );
+        assert!(merged.get_summary().rmssd.is_some());
+        assert!(merged.get_hr().is_some());
+
+        // overlapping measurements cannot be merged
+        let copy = storage.get_measurement(1).unwrap().read().await.clone();
+        assert!(storage
+            .store_measurement(Arc::new(RwLock::new(copy)))
+            .is_ok());
+        assert!(storage.merge_measurements((1, 3)).await.is_err());
+        assert_eq!(storage.get_acquisitions().len(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_split_measurement() {
+        let mut storage = StorageComponent::<MeasurementData>::default();
+        let data = get_data(120);
+        let at = data[59].0;
+        let mut measurement = MeasurementData::default();
+        measurement.import_messages(data).unwrap();
+        let beats = measurement.get_beat_count();
+        let end = measurement.get_elapsed_time();
+        assert!(storage
+            .store_measurement(Arc::new(RwLock::new(measurement)))
+            .is_ok());
+
+        // the split point must lie within the recording
+        assert!(storage.split_measurement((1, at)).await.is_err());
+        assert!(storage.split_measurement((0, -at)).await.is_err());
+        assert!(storage.split_measurement((0, end)).await.is_err());
+        assert_eq!(storage.get_acquisitions().len(), 1);
+
+        assert!(storage.split_measurement((0, at)).await.is_ok());
+        assert_eq!(storage.get_acquisitions().len(), 3);
+        assert_eq!(storage.get_selected_index(), Some(1));
+        let (first, second) = (
+            storage.get_measurement(1).unwrap(),
+            storage.get_measurement(2).unwrap(),
+        );
+        let (first, second) = (first.read().await, second.read().await);
+        assert_eq!(first.get_beat_count() + second.get_beat_count(), beats);
+        assert_eq!(first.get_elapsed_time(), at);
+        assert_eq!(*second.get_start_time(), *first.get_start_time() + at);
+        assert_eq!(second.get_elapsed_time(), end - at);
+        assert!(first.get_summary().rmssd.is_some());
+        assert!(second.get_summary().rmssd.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_move_measurement() {
+        let mut storage = StorageComponent::<MeasurementData>::default();
+        for size in [30, 40, 50, 60] {
+            let mut measurement = MeasurementData::default();
+            measurement.import_messages(get_data(size)).unwrap();
+            assert!(storage
+                .store_measurement(Arc::new(RwLock::new(measurement)))
+                .is_ok());
+        }
+        async fn beats(storage: &StorageComponent<MeasurementData>) -> Vec<usize> {
+            let mut beats = Vec::new();
+            for (idx, handle) in storage.get_acquisitions().iter().enumerate() {
+                let count = handle.read().await.get_beat_count();
+                // handles and measurements refer to the same data
+                let measurement = storage.get_measurement(idx).unwrap();
+                assert_eq!(measurement.read().await.get_beat_count(), count);
+                beats.push(count);
+            }
+            beats
+        }
+        let initial = beats(&storage).await;
+        let selected = storage.select_measurement(1).unwrap();
+        storage.set_compare(Some(3)).unwrap();
+
+        assert!(storage.move_measurement((4, 0)).await.is_err());
+        assert!(storage.move_measurement((3, 0)).await.is_ok());
+        assert_eq!(
+            beats(&storage).await,
+            vec![initial[3], initial[0], initial[1], initial[2]]
+        );
+        assert_eq!(storage.get_selected_index(), Some(2));
+        assert_eq!(storage.get_compare_index(), Some(0));
+        assert!(Arc::ptr_eq(&selected, &storage.get_measurement(2).unwrap()));
+
+        assert!(storage.move_measurement((2, 3)).await.is_ok());
+        assert_eq!(
+            beats(&storage).await,
+            vec![initial[3], initial[0], initial[2], initial[1]]
+        );
+        assert_eq!(storage.get_selected_index(), Some(3));
+        assert_eq!(storage.get_compare_index(), Some(0));
     }
 
     #[tokio::test]
@@ -184,4 +1160,260 @@ mod tests {
         let retrieved = storage.get_measurement(0).unwrap();
         assert!(Arc::ptr_eq(&measurement, &retrieved))
     }
+
+    #[tokio::test]
+    async fn test_session_cache() {
+        let mut storage = StorageComponent::<MeasurementData>::default();
+        storage.add_messages(get_data(200)).unwrap();
+        storage.add_messages(get_data(100)).unwrap();
+        // selecting does not analyse, the results are computed outside the storage
+        assert!(storage.get_session_cache(1).is_none());
+        assert!(storage.get_session_cache(2).is_none());
+        assert_eq!(storage.stale_caches().len(), 2);
+
+        let measurement = storage.get_measurement(0).unwrap();
+        let cache = SessionCache::from_model(&*measurement.read().await);
+        storage.set_session_cache(&measurement, cache);
+        let cached = storage.get_session_cache(0).unwrap();
+        assert!(cached.is_valid_for(&*measurement.read().await));
+        assert_eq!(cached.summary, measurement.read().await.get_summary());
+        // re-selecting an unchanged measurement keeps the cached results
+        storage.select_measurement(1).unwrap();
+        storage.select_measurement(0).unwrap();
+        assert!(Arc::ptr_eq(&cached, &storage.get_session_cache(0).unwrap()));
+
+        // changed parameters invalidate the cache
+        measurement
+            .write()
+            .await
+            .set_stats_window(20)
+            .await
+            .unwrap();
+        assert!(!cached.is_valid_for(&*measurement.read().await));
+        assert!(storage
+            .stale_caches()
+            .iter()
+            .any(|stale| Arc::ptr_eq(stale, &measurement)));
+        let cache = SessionCache::from_model(&*measurement.read().await);
+        storage.set_session_cache(&measurement, cache);
+        let recomputed = storage.get_session_cache(0).unwrap();
+        assert!(!Arc::ptr_eq(&cached, &recomputed));
+        assert_ne!(recomputed.summary, cached.summary);
+
+        // the cache follows the measurements when they are moved
+        storage.move_measurement((0, 1)).await.unwrap();
+        assert!(Arc::ptr_eq(
+            &recomputed,
+            &storage.get_session_cache(1).unwrap()
+        ));
+        storage.clear().await.unwrap();
+        assert!(storage.get_session_cache(0).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_recorded_measurement_lifecycle() {
+        let mut storage = StorageComponent::<MeasurementData>::default();
+        assert!(storage.store_recorded_measurement().is_err());
+
+        let measurement = storage.new_measurement().unwrap();
+        assert_eq!(storage.get_acquisitions().len(), 0);
+        let stored = storage.store_recorded_measurement().unwrap();
+        assert!(Arc::ptr_eq(&measurement, &stored));
+        assert!(Arc::ptr_eq(
+            &measurement,
+            &storage.get_measurement(0).unwrap()
+        ));
+
+        // the recording can only be stored once
+        assert!(storage.store_recorded_measurement().is_err());
+        assert_eq!(storage.get_acquisitions().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_new_measurement_replaces_recording() {
+        let mut storage = StorageComponent::<MeasurementData>::default();
+        let discarded = storage.new_measurement().unwrap();
+        let measurement = storage.new_measurement().unwrap();
+        let stored = storage.store_recorded_measurement().unwrap();
+        assert!(Arc::ptr_eq(&measurement, &stored));
+        assert!(!Arc::ptr_eq(&discarded, &stored));
+        assert_eq!(storage.get_acquisitions().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_discard_recording() {
+        let mut storage = StorageComponent::<MeasurementData>::default();
+        assert!(storage.discard_recording().is_none());
+        let measurement = storage.new_measurement().unwrap();
+        assert!(storage.recording.is_some());
+        let discarded = storage.discard_recording().unwrap();
+        assert!(Arc::ptr_eq(&measurement, &discarded));
+        assert!(storage.recording.is_none());
+        assert!(storage.store_recorded_measurement().is_err());
+        assert!(storage.get_acquisitions().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_stale_session_caches() {
+        let mut storage = StorageComponent::<MeasurementData>::default();
+        storage.add_messages(get_data(200)).unwrap();
+        let selected = storage.get_measurement(0).unwrap();
+        let cache = SessionCache::from_model(&*selected.read().await);
+        storage.set_session_cache(&selected, cache);
+        let unselected = Arc::new(RwLock::new(MeasurementData::default()));
+        unselected
+            .write()
+            .await
+            .import_messages(get_data(100))
+            .unwrap();
+        storage.store_measurement(unselected.clone()).unwrap();
+        let stale = storage.stale_caches();
+        assert_eq!(stale.len(), 1);
+        assert!(Arc::ptr_eq(&stale[0], &unselected));
+
+        // results of a changed measurement are dropped
+        let outdated = SessionCache::from_model(&*unselected.read().await);
+        unselected.write().await.set_stats_window(20).await.unwrap();
+        storage.set_session_cache(&unselected, outdated);
+        assert!(storage.get_session_cache(1).is_none());
+
+        let cache = SessionCache::from_model(&*unselected.read().await);
+        storage.set_session_cache(&unselected, cache.clone());
+        assert_eq!(*storage.get_session_cache(1).unwrap(), cache);
+        assert!(storage.stale_caches().is_empty());
+
+        // measurements locked for writing are skipped
+        let _guard = storage.get_measurement(0).unwrap().write_owned().await;
+        storage.cache[0] = None;
+        assert!(storage.stale_caches().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_export_edf() {
+        let temp_dir = tempdir::TempDir::new("test").unwrap();
+        let path = temp_dir.path().join(PathBuf::from("test_measurement.edf"));
+        let mut storage = StorageComponent::<MeasurementData>::default();
+        assert!(storage.export_edf(path.clone()).await.is_err());
+
+        let measurement = Arc::new(RwLock::new(MeasurementData::default()));
+        {
+            let mut data = measurement.write().await;
+            data.start_recording().await.unwrap();
+            for (_, msg) in get_data(120) {
+                data.record_message(msg).await.unwrap();
+            }
+        }
+        assert!(storage.store_measurement(measurement).is_ok());
+        assert!(storage.select_measurement(0).is_ok());
+        assert!(storage.export_edf(path.clone()).await.is_ok());
+        let content = fs::read(&path).await.unwrap();
+        assert_eq!(&content[..8], b"0       ");
+    }
+
+    #[tokio::test]
+    async fn test_export_all() {
+        let temp_dir = tempdir::TempDir::new("test").unwrap();
+        let dir = temp_dir.path().join("export");
+        let mut storage = StorageComponent::<MeasurementData>::default();
+        assert!(storage.export_all(dir.clone()).await.is_err());
+
+        let mut rr_counts = Vec::new();
+        for _ in 0..2 {
+            let mut measurement = MeasurementData::default();
+            measurement.import_messages(get_data(120)).unwrap();
+            rr_counts.push(measurement.get_rr_ts().len());
+            assert!(storage
+                .store_measurement(Arc::new(RwLock::new(measurement)))
+                .is_ok());
+        }
+        assert!(storage.export_all(dir.clone()).await.is_ok());
+
+        let mut names = Vec::new();
+        let mut entries = fs::read_dir(&dir).await.unwrap();
+        while let Some(entry) = entries.next_entry().await.unwrap() {
+            names.push(entry.file_name().into_string().unwrap());
+        }
+        // measurements started in the same second get distinct files
+        assert_eq!(names.len(), 3);
+        assert!(names.contains(&SUMMARY_FILE.to_owned()));
+
+        let summary = fs::read_to_string(dir.join(SUMMARY_FILE)).await.unwrap();
+        let rows: Vec<&str> = summary.lines().skip(1).collect();
+        assert_eq!(rows.len(), 2);
+        for (row, rr_count) in rows.iter().zip(rr_counts) {
+            let name = row.split(',').next().unwrap();
+            let csv = fs::read_to_string(dir.join(name)).await.unwrap();
+            // header row plus one row per RR interval
+            assert_eq!(csv.lines().count(), 1 + rr_count);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_export_kubios() {
+        let temp_dir = tempdir::TempDir::new("test").unwrap();
+        let path = temp_dir.path().join(PathBuf::from("kubios.txt"));
+        let mut storage = StorageComponent::<MeasurementData>::default();
+        assert!(storage.export_kubios(path.clone()).await.is_err());
+
+        let mut measurement = MeasurementData::default();
+        measurement.import_messages(get_data(120)).unwrap();
+        let rr_count = measurement.get_rr_ts().len();
+        assert!(storage
+            .store_measurement(Arc::new(RwLock::new(measurement)))
+            .is_ok());
+        assert!(storage.select_measurement(0).is_ok());
+        assert!(storage.export_kubios(path.clone()).await.is_ok());
+        let content = fs::read_to_string(&path).await.unwrap();
+        assert_eq!(content.lines().count(), rr_count);
+    }
+
+    #[tokio::test]
+    async fn test_export_correction_log() {
+        let temp_dir = tempdir::TempDir::new("test").unwrap();
+        let path = temp_dir.path().join(PathBuf::from("corrections.csv"));
+        let mut storage = StorageComponent::<MeasurementData>::default();
+        assert!(storage.export_correction_log(path.clone()).await.is_err());
+
+        let measurement = Arc::new(RwLock::new(MeasurementData::default()));
+        {
+            let mut data = measurement.write().await;
+            data.start_recording().await.unwrap();
+            for (idx, (_, msg)) in get_data(40).into_iter().enumerate() {
+                let msg = if idx == 20 {
+                    HeartrateMessage::from_values(60, None, &[20000])
+                } else {
+                    msg
+                };
+                data.record_message(msg).await.unwrap();
+            }
+        }
+        assert!(storage.store_measurement(measurement).is_ok());
+        assert!(storage.select_measurement(0).is_ok());
+        assert!(storage.export_correction_log(path.clone()).await.is_ok());
+        let content = fs::read_to_string(&path).await.unwrap();
+        let mut lines = content.lines();
+        assert_eq!(lines.next(), Some("index,time_s,rr_ms,action,reason"));
+        assert!(lines
+            .any(|line| line.starts_with("20,") && line.ends_with(",20000.000,Excluded,TooLong")));
+
+        // the subject precedes the header
+        let metadata = MeasurementMetadata {
+            subject_id: "S-017".to_owned(),
+            posture: Some(Posture::Sitting),
+            ..Default::default()
+        };
+        let selected = storage.get_measurement(0).unwrap();
+        selected
+            .write()
+            .await
+            .set_metadata(Some(metadata))
+            .await
+            .unwrap();
+        assert!(storage.export_correction_log(path.clone()).await.is_ok());
+        let content = fs::read_to_string(&path).await.unwrap();
+        let mut lines = content.lines();
+        assert_eq!(lines.next(), Some("# subject_id: S-017"));
+        assert_eq!(lines.next(), Some("# posture: sitting"));
+        assert_eq!(lines.next(), Some("index,time_s,rr_ms,action,reason"));
+    }
 }
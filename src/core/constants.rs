@@ -3,3 +3,18 @@ use uuid::Uuid;
 
 /// UUID for the Heart Rate Measurement Characteristic.
 pub const HEARTRATE_MEASUREMENT_UUID: Uuid = uuid_from_u16(0x2A37);
+
+/// UUID for the Heart Rate Service.
+pub const HEARTRATE_SERVICE_UUID: Uuid = uuid_from_u16(0x180D);
+
+/// Default capacity of the application event bus.
+pub const DEFAULT_EVENT_BUS_CAPACITY: usize = 256;
+
+/// Environment variable overriding the capacity of the application event bus.
+pub const EVENT_BUS_CAPACITY_ENV: &str = "HRV_EVENT_BUS_CAPACITY";
+
+/// Maximum time to wait for the application controller to shut down on exit.
+pub const SHUTDOWN_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// Time to wait before trying to reconnect a sensor that was disconnected.
+pub const RECONNECT_DELAY: std::time::Duration = std::time::Duration::from_secs(2);
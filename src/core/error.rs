@@ -0,0 +1,202 @@
+//! Application Errors
+//!
+//! This module defines the typed errors of the event handling. The controllers report
+//! `anyhow` errors, which are classified by their cause and the event they occurred in, so
+//! the application can react differently to e.g. a lost sensor and a corrupt file.
+//! Controllers report malformed input as `InvalidData` to have it classified independent of
+//! the event.
+
+use std::fmt;
+
+/// Kind of failure of an event.
+#[derive(Debug)]
+pub enum AppError {
+    /// The Bluetooth adapter or the sensor failed.
+    Bluetooth(anyhow::Error),
+    /// A stored measurement could not be selected, loaded or written.
+    Storage(anyhow::Error),
+    /// Data read from a file or entered by the user is invalid.
+    Parse(anyhow::Error),
+    /// The analysis of a measurement failed.
+    Analysis(anyhow::Error),
+    /// Reading or writing a file failed.
+    Io(anyhow::Error),
+    /// A value entered by the user is invalid or can not be applied right now.
+    Input(anyhow::Error),
+}
+
+/// Kind of an `AppError`, sent to the views so they can offer a way to resolve it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ErrorKind {
+    Bluetooth,
+    Storage,
+    Parse,
+    Analysis,
+    Io,
+    Input,
+}
+
+/// Data read from a file that is not in the expected format, e.g. a malformed RR file.
+#[derive(Debug)]
+pub struct InvalidData(pub String);
+
+impl fmt::Display for InvalidData {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for InvalidData {}
+
+impl AppError {
+    /// Classifies an error raised while handling an event.
+    ///
+    /// Bluetooth, parse and I/O errors anywhere in the chain of causes determine the
+    /// variant, other errors are attributed to the origin of the event.
+    ///
+    /// # Arguments
+    /// * `error` - The error returned by a controller.
+    /// * `origin` - The variant of the component handling the event.
+    pub fn classify(error: anyhow::Error, origin: fn(anyhow::Error) -> AppError) -> Self {
+        let variant = error.chain().find_map(|cause| {
+            let variant: fn(anyhow::Error) -> AppError = if cause.is::<btleplug::Error>() {
+                AppError::Bluetooth
+            } else if cause.is::<InvalidData>()
+                || cause.is::<serde_json::Error>()
+                || cause.is::<std::num::ParseFloatError>()
+                || cause.is::<std::num::ParseIntError>()
+            {
+                AppError::Parse
+            } else if cause.is::<std::io::Error>() {
+                AppError::Io
+            } else {
+                return None;
+            };
+            Some(variant)
+        });
+        variant.unwrap_or(origin)(error)
+    }
+
+    /// Returns the underlying error.
+    pub fn inner(&self) -> &anyhow::Error {
+        match self {
+            Self::Bluetooth(e)
+            | Self::Storage(e)
+            | Self::Parse(e)
+            | Self::Analysis(e)
+            | Self::Io(e)
+            | Self::Input(e) => e,
+        }
+    }
+
+    /// Returns the kind of the error.
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            Self::Bluetooth(_) => ErrorKind::Bluetooth,
+            Self::Storage(_) => ErrorKind::Storage,
+            Self::Parse(_) => ErrorKind::Parse,
+            Self::Analysis(_) => ErrorKind::Analysis,
+            Self::Io(_) => ErrorKind::Io,
+            Self::Input(_) => ErrorKind::Input,
+        }
+    }
+
+    /// Returns a hint how the user can resolve the error, if there is one.
+    pub fn hint(&self) -> Option<&'static str> {
+        match self {
+            Self::Bluetooth(_) => Some("check that the sensor is in range and select it again"),
+            Self::Parse(_) => Some("the file has an unexpected format"),
+            Self::Io(_) => Some("check the path and the permissions"),
+            Self::Input(_) => Some("the change was not applied"),
+            Self::Storage(_) | Self::Analysis(_) => None,
+        }
+    }
+}
+
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Bluetooth(e) => write!(f, "bluetooth error: {}", e),
+            Self::Storage(e) => write!(f, "storage error: {}", e),
+            Self::Parse(e) => write!(f, "invalid data: {}", e),
+            Self::Analysis(e) => write!(f, "analysis error: {}", e),
+            Self::Io(e) => write!(f, "file error: {}", e),
+            Self::Input(e) => write!(f, "invalid input: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for AppError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(self.inner().as_ref())
+    }
+}
+
+impl From<btleplug::Error> for AppError {
+    fn from(error: btleplug::Error) -> Self {
+        Self::Bluetooth(error.into())
+    }
+}
+
+impl From<serde_json::Error> for AppError {
+    fn from(error: serde_json::Error) -> Self {
+        Self::Parse(error.into())
+    }
+}
+
+impl From<std::io::Error> for AppError {
+    fn from(error: std::io::Error) -> Self {
+        Self::Io(error.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::{anyhow, Context};
+
+    #[test]
+    fn test_classify_by_cause() {
+        let io = std::io::Error::new(std::io::ErrorKind::NotFound, "missing.json");
+        let error = AppError::classify(
+            anyhow::Error::from(io).context("loading"),
+            AppError::Storage,
+        );
+        assert!(matches!(error, AppError::Io(_)));
+        assert_eq!(error.to_string(), "file error: loading");
+
+        let parse = serde_json::from_str::<Vec<f64>>("[1,").unwrap_err();
+        let error = AppError::classify(parse.into(), AppError::Storage);
+        assert!(matches!(error, AppError::Parse(_)));
+        let error = AppError::classify("x".parse::<f64>().unwrap_err().into(), AppError::Storage);
+        assert!(matches!(error, AppError::Parse(_)));
+        let invalid = anyhow::Error::new(InvalidData("no RR intervals found".to_owned()))
+            .context("importing empty.txt");
+        let error = AppError::classify(invalid, AppError::Storage);
+        assert!(matches!(error, AppError::Parse(_)));
+        assert!(error.hint().is_some());
+
+        let ble = Err::<(), _>(btleplug::Error::DeviceNotFound)
+            .context("selecting the sensor")
+            .unwrap_err();
+        let error = AppError::classify(ble, AppError::Storage);
+        assert!(matches!(error, AppError::Bluetooth(_)));
+        assert!(error.hint().is_some());
+    }
+
+    #[test]
+    fn test_classify_by_origin() {
+        let error = AppError::classify(anyhow!("not enough beats"), AppError::Analysis);
+        assert!(matches!(error, AppError::Analysis(_)));
+        assert!(error.hint().is_none());
+        assert_eq!(error.kind(), ErrorKind::Analysis);
+        assert_eq!(error.inner().to_string(), "not enough beats");
+        let error = AppError::classify(anyhow!("invalid pNNx threshold"), AppError::Input);
+        assert_eq!(error.kind(), ErrorKind::Input);
+        assert!(error.hint().is_some());
+        assert!(matches!(
+            AppError::from(std::io::Error::other("disk full")),
+            AppError::Io(_)
+        ));
+    }
+}
@@ -6,10 +6,23 @@
 use anyhow::Result;
 use event_bridge::EventBridge;
 use std::path::PathBuf;
+use time::Duration;
 
 use crate::{
     api::controller::{BluetoothApi, MeasurementApi, OutlierFilter, RecordingApi, StorageEventApi},
-    model::bluetooth::{AdapterDescriptor, DeviceDescriptor, HeartrateMessage},
+    model::{
+        alert::HrAlert,
+        bluetooth::{AdapterDescriptor, ConnectionState, DeviceDescriptor, HeartrateMessage},
+        hrv::{HrvConfig, SettlingFilter},
+        import::RrUnit,
+        metadata::MeasurementMetadata,
+        metrics::{DetrendOption, DifferenceMode},
+        readiness::ReadinessBaseline,
+        settings::{
+            AnalysisTemplate, ColorPalette, DeviceProfile, HrZones, MarkerInterval, PoincareStyle,
+            Retention,
+        },
+    },
 };
 
 #[derive(Debug, Clone, EventBridge)]
@@ -18,7 +31,20 @@ use crate::{
 pub enum StorageEvent {
     Clear,
     LoadFromFile(PathBuf),
+    AppendFromFile(PathBuf),
     StoreToFile(PathBuf),
+    ExportEdf(PathBuf),
+    ExportKubios(PathBuf),
+    ExportCorrectionLog(PathBuf),
+    ExportHtml(PathBuf),
+    ExportAll(PathBuf),
+    ImportRr((PathBuf, RrUnit)),
+    ImportFit(PathBuf),
+    LoadDemo,
+    MergeMeasurements((usize, usize)),
+    SplitMeasurement((usize, Duration)),
+    MoveMeasurement((usize, usize)),
+    ReplayMeasurement((usize, f64)),
 }
 
 #[derive(Debug, Clone, EventBridge)]
@@ -27,15 +53,32 @@ pub enum StorageEvent {
 pub enum MeasurementEvent {
     SetStatsWindow(usize),
     SetOutlierFilter(OutlierFilter),
+    SetAnalysisRange(Option<(Duration, Duration)>),
+    SetSkipInitial(Duration),
+    SetPnnThreshold(f64),
+    SetDetrend(DetrendOption),
+    SetDifferences(DifferenceMode),
+    SetSettlingFilter(Option<SettlingFilter>),
+    SetHrvConfig(HrvConfig),
+    AutoTuneOutlierScale(f64),
     RecordMessage(HeartrateMessage),
+    AddMarker(String),
+    SetMetadata(Option<MeasurementMetadata>),
+    SetExcluded(bool),
+    MarkDisconnected,
+    MarkReconnected,
 }
 
 #[derive(Debug, Clone, EventBridge)]
 #[forward_to_trait(RecordingApi)]
 #[trait_returned_type(HandlerResult)]
+// the variants are forwarded to the equally named methods of the `RecordingApi`
+#[allow(clippy::enum_variant_names)]
 pub enum RecordingEvent {
     StartRecording,
     StopRecording,
+    PauseRecording,
+    ResumeRecording,
 }
 
 type HandlerResult = Result<()>;
@@ -45,6 +88,7 @@ type HandlerResult = Result<()>;
 pub enum BluetoothEvent {
     SelectAdapter(AdapterDescriptor),
     SelectPeripheral(DeviceDescriptor),
+    ConnectionStateChanged(ConnectionState),
     //StartScan,
     //StopScan,
 }
@@ -53,9 +97,94 @@ pub enum BluetoothEvent {
 pub enum StateChangeEvent {
     DiscardRecording,
     StoreRecording,
+    /// Store the recording and continue with a new one without disconnecting the sensor.
+    StoreAndContinue,
     ToRecordingState,
     InitialState,
     SelectMeasurement(usize),
+    /// Compare the selected measurement to the stored one at the index, `None` ends the comparison.
+    CompareMeasurement(Option<usize>),
+    /// Cancel the countdown of an automatically starting recording.
+    CancelAutoStart,
+    /// Stop all listeners and disconnect the sensor before the application exits.
+    Shutdown,
+    /// Restart the scan of the selected adapter, e.g. after the sensor could not be found.
+    Rescan,
+}
+
+#[derive(Debug, Clone)]
+// named like the setter events of the other components
+#[allow(clippy::enum_variant_names)]
+pub enum SettingsEvent {
+    /// Directory to automatically save stopped recordings to, `None` disables auto-save.
+    SetAutoSaveDir(Option<PathBuf>),
+    /// Format description of displayed dates.
+    SetDateFormat(String),
+    /// Scaling factor of the user interface.
+    SetUiScale(f32),
+    /// Number of decimals of displayed metrics.
+    SetPrecision(usize),
+    /// Weight of the previous average when smoothing the live readout.
+    SetSmoothing(f64),
+    /// Appearance of the Poincaré plot.
+    SetPoincareStyle(PoincareStyle),
+    /// Colors of the metric time series and the Poincaré plot.
+    SetPalette(ColorPalette),
+    /// Time between two refreshes of the device list in seconds.
+    SetScanInterval(f64),
+    /// Number of attempts to reconnect a lost sensor.
+    SetReconnectAttempts(usize),
+    /// Whether the scan only discovers devices advertising the heart rate service.
+    SetScanHeartRateOnly(bool),
+    /// Minimum time between two repaints while receiving data in ms.
+    SetRepaintInterval(u64),
+    /// Time the window and outlier sliders have to settle before the statistics are
+    /// recalculated in ms.
+    SetRecomputeDebounce(u64),
+    /// Save the analysis profile of a device, replacing a profile of the same device.
+    SaveProfile(DeviceProfile),
+    /// Delete the analysis profile of the device with the given address.
+    DeleteProfile(String),
+    /// Save an analysis template, replacing a template of the same name.
+    SaveTemplate(AnalysisTemplate),
+    /// Delete the analysis template with the given name.
+    DeleteTemplate(String),
+    /// Name of the template applied to new recordings and imports, `None` keeps the defaults.
+    SetDefaultTemplate(Option<String>),
+    /// Personal RMSSD range the readiness is scored against, `None` hides the readiness.
+    SetReadinessBaseline(Option<ReadinessBaseline>),
+    /// Heart rate zones shaded behind the time series.
+    SetHrZones(HrZones),
+    /// Stop and store the recording when the lost sensor does not reconnect.
+    SetAutoStopOnDisconnect(bool),
+    /// Save storage files as gzip compressed JSON.
+    SetCompressFiles(bool),
+    /// Countdown in seconds before the recording starts once the sensor connected, `None`
+    /// waits for the user to start it.
+    SetAutoStart(Option<f64>),
+    /// Interval of the markers inserted automatically during a recording, `None` disables
+    /// them. Applies to the recordings started afterwards.
+    SetAutoMarkers(Option<MarkerInterval>),
+    /// Minimum number of inlier beats of a recording before it can be saved.
+    SetMinSaveBeats(usize),
+    /// Bounds of the heart rate raising an alert during a recording.
+    SetHrAlert(HrAlert),
+    /// Analyse loaded measurements when they are shown instead of while loading.
+    SetLazyAnalysis(bool),
+    /// Number of threads the analysis is limited to, `None` uses all cores.
+    SetAnalysisThreads(Option<usize>),
+    /// Cleanup of old auto-saved recordings at startup.
+    SetRetention(Retention),
+    /// Number of windows the plotted DFA alpha is median filtered over.
+    SetDfaSmoothing(usize),
+    /// Names of the metrics hidden in the time series plot.
+    SetHiddenMetrics(Vec<String>),
+    /// Unit of the RR intervals of imported files.
+    SetRrUnit(RrUnit),
+    /// Maximum change of the mean heart rate in 1/min of a stabilized recording.
+    SetStabilizationThreshold(f64),
+    /// Alias of the Bluetooth adapter with the given stable key, `None` removes it.
+    SetAdapterAlias((String, Option<String>)),
 }
 
 #[derive(Debug, Clone)]
@@ -65,4 +194,5 @@ pub enum AppEvent {
     Recording(RecordingEvent),
     Measurement(MeasurementEvent),
     AppState(StateChangeEvent),
+    Settings(SettingsEvent),
 }
@@ -0,0 +1,26 @@
+//! HRV Analysis Library
+//!
+//! This library holds the analysis of RR interval series used by the HRV analysis tool.
+//! It parses the messages of BLE heart rate sensors and computes the HRV statistics
+//! without depending on the Bluetooth controller or the user interface.
+
+/// Core utilities shared with the application.
+pub mod core {
+    /// Application-wide constants.
+    pub mod constants;
+}
+
+/// Data models of the HRV analysis.
+pub mod model {
+    /// Model for managing Bluetooth communication.
+    pub mod bluetooth;
+    /// Model for HRV-related data storage and processing.
+    pub mod hrv;
+    /// Pluggable HRV metrics and the default metric set.
+    pub mod metrics;
+    /// Synthetic recordings for validating the analysis.
+    pub mod synthetic;
+    /// Seeded random RR data shared by the tests.
+    #[cfg(test)]
+    pub mod testdata;
+}
@@ -7,11 +7,22 @@
 
 use btleplug::platform::Adapter;
 
+use crate::core::constants::{
+    DEFAULT_EVENT_BUS_CAPACITY, EVENT_BUS_CAPACITY_ENV, SHUTDOWN_TIMEOUT,
+};
 use components::application::AppController;
 use components::bluetooth::BluetoothComponent;
 use components::measurement::MeasurementData;
 use components::storage::StorageComponent;
 use eframe::NativeOptions;
+use model::hrv::{HrvAnalysisData, HrvConfig};
+use model::operation::FileOperation;
+use model::settings::{AppSettings, DEFAULT_PRECISION};
+use model::synthetic::{self, SyntheticConfig};
+use std::path::PathBuf;
+use std::sync::Arc;
+use time::{Duration, UtcOffset};
+use view::format::MetricFormat;
 
 use tokio::runtime::Runtime;
 use tokio::sync::broadcast;
@@ -19,7 +30,9 @@ use tokio::sync::broadcast;
 /// Core utilities and traits used throughout the application.
 mod core {
     /// Application-wide constants.
-    pub mod constants;
+    pub use hrv_rs::core::constants;
+    /// Typed errors of the event handling.
+    pub mod error;
     /// Event system for inter-module communication.
     pub mod events;
 }
@@ -42,59 +55,176 @@ mod components {
 
 /// Data models representing the application's domain.
 mod model {
+    /// The analysis models are shared with the library.
+    pub use hrv_rs::model::{bluetooth, hrv, metrics, synthetic};
 
-    /// Model for managing Bluetooth communication.
-    pub mod bluetooth;
-    /// Model for HRV-related data storage and processing.
-    pub mod hrv;
+    /// Alerts on an abnormal heart rate during a recording.
+    pub mod alert;
+    /// Cached results of the stored measurements.
+    pub mod cache;
+    /// Beat matching and agreement statistics for comparing recordings.
+    pub mod comparison;
+    /// Correlation of summary metrics across measurements.
+    pub mod correlation;
+    /// EDF+ export of RR intervals.
+    pub mod edf;
+    /// Import of RR interval series from text files.
+    pub mod import;
+    /// Export of RR intervals for Kubios HRV.
+    pub mod kubios;
+    /// Subject information of measurements.
+    pub mod metadata;
+    /// Progress and cancellation of long running file operations.
+    pub mod operation;
+    /// Readiness score of a recording against the personal baseline.
+    pub mod readiness;
+    /// Scheduling of stored measurements for replay.
+    pub mod replay;
+    /// Self-contained HTML report of a measurement.
+    pub mod report;
+    /// User configurable settings.
+    pub mod settings;
+    /// Seeded random RR data shared by the tests.
+    #[cfg(test)]
+    pub mod testdata;
 }
 
 /// UI-related components for the application.
 mod view {
+    /// Information about the application.
+    pub mod about;
     /// Bluetooth device management UI.
     pub mod acquisition;
+    /// Formatting of displayed metric values.
+    pub mod format;
+    /// Non-blocking access to the shared locks.
+    pub mod lock;
     /// Manages transitions between views.
     pub mod manager;
     /// HRV analysis user interface.
     pub mod overview;
+    /// Application settings window.
+    pub mod settings;
+    /// Smoothing of the live readout.
+    pub mod smoothing;
+}
+
+/// Runs a synthetic recording through the analysis and prints the computed metrics.
+///
+/// # Returns
+/// An error if a metric does not match the parameters of the recording.
+fn self_test() -> anyhow::Result<()> {
+    let config = SyntheticConfig::default();
+    let data = synthetic::generate(&config);
+    let analysis =
+        HrvAnalysisData::from_acquisition(&data, &[], &HrvConfig::default(), None, Duration::ZERO)?;
+    for metric in analysis.get_metrics() {
+        match metric.last() {
+            Some(val) => {
+                let format = MetricFormat::of(&metric.name, &metric.unit, DEFAULT_PRECISION);
+                println!("{}: {}", format.label, format.format(val))
+            }
+            None => println!("{}: -", metric.name),
+        }
+    }
+    let summary = analysis.summary();
+    println!("artifact ratio: {:.3}", summary.artifact_ratio);
+    synthetic::validate(&config, &summary)
 }
 
 /// Main entry point of the application.
 ///
 /// This function performs the following tasks:
-/// 1. Initializes the logger for debugging and informational output.
+/// 1. Initializes the logger for debugging and informational output. If `--self-test` is
+///    passed, runs the self-test instead of the application. A file passed as positional
+///    argument is loaded at startup.
 /// 2. Sets up a Tokio runtime for handling asynchronous operations.
 /// 3. Creates a broadcast channel for event-driven communication between modules.
 /// 4. Initializes shared state models for Bluetooth and data storage.
 /// 5. Starts the eframe application with the main view manager.
+/// 6. Waits for the application controller to shut down after the window was closed.
 ///
 /// The application is structured using a modular, event-driven MVC architecture.
 fn main() {
     // Initialize logger
     env_logger::init();
 
+    if std::env::args().any(|arg| arg == "--self-test") {
+        match self_test() {
+            Ok(()) => println!("self-test passed"),
+            Err(e) => {
+                eprintln!("self-test failed: {}", e);
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+
+    // The local offset can only be determined reliably while the process is single threaded.
+    let utc_offset = UtcOffset::current_local_offset().unwrap_or(UtcOffset::UTC);
+
     // Create a new Tokio runtime for asynchronous operations.
     let rt = Runtime::new().expect("Unable to create Runtime");
     let _enter = rt.enter();
 
     // Create a broadcast channel for event-driven communication.
-    let (event_bus, _) = broadcast::channel(16);
+    let capacity = std::env::var(EVENT_BUS_CAPACITY_ENV)
+        .ok()
+        .and_then(|val| val.parse::<usize>().ok())
+        .filter(|&capacity| capacity > 0)
+        .unwrap_or(DEFAULT_EVENT_BUS_CAPACITY);
+    let (event_bus, _) = broadcast::channel(capacity);
 
     // Shared state for Bluetooth model.
     let bluetooth = BluetoothComponent::<Adapter>::new(event_bus.clone());
+    // Shared state of the file operations, the views cancel them without locking the storage.
+    let file_operation = Arc::new(FileOperation::default());
     // Shared state for data storage model.
-    let storage = StorageComponent::<MeasurementData>::default();
+    let storage =
+        StorageComponent::<MeasurementData>::default().with_operation(file_operation.clone());
+
+    let settings_path = AppSettings::default_path();
+    let mut settings = settings_path
+        .as_deref()
+        .map(AppSettings::load)
+        .transpose()
+        .unwrap_or_else(|e| {
+            log::error!("could not load settings, using defaults: {}", e);
+            None
+        })
+        .unwrap_or_default();
+    settings.display.set_utc_offset(utc_offset);
 
-    let app = AppController::new(bluetooth, storage, event_bus.clone());
+    // a file passed as positional argument is opened for analysis
+    let file = std::env::args()
+        .skip(1)
+        .find(|arg| !arg.starts_with("--"))
+        .map(PathBuf::from);
+
+    let app = AppController::new(bluetooth, storage, event_bus.clone())
+        .with_settings(settings, settings_path)
+        .with_file_operation(file_operation)
+        .with_initial_file(file);
+    let (handler_tx, handler_rx) = std::sync::mpsc::channel();
     // Start the eframe application with the main view manager.
     eframe::run_native(
         "Hrv-rs",
         NativeOptions::default(),
-        Box::new(|cc| {
+        Box::new(move |cc| {
             let res = Box::new(app.get_viewmanager());
-            tokio::spawn(app.event_handler(cc.egui_ctx.clone()));
+            let _ = handler_tx.send(tokio::spawn(app.event_handler(cc.egui_ctx.clone())));
             Ok(res)
         }),
     )
     .expect("Failed to start eframe application");
+
+    // The view manager requested the shutdown on exit, wait until the sensor is disconnected.
+    if let Ok(handler) = handler_rx.try_recv() {
+        if rt
+            .block_on(tokio::time::timeout(SHUTDOWN_TIMEOUT, handler))
+            .is_err()
+        {
+            log::warn!("application controller did not shut down in time");
+        }
+    }
 }
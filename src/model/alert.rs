@@ -0,0 +1,143 @@
+//! Heart Rate Alert
+//!
+//! This module watches the heart rate of a recording for values outside configured bounds.
+//! An alert is only raised once the heart rate stayed outside the bounds for the hold time,
+//! so single outliers and short excursions do not cause flapping alerts.
+
+use std::ops::RangeInclusive;
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use time::Duration;
+
+/// Valid time in seconds the heart rate has to stay out of bounds.
+pub const HR_ALERT_HOLD_RANGE: RangeInclusive<f64> = 1.0..=60.0;
+
+/// Bounds of the heart rate of an unattended recording.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HrAlert {
+    /// Whether the heart rate is watched.
+    pub enabled: bool,
+    /// Lowest normal heart rate in 1/min.
+    pub low: f64,
+    /// Highest normal heart rate in 1/min.
+    pub high: f64,
+    /// Time in seconds the heart rate has to stay out of bounds before an alert is raised.
+    pub hold: f64,
+}
+
+impl Default for HrAlert {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            low: 40.0,
+            high: 180.0,
+            hold: 5.0,
+        }
+    }
+}
+
+impl HrAlert {
+    /// Checks that the bounds are positive and ordered and the hold time is valid.
+    pub fn validate(&self) -> Result<()> {
+        if !(self.low > 0.0 && self.low < self.high) {
+            return Err(anyhow!(
+                "invalid heart rate bounds: {} - {} 1/min",
+                self.low,
+                self.high
+            ));
+        }
+        if !HR_ALERT_HOLD_RANGE.contains(&self.hold) {
+            return Err(anyhow!("invalid alert hold time: {} s", self.hold));
+        }
+        Ok(())
+    }
+}
+
+/// Raises an alert when the heart rate stays out of bounds.
+#[derive(Debug, Clone, Default)]
+pub struct HrAlertMonitor {
+    /// Elapsed time the heart rate left the bounds, `None` while within the bounds.
+    since: Option<Duration>,
+    /// Whether the alert of the current excursion was raised.
+    raised: bool,
+}
+
+impl HrAlertMonitor {
+    /// Checks the current heart rate.
+    ///
+    /// The alert is raised once per excursion, the heart rate has to return within the
+    /// bounds before the next alert.
+    ///
+    /// # Arguments
+    /// * `alert` - The bounds of the heart rate.
+    /// * `elapsed` - Elapsed time of the heart rate since the start of the recording.
+    /// * `hr` - The smoothed heart rate in 1/min.
+    ///
+    /// # Returns
+    /// The alert message, if the alert is raised.
+    pub fn update(&mut self, alert: &HrAlert, elapsed: Duration, hr: f64) -> Option<String> {
+        if !alert.enabled || (alert.low..=alert.high).contains(&hr) {
+            self.since = None;
+            self.raised = false;
+            return None;
+        }
+        let since = *self.since.get_or_insert(elapsed);
+        if self.raised || (elapsed - since).as_seconds_f64() < alert.hold {
+            return None;
+        }
+        self.raised = true;
+        let bound = if hr < alert.low {
+            format!("below {:.0}", alert.low)
+        } else {
+            format!("above {:.0}", alert.high)
+        };
+        Some(format!(
+            "heart rate {:.0} 1/min {} 1/min for {:.0} s",
+            hr, bound, alert.hold
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hr_alert_monitor() {
+        let alert = HrAlert {
+            enabled: true,
+            ..Default::default()
+        };
+        assert!(alert.validate().is_ok());
+        let mut monitor = HrAlertMonitor::default();
+        let feed = |monitor: &mut HrAlertMonitor, from: i64, to: i64, hr: f64| {
+            (from..to)
+                .filter_map(|sec| monitor.update(&alert, Duration::seconds(sec), hr))
+                .collect::<Vec<_>>()
+        };
+        // a short excursion is ignored
+        assert!(feed(&mut monitor, 0, 3, 190.0).is_empty());
+        assert!(feed(&mut monitor, 3, 10, 120.0).is_empty());
+        // a sustained excursion raises exactly one alert
+        let alerts = feed(&mut monitor, 10, 30, 35.0);
+        assert_eq!(alerts.len(), 1);
+        assert!(alerts[0].contains("below 40"));
+        // the next excursion raises a new alert
+        assert!(feed(&mut monitor, 30, 31, 60.0).is_empty());
+        assert_eq!(feed(&mut monitor, 31, 40, 200.0).len(), 1);
+
+        let disabled = HrAlert::default();
+        let mut monitor = HrAlertMonitor::default();
+        assert!((0..20).all(|sec| monitor
+            .update(&disabled, Duration::seconds(sec), 200.0)
+            .is_none()));
+        let invalid = HrAlert {
+            low: 100.0,
+            high: 90.0,
+            ..Default::default()
+        };
+        assert!(invalid.validate().is_err());
+    }
+}
@@ -6,13 +6,18 @@
 //! - Device and adapter management
 //! - Scanning and connection state tracking
 
-use btleplug::api::BDAddr;
+use crate::core::constants::HEARTRATE_SERVICE_UUID;
+use anyhow::{anyhow, Result};
+use btleplug::api::{BDAddr, ScanFilter};
 use serde::{Deserialize, Serialize};
 use std::fmt;
 use std::fmt::Debug;
 use std::hash::Hash;
 use uuid::Uuid;
 
+/// Highest physiologically plausible heart rate in 1/min, higher values are transmission errors.
+pub const MAX_PLAUSIBLE_HR: u16 = 250;
+
 /// Helper macro to check if a specific bit is set in a byte.
 macro_rules! is_bit_set {
     ($byte:expr, $pos:expr) => {
@@ -42,8 +47,9 @@ pub struct HeartrateMessage {
     hr_value: u16,
     /// Energy expenditure in kilojoules (if present).
     energy_expended: u16,
-    /// Array of RR interval values in milliseconds (if present).
-    rr_values: [u16; 9],
+    /// Array of RR interval values in milliseconds (if present), at the full 1/1024 s
+    /// resolution of the sensor.
+    rr_values: [f64; 9],
 }
 
 impl HeartrateMessage {
@@ -52,6 +58,9 @@ impl HeartrateMessage {
     /// # Arguments
     /// * `data` - A byte slice containing the raw HRS message data.
     ///
+    /// Fields announced by the flags but missing in a truncated message are left empty.
+    /// An implausible heart rate is discarded, the heart rate is then unavailable (0).
+    ///
     /// # Panics
     /// Panics if the provided data slice is too short.
     pub fn new(data: &[u8]) -> Self {
@@ -66,28 +75,37 @@ impl HeartrateMessage {
         } else {
             data[1] as u16
         };
+        let hr_value = if hr_value <= MAX_PLAUSIBLE_HR {
+            hr_value
+        } else {
+            log::warn!("implausible heart rate {} 1/min, discarded", hr_value);
+            0
+        };
 
         let mut result = HeartrateMessage {
             flags,
             hr_value,
             energy_expended: 0,
-            rr_values: [0u16; 9],
+            rr_values: [0f64; 9],
         };
 
-        if result.has_energy_exp() {
-            result.energy_expended = get_u16_little_endian!(data, result.energy_exp_offset());
+        let energy_offset = result.energy_exp_offset();
+        if result.has_energy_exp() && data.len() >= energy_offset + 2 {
+            result.energy_expended = get_u16_little_endian!(data, energy_offset);
         }
 
-        let rr_offset = result.rr_offset();
-        for (rr_store, chunk) in result.rr_values.iter_mut().zip(data[rr_offset..].chunks(2)) {
-            *rr_store = (get_u16_little_endian!(chunk, 0) as f64 * 1000f64 / 1024f64) as u16;
+        if result.has_rr_interval() {
+            let rr_data = data.get(result.rr_offset()..).unwrap_or_default();
+            for (rr_store, chunk) in result.rr_values.iter_mut().zip(rr_data.chunks_exact(2)) {
+                *rr_store = get_u16_little_endian!(chunk, 0) as f64 * 1000f64 / 1024f64;
+            }
         }
 
         result
     }
 
     /// Constructs a new `HeartrateMessage` from individual values.
-    /// This method is useful for testing and constructing messages with specific data.
+    /// This method is useful for testing and generating synthetic data.
     /// # Arguments
     /// * `hr_value` - The heart rate value in BPM.
     /// * `energy_expended` - The energy expenditure in kilojoules (optional).
@@ -104,10 +122,34 @@ impl HeartrateMessage {
     /// assert!(msg.has_energy_exp());
     /// assert_eq!(msg.get_energy_exp(), 10.0);
     /// assert!(msg.has_rr_interval());
-    /// assert_eq!(msg.get_rr_intervals(), &[1000, 250]);
+    /// assert_eq!(msg.get_rr_intervals(), &[1000.0, 250.0]);
     /// ```
-    #[cfg(test)]
     pub fn from_values(hr_value: u16, energy_expended: Option<u16>, rr_values_ms: &[u16]) -> Self {
+        let rr_values_ms: Vec<f64> = rr_values_ms.iter().map(|&rr| f64::from(rr)).collect();
+        Self::from_fractional_values(hr_value, energy_expended, &rr_values_ms)
+    }
+
+    /// Constructs a new `HeartrateMessage` from individual values with fractional RR intervals.
+    ///
+    /// Unlike `from_values`, the RR intervals keep their sub-millisecond resolution, e.g. of
+    /// imported intervals in seconds.
+    /// # Arguments
+    /// * `hr_value` - The heart rate value in BPM.
+    /// * `energy_expended` - The energy expenditure in kilojoules (optional).
+    /// * `rr_values_ms` - A slice of RR interval values in milliseconds.
+    /// # Returns
+    /// A new `HeartrateMessage` instance with the specified values.
+    /// # Example
+    /// ```
+    /// use hrv_rs::model::bluetooth::HeartrateMessage;
+    /// let msg = HeartrateMessage::from_fractional_values(80, None, &[812.5]);
+    /// assert_eq!(msg.get_rr_intervals(), &[812.5]);
+    /// ```
+    pub fn from_fractional_values(
+        hr_value: u16,
+        energy_expended: Option<u16>,
+        rr_values_ms: &[f64],
+    ) -> Self {
         let mut flags = 0b00000000;
         if !rr_values_ms.is_empty() {
             flags |= 0b00010000;
@@ -115,7 +157,7 @@ impl HeartrateMessage {
         if energy_expended.is_some() {
             flags |= 0b00001000;
         }
-        let mut rr_values = [0u16; 9];
+        let mut rr_values = [0f64; 9];
         rr_values
             .iter_mut()
             .zip(rr_values_ms.iter())
@@ -138,14 +180,19 @@ impl HeartrateMessage {
         self.hr_value.into()
     }
 
+    /// Checks if the message carries a plausible heart rate.
+    pub fn has_valid_hr(&self) -> bool {
+        (1..=MAX_PLAUSIBLE_HR).contains(&self.hr_value)
+    }
+
     /// Checks if RR intervals are present.
     pub fn has_rr_interval(&self) -> bool {
         is_bit_set!(self.flags, 4)
     }
 
-    /// Returns a slice of the valid RR intervals.
-    pub fn get_rr_intervals(&self) -> &[u16] {
-        let count = self.rr_values.iter().take_while(|&&x| x != 0).count();
+    /// Returns a slice of the valid RR intervals in milliseconds.
+    pub fn get_rr_intervals(&self) -> &[f64] {
+        let count = self.rr_values.iter().take_while(|&&x| x > 0.0).count();
         &self.rr_values[..count]
     }
 
@@ -233,15 +280,83 @@ pub struct DeviceDescriptor {
     pub address: BDAddr,
 }
 
+impl DeviceDescriptor {
+    /// Creates a descriptor for a device given by its address only.
+    ///
+    /// # Arguments
+    /// * `address` - The MAC address, e.g. `"AA:BB:CC:DD:EE:FF"` or `"AABBCCDDEEFF"`.
+    ///
+    /// # Returns
+    /// A `DeviceDescriptor` named after its address, or an error if the address is malformed.
+    pub fn from_address(address: &str) -> Result<Self> {
+        let address: BDAddr = address
+            .trim()
+            .parse()
+            .map_err(|e| anyhow!("invalid address '{}': {}", address.trim(), e))?;
+        Ok(Self {
+            name: address.to_string(),
+            address,
+        })
+    }
+}
+
+/// Returns the filter of the devices the adapter scans for.
+///
+/// # Arguments
+/// - `heart_rate_only`: Only discover devices advertising the heart rate service. Some sensors
+///   do not advertise their services and are missed with the filter.
+pub fn scan_filter(heart_rate_only: bool) -> ScanFilter {
+    ScanFilter {
+        services: if heart_rate_only {
+            vec![HEARTRATE_SERVICE_UUID]
+        } else {
+            Vec::new()
+        },
+    }
+}
+
+/// State of the connection to the sensor.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ConnectionState {
+    /// No adapter is scanning and no sensor is connected.
+    #[default]
+    Idle,
+    /// The selected adapter refreshes the list of devices.
+    Scanning,
+    /// Connecting to the selected sensor, or reconnecting after the connection was lost.
+    Connecting,
+    /// The selected sensor streams its notifications.
+    Connected,
+    /// The selected sensor streams its notifications into a recording.
+    Recording,
+}
+
+impl fmt::Display for ConnectionState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            Self::Idle => "idle",
+            Self::Scanning => "scanning",
+            Self::Connecting => "connecting",
+            Self::Connected => "connected",
+            Self::Recording => "recording",
+        };
+        write!(f, "{}", label)
+    }
+}
+
 /// Represents a Bluetooth adapter with a unique identifier.
 ///
 /// Stores information about a Bluetooth adapter including:
 /// - A human-readable name
 /// - A unique UUID for identification
+/// - The position in the discovery order, which tells adapters of the same name apart
+/// - The position among the discovered adapters of the same name
 #[derive(Clone, Debug)]
 pub struct AdapterDescriptor {
     name: String,
     uuid: Uuid,
+    index: usize,
+    occurrence: usize,
 }
 
 impl Hash for AdapterDescriptor {
@@ -262,14 +377,44 @@ impl AdapterDescriptor {
         Self {
             name,
             uuid: Uuid::new_v4(),
+            index: 0,
+            occurrence: 0,
         }
     }
+    /// Sets the position of the adapter in the discovery order.
+    pub fn with_index(mut self, index: usize) -> Self {
+        self.index = index;
+        self
+    }
+    /// Sets the position of the adapter among the discovered adapters of the same name.
+    pub fn with_occurrence(mut self, occurrence: usize) -> Self {
+        self.occurrence = occurrence;
+        self
+    }
     pub fn get_name(&self) -> &str {
         &self.name
     }
     pub fn get_uuid(&self) -> &Uuid {
         &self.uuid
     }
+    pub fn get_index(&self) -> usize {
+        self.index
+    }
+    /// Returns an identifier of the adapter that is unique and stable across restarts.
+    ///
+    /// The UUID is generated on every discovery, the discovery index changes when another
+    /// adapter is plugged in and the platforms do not report an adapter address. The key is
+    /// the name reported by the platform together with the position among the adapters of
+    /// the same name instead, e.g. to assign an alias to the adapter.
+    pub fn get_key(&self) -> String {
+        format!("{}#{}", self.name, self.occurrence)
+    }
+}
+
+impl fmt::Display for AdapterDescriptor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{} {}", self.index, self.name)
+    }
 }
 
 impl PartialOrd for AdapterDescriptor {
@@ -281,6 +426,93 @@ impl PartialOrd for AdapterDescriptor {
 #[cfg(test)]
 mod test {
     use super::*;
+    use proptest::{collection::vec, option, prelude::*};
+
+    /// Encodes an HRS message as sent by a sensor.
+    ///
+    /// # Arguments
+    /// * `flags` - The flags, the HR format, energy and RR bits are derived from the values.
+    /// * `hr` - The heart rate, encoded in 16 bit if it exceeds 8 bit or `flags` requests it.
+    /// * `energy` - The optional energy expenditure.
+    /// * `rr` - The RR intervals in 1/1024 s.
+    fn encode(flags: u8, hr: u16, energy: Option<u16>, rr: &[u16]) -> Vec<u8> {
+        let long_hr = flags & 1 != 0 || hr > u16::from(u8::MAX);
+        let mut flags = flags & 0b0000_0110;
+        flags |= long_hr as u8;
+        flags |= (energy.is_some() as u8) << 3;
+        flags |= (!rr.is_empty() as u8) << 4;
+        let mut data = vec![flags];
+        if long_hr {
+            data.extend_from_slice(&hr.to_le_bytes());
+        } else {
+            data.push(hr as u8);
+        }
+        if let Some(energy) = energy {
+            data.extend_from_slice(&energy.to_le_bytes());
+        }
+        for rr in rr {
+            data.extend_from_slice(&rr.to_le_bytes());
+        }
+        data
+    }
+
+    proptest! {
+        #[test]
+        fn prop_parse_matches_from_values(
+            flags in 0u8..8,
+            hr in any::<u16>(),
+            energy in option::of(any::<u16>()),
+            rr in vec(1u16..=u16::MAX, 0..=9),
+        ) {
+            let parsed = HeartrateMessage::new(&encode(flags, hr, energy, &rr));
+            let rr_ms: Vec<u16> = rr
+                .iter()
+                .map(|rr| (f64::from(*rr) * 1000.0 / 1024.0).round() as u16)
+                .collect();
+            // implausible rates are discarded
+            let expected_hr = if hr <= MAX_PLAUSIBLE_HR { hr } else { 0 };
+            let expected = HeartrateMessage::from_values(expected_hr, energy, &rr_ms);
+
+            prop_assert_eq!(parsed.get_hr(), expected.get_hr());
+            prop_assert_eq!(parsed.has_valid_hr(), (1..=MAX_PLAUSIBLE_HR).contains(&expected_hr));
+            prop_assert_eq!(parsed.has_energy_exp(), expected.has_energy_exp());
+            prop_assert_eq!(parsed.get_energy_exp(), expected.get_energy_exp());
+            prop_assert_eq!(parsed.has_rr_interval(), !rr.is_empty());
+            prop_assert_eq!(parsed.sen_has_contact(), flags & 0b010 != 0);
+            prop_assert_eq!(parsed.sen_contact_supported(), flags & 0b100 != 0);
+            let parsed_rr = parsed.get_rr_intervals();
+            prop_assert_eq!(parsed_rr.len(), expected.get_rr_intervals().len());
+            for (parsed, expected) in parsed_rr.iter().zip(expected.get_rr_intervals()) {
+                prop_assert!((parsed - expected).abs() <= 0.5);
+            }
+        }
+
+        #[test]
+        fn prop_parse_arbitrary_bytes(data in vec(any::<u8>(), 2..32)) {
+            // any flag combination and length is parsed without reading out of bounds
+            let msg = HeartrateMessage::new(&data);
+            let payload = data.len().saturating_sub(msg.rr_offset());
+            prop_assert!(msg.get_rr_intervals().len() <= payload / 2);
+            if !msg.has_rr_interval() {
+                prop_assert!(msg.get_rr_intervals().is_empty());
+            }
+        }
+    }
+
+    #[test]
+    fn test_truncated_messages() {
+        // energy and RR announced by the flags but missing
+        let msg = HeartrateMessage::new(&[0b00011001, 80]);
+        assert_eq!(msg.get_hr(), 80.0);
+        assert_eq!(msg.get_energy_exp(), 0.0);
+        assert!(msg.get_rr_intervals().is_empty());
+        // a trailing odd byte is not an RR interval
+        let msg = HeartrateMessage::new(&[0b00010000, 80, 0, 4, 1]);
+        assert_eq!(msg.get_rr_intervals(), &[1000.0]);
+        // RR bytes without the RR flag are ignored
+        let msg = HeartrateMessage::new(&[0b00000000, 80, 0, 4]);
+        assert!(msg.get_rr_intervals().is_empty());
+    }
     #[test]
     fn test_hr_service_msg_short_hr_no_exp() {
         // Short HR, no energy expenditure, no sensor contact, RR intervals (1000 and 250)
@@ -288,7 +520,7 @@ mod test {
         let msg = HeartrateMessage::new(&data);
         assert_eq!(msg.get_hr(), 80.0);
         assert!(msg.has_rr_interval());
-        assert_eq!(msg.get_rr_intervals(), &[1000, 250]);
+        assert_eq!(msg.get_rr_intervals(), &[1000.0, 250.0]);
     }
 
     #[test]
@@ -304,7 +536,7 @@ mod test {
 
         // Verify RR intervals
         assert!(msg.has_rr_interval());
-        assert_eq!(msg.get_rr_intervals(), &[1000, 250]);
+        assert_eq!(msg.get_rr_intervals(), &[1000.0, 250.0]);
     }
 
     #[test]
@@ -323,7 +555,7 @@ mod test {
 
         // Verify RR intervals
         assert!(msg.has_rr_interval());
-        assert_eq!(msg.get_rr_intervals(), &[1000, 250]);
+        assert_eq!(msg.get_rr_intervals(), &[1000.0, 250.0]);
     }
 
     #[test]
@@ -347,7 +579,7 @@ mod test {
         let msg = HeartrateMessage::new(&data);
         assert_eq!(msg.get_hr(), 75.0);
         assert!(!msg.has_rr_interval());
-        assert_eq!(msg.get_rr_intervals(), &[] as &[u16]);
+        assert_eq!(msg.get_rr_intervals(), &[] as &[f64]);
     }
 
     #[test]
@@ -363,13 +595,28 @@ mod test {
     #[test]
     fn test_hr_service_msg_with_long_hr_and_energy_exp() {
         // Long HR, energy expenditure, no sensor contact, no RR intervals
-        let data = [0b00001001, 90, 1, 10, 0];
+        let data = [0b00001001, 90, 0, 10, 0];
         let msg = HeartrateMessage::new(&data);
-        assert_eq!(msg.get_hr(), 346.0); // 90 + (1 << 8)
+        assert_eq!(msg.get_hr(), 90.0);
         assert!(msg.has_energy_exp());
         assert_eq!(msg.get_energy_exp(), 10.0);
     }
 
+    #[test]
+    fn test_implausible_long_hr() {
+        // long HR of 346 (90 + (1 << 8)) is discarded, the other fields are kept
+        let msg = HeartrateMessage::new(&[0b00001001, 90, 1, 10, 0]);
+        assert_eq!(msg.get_hr(), 0.0);
+        assert!(!msg.has_valid_hr());
+        assert_eq!(msg.get_energy_exp(), 10.0);
+        let msg = HeartrateMessage::new(&[0b00000001, 255, 1]);
+        assert_eq!(msg.get_hr(), 0.0);
+        assert!(!msg.has_valid_hr());
+        let msg = HeartrateMessage::new(&[0b00000001, 250, 0]);
+        assert_eq!(msg.get_hr(), 250.0);
+        assert!(msg.has_valid_hr());
+    }
+
     #[test]
     fn test_hr_service_msg_with_all_flags() {
         // Long HR, energy expenditure, sensor contact, RR intervals
@@ -379,11 +626,30 @@ mod test {
         assert!(msg.has_energy_exp());
         assert_eq!(msg.get_energy_exp(), 5.0);
         assert!(msg.has_rr_interval());
-        assert_eq!(msg.get_rr_intervals(), &[1000, 250]);
+        assert_eq!(msg.get_rr_intervals(), &[1000.0, 250.0]);
         assert!(msg.sen_has_contact());
         assert!(msg.sen_contact_supported());
     }
 
+    #[test]
+    fn test_rr_interval_sub_millisecond_precision() {
+        // RR interval of 821 / 1024 s
+        let data = [0b00010000, 80, 0x35, 0x03];
+        let msg = HeartrateMessage::new(&data);
+        assert_eq!(msg.get_rr_intervals(), &[821.0 * 1000.0 / 1024.0]);
+    }
+
+    #[test]
+    fn test_device_descriptor_from_address() {
+        let device = DeviceDescriptor::from_address(" AA:BB:CC:DD:EE:FF ").unwrap();
+        assert_eq!(
+            device.address,
+            BDAddr::from([0xAA, 0xBB, 0xCC, 0xDD, 0xEE, 0xFF])
+        );
+        assert!(DeviceDescriptor::from_address("AA:BB:CC:DD:EE").is_err());
+        assert!(DeviceDescriptor::from_address("not an address").is_err());
+    }
+
     #[test]
     fn test_from_values_full() {
         let msg = HeartrateMessage::from_values(80, Some(10), &[1000, 250]);
@@ -391,7 +657,7 @@ mod test {
         assert!(msg.has_energy_exp());
         assert_eq!(msg.get_energy_exp(), 10.0);
         assert!(msg.has_rr_interval());
-        assert_eq!(msg.get_rr_intervals(), &[1000, 250]);
+        assert_eq!(msg.get_rr_intervals(), &[1000.0, 250.0]);
     }
 
     #[test]
@@ -409,6 +675,6 @@ mod test {
         assert_eq!(msg.get_hr(), 80.0);
         assert!(!msg.has_energy_exp());
         assert!(msg.has_rr_interval());
-        assert_eq!(msg.get_rr_intervals(), &[1000, 250]);
+        assert_eq!(msg.get_rr_intervals(), &[1000.0, 250.0]);
     }
 }
@@ -0,0 +1,102 @@
+//! Session Cache
+//!
+//! This module holds the results of a stored measurement shown by the overview, so browsing
+//! the stored measurements does not recompute them. A cache entry is valid as long as the
+//! revision of its measurement is unchanged, the revision increases whenever the measurement
+//! changes.
+
+use crate::{
+    api::model::MeasurementModelApi,
+    model::{hrv::HrvSummary, metrics::MetricSeries},
+};
+
+/// Maximum number of points kept of every cached time series.
+pub const MAX_CACHED_POINTS: usize = 2000;
+
+/// Computed results of a stored measurement.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SessionCache {
+    /// Revision of the measurement the results were computed from.
+    pub revision: u64,
+    /// Summary of the HRV statistics.
+    pub summary: HrvSummary,
+    /// Stabilized RMSSD of the final period of the recording.
+    pub final_rmssd: Option<f64>,
+    /// Decimated time series of the metrics.
+    pub metrics: Vec<MetricSeries>,
+}
+
+impl SessionCache {
+    /// Computes the cached results of a measurement.
+    pub fn from_model(model: &dyn MeasurementModelApi) -> Self {
+        Self {
+            revision: model.get_revision(),
+            summary: model.get_summary(),
+            final_rmssd: model.get_final_rmssd(),
+            metrics: model
+                .get_metrics()
+                .into_iter()
+                .map(|metric| MetricSeries {
+                    time_series: decimate(&metric.time_series, MAX_CACHED_POINTS),
+                    ..metric
+                })
+                .collect(),
+        }
+    }
+
+    /// Checks if the results are still valid for `model`.
+    pub fn is_valid_for(&self, model: &dyn MeasurementModelApi) -> bool {
+        self.revision == model.get_revision()
+    }
+}
+
+/// Reduces a time series to at most `max_points` points for plotting.
+///
+/// The series is split into chunks and the minimum and maximum of every chunk are kept in
+/// time order, so peaks stay visible. Chunks without finite values are kept as a single
+/// point to preserve the gaps of the plot.
+///
+/// # Arguments
+/// * `series` - `[time, value]` points sorted by time.
+/// * `max_points` - Maximum number of returned points, at least 2.
+pub fn decimate(series: &[[f64; 2]], max_points: usize) -> Vec<[f64; 2]> {
+    if series.len() <= max_points || max_points < 2 {
+        return series.to_vec();
+    }
+    let chunk_size = series.len().div_ceil(max_points / 2);
+    let mut decimated = Vec::with_capacity(max_points);
+    for chunk in series.chunks(chunk_size) {
+        let finite = chunk.iter().filter(|p| p[1].is_finite());
+        let min = finite.clone().min_by(|a, b| a[1].total_cmp(&b[1]));
+        let max = finite.max_by(|a, b| a[1].total_cmp(&b[1]));
+        match (min, max) {
+            (Some(min), Some(max)) if min == max => decimated.push(*min),
+            (Some(min), Some(max)) if min[0] <= max[0] => decimated.extend([*min, *max]),
+            (Some(min), Some(max)) => decimated.extend([*max, *min]),
+            _ => decimated.push(chunk[0]),
+        }
+    }
+    decimated
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decimate() {
+        let series: Vec<[f64; 2]> = (0..1000)
+            .map(|idx| [idx as f64, if idx == 500 { 100.0 } else { 1.0 }])
+            .collect();
+        let decimated = decimate(&series, 100);
+        assert!(decimated.len() <= 100);
+        assert!(decimated.windows(2).all(|pair| pair[0][0] < pair[1][0]));
+        // the peak is kept
+        assert!(decimated.contains(&[500.0, 100.0]));
+        assert_eq!(decimate(&series[..50], 100), &series[..50]);
+
+        let mut with_gap = series.clone();
+        with_gap[200..300].iter_mut().for_each(|p| p[1] = f64::NAN);
+        assert!(decimate(&with_gap, 100).iter().any(|p| p[1].is_nan()));
+    }
+}
@@ -0,0 +1,141 @@
+//! Comparison Model
+//!
+//! This module provides the tools to compare two simultaneously recorded measurements.
+//! Beats of both recordings are matched by their timestamps and the agreement of the
+//! matched RR intervals is summarized in the Bland-Altman fashion (bias and limits of agreement).
+
+use anyhow::{anyhow, Result};
+
+/// Agreement statistics of two RR interval series.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Agreement {
+    /// `[mean, difference]` of every matched RR interval pair in ms.
+    pub points: Vec<[f64; 2]>,
+    /// Mean difference between the two series in ms.
+    pub bias: f64,
+    /// Lower limit of agreement (`bias - 1.96 * sd`) in ms.
+    pub lower_loa: f64,
+    /// Upper limit of agreement (`bias + 1.96 * sd`) in ms.
+    pub upper_loa: f64,
+}
+
+/// Matches the beats of two RR series by their timestamps.
+///
+/// Each beat of `a` is paired with the nearest not yet matched beat of `b`, if the
+/// timestamps differ by at most `tolerance` seconds. Both series are expected to be
+/// sorted by time.
+///
+/// # Arguments
+/// * `a` - `[time in s, RR in ms]` points of the first recording.
+/// * `b` - `[time in s, RR in ms]` points of the second recording.
+/// * `tolerance` - Maximum time difference in seconds for two beats to match.
+///
+/// # Returns
+/// A vector of matched `(rr_a, rr_b)` pairs.
+pub fn match_beats(a: &[[f64; 2]], b: &[[f64; 2]], tolerance: f64) -> Vec<(f64, f64)> {
+    let mut pairs = Vec::new();
+    let mut j = 0;
+    for &[t_a, rr_a] in a {
+        // advance to the last beat of b that is not after t_a
+        while j + 1 < b.len() && b[j + 1][0] <= t_a {
+            j += 1;
+        }
+        let Some(candidate) = [j, j + 1]
+            .into_iter()
+            .filter(|&idx| idx < b.len())
+            .min_by(|&l, &r| (b[l][0] - t_a).abs().total_cmp(&(b[r][0] - t_a).abs()))
+        else {
+            break;
+        };
+        if (b[candidate][0] - t_a).abs() <= tolerance {
+            pairs.push((rr_a, b[candidate][1]));
+            // every beat of b is matched at most once
+            j = candidate + 1;
+            if j >= b.len() {
+                break;
+            }
+        }
+    }
+    pairs
+}
+
+/// Calculates the Bland-Altman agreement of matched RR interval pairs.
+///
+/// # Arguments
+/// * `pairs` - Matched `(rr_a, rr_b)` pairs, see [`match_beats`].
+///
+/// # Returns
+/// The `Agreement` of the two series or an error if fewer than two pairs are given.
+pub fn calc_agreement(pairs: &[(f64, f64)]) -> Result<Agreement> {
+    if pairs.len() < 2 {
+        return Err(anyhow!("too few matched beats for agreement analysis"));
+    }
+    let points: Vec<[f64; 2]> = pairs.iter().map(|(a, b)| [(a + b) / 2.0, a - b]).collect();
+    let n = points.len() as f64;
+    let bias = points.iter().map(|p| p[1]).sum::<f64>() / n;
+    let sd = (points.iter().map(|p| (p[1] - bias).powi(2)).sum::<f64>() / (n - 1.0)).sqrt();
+    Ok(Agreement {
+        points,
+        bias,
+        lower_loa: bias - 1.96 * sd,
+        upper_loa: bias + 1.96 * sd,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rr_series(rr: &[f64], offset: f64) -> Vec<[f64; 2]> {
+        rr.iter()
+            .scan(offset, |t, &rr| {
+                *t += rr / 1000.0;
+                Some([*t, rr])
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_match_identical_series() {
+        let rr = [800.0, 810.0, 790.0, 805.0, 820.0];
+        let a = rr_series(&rr, 0.0);
+        let pairs = match_beats(&a, &a, 0.1);
+        assert_eq!(pairs.len(), rr.len());
+        assert!(pairs.iter().all(|(a, b)| a == b));
+    }
+
+    #[test]
+    fn test_match_respects_tolerance() {
+        let a = rr_series(&[800.0, 800.0, 800.0], 0.0);
+        let b = rr_series(&[800.0, 800.0, 800.0], 0.5);
+        assert!(match_beats(&a, &b, 0.1).is_empty());
+        assert_eq!(match_beats(&a, &b, 0.6).len(), 3);
+    }
+
+    #[test]
+    fn test_agreement_near_identical() {
+        let rr: Vec<f64> = (0..200)
+            .map(|idx| 800.0 + 50.0 * (idx as f64 / 5.0).sin())
+            .collect();
+        let a = rr_series(&rr, 0.0);
+        // second strap: slightly shifted clock and +-0.5 ms jitter
+        let rr_b: Vec<f64> = rr
+            .iter()
+            .enumerate()
+            .map(|(idx, rr)| if idx % 2 == 0 { rr + 0.5 } else { rr - 0.5 })
+            .collect();
+        let b = rr_series(&rr_b, 0.01);
+        let pairs = match_beats(&a, &b, 0.1);
+        assert_eq!(pairs.len(), rr.len());
+        let agreement = calc_agreement(&pairs).unwrap();
+        assert!(agreement.bias.abs() < 0.01);
+        assert!(agreement.lower_loa < agreement.bias);
+        assert!(agreement.upper_loa > agreement.bias);
+        assert!(agreement.upper_loa - agreement.lower_loa < 5.0);
+    }
+
+    #[test]
+    fn test_agreement_too_few_pairs() {
+        assert!(calc_agreement(&[(800.0, 800.0)]).is_err());
+    }
+}
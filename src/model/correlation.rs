@@ -0,0 +1,148 @@
+//! Correlation Model
+//!
+//! This module correlates the summary metrics of stored measurements, e.g. to see whether the
+//! RMSSD and the DFA alpha of a person move together across sessions. Measurements missing
+//! one of two metrics are left out of that pair only (pairwise deletion).
+
+use crate::model::hrv::HrvSummary;
+
+/// Minimum number of measurements with both metrics for a correlation.
+pub const MIN_CORRELATION_PAIRS: usize = 3;
+
+/// Summary metrics that are correlated, with their labels.
+pub const CORRELATION_METRICS: [(&str, fn(&HrvSummary) -> Option<f64>); 7] = [
+    ("RMSSD", |s| s.rmssd),
+    ("SDRR", |s| s.sdrr),
+    ("SD1", |s| s.sd1),
+    ("SD2", |s| s.sd2),
+    ("HR", |s| s.hr),
+    ("DFA α", |s| s.dfa_alpha),
+    ("mean RR", |s| s.mean_rr),
+];
+
+/// Calculates the Pearson correlation coefficient of paired values.
+///
+/// # Arguments
+/// * `pairs` - The `(x, y)` pairs.
+///
+/// # Returns
+/// The coefficient in `[-1, 1]`, or `None` for fewer than `MIN_CORRELATION_PAIRS` pairs or
+/// if one of the variables is constant.
+pub fn pearson(pairs: &[(f64, f64)]) -> Option<f64> {
+    if pairs.len() < MIN_CORRELATION_PAIRS {
+        return None;
+    }
+    let n = pairs.len() as f64;
+    let mean_x = pairs.iter().map(|(x, _)| x).sum::<f64>() / n;
+    let mean_y = pairs.iter().map(|(_, y)| y).sum::<f64>() / n;
+    let (cov, var_x, var_y) = pairs.iter().fold((0.0, 0.0, 0.0), |(cov, vx, vy), (x, y)| {
+        let (dx, dy) = (x - mean_x, y - mean_y);
+        (cov + dx * dy, vx + dx * dx, vy + dy * dy)
+    });
+    if var_x == 0.0 || var_y == 0.0 {
+        return None;
+    }
+    Some((cov / (var_x * var_y).sqrt()).clamp(-1.0, 1.0))
+}
+
+/// Pairwise correlations of the summary metrics of several measurements.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CorrelationMatrix {
+    /// Labels of the rows and columns, see `CORRELATION_METRICS`.
+    pub labels: Vec<&'static str>,
+    /// Correlation coefficient of every pair of metrics, `None` if it can not be calculated.
+    pub values: Vec<Vec<Option<f64>>>,
+    /// Number of measurements providing both metrics of a pair.
+    pub counts: Vec<Vec<usize>>,
+}
+
+impl CorrelationMatrix {
+    /// Correlates the metrics of the given summaries.
+    ///
+    /// # Arguments
+    /// * `summaries` - The summary of every measurement.
+    pub fn from_summaries(summaries: &[HrvSummary]) -> Self {
+        let columns: Vec<Vec<Option<f64>>> = CORRELATION_METRICS
+            .iter()
+            .map(|(_, metric)| summaries.iter().map(metric).collect())
+            .collect();
+        let (values, counts) = columns
+            .iter()
+            .map(|x| {
+                columns
+                    .iter()
+                    .map(|y| {
+                        let pairs: Vec<(f64, f64)> = x
+                            .iter()
+                            .zip(y)
+                            .filter_map(|(x, y)| Some(((*x)?, (*y)?)))
+                            .collect();
+                        (pearson(&pairs), pairs.len())
+                    })
+                    .unzip::<_, _, Vec<_>, Vec<_>>()
+            })
+            .unzip();
+        Self {
+            labels: CORRELATION_METRICS
+                .iter()
+                .map(|(label, _)| *label)
+                .collect(),
+            values,
+            counts,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn summary(rmssd: Option<f64>, hr: Option<f64>, dfa_alpha: Option<f64>) -> HrvSummary {
+        HrvSummary {
+            rmssd,
+            hr,
+            dfa_alpha,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_pearson() {
+        let pairs = [(1.0, 2.0), (2.0, 4.0), (3.0, 6.0)];
+        assert!((pearson(&pairs).unwrap() - 1.0).abs() < 1e-12);
+        let pairs = [(1.0, 3.0), (2.0, 2.0), (3.0, 1.0)];
+        assert!((pearson(&pairs).unwrap() + 1.0).abs() < 1e-12);
+        // x = [1, 2, 3, 4], y = [2, 1, 4, 3]: cov = 3, var_x = var_y = 5
+        let pairs = [(1.0, 2.0), (2.0, 1.0), (3.0, 4.0), (4.0, 3.0)];
+        assert!((pearson(&pairs).unwrap() - 0.6).abs() < 1e-12);
+        assert!(pearson(&pairs[..2]).is_none());
+        assert!(pearson(&[(1.0, 1.0), (2.0, 1.0), (3.0, 1.0)]).is_none());
+    }
+
+    #[test]
+    fn test_correlation_matrix() {
+        let summaries = [
+            summary(Some(20.0), Some(70.0), Some(1.2)),
+            summary(Some(40.0), Some(60.0), None),
+            summary(Some(60.0), Some(50.0), Some(0.8)),
+            summary(Some(80.0), Some(40.0), Some(0.6)),
+            summary(None, Some(55.0), Some(1.0)),
+        ];
+        let matrix = CorrelationMatrix::from_summaries(&summaries);
+        let idx = |label| matrix.labels.iter().position(|l| *l == label).unwrap();
+        let (rmssd, hr, dfa) = (idx("RMSSD"), idx("HR"), idx("DFA α"));
+
+        assert!((matrix.values[rmssd][hr].unwrap() + 1.0).abs() < 1e-12);
+        assert_eq!(matrix.counts[rmssd][hr], 4);
+        assert!((matrix.values[rmssd][rmssd].unwrap() - 1.0).abs() < 1e-12);
+        // pairwise deletion keeps the sessions with both metrics only
+        assert_eq!(matrix.counts[rmssd][dfa], 3);
+        let expected = pearson(&[(20.0, 1.2), (60.0, 0.8), (80.0, 0.6)]).unwrap();
+        assert_eq!(matrix.values[rmssd][dfa], Some(expected));
+        assert_eq!(matrix.values[dfa][rmssd], Some(expected));
+        assert_eq!(matrix.counts[hr][dfa], 4);
+        // metrics missing in every session are not correlated
+        assert!(matrix.values[idx("SD1")][hr].is_none());
+        assert_eq!(matrix.counts[idx("SD1")][hr], 0);
+    }
+}
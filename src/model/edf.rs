@@ -0,0 +1,164 @@
+//! EDF+ Export
+//!
+//! This module encodes RR intervals as an EDF+ file containing a single "EDF Annotations"
+//! signal. Every RR interval is written as an annotation with the time of the beat as onset
+//! and the interval in ms as text, see <https://www.edfplus.info/specs/edfplus.html>.
+
+use anyhow::{anyhow, Result};
+use time::{macros::format_description, Month, OffsetDateTime};
+
+/// Duration of a data record in seconds.
+const RECORD_DURATION: f64 = 60.0;
+
+/// Label of the annotation signal.
+const ANNOTATION_LABEL: &str = "EDF Annotations";
+
+/// Appends `value` left aligned and padded with spaces to `len` bytes.
+fn push_field(buf: &mut Vec<u8>, value: &str, len: usize) -> Result<()> {
+    if !value.is_ascii() || value.len() > len {
+        return Err(anyhow!("invalid EDF header field: {:?}", value));
+    }
+    buf.extend_from_slice(value.as_bytes());
+    buf.resize(buf.len() + len - value.len(), b' ');
+    Ok(())
+}
+
+/// Returns the upper case english abbreviation of `month` used in the recording field.
+fn month_abbreviation(month: Month) -> &'static str {
+    match month {
+        Month::January => "JAN",
+        Month::February => "FEB",
+        Month::March => "MAR",
+        Month::April => "APR",
+        Month::May => "MAY",
+        Month::June => "JUN",
+        Month::July => "JUL",
+        Month::August => "AUG",
+        Month::September => "SEP",
+        Month::October => "OCT",
+        Month::November => "NOV",
+        Month::December => "DEC",
+    }
+}
+
+/// Encodes the annotations of all data records.
+///
+/// Each record starts with the time-keeping annotation followed by the beats in its time span.
+fn encode_records(rr_ts: &[[f64; 2]]) -> Vec<Vec<u8>> {
+    let last = rr_ts.last().map_or(0.0, |[t, _]| t.max(0.0));
+    let count = (last / RECORD_DURATION).floor() as usize + 1;
+    let mut records: Vec<Vec<u8>> = (0..count)
+        .map(|idx| format!("+{}\x14\x14\0", idx as f64 * RECORD_DURATION).into_bytes())
+        .collect();
+    for [t, rr] in rr_ts {
+        let t = t.max(0.0);
+        let idx = ((t / RECORD_DURATION).floor() as usize).min(count - 1);
+        records[idx].extend_from_slice(format!("+{:.4}\x14RR {:.3}\x14\0", t, rr).as_bytes());
+    }
+    records
+}
+
+/// Encodes RR intervals as an EDF+ file.
+///
+/// # Arguments
+/// * `start_time` - Start of the recording, written as is.
+/// * `rr_ts` - RR intervals as `[time in s, RR in ms]` points relative to `start_time`.
+///
+/// # Returns
+/// The content of the EDF+ file.
+pub fn encode_rr_annotations(start_time: &OffsetDateTime, rr_ts: &[[f64; 2]]) -> Result<Vec<u8>> {
+    let records = encode_records(rr_ts);
+    // all records have the same size of an even number of bytes
+    let samples = records
+        .iter()
+        .map(|record| record.len().div_ceil(2))
+        .max()
+        .unwrap_or(1);
+
+    let mut buf = Vec::with_capacity(512 + records.len() * samples * 2);
+    push_field(&mut buf, "0", 8)?;
+    push_field(&mut buf, "X X X X", 80)?;
+    let startdate = format!(
+        "Startdate {:02}-{}-{} X X X",
+        start_time.day(),
+        month_abbreviation(start_time.month()),
+        start_time.year()
+    );
+    push_field(&mut buf, &startdate, 80)?;
+    push_field(
+        &mut buf,
+        &start_time.format(format_description!("[day].[month].[year repr:last_two]"))?,
+        8,
+    )?;
+    push_field(
+        &mut buf,
+        &start_time.format(format_description!("[hour].[minute].[second]"))?,
+        8,
+    )?;
+    push_field(&mut buf, "512", 8)?;
+    push_field(&mut buf, "EDF+C", 44)?;
+    push_field(&mut buf, &records.len().to_string(), 8)?;
+    push_field(&mut buf, &RECORD_DURATION.to_string(), 8)?;
+    push_field(&mut buf, "1", 4)?;
+
+    push_field(&mut buf, ANNOTATION_LABEL, 16)?;
+    push_field(&mut buf, "", 80)?;
+    push_field(&mut buf, "", 8)?;
+    push_field(&mut buf, "-1", 8)?;
+    push_field(&mut buf, "1", 8)?;
+    push_field(&mut buf, "-32768", 8)?;
+    push_field(&mut buf, "32767", 8)?;
+    push_field(&mut buf, "", 80)?;
+    push_field(&mut buf, &samples.to_string(), 8)?;
+    push_field(&mut buf, "", 32)?;
+
+    for mut record in records {
+        record.resize(samples * 2, 0);
+        buf.extend_from_slice(&record);
+    }
+    Ok(buf)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::macros::datetime;
+
+    fn field(buf: &[u8], start: usize, len: usize) -> &str {
+        std::str::from_utf8(&buf[start..start + len])
+            .unwrap()
+            .trim_end()
+    }
+
+    #[test]
+    fn test_header_fields() {
+        let start = datetime!(2024-03-01 22:30:15 UTC);
+        let rr_ts: Vec<[f64; 2]> = (1..=100).map(|i| [i as f64 * 1.5, 1500.0]).collect();
+        let edf = encode_rr_annotations(&start, &rr_ts).unwrap();
+
+        assert_eq!(&edf[0..8], b"0       ");
+        assert_eq!(field(&edf, 88, 80), "Startdate 01-MAR-2024 X X X");
+        assert_eq!(field(&edf, 168, 8), "01.03.24");
+        assert_eq!(field(&edf, 176, 8), "22.30.15");
+        assert_eq!(field(&edf, 184, 8), "512");
+        assert_eq!(field(&edf, 192, 44), "EDF+C");
+        // 150 s of beats span three records of 60 s
+        assert_eq!(field(&edf, 236, 8), "3");
+        assert_eq!(field(&edf, 244, 8), "60");
+        assert_eq!(field(&edf, 252, 4), "1");
+        assert_eq!(field(&edf, 256, 16), ANNOTATION_LABEL);
+
+        let samples: usize = field(&edf, 256 + 216, 8).parse().unwrap();
+        assert_eq!(edf.len(), 512 + 3 * samples * 2);
+        // every record starts with its time-keeping annotation
+        assert!(edf[512..].starts_with(b"+0\x14\x14\0+1.5000\x14RR 1500.000\x14\0"));
+        assert!(edf[512 + samples * 2..].starts_with(b"+60\x14\x14\0"));
+    }
+
+    #[test]
+    fn test_empty_recording() {
+        let edf = encode_rr_annotations(&datetime!(2024-03-01 0:00 UTC), &[]).unwrap();
+        assert_eq!(field(&edf, 236, 8), "1");
+        assert!(edf[512..].starts_with(b"+0\x14\x14\0"));
+    }
+}
@@ -7,8 +7,11 @@
 //! in the analysis of heart rate variability.
 
 use super::bluetooth::HeartrateMessage;
+use super::metrics::{
+    default_metrics, DetrendOption, DfaAlpha, DfaConfig, DifferenceMode, HrvMetric, MetricSeries,
+    DFA_ALPHA, HR, RMSSD, SD1, SD2, SDRR,
+};
 use anyhow::{anyhow, Result};
-use hrv_algos::analysis::dfa::{DFAnalysis, DetrendStrategy};
 use hrv_algos::analysis::nonlinear::calc_poincare_metrics;
 use hrv_algos::analysis::time::{calc_rmssd, calc_sdrr};
 use hrv_algos::preprocessing::outliers::{MovingQuantileFilter, OutlierClassifier};
@@ -16,14 +19,484 @@ use hrv_algos::preprocessing::outliers::{MovingQuantileFilter, OutlierClassifier
 use rayon::iter::{
     IndexedParallelIterator, IntoParallelIterator, IntoParallelRefIterator, ParallelIterator,
 };
+use rayon::{ThreadPool, ThreadPoolBuilder};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fmt::Debug;
-use std::ops::Range;
+use std::ops::{Range, RangeInclusive};
+use std::sync::{Arc, RwLock};
 use time::Duration;
 
 /// Represents inliers and outliers on the Poincare plot.
 pub type PoincarePoints = (Vec<[f64; 2]>, Vec<[f64; 2]>);
 
+/// SD1/SD2 ellipse of the Poincaré plot, oriented along the line of identity.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PoincareEllipse {
+    /// Centroid of the Poincaré points.
+    pub center: [f64; 2],
+    /// Semi-axis perpendicular to the line of identity.
+    pub sd1: f64,
+    /// Semi-axis along the line of identity.
+    pub sd2: f64,
+}
+
+impl PoincareEllipse {
+    /// Returns the outline of the ellipse.
+    ///
+    /// # Arguments
+    ///
+    /// * `segments` - Number of line segments, the first point is repeated at the end.
+    pub fn polyline(&self, segments: usize) -> Vec<[f64; 2]> {
+        let axis = std::f64::consts::FRAC_1_SQRT_2;
+        (0..=segments)
+            .map(|idx| {
+                let angle = std::f64::consts::TAU * idx as f64 / segments as f64;
+                let along = self.sd2 * angle.cos();
+                let across = self.sd1 * angle.sin();
+                [
+                    self.center[0] + (along - across) * axis,
+                    self.center[1] + (along + across) * axis,
+                ]
+            })
+            .collect()
+    }
+}
+
+/// Supported scales of the outlier filter.
+///
+/// Smaller scales flag the normal beat to beat variability as artifacts, with larger scales
+/// the filter degenerates and flags nothing.
+pub const OUTLIER_SCALE_RANGE: RangeInclusive<f64> = 0.5..=10.0;
+
+/// Supported numbers of RR intervals discarded after a gap.
+pub const BLANK_AFTER_GAP_RANGE: RangeInclusive<usize> = 0..=20;
+
+/// Valid limits of successive identical RR intervals.
+pub const IDENTICAL_RUN_RANGE: RangeInclusive<usize> = 2..=50;
+
+/// Supported lengths of the settling phase with a separate outlier filter scale, in seconds.
+pub const SETTLING_PERIOD_RANGE: RangeInclusive<i64> = 10..=600;
+
+/// Outlier filter scale applied to the RR intervals of the settling phase.
+///
+/// The beats shortly after putting on the sensor are noisier than the ones of the stable
+/// phase, a looser filter avoids flagging them as artifacts. RR intervals ending later than
+/// `period` after the start of the recording are judged by the regular outlier filter.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SettlingFilter {
+    /// Elapsed time since the start of the recording at which the settling phase ends.
+    pub period: Duration,
+    /// Outlier filter scale within the settling phase.
+    pub scale: f64,
+}
+
+impl Default for SettlingFilter {
+    fn default() -> Self {
+        Self {
+            period: Duration::minutes(2),
+            scale: 8.0,
+        }
+    }
+}
+
+impl SettlingFilter {
+    /// Checks that the period and the scale are supported.
+    pub fn validate(&self) -> Result<()> {
+        if !SETTLING_PERIOD_RANGE.contains(&self.period.whole_seconds()) {
+            return Err(anyhow!("invalid settling period: {}", self.period));
+        }
+        if !OUTLIER_SCALE_RANGE.contains(&self.scale) {
+            return Err(anyhow!("invalid settling outlier scale: {}", self.scale));
+        }
+        Ok(())
+    }
+}
+
+impl std::fmt::Display for SettlingFilter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "scale {:.1} for {} s",
+            self.scale,
+            self.period.whole_seconds()
+        )
+    }
+}
+
+/// Supported numbers of threads of a dedicated analysis thread pool.
+pub const ANALYSIS_THREADS_RANGE: RangeInclusive<usize> = 1..=64;
+
+/// Dedicated thread pool of the analysis, `None` uses the global rayon pool.
+static ANALYSIS_POOL: RwLock<Option<Arc<ThreadPool>>> = RwLock::new(None);
+
+/// Limits the number of threads used for the analysis.
+///
+/// The windowed metrics are calculated in a dedicated thread pool of the given size, so the
+/// analysis does not occupy all cores.
+///
+/// # Arguments
+/// * `threads` - Number of analysis threads, `None` uses the global rayon pool.
+pub fn set_analysis_threads(threads: Option<usize>) -> Result<()> {
+    let pool = match threads {
+        Some(threads) if !ANALYSIS_THREADS_RANGE.contains(&threads) => {
+            return Err(anyhow!("invalid number of analysis threads: {}", threads));
+        }
+        Some(threads) => Some(Arc::new(
+            ThreadPoolBuilder::new()
+                .num_threads(threads)
+                .thread_name(|idx| format!("analysis-{}", idx))
+                .build()?,
+        )),
+        None => None,
+    };
+    *ANALYSIS_POOL
+        .write()
+        .map_err(|_| anyhow!("analysis thread pool lock poisoned"))? = pool;
+    Ok(())
+}
+
+/// Runs `op` in the analysis thread pool.
+///
+/// Parallel iterators within `op` use the dedicated pool if one is set.
+fn in_analysis_pool<R: Send>(op: impl FnOnce() -> R + Send) -> R {
+    let pool = ANALYSIS_POOL.read().ok().and_then(|pool| pool.clone());
+    match pool {
+        Some(pool) => pool.install(op),
+        None => op(),
+    }
+}
+
+/// Number of bisection steps when tuning the outlier scale.
+const OUTLIER_TUNING_STEPS: usize = 16;
+
+/// Returns the fraction of the RR intervals the outlier filter classifies as artifacts.
+///
+/// # Arguments
+/// * `rr` - RR intervals in ms.
+/// * `scale` - Scale of the outlier filter.
+pub fn artifact_ratio(rr: &[f64], scale: f64) -> Result<f64> {
+    if rr.is_empty() {
+        return Ok(0.0);
+    }
+    let mut filter = MovingQuantileFilter::new(None, None, None);
+    filter.set_quantile_scale(scale)?;
+    filter.add_data(rr)?;
+    let outliers = filter
+        .get_classification()
+        .iter()
+        .filter(|class| class.is_outlier())
+        .count();
+    Ok(outliers as f64 / rr.len() as f64)
+}
+
+/// Searches the outlier scale whose artifact ratio is closest to a target.
+///
+/// The artifact ratio decreases with the scale, so the scale is found by bisection
+/// within `OUTLIER_SCALE_RANGE`.
+///
+/// # Arguments
+/// * `rr` - RR intervals in ms.
+/// * `target_ratio` - Desired fraction of artifacts, e.g. `0.02`.
+pub fn tune_outlier_scale(rr: &[f64], target_ratio: f64) -> Result<f64> {
+    if !(0.0..1.0).contains(&target_ratio) {
+        return Err(anyhow!("invalid artifact ratio: {}", target_ratio));
+    }
+    let (mut lo, mut hi) = (*OUTLIER_SCALE_RANGE.start(), *OUTLIER_SCALE_RANGE.end());
+    let mut best = (hi, f64::MAX);
+    for _ in 0..OUTLIER_TUNING_STEPS {
+        let scale = (lo + hi) / 2.0;
+        let ratio = artifact_ratio(rr, scale)?;
+        if (ratio - target_ratio).abs() < best.1 {
+            best = (scale, (ratio - target_ratio).abs());
+        }
+        if ratio > target_ratio {
+            lo = scale;
+        } else {
+            hi = scale;
+        }
+    }
+    Ok(best.0)
+}
+
+/// Length of the windows whose mean heart rates are compared to detect the stabilization.
+pub const STABILIZATION_WINDOW: Duration = Duration::seconds(30);
+/// Shift between two compared windows of the stabilization detection.
+const STABILIZATION_STEP: Duration = Duration::seconds(5);
+/// Supported thresholds of the mean heart rate change of a stable recording in 1/min.
+pub const STABILIZATION_THRESHOLD_RANGE: RangeInclusive<f64> = 0.5..=10.0;
+/// Default threshold of the mean heart rate change of a stable recording in 1/min.
+pub const DEFAULT_STABILIZATION_THRESHOLD: f64 = 2.0;
+
+/// Detects when the heart rate stabilized after the initial transient of a recording.
+///
+/// Windows of `STABILIZATION_WINDOW` are shifted over the recording, the first window
+/// whose mean heart rate differs by less than `threshold` from the following window marks
+/// the stable part, e.g. as start of the analysis range.
+///
+/// # Arguments
+/// * `rr_ts` - `[elapsed time in s, RR interval in ms]` points sorted by time.
+/// * `threshold` - Maximum change of the mean heart rate in 1/min.
+///
+/// # Returns
+/// The elapsed time at which the stable part starts, `None` if the recording is too short
+/// or never stabilizes.
+pub fn detect_stabilization(rr_ts: &[[f64; 2]], threshold: f64) -> Option<Duration> {
+    let window = STABILIZATION_WINDOW.as_seconds_f64();
+    let mean_hr = |start: f64| {
+        let lo = rr_ts.partition_point(|p| p[0] < start);
+        let hi = rr_ts.partition_point(|p| p[0] < start + window);
+        let rr = &rr_ts[lo..hi];
+        let sum: f64 = rr.iter().map(|p| p[1]).sum();
+        (sum > 0.0).then(|| 60000.0 * rr.len() as f64 / sum)
+    };
+    let (first, last) = (rr_ts.first()?[0], rr_ts.last()?[0]);
+    let mut start = first;
+    while start + 2.0 * window <= last {
+        if let (Some(current), Some(next)) = (mean_hr(start), mean_hr(start + window)) {
+            if (next - current).abs() < threshold {
+                return Some(Duration::seconds_f64(start));
+            }
+        }
+        start += STABILIZATION_STEP.as_seconds_f64();
+    }
+    None
+}
+
+/// Parameters of the HRV analysis.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HrvConfig {
+    /// Statistics window in number of RR intervals, `None` uses all intervals.
+    pub window: Option<usize>,
+    /// Scale of the outlier filter.
+    pub outlier_filter: f64,
+    /// Box sizes of the DFA 1 alpha.
+    pub dfa: DfaConfig,
+    /// Number of RR intervals discarded after every gap, e.g. beats the sensor buffered
+    /// while it was disconnected.
+    pub blank_after_gap: usize,
+    /// Number of successive identical RR intervals that are kept, further repetitions are
+    /// discarded as a flatline of the sensor. `None` keeps all intervals.
+    pub max_identical_run: Option<usize>,
+}
+
+impl HrvConfig {
+    /// Checks that the parameters are within their supported ranges.
+    pub fn validate(&self) -> Result<()> {
+        self.dfa.validate()?;
+        if !OUTLIER_SCALE_RANGE.contains(&self.outlier_filter)
+            || self.window == Some(0)
+            || !BLANK_AFTER_GAP_RANGE.contains(&self.blank_after_gap)
+            || self
+                .max_identical_run
+                .is_some_and(|run| !IDENTICAL_RUN_RANGE.contains(&run))
+        {
+            return Err(anyhow!("invalid analysis parameters: {:?}", self));
+        }
+        Ok(())
+    }
+}
+
+impl Default for HrvConfig {
+    fn default() -> Self {
+        Self {
+            window: None,
+            outlier_filter: 5.0,
+            dfa: DfaConfig::default(),
+            blank_after_gap: 0,
+            max_identical_run: None,
+        }
+    }
+}
+
+/// Action taken on an RR interval by the artifact correction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CorrectionAction {
+    /// The interval is excluded from the analysis.
+    Excluded,
+}
+
+/// Reason an RR interval was classified as artifact.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CorrectionReason {
+    /// The interval is longer than the surrounding intervals, e.g. a missed beat.
+    TooLong,
+    /// The interval is shorter than the surrounding intervals, e.g. an extra beat.
+    TooShort,
+}
+
+/// Entry of the artifact correction log.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct CorrectionEntry {
+    /// Index of the RR interval in the recording.
+    pub index: usize,
+    /// Elapsed time of the beat ending the interval in s.
+    pub time: f64,
+    /// Original RR interval in ms.
+    pub rr: f64,
+    /// Action taken on the interval.
+    pub action: CorrectionAction,
+    /// Reason of the action.
+    pub reason: CorrectionReason,
+}
+
+/// Encodes a correction log as CSV with a header row.
+pub fn correction_log_csv(log: &[CorrectionEntry]) -> String {
+    let mut csv = String::from("index,time_s,rr_ms,action,reason\n");
+    for entry in log {
+        csv.push_str(&format!(
+            "{},{:.3},{:.3},{:?},{:?}\n",
+            entry.index, entry.time, entry.rr, entry.action, entry.reason
+        ));
+    }
+    csv
+}
+
+/// Number of intervals on each side of an artifact its reference is computed from.
+const CORRECTION_CONTEXT: usize = 10;
+
+/// Length of the segments used for SDANN and the SDNN index.
+pub const LONG_TERM_SEGMENT: Duration = Duration::minutes(5);
+/// Minimum number of complete segments required for SDANN and the SDNN index.
+pub const MIN_LONG_TERM_SEGMENTS: usize = 2;
+/// Minimum number of RR intervals required for the HRV statistics.
+pub const MIN_BEATS: usize = 4;
+/// Length of the final period the stabilized RMSSD of a recording is calculated from.
+pub const FINAL_RMSSD_PERIOD: Duration = Duration::minutes(5);
+/// Number of most recent beats the instantaneous SD1 is calculated from.
+pub const INSTANT_SD1_BEATS: usize = 30;
+/// Length of the most recent period the rolling artifact rate is calculated over.
+pub const RECENT_ARTIFACT_PERIOD: Duration = Duration::seconds(60);
+
+/// State of the analysis of a measurement.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnalysisState {
+    /// No RR intervals were received yet.
+    NoData,
+    /// Fewer than `MIN_BEATS` RR intervals were received.
+    Insufficient,
+    /// The statistics are calculated, individual metrics may still be unavailable.
+    Ready,
+}
+
+/// Summary of the HRV statistics of an analysis.
+///
+/// Values that can not be calculated for the analysed data are `None`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct HrvSummary {
+    /// Most recent RMSSD in ms.
+    pub rmssd: Option<f64>,
+    /// Most recent SDRR in ms.
+    pub sdrr: Option<f64>,
+    /// Most recent SD1 in ms.
+    pub sd1: Option<f64>,
+    /// Most recent SD2 in ms.
+    pub sd2: Option<f64>,
+    /// Most recent heart rate in 1/min.
+    pub hr: Option<f64>,
+    /// Most recent DFA 1 alpha.
+    pub dfa_alpha: Option<f64>,
+    /// SDANN in ms.
+    pub sdann: Option<f64>,
+    /// SDNN index in ms.
+    pub sdnn_index: Option<f64>,
+    /// Mean of the inlier RR intervals in ms.
+    pub mean_rr: Option<f64>,
+    /// Number of analysed RR intervals.
+    pub rr_count: usize,
+    /// Fraction of the RR intervals classified as outliers.
+    pub artifact_ratio: f64,
+    /// Number of sensor messages lost while recording.
+    #[serde(default)]
+    pub dropped_events: u64,
+    /// Duration of the analysed RR series.
+    pub duration: Duration,
+}
+
+impl HrvSummary {
+    /// Tab separated column names matching `to_tsv`.
+    pub const TSV_HEADER: &'static str = "rmssd_ms\tsdrr_ms\tsd1_ms\tsd2_ms\thr_bpm\tdfa_alpha\t\
+        sdann_ms\tsdnn_index_ms\tmean_rr_ms\trr_count\tartifact_ratio\tdropped_events\tduration_s";
+
+    /// Encodes the summary as a tab separated row, e.g. to paste it into a spreadsheet.
+    ///
+    /// Unavailable values are left empty.
+    pub fn to_tsv(&self) -> String {
+        let optional = |val: Option<f64>| val.map_or(String::new(), |val| format!("{:.3}", val));
+        [
+            optional(self.rmssd),
+            optional(self.sdrr),
+            optional(self.sd1),
+            optional(self.sd2),
+            optional(self.hr),
+            optional(self.dfa_alpha),
+            optional(self.sdann),
+            optional(self.sdnn_index),
+            optional(self.mean_rr),
+            self.rr_count.to_string(),
+            format!("{:.4}", self.artifact_ratio),
+            self.dropped_events.to_string(),
+            format!("{:.3}", self.duration.as_seconds_f64()),
+        ]
+        .join("\t")
+    }
+}
+
+/// Analyses a series of RR intervals without going through sensor messages.
+///
+/// The intervals are filtered and analysed like a recording without gaps. Intervals of 0 ms
+/// or less and non-finite intervals are ignored, the DFA 1 alpha is `None` until enough
+/// intervals for its largest box are available.
+///
+/// # Arguments
+///
+/// * `rr_ms` - RR intervals in ms.
+/// * `config` - Parameters of the analysis.
+///
+/// # Returns
+///
+/// The summary of the analysis, or an `Err` if the configuration is invalid or the intervals
+/// exceed the representable duration.
+///
+/// # Example
+/// ```
+/// use hrv_rs::model::hrv::{analyze_rr, HrvConfig};
+/// let rr: Vec<f64> = (0..120)
+///     .map(|idx| if idx % 2 == 0 { 800.0 } else { 840.0 })
+///     .collect();
+/// let summary = analyze_rr(&rr, &HrvConfig::default()).unwrap();
+/// assert_eq!(summary.rr_count, 120);
+/// assert!(summary.rmssd.is_some());
+/// assert!(summary.hr.is_some());
+/// ```
+pub fn analyze_rr(rr_ms: &[f64], config: &HrvConfig) -> Result<HrvSummary> {
+    config.validate()?;
+    let window = config.window.unwrap_or(usize::MAX);
+    let mut data = HrvAnalysisData {
+        blank_after_gap: config.blank_after_gap,
+        max_identical_run: config.max_identical_run,
+        dfa_min_beats: config.dfa.min_beats(),
+        ..Default::default()
+    };
+    data.data.set_quantile_scale(config.outlier_filter)?;
+    // registered before the intervals are added, so the DFA is only calculated once
+    data.replace_metric(Arc::new(DfaAlpha::new(config.dfa.clone())), window)?;
+    let rr: Vec<f64> = rr_ms
+        .iter()
+        .filter(|rr| rr.is_finite() && **rr > 0.0)
+        .copied()
+        .collect();
+    if rr.is_empty() {
+        return Ok(data.summary());
+    }
+    data.covered = Duration::checked_seconds_f64(rr.iter().sum::<f64>() / 1000.0)
+        .ok_or_else(|| anyhow!("RR intervals exceed the representable duration"))?;
+    let contact = vec![true; rr.len()];
+    data.add_intervals(rr, contact, None, window)?;
+    Ok(data.summary())
+}
+
 /// Manages runtime data related to HRV analysis.
 ///
 /// This structure collects RR intervals, heart rate values, and timestamps.
@@ -32,18 +505,58 @@ pub type PoincarePoints = (Vec<[f64; 2]>, Vec<[f64; 2]>);
 pub struct HrvAnalysisData {
     data: MovingQuantileFilter,
     rr_timepoints: Vec<Duration>,
-    /// Time series of RMSSD values.
-    rmssd_ts: Vec<[f64; 2]>,
-    /// Time series of SDRR values.
-    sdrr_ts: Vec<[f64; 2]>,
-    /// Time series of SD1 values.
-    sd1_ts: Vec<[f64; 2]>,
-    /// Time series of SD2 values.
-    sd2_ts: Vec<[f64; 2]>,
-    /// Time series of heart rate values.
-    hr_ts: Vec<[f64; 2]>,
-    /// Time series of DFA alpha values
-    dfa_alpha_ts: Vec<[f64; 2]>,
+    /// Registered metrics, in display order.
+    #[serde(skip, default = "default_metrics")]
+    metrics: Vec<Arc<dyn HrvMetric>>,
+    /// Time series of the registered metrics, keyed by metric name.
+    metric_ts: HashMap<String, Vec<[f64; 2]>>,
+    /// Whether the sensor had contact during the analysed RR intervals.
+    #[serde(default)]
+    contact: Vec<bool>,
+    /// Elapsed time at which the analysed RR series starts.
+    time_offset: Duration,
+    /// Indices of the RR intervals that start a new contiguous segment after a gap.
+    segment_starts: Vec<usize>,
+    /// Set when a gap was marked and the next RR interval starts a new segment.
+    pending_gap: bool,
+    /// Number of RR intervals discarded after every gap.
+    #[serde(default)]
+    blank_after_gap: usize,
+    /// Number of RR intervals after the last gap that are still to be discarded.
+    #[serde(default)]
+    pending_blank: usize,
+    /// Number of successive identical RR intervals that are kept, `None` keeps all.
+    #[serde(default)]
+    max_identical_run: Option<usize>,
+    /// Number of inlier RR intervals below which the summary reports no DFA alpha.
+    #[serde(default)]
+    dfa_min_beats: usize,
+    /// The last RR interval and the number of its successive repetitions.
+    #[serde(default)]
+    identical_run: (f64, usize),
+    /// Reception time covered by the analysed RR intervals, gaps are not counted.
+    #[serde(default)]
+    covered: Duration,
+    /// Elapsed time of the last message of the current segment.
+    #[serde(default)]
+    last_message: Option<Duration>,
+    /// Elapsed time before which RR intervals are ignored.
+    skip_initial: Duration,
+    /// Trend removed from the RR intervals before the statistics are calculated.
+    #[serde(default)]
+    detrend: DetrendOption,
+    /// Pairing of the inlier RR intervals for the successive differences.
+    #[serde(default)]
+    differences: DifferenceMode,
+    /// Separate outlier filter scale of the settling phase, `None` uses one scale throughout.
+    #[serde(default)]
+    settling: Option<SettlingFilter>,
+    /// Outlier filter of the RR intervals within the settling phase.
+    #[serde(default)]
+    settling_data: Option<MovingQuantileFilter>,
+    /// Outlier classification of every analysed RR interval, merged from both filters.
+    #[serde(default)]
+    outliers: Vec<bool>,
 }
 
 impl Default for HrvAnalysisData {
@@ -51,12 +564,25 @@ impl Default for HrvAnalysisData {
         Self {
             data: MovingQuantileFilter::new(None, None, None),
             rr_timepoints: Vec::new(),
-            rmssd_ts: Vec::new(),
-            sdrr_ts: Vec::new(),
-            sd1_ts: Vec::new(),
-            sd2_ts: Vec::new(),
-            hr_ts: Vec::new(),
-            dfa_alpha_ts: Vec::new(),
+            contact: Vec::new(),
+            metrics: default_metrics(),
+            metric_ts: HashMap::new(),
+            time_offset: Duration::default(),
+            segment_starts: Vec::new(),
+            pending_gap: false,
+            blank_after_gap: 0,
+            pending_blank: 0,
+            max_identical_run: None,
+            dfa_min_beats: 0,
+            identical_run: (0.0, 0),
+            covered: Duration::ZERO,
+            last_message: None,
+            skip_initial: Duration::ZERO,
+            detrend: DetrendOption::None,
+            differences: DifferenceMode::AcrossOutliers,
+            settling: None,
+            settling_data: None,
+            outliers: Vec::new(),
         }
     }
 }
@@ -76,10 +602,13 @@ impl HrvAnalysisData {
     ///
     /// * `data` - A slice of `(Duration, HeartrateMessage)` tuples representing
     ///   time-stamped heart rate measurements.
-    /// * `window` - An optional `Duration` specifying the time window for filtering data.
-    ///   Only measurements within this window will be included.
-    /// * `outlier_filter` - A threshold value used for identifying and removing outliers
-    ///   in RR intervals.
+    /// * `gaps` - `(start, end)` elapsed times of interruptions in the recording, sorted by time.
+    ///   Statistics are not calculated across gaps.
+    /// * `config` - Parameters of the analysis.
+    /// * `range` - An optional `(start, end)` range of elapsed times. Only measurements
+    ///   received within this range are analysed.
+    /// * `skip_initial` - Settling period at the start of the recording. RR intervals
+    ///   ending before this elapsed time are ignored.
     ///
     /// # Returns
     ///
@@ -87,19 +616,62 @@ impl HrvAnalysisData {
     /// statistics calculation fails (e.g., due to insufficient data).
     pub fn from_acquisition(
         data: &[(Duration, HeartrateMessage)],
-        window: Option<usize>,
-        outlier_filter: f64,
+        gaps: &[(Duration, Duration)],
+        config: &HrvConfig,
+        range: Option<(Duration, Duration)>,
+        skip_initial: Duration,
     ) -> Result<Self> {
-        let mut new = Self::default();
+        let mut new = Self {
+            skip_initial,
+            blank_after_gap: config.blank_after_gap,
+            max_identical_run: config.max_identical_run,
+            dfa_min_beats: config.dfa.min_beats(),
+            ..Default::default()
+        };
+        let window = config.window.unwrap_or(usize::MAX);
+        // registered before the measurements are added, so the DFA is only calculated once
+        new.replace_metric(Arc::new(DfaAlpha::new(config.dfa.clone())), window)?;
+        let data = match range {
+            Some((start, end)) => {
+                new.time_offset = start;
+                Self::crop(data, start, end)
+            }
+            None => data,
+        };
         if data.is_empty() {
             return Ok(new);
         }
-        new.data.set_quantile_scale(outlier_filter)?;
-        new.add_measurements(data, window.unwrap_or(usize::MAX))?;
+        new.data.set_quantile_scale(config.outlier_filter)?;
+        let mut remaining = data;
+        for (_, gap_end) in gaps {
+            let (head, tail) =
+                remaining.split_at(remaining.partition_point(|(ts, _)| ts < gap_end));
+            if !head.is_empty() {
+                new.add_measurements(head, window)?;
+            }
+            new.mark_gap();
+            remaining = tail;
+        }
+        if !remaining.is_empty() {
+            new.add_measurements(remaining, window)?;
+        }
 
         Ok(new)
     }
 
+    /// Returns the measurements received within `start..=end`.
+    ///
+    /// Measurements are expected to be ordered by their elapsed time.
+    fn crop(
+        data: &[(Duration, HeartrateMessage)],
+        start: Duration,
+        end: Duration,
+    ) -> &[(Duration, HeartrateMessage)] {
+        let lo = data.partition_point(|(ts, _)| *ts < start);
+        let hi = data.partition_point(|(ts, _)| *ts <= end);
+        &data[lo..hi.max(lo)]
+    }
+
     fn calc_time_series<
         'a,
         T: Send + Sync + 'a,
@@ -110,237 +682,652 @@ impl HrvAnalysisData {
         window: usize,
         data: &[T],
         time: &[Duration],
+        segments: &[usize],
         func: F,
     ) -> Result<(Vec<R>, Vec<Duration>)> {
         if start >= data.len() {
             return Err(anyhow!("start index out of bounds"));
         }
-        if data.len() != time.len() {
+        if data.len() != time.len() || data.len() != segments.len() {
             return Err(anyhow!("data and time series length mismatch"));
         }
-        Ok(time
-            .into_par_iter()
-            .enumerate()
-            .skip(start)
-            .filter_map(|(idx, ts)| {
-                let rr = &data[idx.saturating_sub(window) + 1..idx + 1];
-                if let Ok(res) = func(rr) {
-                    Some((res, *ts))
-                } else {
-                    None
-                }
-            })
-            .unzip())
+        Ok(in_analysis_pool(|| {
+            time.into_par_iter()
+                .enumerate()
+                .skip(start)
+                .filter_map(|(idx, ts)| {
+                    // windows larger than the available data cover all of it, but never
+                    // reach back across the start of the segment
+                    let lower = (idx + 1).saturating_sub(window).max(segments[idx]);
+                    let rr = &data[lower..idx + 1];
+                    if let Ok(res) = func(rr) {
+                        Some((res, *ts))
+                    } else {
+                        None
+                    }
+                })
+                .unzip()
+        }))
     }
 
-    pub fn add_measurement(&mut self, hrs_msg: &HeartrateMessage, window: usize) -> Result<()> {
+    pub fn add_measurement(
+        &mut self,
+        elapsed: Duration,
+        hrs_msg: &HeartrateMessage,
+        window: usize,
+    ) -> Result<()> {
         // add rr point
-        self.add_measurements(&[(Duration::default(), *hrs_msg)], window)
+        self.add_measurements(&[(elapsed, *hrs_msg)], window)
+    }
+
+    /// Marks a gap in the recording.
+    ///
+    /// The next RR interval starts a new contiguous segment. Its timestamp is synchronized
+    /// to the elapsed time of its message and no statistics window spans the gap. The first
+    /// `blank_after_gap` intervals after the gap are discarded and no run of identical
+    /// intervals continues across the gap.
+    pub fn mark_gap(&mut self) {
+        self.pending_gap = true;
+        self.pending_blank = self.blank_after_gap;
+        self.identical_run = (0.0, 0);
+        self.last_message = None;
+    }
+
+    /// Counts `rr` into the current run of identical RR intervals.
+    ///
+    /// # Returns
+    ///
+    /// `false` if the run exceeds `max_identical_run` and the interval is to be discarded.
+    fn count_identical(&mut self, rr: f64) -> bool {
+        let (last, count) = &mut self.identical_run;
+        if *last == rr {
+            *count += 1;
+        } else {
+            *last = rr;
+            *count = 1;
+        }
+        self.max_identical_run.is_none_or(|max| *count <= max)
     }
 
-    fn get_last_filtered(&self, window: Range<usize>) -> Result<(Vec<f64>, Vec<Duration>)> {
+    /// Returns the index of the first RR interval of the segment containing `idx`.
+    fn segment_start(&self, idx: usize) -> usize {
+        let segment = self.segment_starts.partition_point(|&start| start <= idx);
+        if segment == 0 {
+            0
+        } else {
+            self.segment_starts[segment - 1]
+        }
+    }
+
+    /// Returns the inlier RR intervals within `window`.
+    ///
+    /// # Returns
+    ///
+    /// A tuple of the RR intervals, their timestamps, and for every interval the index
+    /// (within the returned intervals) at which its contiguous segment starts.
+    fn get_last_filtered(
+        &self,
+        window: Range<usize>,
+    ) -> Result<(Vec<f64>, Vec<Duration>, Vec<usize>)> {
         if window.end > self.data.get_data().len() {
             return Err(anyhow!("window end out of bounds"));
         }
         let data = self.data.get_data();
-        let classes = self.data.get_classification();
-        Ok(window
+        let outliers = self.outliers();
+        let indices: Vec<usize> = window
             .into_par_iter()
-            .filter_map(|idx| {
-                if classes[idx].is_outlier() {
-                    None
-                } else {
-                    Some((data[idx], self.rr_timepoints[idx]))
-                }
+            .filter(|&idx| !outliers[idx])
+            .collect();
+        let mut segments = Vec::with_capacity(indices.len());
+        let mut current = (usize::MAX, 0);
+        for (pos, &idx) in indices.iter().enumerate() {
+            let segment = self.segment_start(idx);
+            if segment != current.0 {
+                current = (segment, pos);
+            }
+            segments.push(current.1);
+        }
+        Ok((
+            indices.iter().map(|&idx| data[idx]).collect(),
+            indices.iter().map(|&idx| self.rr_timepoints[idx]).collect(),
+            segments,
+        ))
+    }
+
+    /// Flags the inlier RR intervals within `window` that start a run of adjacent inliers.
+    ///
+    /// A run ends at every removed beat and at every gap.
+    fn inlier_run_starts(&self, window: Range<usize>) -> Vec<bool> {
+        let outliers = self.outliers();
+        let mut previous: Option<usize> = None;
+        window
+            .filter(|&idx| !outliers[idx])
+            .map(|idx| {
+                let start =
+                    previous.is_none_or(|prev| prev + 1 != idx) || self.segment_start(idx) == idx;
+                previous = Some(idx);
+                start
             })
-            .unzip())
+            .collect()
+    }
+
+    /// Splits a window of inlier RR intervals at the run starts flagged by `inlier_run_starts`.
+    fn split_runs<'a>(rr: &'a [f64], run_starts: &[bool]) -> Vec<&'a [f64]> {
+        let mut runs = Vec::new();
+        let mut begin = 0;
+        for idx in 1..rr.len() {
+            if run_starts[idx] {
+                runs.push(&rr[begin..idx]);
+                begin = idx;
+            }
+        }
+        if begin < rr.len() {
+            runs.push(&rr[begin..]);
+        }
+        runs
     }
 
     fn calc_statistics(&mut self, window: usize, new: usize) -> Result<()> {
+        let metrics = self.metrics.clone();
+        self.calc_metrics(&metrics, window, new)
+    }
+
+    /// Extends the time series of `metrics` by the values of the `new` most recent RR intervals.
+    fn calc_metrics(
+        &mut self,
+        metrics: &[Arc<dyn HrvMetric>],
+        window: usize,
+        new: usize,
+    ) -> Result<()> {
         let start_idx = self
             .data
             .get_data()
             .len()
             .saturating_sub(new.saturating_add(window));
-        let (filtered_rr, filtered_ts) =
+        let (filtered_rr, filtered_ts, segments) =
             self.get_last_filtered(start_idx..self.data.get_data().len())?;
+        let tagged: Vec<(f64, bool)> = match self.differences {
+            DifferenceMode::AcrossOutliers => Vec::new(),
+            DifferenceMode::WithinRuns => filtered_rr
+                .iter()
+                .copied()
+                .zip(self.inlier_run_starts(start_idx..self.data.get_data().len()))
+                .collect(),
+        };
         // estimate start index of new data in filtered_rr assuming no outliers
         // add 5 to have room for some outliers
-        let start_idx = filtered_rr.len().saturating_sub(new + 5);
+        let start_idx = filtered_rr.len().saturating_sub(new.saturating_add(5));
 
-        {
-            let (mut new_data, ts) =
-                Self::calc_time_series(start_idx, window, &filtered_rr, &filtered_ts, |win| {
-                    calc_rmssd(win)
-                })?;
-            let last_ts = self.rmssd_ts.last().map(|v| v[0]).unwrap_or(0.0);
-            self.rmssd_ts
-                .extend(new_data.drain(..).zip(ts).filter_map(|(data, ts)| {
-                    let ts = ts.as_seconds_f64();
-                    if ts > last_ts {
-                        Some([ts, data])
-                    } else {
-                        None
-                    }
-                }));
-        }
-        {
-            let (mut new_data, ts) =
-                Self::calc_time_series(start_idx, window, &filtered_rr, &filtered_ts, |win| {
-                    calc_sdrr(win)
-                })?;
-            let last_ts = self.sdrr_ts.last().map(|v| v[0]).unwrap_or(0.0);
-
-            self.sdrr_ts
-                .extend(new_data.drain(..).zip(ts).filter_map(|(data, ts)| {
-                    let ts = ts.as_seconds_f64();
-                    if ts > last_ts {
-                        Some([ts, data])
-                    } else {
-                        None
-                    }
-                }));
-        }
-        {
-            let (mut new_data, ts) =
-                Self::calc_time_series(start_idx, window, &filtered_rr, &filtered_ts, |win| {
-                    let dfa = DFAnalysis::udfa(
-                        win,
-                        &[4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16],
-                        DetrendStrategy::Linear,
-                    )?;
-                    Ok(dfa.alpha)
-                })?;
-            let last_ts = self.dfa_alpha_ts.last().map(|v| v[0]).unwrap_or(0.0);
-
-            self.dfa_alpha_ts
-                .extend(new_data.drain(..).zip(ts).filter_map(|(data, ts)| {
-                    let ts = ts.as_seconds_f64();
-                    if ts > last_ts {
-                        Some([ts, data])
-                    } else {
-                        None
-                    }
-                }));
+        for metric in metrics {
+            let detrend = if metric.detrended() {
+                self.detrend
+            } else {
+                DetrendOption::None
+            };
+            let (mut new_data, ts) = match self.differences {
+                DifferenceMode::AcrossOutliers => Self::calc_time_series(
+                    start_idx,
+                    window,
+                    &filtered_rr,
+                    &filtered_ts,
+                    &segments,
+                    |win| metric.compute(&detrend.apply(win)?),
+                )?,
+                DifferenceMode::WithinRuns => Self::calc_time_series(
+                    start_idx,
+                    window,
+                    &tagged,
+                    &filtered_ts,
+                    &segments,
+                    |win| {
+                        let (rr, run_starts): (Vec<f64>, Vec<bool>) = win.iter().copied().unzip();
+                        let rr = detrend.apply(&rr)?;
+                        metric.compute_runs(&Self::split_runs(&rr, &run_starts))
+                    },
+                )?,
+            };
+            let series = self.metric_ts.entry(metric.name().to_owned()).or_default();
+            let last_ts = series.last().map(|v| v[0]).unwrap_or(0.0);
+            series.extend(new_data.drain(..).zip(ts).filter_map(|(data, ts)| {
+                let ts = ts.as_seconds_f64();
+                if ts > last_ts {
+                    Some([ts, data])
+                } else {
+                    None
+                }
+            }));
         }
-        {
-            let (new_data, ts) =
-                Self::calc_time_series(start_idx, window, &filtered_rr, &filtered_ts, |win| {
-                    let res = calc_poincare_metrics(win)?;
-                    Ok((res.sd1, res.sd2))
-                })?;
-            let (mut new_sd1_ts, mut new_sd2_ts): (Vec<_>, Vec<_>) = new_data.into_iter().unzip();
-            let last_ts = self.sd1_ts.last().map(|v| v[0]).unwrap_or(0.0);
-            self.sd1_ts
-                .extend(
-                    new_sd1_ts
-                        .drain(..)
-                        .zip(ts.iter().cloned())
-                        .filter_map(|(data, ts)| {
-                            let ts = ts.as_seconds_f64();
-                            if ts > last_ts {
-                                Some([ts, data])
-                            } else {
-                                None
-                            }
-                        }),
-                );
-            self.sd2_ts
-                .extend(new_sd2_ts.drain(..).zip(ts).filter_map(|(data, ts)| {
-                    let ts = ts.as_seconds_f64();
-                    if ts > last_ts {
-                        Some([ts, data])
-                    } else {
-                        None
-                    }
-                }));
+        Ok(())
+    }
+
+    /// Registers an additional metric.
+    ///
+    /// The time series of the metric is calculated for the data already present.
+    ///
+    /// # Arguments
+    ///
+    /// * `metric` - The metric to register. Its name must be unique.
+    /// * `window` - The statistics window in number of RR intervals.
+    pub fn register_metric(&mut self, metric: Arc<dyn HrvMetric>, window: usize) -> Result<()> {
+        if self.metrics.iter().any(|m| m.name() == metric.name()) {
+            return Err(anyhow!("metric {} is already registered", metric.name()));
         }
-        {
-            let (mut new_data, ts) =
-                Self::calc_time_series(start_idx, window, &filtered_rr, &filtered_ts, |rr| {
-                    Ok(60000.0 * rr.len() as f64 / rr.iter().sum::<f64>())
-                })?;
-            let last_ts = self.hr_ts.last().map(|v| v[0]).unwrap_or(0.0);
-            self.hr_ts
-                .extend(new_data.drain(..).zip(ts).filter_map(|(data, ts)| {
-                    let ts = ts.as_seconds_f64();
-                    if ts > last_ts {
-                        Some([ts, data])
-                    } else {
-                        None
-                    }
-                }));
+        self.metrics.push(metric.clone());
+        if !self.data.get_data().is_empty() {
+            self.calc_metrics(&[metric], window, self.data.get_data().len())?;
         }
         Ok(())
     }
 
-    /// Adds a heart rate measurement to the session data.
+    /// Replaces a registered metric of the same name.
     ///
-    /// Updates the session with RR intervals, heart rate values, and reception timestamps
-    /// extracted from the provided `HeartrateMessage`.
+    /// The time series of the metric is recalculated for the data already present.
     ///
     /// # Arguments
     ///
-    /// * `hrs_msg` - The `HeartrateMessage` containing HR and RR interval data.
-    /// * `elapsed_time` - The timestamp associated with the message.
-    fn add_measurements(
+    /// * `metric` - The metric replacing the registered one.
+    /// * `window` - The statistics window in number of RR intervals.
+    pub fn replace_metric(&mut self, metric: Arc<dyn HrvMetric>, window: usize) -> Result<()> {
+        let name = metric.name().to_owned();
+        self.swap_metric(&name, metric, window)
+    }
+
+    /// Replaces the registered metric `name` by a metric that may be named differently.
+    ///
+    /// The time series of the new metric is calculated for the data already present.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - Name of the registered metric.
+    /// * `metric` - The metric replacing the registered one. Its name must be unique.
+    /// * `window` - The statistics window in number of RR intervals.
+    pub fn swap_metric(
         &mut self,
-        hrs_msgs: &[(Duration, HeartrateMessage)],
+        name: &str,
+        metric: Arc<dyn HrvMetric>,
         window: usize,
     ) -> Result<()> {
-        let rr: Vec<_> = hrs_msgs
-            .par_iter()
-            .map(|(_, hrs_msg)| {
-                hrs_msg
-                    .get_rr_intervals()
-                    .iter()
-                    .filter_map(|&rr| if rr > 0 { Some(f64::from(rr)) } else { None })
-                    .collect::<Vec<f64>>()
-            })
-            .flatten()
-            .collect();
-        let rr_len = rr.len();
-        self.data.add_data(&rr)?;
-        self.rr_timepoints.extend(rr.iter().scan(
-            *self.rr_timepoints.last().unwrap_or(&Duration::default()),
-            |acc, &rr| {
-                *acc += Duration::milliseconds(rr as i64);
-                Some(*acc)
-            },
-        ));
-
-        if let Err(e) = self.calc_statistics(window, rr_len) {
-            log::warn!("error calculating statistics: {}", e);
+        let pos = self
+            .metrics
+            .iter()
+            .position(|m| m.name() == name)
+            .ok_or_else(|| anyhow!("metric {} is not registered", name))?;
+        if metric.name() != name && self.metrics.iter().any(|m| m.name() == metric.name()) {
+            return Err(anyhow!("metric {} is already registered", metric.name()));
+        }
+        self.metrics[pos] = metric.clone();
+        self.metric_ts.remove(name);
+        if !self.data.get_data().is_empty() {
+            self.calc_metrics(&[metric], window, self.data.get_data().len())?;
         }
         Ok(())
     }
 
-    /// Returns a list of Poincaré plot points.
+    /// Sets the trend removed from the RR intervals before the statistics are calculated.
     ///
-    /// # Returns
+    /// The time series of all metrics are recalculated for the data already present, the
+    /// RR intervals themselves are kept as recorded.
     ///
-    /// A tuple containing two lists of `[x, y]` points: the first list contains inlier points,
-    /// and the second list contains outlier points.
-    pub fn get_poincare(&self, window: Option<usize>) -> Result<PoincarePoints> {
-        let data = self.data.get_data();
-        let classes = self.data.get_classification();
-        if data.len() < 2 {
-            return Err(anyhow!("too few rr intervals for poincare points"));
-        }
-        let start = window.map(|s| data.len().saturating_sub(s)).unwrap_or(0);
-        let mut inliers = Vec::with_capacity(window.unwrap_or(data.len()));
-        let mut outliers = Vec::with_capacity(window.unwrap_or(data.len()));
-        for (rr, classes) in data.windows(2).zip(classes.windows(2)).skip(start) {
-            if classes[0].is_outlier() || classes[1].is_outlier() {
-                outliers.push([rr[0], rr[1]]);
-            } else {
-                inliers.push([rr[0], rr[1]]);
-            }
+    /// # Arguments
+    ///
+    /// * `detrend` - The trend to remove.
+    /// * `window` - The statistics window in number of RR intervals.
+    pub fn set_detrend(&mut self, detrend: DetrendOption, window: usize) -> Result<()> {
+        detrend.validate()?;
+        self.detrend = detrend;
+        self.metric_ts.clear();
+        if !self.data.get_data().is_empty() {
+            self.calc_statistics(window, self.data.get_data().len())?;
         }
-        inliers.shrink_to_fit();
-        outliers.shrink_to_fit();
-
-        Ok((inliers, outliers))
+        Ok(())
+    }
+
+    /// Sets the pairing of the inlier RR intervals for the successive differences.
+    ///
+    /// The time series of all metrics are recalculated for the data already present.
+    ///
+    /// # Arguments
+    ///
+    /// * `differences` - The pairing of the intervals.
+    /// * `window` - The statistics window in number of RR intervals.
+    pub fn set_differences(&mut self, differences: DifferenceMode, window: usize) -> Result<()> {
+        self.differences = differences;
+        self.metric_ts.clear();
+        if !self.data.get_data().is_empty() {
+            self.calc_statistics(window, self.data.get_data().len())?;
+        }
+        Ok(())
+    }
+
+    /// Sets the separate outlier filter scale of the settling phase.
+    ///
+    /// The RR intervals already present are reclassified and the time series of all
+    /// metrics are recalculated.
+    ///
+    /// # Arguments
+    ///
+    /// * `settling` - The filter of the settling phase, `None` uses one scale throughout.
+    /// * `window` - The statistics window in number of RR intervals.
+    pub fn set_settling_filter(
+        &mut self,
+        settling: Option<SettlingFilter>,
+        window: usize,
+    ) -> Result<()> {
+        self.settling_data = match settling {
+            Some(settling) => {
+                settling.validate()?;
+                let mut filter = MovingQuantileFilter::new(None, None, None);
+                filter.set_quantile_scale(settling.scale)?;
+                let len = self
+                    .rr_timepoints
+                    .partition_point(|ts| *ts <= settling.period);
+                if len > 0 {
+                    filter.add_data(&self.data.get_data()[..len])?;
+                }
+                Some(filter)
+            }
+            None => None,
+        };
+        self.settling = settling;
+        self.update_outliers();
+        self.metric_ts.clear();
+        if !self.data.get_data().is_empty() {
+            self.calc_statistics(window, self.data.get_data().len())?;
+        }
+        Ok(())
+    }
+
+    /// Returns for every analysed RR interval whether it is classified as outlier.
+    ///
+    /// Intervals of the settling phase are judged by the filter of the settling phase.
+    fn outliers(&self) -> &[bool] {
+        &self.outliers
+    }
+
+    /// Merges the classifications of both outlier filters into `outliers`.
+    ///
+    /// Must be called whenever RR intervals are added or the settling filter changes.
+    fn update_outliers(&mut self) {
+        self.outliers.clear();
+        self.outliers.extend(
+            self.data
+                .get_classification()
+                .iter()
+                .map(|class| class.is_outlier()),
+        );
+        if let Some(settling) = &self.settling_data {
+            for (outlier, class) in self.outliers.iter_mut().zip(settling.get_classification()) {
+                *outlier = class.is_outlier();
+            }
+        }
+    }
+
+    /// Adds the RR intervals of the settling phase to the filter of the settling phase.
+    ///
+    /// Must be called before the intervals are added to the regular filter.
+    fn add_settling_intervals(&mut self, rr: &[f64], timepoints: &[Duration]) -> Result<()> {
+        let (Some(settling), Some(filter)) = (self.settling, self.settling_data.as_mut()) else {
+            return Ok(());
+        };
+        if filter.get_data().len() < self.data.get_data().len() {
+            // the settling phase is already over
+            return Ok(());
+        }
+        let len = timepoints.partition_point(|ts| *ts <= settling.period);
+        if len > 0 {
+            filter.add_data(&rr[..len])?;
+        }
+        Ok(())
+    }
+
+    /// Adds a heart rate measurement to the session data.
+    ///
+    /// Updates the session with RR intervals, heart rate values, and reception timestamps
+    /// extracted from the provided `HeartrateMessage`.
+    ///
+    /// # Arguments
+    ///
+    /// * `hrs_msg` - The `HeartrateMessage` containing HR and RR interval data.
+    /// * `elapsed_time` - The timestamp associated with the message.
+    fn add_measurements(
+        &mut self,
+        hrs_msgs: &[(Duration, HeartrateMessage)],
+        window: usize,
+    ) -> Result<()> {
+        let rr: Vec<_> = hrs_msgs
+            .par_iter()
+            .map(|(_, hrs_msg)| {
+                hrs_msg
+                    .get_rr_intervals()
+                    .iter()
+                    .filter(|&&rr| rr > 0.0)
+                    .copied()
+                    .collect::<Vec<f64>>()
+            })
+            .flatten()
+            .collect();
+        // sensors without contact detection are assumed to have contact
+        let contact: Vec<bool> = hrs_msgs
+            .iter()
+            .flat_map(|(_, hrs_msg)| {
+                let contact = hrs_msg.sen_has_contact() || !hrs_msg.sen_contact_supported();
+                hrs_msg
+                    .get_rr_intervals()
+                    .iter()
+                    .filter(|&&rr| rr > 0.0)
+                    .map(move |_| contact)
+            })
+            .collect();
+        // the first message of a segment covers the time of its intervals, later messages the
+        // time since the previous message, which includes the beats of lost messages
+        for (elapsed, hrs_msg) in hrs_msgs {
+            self.covered += match self.last_message {
+                Some(last) => (*elapsed - last).max(Duration::ZERO),
+                None => {
+                    Duration::seconds_f64(hrs_msg.get_rr_intervals().iter().sum::<f64>() / 1000.0)
+                }
+            };
+            self.last_message = Some(*elapsed);
+        }
+        let elapsed = hrs_msgs.first().map(|(elapsed, _)| *elapsed);
+        self.add_intervals(rr, contact, elapsed, window)
+    }
+
+    /// Adds RR intervals to the session and updates the statistics.
+    ///
+    /// # Arguments
+    ///
+    /// * `rr` - RR intervals in ms.
+    /// * `contact` - Whether the sensor had contact during each interval.
+    /// * `elapsed` - Elapsed time of the first interval's message, the start of a segment after
+    ///   a gap is synchronized to it.
+    /// * `window` - Number of RR intervals the statistics are calculated over.
+    fn add_intervals(
+        &mut self,
+        mut rr: Vec<f64>,
+        mut contact: Vec<bool>,
+        elapsed: Option<Duration>,
+        window: usize,
+    ) -> Result<()> {
+        let mut discarded_ms = 0.0;
+        if self.rr_timepoints.is_empty() {
+            // drop the intervals of the settling period, the series starts after them
+            let mut skipped = 0;
+            while let Some(&next) = rr.get(skipped) {
+                let end = self.time_offset + Duration::seconds_f64(next / 1000.0);
+                if end > self.skip_initial {
+                    break;
+                }
+                self.time_offset = end;
+                skipped += 1;
+            }
+            discarded_ms += rr.drain(..skipped).sum::<f64>();
+            contact.drain(..skipped);
+        }
+        // beats right after a gap are often buffered or re-synchronized by the sensor
+        let blanked = self.pending_blank.min(rr.len());
+        self.pending_blank -= blanked;
+        let blanked_ms: f64 = rr.drain(..blanked).sum();
+        contact.drain(..blanked);
+        discarded_ms += blanked_ms;
+        self.covered =
+            (self.covered - Duration::seconds_f64(discarded_ms / 1000.0)).max(Duration::ZERO);
+        // repetitions of an identical interval beyond the limit are a flatline of the sensor
+        let kept: Vec<bool> = rr.iter().map(|&rr| self.count_identical(rr)).collect();
+        let rr_len = kept.iter().filter(|&&keep| keep).count();
+        let mut base = *self.rr_timepoints.last().unwrap_or(&self.time_offset);
+        if self.pending_gap && rr_len > 0 {
+            self.pending_gap = false;
+            self.segment_starts.push(self.data.get_data().len());
+            if let Some(elapsed) = elapsed {
+                base = base.max(elapsed);
+            }
+        }
+        // the remaining intervals keep their position in time
+        base += Duration::seconds_f64(blanked_ms / 1000.0);
+        let (accepted, timepoints): (Vec<f64>, Vec<Duration>) = rr
+            .iter()
+            .scan(base, |acc, &rr| {
+                *acc += Duration::seconds_f64(rr / 1000.0);
+                Some((rr, *acc))
+            })
+            .zip(&kept)
+            .filter_map(|(beat, &keep)| keep.then_some(beat))
+            .unzip();
+        self.add_settling_intervals(&accepted, &timepoints)?;
+        self.data.add_data(&accepted)?;
+        self.update_outliers();
+        self.rr_timepoints.extend(timepoints);
+        self.contact.extend(
+            contact
+                .into_iter()
+                .zip(&kept)
+                .filter_map(|(contact, &keep)| keep.then_some(contact)),
+        );
+
+        if let Err(e) = self.calc_statistics(window, rr_len) {
+            log::warn!("error calculating statistics: {}", e);
+        }
+        Ok(())
+    }
+
+    /// Returns the differences of successive inlier RR intervals (ΔRR) in ms.
+    ///
+    /// These are the differences RMSSD and pNNx are calculated from. Outliers are removed
+    /// before pairing and intervals across a gap are not paired. With
+    /// `DifferenceMode::WithinRuns` intervals across a removed beat are not paired either.
+    ///
+    /// # Arguments
+    ///
+    /// * `window` - Number of most recent inlier RR intervals, `None` uses all of them.
+    pub fn get_successive_differences(&self, window: Option<usize>) -> Result<Vec<f64>> {
+        let len = self.data.get_data().len();
+        let (rr, _, segments) = self.get_last_filtered(0..len)?;
+        let run_starts = match self.differences {
+            DifferenceMode::AcrossOutliers => vec![false; rr.len()],
+            DifferenceMode::WithinRuns => self.inlier_run_starts(0..len),
+        };
+        let start = window.map_or(0, |window| rr.len().saturating_sub(window));
+        Ok((start + 1..rr.len())
+            .filter(|&idx| segments[idx] == segments[idx - 1] && !run_starts[idx])
+            .map(|idx| rr[idx] - rr[idx - 1])
+            .collect())
+    }
+
+    /// Returns a list of Poincaré plot points.
+    ///
+    /// # Returns
+    ///
+    /// A tuple containing two lists of `[x, y]` points: the first list contains inlier points,
+    /// and the second list contains outlier points.
+    pub fn get_poincare(&self, window: Option<usize>) -> Result<PoincarePoints> {
+        Self::poincare_points(
+            self.data.get_data(),
+            self.outliers(),
+            |outlier| *outlier,
+            &self.segment_starts,
+            window,
+        )
+    }
+
+    /// Pairs successive RR intervals to Poincaré plot points.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - The RR intervals.
+    /// * `classes` - The outlier classification of every RR interval.
+    /// * `is_outlier` - Checks if a classification marks an outlier.
+    /// * `segment_starts` - Indices of the RR intervals starting a segment after a gap.
+    /// * `window` - Number of most recent RR intervals to pair, `None` pairs all of them.
+    ///
+    /// # Returns
+    ///
+    /// The inlier and outlier points, or an error if the classification does not cover
+    /// exactly the RR intervals.
+    fn poincare_points<C>(
+        data: &[f64],
+        classes: &[C],
+        is_outlier: impl Fn(&C) -> bool,
+        segment_starts: &[usize],
+        window: Option<usize>,
+    ) -> Result<PoincarePoints> {
+        if data.len() != classes.len() {
+            return Err(anyhow!(
+                "outlier classification of {} rr intervals does not match {} rr intervals",
+                classes.len(),
+                data.len()
+            ));
+        }
+        if data.len() < 2 {
+            return Err(anyhow!("too few rr intervals for poincare points"));
+        }
+        let start = window.map(|s| data.len().saturating_sub(s)).unwrap_or(0);
+        let mut inliers = Vec::with_capacity(window.unwrap_or(data.len()));
+        let mut outliers = Vec::with_capacity(window.unwrap_or(data.len()));
+        for (idx, (rr, classes)) in data
+            .windows(2)
+            .zip(classes.windows(2))
+            .enumerate()
+            .skip(start)
+        {
+            if segment_starts.binary_search(&(idx + 1)).is_ok() {
+                // successive intervals across a gap are not adjacent beats
+                continue;
+            }
+            if is_outlier(&classes[0]) || is_outlier(&classes[1]) {
+                outliers.push([rr[0], rr[1]]);
+            } else {
+                inliers.push([rr[0], rr[1]]);
+            }
+        }
+        inliers.shrink_to_fit();
+        outliers.shrink_to_fit();
+
+        Ok((inliers, outliers))
+    }
+
+    /// Returns the SD1/SD2 ellipse of the Poincaré plot.
+    ///
+    /// The ellipse is centered on the centroid of the inlier points, its semi-axes are the
+    /// SD1 and SD2 of the filtered RR intervals.
+    pub fn get_poincare_ellipse(&self, window: Option<usize>) -> Result<PoincareEllipse> {
+        let (inliers, _) = self.get_poincare(window)?;
+        if inliers.is_empty() {
+            return Err(anyhow!("no inliers for the poincare ellipse"));
+        }
+        let len = self.data.get_data().len();
+        let start = window.map(|s| len.saturating_sub(s)).unwrap_or(0);
+        let (rr, _, _) = self.get_last_filtered(start..len)?;
+        let metrics = calc_poincare_metrics(&rr)?;
+        let count = inliers.len() as f64;
+        let center = inliers.iter().fold([0.0, 0.0], |acc, p| {
+            [acc[0] + p[0] / count, acc[1] + p[1] / count]
+        });
+        Ok(PoincareEllipse {
+            center,
+            sd1: metrics.sd1,
+            sd2: metrics.sd2,
+        })
     }
 
     /// Checks if there is sufficient data for HRV calculations.
@@ -348,68 +1335,399 @@ impl HrvAnalysisData {
     /// # Returns
     ///
     /// `true` if there are enough RR intervals to perform HRV analysis; `false` otherwise.
-    #[allow(dead_code)]
     pub fn has_sufficient_data(&self) -> bool {
-        self.data.get_data().len() >= 4
+        self.data.get_data().len() >= MIN_BEATS
     }
 
-    pub fn get_rmssd_ts(&self) -> &[[f64; 2]] {
-        &self.rmssd_ts
+    /// Returns the state of the analysis.
+    pub fn state(&self) -> AnalysisState {
+        if self.data.get_data().is_empty() {
+            AnalysisState::NoData
+        } else if self.has_sufficient_data() {
+            AnalysisState::Ready
+        } else {
+            AnalysisState::Insufficient
+        }
+    }
+
+    /// Returns the unfiltered RR intervals as `[time in s, RR in ms]` points.
+    pub fn get_rr_ts(&self) -> Vec<[f64; 2]> {
+        self.rr_timepoints
+            .iter()
+            .zip(self.data.get_data())
+            .map(|(ts, rr)| [ts.as_seconds_f64(), *rr])
+            .collect()
+    }
+
+    /// Returns the sensor contact of the analysed RR intervals.
+    ///
+    /// # Returns
+    ///
+    /// A list of `(time in s, has contact)` tuples aligned with `get_rr_ts`.
+    pub fn get_contact_track(&self) -> Vec<(f64, bool)> {
+        self.rr_timepoints
+            .iter()
+            .zip(&self.contact)
+            .map(|(ts, contact)| (ts.as_seconds_f64(), *contact))
+            .collect()
+    }
+
+    /// Returns the most recent RR intervals together with their classification.
+    ///
+    /// # Arguments
+    ///
+    /// * `count` - The maximum number of RR intervals to return.
+    ///
+    /// # Returns
+    ///
+    /// A list of `(RR in ms, is_outlier)` tuples ordered from oldest to newest.
+    pub fn get_recent_rr(&self, count: usize) -> Vec<(f64, bool)> {
+        let data = self.data.get_data();
+        let outliers = self.outliers();
+        let start = data.len().saturating_sub(count);
+        data[start..]
+            .iter()
+            .zip(&outliers[start..])
+            .map(|(rr, outlier)| (*rr, *outlier))
+            .collect()
+    }
+
+    /// Returns the log of all RR intervals altered by the artifact correction.
+    ///
+    /// An artifact is too long or too short compared to the median of the surrounding inliers.
+    pub fn get_correction_log(&self) -> Vec<CorrectionEntry> {
+        let data = self.data.get_data();
+        let outliers = self.outliers();
+        (0..data.len())
+            .filter(|&idx| outliers[idx])
+            .map(|idx| {
+                let context = idx.saturating_sub(CORRECTION_CONTEXT)
+                    ..(idx + CORRECTION_CONTEXT + 1).min(data.len());
+                let mut neighbours: Vec<f64> = context
+                    .filter(|&pos| !outliers[pos])
+                    .map(|pos| data[pos])
+                    .collect();
+                neighbours.sort_by(f64::total_cmp);
+                let reference = neighbours
+                    .get(neighbours.len() / 2)
+                    .copied()
+                    .unwrap_or(data[idx]);
+                CorrectionEntry {
+                    index: idx,
+                    time: self.rr_timepoints[idx].as_seconds_f64(),
+                    rr: data[idx],
+                    action: CorrectionAction::Excluded,
+                    reason: if data[idx] > reference {
+                        CorrectionReason::TooLong
+                    } else {
+                        CorrectionReason::TooShort
+                    },
+                }
+            })
+            .collect()
+    }
+
+    /// Groups the inlier RR intervals into complete segments of `LONG_TERM_SEGMENT` length.
+    ///
+    /// Segments are defined by the elapsed timestamps of the RR intervals, which follow the
+    /// recorded time across gaps, and start with the analysed series. A trailing, incomplete
+    /// segment is dropped, as are segments with fewer than two intervals (e.g. due to a
+    /// pause).
+    fn get_long_term_segments(&self) -> Vec<Vec<f64>> {
+        let Some(last) = self.rr_timepoints.last() else {
+            return Vec::new();
+        };
+        let first = &self.time_offset;
+        let segment_len = LONG_TERM_SEGMENT.as_seconds_f64();
+        let count = ((*last - *first).as_seconds_f64() / segment_len).floor() as usize;
+        let mut segments = vec![Vec::new(); count];
+        for ((rr, ts), outlier) in self
+            .data
+            .get_data()
+            .iter()
+            .zip(&self.rr_timepoints)
+            .zip(self.outliers())
+        {
+            if *outlier {
+                continue;
+            }
+            let idx = ((*ts - *first).as_seconds_f64() / segment_len) as usize;
+            if let Some(segment) = segments.get_mut(idx) {
+                segment.push(*rr);
+            }
+        }
+        segments.retain(|segment| segment.len() >= 2);
+        segments
+    }
+
+    /// Returns the SDANN, the standard deviation of the mean RR intervals of successive
+    /// 5 minute segments.
+    ///
+    /// # Returns
+    ///
+    /// `None` if the recording spans fewer than `MIN_LONG_TERM_SEGMENTS` complete segments.
+    pub fn get_sdann(&self) -> Option<f64> {
+        let segments = self.get_long_term_segments();
+        if segments.len() < MIN_LONG_TERM_SEGMENTS {
+            return None;
+        }
+        let means: Vec<f64> = segments
+            .iter()
+            .map(|segment| segment.iter().sum::<f64>() / segment.len() as f64)
+            .collect();
+        calc_sdrr(&means).ok()
+    }
+
+    /// Returns the SDNN index, the mean of the SDRR values of successive 5 minute segments.
+    ///
+    /// # Returns
+    ///
+    /// `None` if the recording spans fewer than `MIN_LONG_TERM_SEGMENTS` complete segments.
+    pub fn get_sdnn_index(&self) -> Option<f64> {
+        let segments = self.get_long_term_segments();
+        if segments.len() < MIN_LONG_TERM_SEGMENTS {
+            return None;
+        }
+        let sdnns = segments
+            .iter()
+            .map(|segment| calc_sdrr(segment))
+            .collect::<Result<Vec<f64>>>()
+            .ok()?;
+        Some(sdnns.iter().sum::<f64>() / sdnns.len() as f64)
+    }
+
+    /// Returns the RMSSD of the inlier RR intervals within the final `FINAL_RMSSD_PERIOD`.
+    ///
+    /// # Returns
+    ///
+    /// `None` if the period holds fewer than `MIN_BEATS` RR intervals.
+    pub fn get_final_rmssd(&self) -> Option<f64> {
+        let last = *self.rr_timepoints.last()?;
+        let rr: Vec<f64> = self
+            .data
+            .get_data()
+            .iter()
+            .zip(&self.rr_timepoints)
+            .zip(self.outliers())
+            .filter(|((_, ts), outlier)| !**outlier && last - **ts < FINAL_RMSSD_PERIOD)
+            .map(|((rr, _), _)| *rr)
+            .collect();
+        if rr.len() < MIN_BEATS {
+            return None;
+        }
+        calc_rmssd(&rr).ok()
+    }
+
+    /// Returns the SD1 of the most recent `INSTANT_SD1_BEATS` beats.
+    ///
+    /// Independent of the configured window, outliers are left out and only the intervals
+    /// after the last gap are used.
+    ///
+    /// # Returns
+    ///
+    /// `None` if fewer than `MIN_BEATS` inlier RR intervals remain.
+    pub fn get_instant_sd1(&self) -> Option<f64> {
+        let len = self.data.get_data().len();
+        let (rr, _, segments) = self
+            .get_last_filtered(len.saturating_sub(INSTANT_SD1_BEATS)..len)
+            .ok()?;
+        let rr = &rr[*segments.last()?..];
+        if rr.len() < MIN_BEATS {
+            return None;
+        }
+        calc_poincare_metrics(rr).ok().map(|poincare| poincare.sd1)
+    }
+
+    /// Returns the number of analysed RR intervals after the most recent outlier.
+    ///
+    /// All analysed intervals are counted if none was classified as outlier.
+    pub fn get_beats_since_outlier(&self) -> usize {
+        self.outliers()
+            .iter()
+            .rev()
+            .take_while(|outlier| !**outlier)
+            .count()
+    }
+
+    /// Returns the fraction of outliers among the RR intervals of the last
+    /// `RECENT_ARTIFACT_PERIOD`.
+    ///
+    /// # Returns
+    ///
+    /// `None` if no RR interval was analysed.
+    pub fn get_recent_artifact_rate(&self) -> Option<f64> {
+        let last = *self.rr_timepoints.last()?;
+        let start = self
+            .rr_timepoints
+            .partition_point(|ts| *ts <= last - RECENT_ARTIFACT_PERIOD);
+        let outliers = self.outliers();
+        let recent = outliers.get(start..)?;
+        if recent.is_empty() {
+            return None;
+        }
+        let outliers = recent.iter().filter(|outlier| **outlier).count();
+        Some(outliers as f64 / recent.len() as f64)
+    }
+
+    /// Returns the time series of the metric `name`.
+    pub fn get_metric_ts(&self, name: &str) -> &[[f64; 2]] {
+        self.metric_ts.get(name).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    /// Returns the most recent value of the metric `name`.
+    pub fn get_metric(&self, name: &str) -> Option<f64> {
+        self.get_metric_ts(name).last().map(|v| v[1])
+    }
+
+    /// Returns a summary of the HRV statistics.
+    pub fn summary(&self) -> HrvSummary {
+        let data = self.data.get_data();
+        let inliers: Vec<f64> = data
+            .iter()
+            .zip(self.outliers())
+            .filter(|(_, outlier)| !**outlier)
+            .map(|(rr, _)| *rr)
+            .collect();
+        let mean_rr = if inliers.is_empty() {
+            None
+        } else {
+            Some(inliers.iter().sum::<f64>() / inliers.len() as f64)
+        };
+        HrvSummary {
+            rmssd: self.get_rmssd(),
+            sdrr: self.get_sdrr(),
+            sd1: self.get_sd1(),
+            sd2: self.get_sd2(),
+            hr: self.get_hr(),
+            dfa_alpha: self
+                .get_dfa_alpha()
+                .filter(|_| inliers.len() >= self.dfa_min_beats),
+            sdann: self.get_sdann(),
+            sdnn_index: self.get_sdnn_index(),
+            mean_rr,
+            rr_count: data.len(),
+            artifact_ratio: self.get_artifact_ratio(),
+            dropped_events: 0,
+            duration: self
+                .rr_timepoints
+                .last()
+                .map_or(Duration::ZERO, |last| *last - self.time_offset),
+        }
+    }
+
+    /// Returns the fraction of the analysed RR intervals classified as outliers.
+    pub fn get_artifact_ratio(&self) -> f64 {
+        let outliers = self.outliers();
+        if outliers.is_empty() {
+            return 0.0;
+        }
+        let count = outliers.iter().filter(|outlier| **outlier).count();
+        count as f64 / outliers.len() as f64
+    }
+
+    /// Returns the share of the expected beats that were received.
+    ///
+    /// The expected number of beats is the covered reception time divided by the mean inlier
+    /// RR interval, so beats of lost messages reduce the completeness even though no outlier
+    /// marks them. Gaps are not counted as missing.
+    ///
+    /// # Returns
+    ///
+    /// The completeness between `0` and `1`, `None` without inlier RR intervals.
+    pub fn get_completeness(&self) -> Option<f64> {
+        let inliers: Vec<f64> = self
+            .data
+            .get_data()
+            .iter()
+            .zip(self.outliers())
+            .filter(|(_, outlier)| !**outlier)
+            .map(|(rr, _)| *rr)
+            .collect();
+        if inliers.is_empty() || self.covered <= Duration::ZERO {
+            return None;
+        }
+        let mean_rr = inliers.iter().sum::<f64>() / inliers.len() as f64;
+        let expected = self.covered.as_seconds_f64() * 1000.0 / mean_rr;
+        Some((self.data.get_data().len() as f64 / expected).min(1.0))
+    }
+
+    /// Returns the analysed RR intervals classified by the outlier filter.
+    ///
+    /// The intervals are preprocessed like the analysis, i.e. cropped to the analysis range
+    /// and without the settling period.
+    pub fn get_filter_input(&self) -> &[f64] {
+        self.data.get_data()
+    }
+
+    /// Returns the number of analysed RR intervals that are not classified as outliers.
+    pub fn get_inlier_count(&self) -> usize {
+        self.outliers().iter().filter(|outlier| !**outlier).count()
+    }
+
+    /// Returns the time series of all registered metrics in registration order.
+    pub fn get_metrics(&self) -> Vec<MetricSeries> {
+        self.metrics
+            .iter()
+            .map(|metric| MetricSeries {
+                name: metric.name().to_owned(),
+                unit: metric.unit().to_owned(),
+                time_series: self.get_metric_ts(metric.name()).to_owned(),
+            })
+            .collect()
     }
 
+    pub fn get_rmssd_ts(&self) -> &[[f64; 2]] {
+        self.get_metric_ts(RMSSD)
+    }
     pub fn get_sdrr_ts(&self) -> &[[f64; 2]] {
-        &self.sdrr_ts
+        self.get_metric_ts(SDRR)
     }
     pub fn get_sd1_ts(&self) -> &[[f64; 2]] {
-        &self.sd1_ts
+        self.get_metric_ts(SD1)
     }
     pub fn get_sd2_ts(&self) -> &[[f64; 2]] {
-        &self.sd2_ts
+        self.get_metric_ts(SD2)
     }
     pub fn get_hr_ts(&self) -> &[[f64; 2]] {
-        &self.hr_ts
+        self.get_metric_ts(HR)
     }
     pub fn get_dfa_alpha_ts(&self) -> &[[f64; 2]] {
-        &self.dfa_alpha_ts
+        self.get_metric_ts(DFA_ALPHA)
     }
     pub fn get_rmssd(&self) -> Option<f64> {
-        self.rmssd_ts.last().map(|v| v[1])
+        self.get_metric(RMSSD)
     }
     pub fn get_sdrr(&self) -> Option<f64> {
-        self.sdrr_ts.last().map(|v| v[1])
+        self.get_metric(SDRR)
     }
     pub fn get_sd1(&self) -> Option<f64> {
-        self.sd1_ts.last().map(|v| v[1])
+        self.get_metric(SD1)
     }
     pub fn get_sd2(&self) -> Option<f64> {
-        self.sd2_ts.last().map(|v| v[1])
+        self.get_metric(SD2)
     }
     pub fn get_hr(&self) -> Option<f64> {
-        self.hr_ts.last().map(|v| v[1])
+        self.get_metric(HR)
     }
     pub fn get_dfa_alpha(&self) -> Option<f64> {
-        self.dfa_alpha_ts.last().map(|v| v[1])
+        self.get_metric(DFA_ALPHA)
     }
 }
 
 #[cfg(test)]
 pub mod tests {
-    use rand::{Rng, SeedableRng};
+    use hrv_algos::analysis::time::calc_rmssd;
 
     use super::*;
+    use crate::model::synthetic::{self, SyntheticConfig};
+    use crate::model::testdata::get_data;
 
-    pub fn get_data(len: usize) -> Vec<(Duration, HeartrateMessage)> {
-        let mut rng = rand::rngs::StdRng::seed_from_u64(42);
-        (0..len)
-            .map(|idx| {
-                let rr = rng.gen_range(500..1500);
-                let hr = rng.gen_range(55..65);
-                (
-                    Duration::seconds(idx as _),
-                    HeartrateMessage::from_values(hr, None, &[rr]),
-                )
-            })
-            .collect()
+    /// Analysis parameters with an outlier filter that keeps the random test data.
+    fn loose_config() -> HrvConfig {
+        HrvConfig {
+            outlier_filter: 50.0,
+            ..Default::default()
+        }
     }
 
     #[test]
@@ -425,17 +1743,44 @@ pub mod tests {
     #[test]
     fn test_hrv_session_data_from_acquisition() {
         let data = get_data(4);
-        let session_data = HrvAnalysisData::from_acquisition(&data, None, 50.0).unwrap();
+        let session_data =
+            HrvAnalysisData::from_acquisition(&data, &[], &loose_config(), None, Duration::ZERO)
+                .unwrap();
         assert!(session_data.has_sufficient_data());
     }
 
     #[test]
     fn test_hrv_insufficient_data() {
         let data = get_data(2);
-        let session_data = HrvAnalysisData::from_acquisition(&data, None, 50.0).unwrap();
+        let session_data =
+            HrvAnalysisData::from_acquisition(&data, &[], &loose_config(), None, Duration::ZERO)
+                .unwrap();
         assert!(!session_data.has_sufficient_data());
     }
 
+    #[test]
+    fn test_analysis_state() {
+        let empty =
+            HrvAnalysisData::from_acquisition(&[], &[], &loose_config(), None, Duration::ZERO)
+                .unwrap();
+        assert_eq!(empty.state(), AnalysisState::NoData);
+        assert!(empty.get_rmssd().is_none());
+
+        let data = get_data(2);
+        let insufficient =
+            HrvAnalysisData::from_acquisition(&data, &[], &loose_config(), None, Duration::ZERO)
+                .unwrap();
+        assert_eq!(insufficient.state(), AnalysisState::Insufficient);
+
+        // a ready analysis may still lack individual metrics
+        let data = get_data(MIN_BEATS);
+        let ready =
+            HrvAnalysisData::from_acquisition(&data, &[], &loose_config(), None, Duration::ZERO)
+                .unwrap();
+        assert_eq!(ready.state(), AnalysisState::Ready);
+        assert!(ready.get_sdann().is_none());
+    }
+
     #[test]
     fn test_hrv_outlier_removal() {
         let data = [
@@ -456,20 +1801,284 @@ pub mod tests {
                 HeartrateMessage::from_values(60, None, &[600, 1000]),
             ),
         ];
-        let session_data = HrvAnalysisData::from_acquisition(&data, None, 50.0).unwrap();
+        let session_data =
+            HrvAnalysisData::from_acquisition(&data, &[], &loose_config(), None, Duration::ZERO)
+                .unwrap();
         let poincare = session_data.get_poincare(None).unwrap();
         // Expect some outliers because of the large RR interval
         assert!(!poincare.1.is_empty());
     }
 
+    #[test]
+    fn test_recent_rr() {
+        let data = [
+            (
+                Duration::seconds(0),
+                HeartrateMessage::from_values(60, None, &[600, 1000]),
+            ),
+            (
+                Duration::seconds(1),
+                HeartrateMessage::from_values(60, None, &[600, 1000]),
+            ),
+            (
+                Duration::seconds(2),
+                HeartrateMessage::from_values(60, None, &[800, 20000]),
+            ),
+            (
+                Duration::seconds(3),
+                HeartrateMessage::from_values(60, None, &[600, 1000]),
+            ),
+        ];
+        let session_data =
+            HrvAnalysisData::from_acquisition(&data, &[], &loose_config(), None, Duration::ZERO)
+                .unwrap();
+        let recent = session_data.get_recent_rr(5);
+        assert_eq!(recent.len(), 5);
+        let rr: Vec<f64> = recent.iter().map(|(rr, _)| *rr).collect();
+        assert_eq!(rr, vec![1000.0, 800.0, 20000.0, 600.0, 1000.0]);
+        assert!(recent.iter().any(|(_, outlier)| *outlier));
+        // asking for more than available returns everything
+        assert_eq!(session_data.get_recent_rr(100).len(), 8);
+        assert!(HrvAnalysisData::default().get_recent_rr(5).is_empty());
+    }
+
+    #[test]
+    fn test_instant_sd1() {
+        // alternating intervals followed by a constant rhythm
+        let mut data: Vec<(Duration, HeartrateMessage)> = (0..40)
+            .map(|idx| {
+                let rr = if idx % 2 == 0 { 900 } else { 1000 };
+                (
+                    Duration::seconds(idx),
+                    HeartrateMessage::from_values(60, None, &[rr]),
+                )
+            })
+            .collect();
+        let session_data =
+            HrvAnalysisData::from_acquisition(&data, &[], &loose_config(), None, Duration::ZERO)
+                .unwrap();
+        let variable = session_data.get_instant_sd1().unwrap();
+        assert!(variable > 50.0, "{}", variable);
+
+        // a slow steady trend has no beat-to-beat variability
+        data.extend((0..INSTANT_SD1_BEATS as u16).map(|idx| {
+            (
+                Duration::seconds(40 + idx as i64),
+                HeartrateMessage::from_values(60, None, &[950 + idx]),
+            )
+        }));
+        let session_data =
+            HrvAnalysisData::from_acquisition(&data, &[], &loose_config(), None, Duration::ZERO)
+                .unwrap();
+        // the older alternating beats are ignored
+        assert!(session_data.get_instant_sd1().unwrap() < 2.0);
+        assert!(HrvAnalysisData::default().get_instant_sd1().is_none());
+    }
+
+    #[test]
+    fn test_completeness() {
+        let data: Vec<(Duration, HeartrateMessage)> = (1..=120)
+            .map(|idx| {
+                let rr = if idx % 2 == 0 { 990 } else { 1010 };
+                (
+                    Duration::seconds(idx),
+                    HeartrateMessage::from_values(60, None, &[rr]),
+                )
+            })
+            .collect();
+        let complete =
+            HrvAnalysisData::from_acquisition(&data, &[], &loose_config(), None, Duration::ZERO)
+                .unwrap();
+        assert!((complete.get_completeness().unwrap() - 1.0).abs() < 1e-3);
+
+        // 30 messages are lost without a marked gap
+        let lost: Vec<_> = data[..40].iter().chain(&data[70..]).copied().collect();
+        let incomplete =
+            HrvAnalysisData::from_acquisition(&lost, &[], &loose_config(), None, Duration::ZERO)
+                .unwrap();
+        assert!((incomplete.get_completeness().unwrap() - 0.75).abs() < 1e-3);
+
+        // a marked gap is not counted as missing
+        let gaps = [(Duration::seconds(40), Duration::seconds(71))];
+        let paused =
+            HrvAnalysisData::from_acquisition(&lost, &gaps, &loose_config(), None, Duration::ZERO)
+                .unwrap();
+        assert!((paused.get_completeness().unwrap() - 1.0).abs() < 1e-3);
+        assert!(HrvAnalysisData::default().get_completeness().is_none());
+    }
+
+    #[test]
+    fn test_correction_log() {
+        let mut data = get_data(40);
+        data[20].1 = HeartrateMessage::from_values(60, None, &[20000]);
+        let session_data = HrvAnalysisData::from_acquisition(
+            &data,
+            &[],
+            &HrvConfig::default(),
+            None,
+            Duration::ZERO,
+        )
+        .unwrap();
+        let log = session_data.get_correction_log();
+        let entry = log
+            .iter()
+            .find(|entry| entry.index == 20)
+            .expect("artifact is logged");
+        assert_eq!(entry.rr, 20000.0);
+        assert_eq!(entry.action, CorrectionAction::Excluded);
+        assert_eq!(entry.reason, CorrectionReason::TooLong);
+        assert!(HrvAnalysisData::default().get_correction_log().is_empty());
+    }
+
+    #[test]
+    fn test_detect_stabilization() {
+        // the heart rate settles from 90 to 60 1/min within the first minute
+        let mut elapsed = 0.0;
+        let rr_ts: Vec<[f64; 2]> = (0..400)
+            .map(|idx| {
+                let hr = 60.0 + 30.0 * (-elapsed / 15.0).exp() + [0.0, 2.0][idx % 2];
+                let rr = 60000.0 / hr;
+                elapsed += rr / 1000.0;
+                [elapsed, rr]
+            })
+            .collect();
+        let start = detect_stabilization(&rr_ts, DEFAULT_STABILIZATION_THRESHOLD)
+            .unwrap()
+            .as_seconds_f64();
+        assert!((20.0..90.0).contains(&start), "{}", start);
+        // a stricter threshold waits longer
+        let strict = detect_stabilization(&rr_ts, 0.5).unwrap().as_seconds_f64();
+        assert!(strict > start);
+
+        // a recording without transient is stable from its start
+        let stable: Vec<[f64; 2]> = (1..200).map(|idx| [idx as f64, 1000.0]).collect();
+        assert_eq!(
+            detect_stabilization(&stable, DEFAULT_STABILIZATION_THRESHOLD),
+            Some(Duration::seconds(1))
+        );
+        assert!(detect_stabilization(&stable[..50], DEFAULT_STABILIZATION_THRESHOLD).is_none());
+    }
+
+    #[test]
+    fn test_differences_within_runs() {
+        let mut data = get_data(40);
+        data[20].1 = HeartrateMessage::from_values(60, None, &[20000]);
+        let mut analysis = HrvAnalysisData::from_acquisition(
+            &data,
+            &[],
+            &HrvConfig::default(),
+            None,
+            Duration::ZERO,
+        )
+        .unwrap();
+        let classes = analysis.data.get_classification().to_vec();
+        assert!(classes[20].is_outlier());
+        let inliers: Vec<(usize, f64)> = analysis
+            .data
+            .get_data()
+            .iter()
+            .enumerate()
+            .filter(|(idx, _)| !classes[*idx].is_outlier())
+            .map(|(idx, rr)| (idx, *rr))
+            .collect();
+        let across: Vec<f64> = inliers.windows(2).map(|w| w[1].1 - w[0].1).collect();
+        let within: Vec<f64> = inliers
+            .windows(2)
+            .filter(|w| w[1].0 == w[0].0 + 1)
+            .map(|w| w[1].1 - w[0].1)
+            .collect();
+        // the difference spanning the removed beat is left out
+        assert!(within.len() < across.len());
+        assert!(within.iter().all(|d| across.contains(d)));
+        let rmssd =
+            |diffs: &[f64]| (diffs.iter().map(|d| d * d).sum::<f64>() / diffs.len() as f64).sqrt();
+
+        assert_eq!(analysis.get_successive_differences(None).unwrap(), across);
+        let rr: Vec<f64> = inliers.iter().map(|(_, rr)| *rr).collect();
+        let rmssd_across = analysis.get_rmssd().unwrap();
+        assert!((rmssd_across - calc_rmssd(&rr).unwrap()).abs() < 1e-9);
+
+        analysis
+            .set_differences(DifferenceMode::WithinRuns, usize::MAX)
+            .unwrap();
+        assert_eq!(analysis.get_successive_differences(None).unwrap(), within);
+        let rmssd_within = analysis.get_rmssd().unwrap();
+        assert!((rmssd_within - rmssd(&within)).abs() < 1e-9);
+        assert!((rmssd_within - rmssd_across).abs() > 1e-9);
+        // SD1 is based on the successive differences as well
+        let sd1_within = analysis.get_sd1().unwrap();
+        // metrics not based on successive differences are unaffected
+        let sdrr = analysis.get_sdrr().unwrap();
+        analysis
+            .set_differences(DifferenceMode::AcrossOutliers, usize::MAX)
+            .unwrap();
+        assert!((analysis.get_sdrr().unwrap() - sdrr).abs() < 1e-9);
+        assert!((analysis.get_rmssd().unwrap() - rmssd_across).abs() < 1e-9);
+        assert!((analysis.get_sd1().unwrap() - sd1_within).abs() > 1e-9);
+    }
+
     #[test]
     fn test_hrv_poincare_points() {
         let data = get_data(5);
-        let session_data = HrvAnalysisData::from_acquisition(&data, None, 50.0).unwrap();
+        let session_data =
+            HrvAnalysisData::from_acquisition(&data, &[], &loose_config(), None, Duration::ZERO)
+                .unwrap();
         let (inliers, outliers) = session_data.get_poincare(None).unwrap();
         assert_eq!(inliers.len() + outliers.len(), 4);
     }
 
+    #[test]
+    fn test_poincare_classification_mismatch() {
+        let rr = [800.0, 810.0, 790.0, 1500.0, 805.0];
+        let classes = [false, false, false, true, false];
+        let (inliers, outliers) =
+            HrvAnalysisData::poincare_points(&rr, &classes, |c| *c, &[], None).unwrap();
+        assert_eq!((inliers.len(), outliers.len()), (2, 2));
+        // a classification missing the last interval must not truncate the points
+        let err = HrvAnalysisData::poincare_points(&rr, &classes[..4], |c| *c, &[], None)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("does not match"), "{}", err);
+        assert!(
+            HrvAnalysisData::poincare_points(&rr[..4], &classes, |c| *c, &[], Some(2)).is_err()
+        );
+    }
+
+    #[test]
+    fn test_poincare_ellipse() {
+        let data = get_data(100);
+        let session_data = HrvAnalysisData::from_acquisition(
+            &data,
+            &[],
+            &HrvConfig::default(),
+            None,
+            Duration::ZERO,
+        )
+        .unwrap();
+        let ellipse = session_data.get_poincare_ellipse(None).unwrap();
+        let (rr, _, _) = session_data
+            .get_last_filtered(0..session_data.data.get_data().len())
+            .unwrap();
+        let metrics = calc_poincare_metrics(&rr).unwrap();
+        assert_eq!(ellipse.sd1, metrics.sd1);
+        assert_eq!(ellipse.sd2, metrics.sd2);
+
+        // the extents along and across the line of identity are the semi-axes
+        let axis = std::f64::consts::FRAC_1_SQRT_2;
+        let outline = ellipse.polyline(360);
+        let max_along = outline
+            .iter()
+            .map(|p| ((p[0] - ellipse.center[0]) + (p[1] - ellipse.center[1])) * axis)
+            .fold(f64::MIN, f64::max);
+        let max_across = outline
+            .iter()
+            .map(|p| ((p[1] - ellipse.center[1]) - (p[0] - ellipse.center[0])) * axis)
+            .fold(f64::MIN, f64::max);
+        assert!((max_along - metrics.sd2).abs() < 1e-9);
+        assert!((max_across - metrics.sd1).abs() < 1e-9);
+        assert_eq!(outline.first(), outline.last());
+    }
+
     #[test]
     fn test_full_dataset() {
         fn assert_ts_props(ts: &[[f64; 2]]) {
@@ -481,7 +2090,17 @@ pub mod tests {
             });
         }
         let data = get_data(256);
-        let session_data = HrvAnalysisData::from_acquisition(&data, Some(120), 5.0).unwrap();
+        let session_data = HrvAnalysisData::from_acquisition(
+            &data,
+            &[],
+            &HrvConfig {
+                window: Some(120),
+                ..Default::default()
+            },
+            None,
+            Duration::ZERO,
+        )
+        .unwrap();
         assert!(session_data.has_sufficient_data());
         assert!(session_data.get_rmssd().is_some());
         assert!(session_data.get_sdrr().is_some());
@@ -496,4 +2115,786 @@ pub mod tests {
         assert_ts_props(session_data.get_hr_ts());
         assert_ts_props(session_data.get_dfa_alpha_ts());
     }
+
+    #[test]
+    fn test_analysis_thread_pool() {
+        fn bits(ts: &[[f64; 2]]) -> Vec<[u64; 2]> {
+            ts.iter()
+                .map(|p| [p[0].to_bits(), p[1].to_bits()])
+                .collect()
+        }
+        let data = get_data(256);
+        let global = HrvAnalysisData::from_acquisition(
+            &data,
+            &[],
+            &HrvConfig {
+                window: Some(120),
+                ..Default::default()
+            },
+            None,
+            Duration::ZERO,
+        )
+        .unwrap();
+
+        assert!(set_analysis_threads(Some(0)).is_err());
+        set_analysis_threads(Some(2)).unwrap();
+        let threads = in_analysis_pool(rayon::current_num_threads);
+        let pooled = HrvAnalysisData::from_acquisition(
+            &data,
+            &[],
+            &HrvConfig {
+                window: Some(120),
+                ..Default::default()
+            },
+            None,
+            Duration::ZERO,
+        );
+        set_analysis_threads(None).unwrap();
+
+        assert_eq!(threads, 2);
+        let pooled = pooled.unwrap();
+        assert!(!pooled.get_rmssd_ts().is_empty());
+        assert_eq!(bits(pooled.get_rmssd_ts()), bits(global.get_rmssd_ts()));
+        assert_eq!(bits(pooled.get_sd2_ts()), bits(global.get_sd2_ts()));
+        assert_eq!(
+            bits(pooled.get_dfa_alpha_ts()),
+            bits(global.get_dfa_alpha_ts())
+        );
+    }
+
+    #[test]
+    fn test_analysis_range_changes_rmssd() {
+        let data = get_data(300);
+        let full = HrvAnalysisData::from_acquisition(
+            &data,
+            &[],
+            &HrvConfig::default(),
+            None,
+            Duration::ZERO,
+        )
+        .unwrap();
+        let cropped = HrvAnalysisData::from_acquisition(
+            &data,
+            &[],
+            &HrvConfig::default(),
+            Some((Duration::seconds(100), Duration::seconds(200))),
+            Duration::ZERO,
+        )
+        .unwrap();
+        // 101 messages with one RR interval each fall into the range
+        assert_eq!(cropped.data.get_data().len(), 101);
+        assert!(cropped.rr_timepoints[0] > Duration::seconds(100));
+        assert!(cropped.get_rmssd().is_some());
+        assert_ne!(full.get_rmssd(), cropped.get_rmssd());
+    }
+
+    #[test]
+    fn test_rr_ts() {
+        let data = get_data(10);
+        let session_data =
+            HrvAnalysisData::from_acquisition(&data, &[], &loose_config(), None, Duration::ZERO)
+                .unwrap();
+        let rr_ts = session_data.get_rr_ts();
+        assert_eq!(rr_ts.len(), 10);
+        assert!((rr_ts[0][0] * 1000.0 - rr_ts[0][1]).abs() < 1.0);
+        rr_ts.windows(2).for_each(|w| assert!(w[0][0] < w[1][0]));
+    }
+
+    fn gap_data() -> Vec<(Duration, HeartrateMessage)> {
+        // two segments with successive differences of 20 ms and 40 ms separated by a gap
+        (0..40)
+            .map(|idx| {
+                let rr = match (idx < 20, idx % 2 == 0) {
+                    (true, true) => 800,
+                    (true, false) => 820,
+                    (false, true) => 800,
+                    (false, false) => 840,
+                };
+                let ts = if idx < 20 { idx } else { idx + 60 };
+                (
+                    Duration::seconds(ts),
+                    HeartrateMessage::from_values(60, None, &[rr]),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_gap_splits_statistics() {
+        let data = gap_data();
+        let gaps = [(Duration::seconds(20), Duration::seconds(80))];
+        let continuous =
+            HrvAnalysisData::from_acquisition(&data, &[], &loose_config(), None, Duration::ZERO)
+                .unwrap();
+        let with_gap =
+            HrvAnalysisData::from_acquisition(&data, &gaps, &loose_config(), None, Duration::ZERO)
+                .unwrap();
+        assert_eq!(with_gap.segment_starts, vec![20]);
+        // the last window only covers the second segment
+        assert!((with_gap.get_rmssd().unwrap() - 40.0).abs() < 1e-6);
+        assert!(continuous.get_rmssd().unwrap() < 40.0);
+        // timestamps resume at the end of the gap
+        assert!(with_gap.rr_timepoints[20] >= Duration::seconds(80));
+        // no poincare point spans the gap
+        let (inliers, outliers) = with_gap.get_poincare(None).unwrap();
+        assert_eq!(inliers.len() + outliers.len(), 38);
+    }
+
+    #[test]
+    fn test_blank_after_gap() {
+        // the sensor replays three spurious beats after the gap
+        let data: Vec<_> = gap_data()
+            .into_iter()
+            .enumerate()
+            .map(|(idx, (ts, msg))| match idx {
+                20 => (ts, HeartrateMessage::from_values(60, None, &[600, 1000])),
+                21 => (ts, HeartrateMessage::from_values(60, None, &[500])),
+                _ => (ts, msg),
+            })
+            .collect();
+        let gaps = [(Duration::seconds(20), Duration::seconds(80))];
+        let spurious =
+            HrvAnalysisData::from_acquisition(&data, &gaps, &loose_config(), None, Duration::ZERO)
+                .unwrap();
+        assert!(spurious.get_rmssd().unwrap() > 100.0);
+
+        let blanked = HrvAnalysisData::from_acquisition(
+            &data,
+            &gaps,
+            &HrvConfig {
+                outlier_filter: 50.0,
+                blank_after_gap: 3,
+                ..Default::default()
+            },
+            None,
+            Duration::ZERO,
+        )
+        .unwrap();
+        assert_eq!(blanked.data.get_data().len(), 20 + 18);
+        assert_eq!(blanked.segment_starts, vec![20]);
+        assert!(!blanked.data.get_data()[20..].iter().any(|&rr| rr < 800.0));
+        assert!((blanked.get_rmssd().unwrap() - 40.0).abs() < 1e-6);
+        // the remaining beats keep their position in time
+        assert!(blanked.rr_timepoints[20] >= Duration::seconds(82));
+    }
+
+    #[test]
+    fn test_analyze_rr() {
+        let data = synthetic::generate(&SyntheticConfig::default());
+        let mut rr: Vec<f64> = data
+            .iter()
+            .flat_map(|(_, msg)| msg.get_rr_intervals().to_vec())
+            .collect();
+        let config = HrvConfig {
+            outlier_filter: 3.0,
+            ..Default::default()
+        };
+        let expected = HrvAnalysisData::from_acquisition(&data, &[], &config, None, Duration::ZERO)
+            .unwrap()
+            .summary();
+        assert_eq!(analyze_rr(&rr, &config).unwrap(), expected);
+        // invalid intervals are ignored
+        rr.insert(10, 0.0);
+        rr.insert(20, f64::INFINITY);
+        rr.insert(30, f64::NAN);
+        assert_eq!(analyze_rr(&rr, &config).unwrap(), expected);
+        // intervals beyond the representable duration are rejected
+        assert!(analyze_rr(&[800.0, f64::MAX, f64::MAX], &config).is_err());
+
+        // both analyses use the DFA box sizes of the configuration
+        let dfa = HrvConfig {
+            dfa: DfaConfig {
+                min_box: 4,
+                max_box: 8,
+            },
+            ..config.clone()
+        };
+        let expected_dfa =
+            HrvAnalysisData::from_acquisition(&data, &[], &dfa, None, Duration::ZERO)
+                .unwrap()
+                .summary();
+        assert_ne!(expected_dfa.dfa_alpha, expected.dfa_alpha);
+        assert_eq!(analyze_rr(&rr, &dfa).unwrap(), expected_dfa);
+
+        // both analyses report no DFA alpha below the required number of beats
+        let required = config.dfa.min_beats();
+        for count in [required - 1, required] {
+            let short: Vec<(Duration, HeartrateMessage)> = (0..count)
+                .map(|idx| {
+                    let rr = if idx % 2 == 0 { 800 } else { 840 };
+                    (
+                        Duration::seconds(idx as i64),
+                        HeartrateMessage::from_values(60, None, &[rr]),
+                    )
+                })
+                .collect();
+            let summary =
+                HrvAnalysisData::from_acquisition(&short, &[], &config, None, Duration::ZERO)
+                    .unwrap()
+                    .summary();
+            assert_eq!(summary.dfa_alpha.is_some(), count >= required);
+            let short_rr: Vec<f64> = short
+                .iter()
+                .flat_map(|(_, msg)| msg.get_rr_intervals().to_vec())
+                .collect();
+            assert_eq!(
+                analyze_rr(&short_rr, &config).unwrap().dfa_alpha,
+                summary.dfa_alpha
+            );
+        }
+
+        let invalid = HrvConfig {
+            window: Some(0),
+            ..Default::default()
+        };
+        assert!(analyze_rr(&rr, &invalid).is_err());
+        let empty = analyze_rr(&[], &config).unwrap();
+        assert_eq!(empty.rr_count, 0);
+        assert!(empty.rmssd.is_none());
+    }
+
+    #[test]
+    fn test_recent_artifacts() {
+        assert_eq!(HrvAnalysisData::default().get_beats_since_outlier(), 0);
+        assert!(HrvAnalysisData::default()
+            .get_recent_artifact_rate()
+            .is_none());
+        // beats of one second on average, with an ectopic beat long ago and a recent one
+        let data: Vec<(Duration, HeartrateMessage)> = (0..100)
+            .map(|idx| {
+                let rr = match idx {
+                    5 | 80 => 1500,
+                    idx => [1000, 1010, 990][idx % 3],
+                };
+                (
+                    Duration::seconds(idx as i64),
+                    HeartrateMessage::from_values(60, None, &[rr]),
+                )
+            })
+            .collect();
+        let config = HrvConfig {
+            outlier_filter: 5.0,
+            ..Default::default()
+        };
+        let analysis =
+            HrvAnalysisData::from_acquisition(&data, &[], &config, None, Duration::ZERO).unwrap();
+        let outliers: Vec<usize> = analysis
+            .get_recent_rr(100)
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, (_, outlier))| outlier.then_some(idx))
+            .collect();
+        assert_eq!(outliers, [5, 80]);
+        assert_eq!(analysis.get_beats_since_outlier(), 19);
+        // only the recent ectopic beat falls into the last minute of about 60 beats
+        let rate = analysis.get_recent_artifact_rate().unwrap();
+        assert!((rate - 1.0 / 60.0).abs() < 0.002, "{}", rate);
+    }
+
+    #[test]
+    fn test_settling_filter() {
+        // beats of one second on average with moderately deviating beats early and late
+        let data: Vec<(Duration, HeartrateMessage)> = (0..120)
+            .map(|idx| {
+                let rr = match idx {
+                    10 | 100 => 1150,
+                    idx => [1000, 1010, 990][idx % 3],
+                };
+                (
+                    Duration::seconds(idx as i64),
+                    HeartrateMessage::from_values(60, None, &[rr]),
+                )
+            })
+            .collect();
+        let analyse = |data: &[(Duration, HeartrateMessage)], scale: f64| {
+            let config = HrvConfig {
+                outlier_filter: scale,
+                ..Default::default()
+            };
+            HrvAnalysisData::from_acquisition(data, &[], &config, None, Duration::ZERO).unwrap()
+        };
+        let settling = SettlingFilter {
+            period: Duration::seconds(60),
+            scale: 10.0,
+        };
+        let mut analysis = analyse(&data, 1.0);
+        let tight = analysis.outliers().to_vec();
+        assert!(tight[10] && tight[100]);
+        analysis
+            .set_settling_filter(Some(settling), usize::MAX)
+            .unwrap();
+        let outliers = analysis.outliers();
+        let early = analysis
+            .rr_timepoints
+            .partition_point(|ts| *ts <= settling.period);
+        assert!(early > 10 && early < 100);
+        // the settling phase is judged by the looser scale, the rest by the tighter one
+        let loose = analyse(&data[..early], 10.0).outliers().to_vec();
+        assert!(!loose[10]);
+        assert_eq!(outliers[..early], loose[..]);
+        assert_eq!(outliers[early..], tight[early..]);
+
+        // RR intervals added while recording are judged the same way
+        let mut live = HrvAnalysisData::default();
+        live.data.set_quantile_scale(1.0).unwrap();
+        live.set_settling_filter(Some(settling), 50).unwrap();
+        for chunk in data.chunks(7) {
+            live.add_measurements(chunk, 50).unwrap();
+        }
+        let live = live.outliers();
+        assert!(!live[10] && live[100]);
+
+        analysis.set_settling_filter(None, usize::MAX).unwrap();
+        assert_eq!(analysis.outliers(), tight);
+        assert!(analysis
+            .set_settling_filter(
+                Some(SettlingFilter {
+                    period: Duration::seconds(5),
+                    ..settling
+                }),
+                usize::MAX
+            )
+            .is_err());
+    }
+
+    #[test]
+    fn test_contact_track() {
+        // flags: RR intervals present, contact detection supported, contact bit per message
+        let data: Vec<(Duration, HeartrateMessage)> = (0..30)
+            .map(|idx| {
+                let flags = if (10..15).contains(&idx) { 0x14 } else { 0x16 };
+                let rr = 1024u16 + (idx % 3) * 20;
+                let [low, high] = rr.to_le_bytes();
+                (
+                    Duration::seconds(idx as i64),
+                    HeartrateMessage::new(&[flags, 60, low, high]),
+                )
+            })
+            .collect();
+        let config = HrvConfig {
+            outlier_filter: 5.0,
+            ..Default::default()
+        };
+        let analysis =
+            HrvAnalysisData::from_acquisition(&data, &[], &config, None, Duration::ZERO).unwrap();
+        let track = analysis.get_contact_track();
+        assert_eq!(track.len(), 30);
+        let lost: Vec<usize> = (0..30).filter(|&idx| !track[idx].1).collect();
+        assert_eq!(lost, (10..15).collect::<Vec<_>>());
+        // the track shares the time axis of the RR intervals
+        let rr_ts = analysis.get_rr_ts();
+        assert!(track.iter().zip(&rr_ts).all(|(beat, rr)| beat.0 == rr[0]));
+
+        // sensors without contact detection always report contact
+        let unsupported: Vec<_> = data
+            .iter()
+            .map(|(elapsed, msg)| {
+                let rr = msg.get_rr_intervals()[0] as u16;
+                (*elapsed, HeartrateMessage::from_values(60, None, &[rr]))
+            })
+            .collect();
+        let analysis =
+            HrvAnalysisData::from_acquisition(&unsupported, &[], &config, None, Duration::ZERO)
+                .unwrap();
+        assert!(analysis.get_contact_track().iter().all(|beat| beat.1));
+    }
+
+    #[test]
+    fn test_identical_runs() {
+        // genuine variability followed by a flatline of the sensor
+        let data: Vec<(Duration, HeartrateMessage)> = (0..60)
+            .map(|idx| {
+                let rr = if idx < 40 { 800 + (idx % 5) * 10 } else { 900 };
+                (
+                    Duration::seconds(idx as i64),
+                    HeartrateMessage::from_values(60, None, &[rr as u16]),
+                )
+            })
+            .collect();
+        let all =
+            HrvAnalysisData::from_acquisition(&data, &[], &loose_config(), None, Duration::ZERO)
+                .unwrap();
+        assert_eq!(all.data.get_data().len(), 60);
+
+        let limited = HrvAnalysisData::from_acquisition(
+            &data,
+            &[],
+            &HrvConfig {
+                outlier_filter: 50.0,
+                max_identical_run: Some(3),
+                ..Default::default()
+            },
+            None,
+            Duration::ZERO,
+        )
+        .unwrap();
+        // only the repetitions beyond the limit are discarded
+        assert_eq!(limited.data.get_data().len(), 40 + 3);
+        assert_eq!(limited.data.get_data()[..40], all.data.get_data()[..40]);
+        // the remaining beats keep their position in time
+        assert_eq!(limited.rr_timepoints[..43], all.rr_timepoints[..43]);
+
+        // a live recording discards the flatline the same way
+        let mut live = HrvAnalysisData {
+            max_identical_run: Some(3),
+            ..Default::default()
+        };
+        for (elapsed, msg) in &data {
+            live.add_measurement(*elapsed, msg, usize::MAX).unwrap();
+        }
+        assert_eq!(live.data.get_data(), limited.data.get_data());
+    }
+
+    #[test]
+    fn test_detrend_reduces_sdrr() {
+        // slow drift of the heart rate with a small beat to beat variation
+        let data: Vec<(Duration, HeartrateMessage)> = (0..300)
+            .map(|idx| {
+                let rr = 700 + idx * 2 + [0, 15, 5, 20][idx % 4];
+                (
+                    Duration::seconds(idx as i64),
+                    HeartrateMessage::from_values(60, None, &[rr as u16]),
+                )
+            })
+            .collect();
+        let mut analysis =
+            HrvAnalysisData::from_acquisition(&data, &[], &loose_config(), None, Duration::ZERO)
+                .unwrap();
+        let (sdrr, hr) = (analysis.get_sdrr().unwrap(), analysis.get_hr().unwrap());
+        let rr_ts = analysis.get_rr_ts();
+        analysis
+            .set_detrend(DetrendOption::Polynomial(1), usize::MAX)
+            .unwrap();
+        assert!(analysis.get_sdrr().unwrap() < sdrr / 5.0);
+        assert!((analysis.get_hr().unwrap() - hr).abs() < 1e-9);
+        // the tachogram shows the recorded intervals
+        assert_eq!(analysis.get_rr_ts(), rr_ts);
+        assert!(analysis
+            .set_detrend(DetrendOption::Polynomial(9), usize::MAX)
+            .is_err());
+    }
+
+    #[test]
+    fn test_successive_differences() {
+        let data = get_data(200);
+        let analysis =
+            HrvAnalysisData::from_acquisition(&data, &[], &loose_config(), None, Duration::ZERO)
+                .unwrap();
+        let (rr, _, _) = analysis
+            .get_last_filtered(0..analysis.data.get_data().len())
+            .unwrap();
+        let diffs = analysis.get_successive_differences(None).unwrap();
+        let expected: Vec<f64> = rr.windows(2).map(|pair| pair[1] - pair[0]).collect();
+        assert_eq!(diffs, expected);
+        // RMSSD is the root mean square of the differences
+        let rms = (diffs.iter().map(|d| d * d).sum::<f64>() / diffs.len() as f64).sqrt();
+        let rmssd = analysis.get_rmssd().unwrap();
+        assert!((rms - rmssd).abs() / rmssd < 0.01);
+
+        let recent = analysis.get_successive_differences(Some(20)).unwrap();
+        assert_eq!(recent, expected[expected.len() - 19..]);
+    }
+
+    #[derive(Debug)]
+    struct MeanRr;
+
+    impl HrvMetric for MeanRr {
+        fn name(&self) -> &str {
+            "mean RR"
+        }
+        fn compute(&self, rr: &[f64]) -> Result<f64> {
+            Ok(rr.iter().sum::<f64>() / rr.len() as f64)
+        }
+    }
+
+    #[test]
+    fn test_rr_precision() {
+        // raw RR intervals in 1/1024 s as sent by the sensor
+        let raw: Vec<u16> = (0..64).map(|idx| 800 + (idx * 37 % 61)).collect();
+        let data: Vec<_> = raw
+            .iter()
+            .enumerate()
+            .map(|(idx, rr)| {
+                let [lsb, msb] = rr.to_le_bytes();
+                (
+                    Duration::seconds(idx as i64),
+                    HeartrateMessage::new(&[0b00010000, 60, lsb, msb]),
+                )
+            })
+            .collect();
+        let precise: Vec<f64> = raw.iter().map(|&rr| rr as f64 * 1000.0 / 1024.0).collect();
+        let rounded: Vec<f64> = precise.iter().map(|rr| rr.trunc()).collect();
+        let analysis =
+            HrvAnalysisData::from_acquisition(&data, &[], &loose_config(), None, Duration::ZERO)
+                .unwrap();
+        let rmssd = analysis.get_rmssd().unwrap();
+        let precise_rmssd = calc_rmssd(&precise).unwrap();
+        let rounded_rmssd = calc_rmssd(&rounded).unwrap();
+        assert!((rmssd - precise_rmssd).abs() < 1e-9);
+        assert!((rounded_rmssd - precise_rmssd).abs() > 1e-3);
+    }
+
+    #[test]
+    fn test_summary() {
+        let data = get_data(300);
+        let analysis = HrvAnalysisData::from_acquisition(
+            &data,
+            &[],
+            &HrvConfig {
+                window: Some(60),
+                ..Default::default()
+            },
+            None,
+            Duration::ZERO,
+        )
+        .unwrap();
+        let summary = analysis.summary();
+        assert_eq!(summary.rmssd, analysis.get_rmssd());
+        assert_eq!(summary.sdrr, analysis.get_sdrr());
+        assert_eq!(summary.sd1, analysis.get_sd1());
+        assert_eq!(summary.sd2, analysis.get_sd2());
+        assert_eq!(summary.hr, analysis.get_hr());
+        assert_eq!(summary.dfa_alpha, analysis.get_dfa_alpha());
+        assert_eq!(summary.sdann, analysis.get_sdann());
+        assert_eq!(summary.sdnn_index, analysis.get_sdnn_index());
+        assert_eq!(summary.rr_count, 300);
+        assert!(summary.mean_rr.is_some());
+        assert!((0.0..1.0).contains(&summary.artifact_ratio));
+        assert_eq!(Some(&summary.duration), analysis.rr_timepoints.last());
+
+        let json = serde_json::to_string(&summary).unwrap();
+        let parsed: HrvSummary = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.rr_count, summary.rr_count);
+        assert_eq!(parsed.duration, summary.duration);
+    }
+
+    #[test]
+    fn test_tune_outlier_scale() {
+        let config = SyntheticConfig {
+            ectopic_rate: 0.02,
+            ..Default::default()
+        };
+        let rr: Vec<f64> = synthetic::generate(&config)
+            .iter()
+            .flat_map(|(_, msg)| msg.get_rr_intervals().to_vec())
+            .collect();
+        // every ectopic beat is followed by a compensatory pause
+        let target = 2.0 * config.ectopic_rate;
+        let scale = tune_outlier_scale(&rr, target).unwrap();
+        assert!(OUTLIER_SCALE_RANGE.contains(&scale));
+        assert!((artifact_ratio(&rr, scale).unwrap() - target).abs() < 0.01);
+        assert!(tune_outlier_scale(&rr, 1.5).is_err());
+    }
+
+    #[test]
+    fn test_filter_input() {
+        let data = get_data(100);
+        let full =
+            HrvAnalysisData::from_acquisition(&data, &[], &loose_config(), None, Duration::ZERO)
+                .unwrap();
+        assert_eq!(full.get_filter_input(), full.data.get_data());
+
+        // the settling period is not classified
+        let settled = HrvAnalysisData::from_acquisition(
+            &data,
+            &[],
+            &loose_config(),
+            None,
+            Duration::seconds(30),
+        )
+        .unwrap();
+        assert!(settled.get_filter_input().len() < full.get_filter_input().len());
+    }
+
+    #[test]
+    fn test_summary_tsv() {
+        let summary = HrvSummary {
+            rmssd: Some(42.5),
+            dfa_alpha: Some(0.9876),
+            rr_count: 120,
+            artifact_ratio: 0.025,
+            duration: Duration::milliseconds(61500),
+            ..Default::default()
+        };
+        let row = summary.to_tsv();
+        assert_eq!(row, "42.500\t\t\t\t\t0.988\t\t\t\t120\t0.0250\t0\t61.500");
+        assert_eq!(
+            row.split('\t').count(),
+            HrvSummary::TSV_HEADER.split('\t').count()
+        );
+    }
+
+    #[test]
+    fn test_skip_initial() {
+        let data = get_data(60);
+        let full =
+            HrvAnalysisData::from_acquisition(&data, &[], &loose_config(), None, Duration::ZERO)
+                .unwrap();
+        let skipped = HrvAnalysisData::from_acquisition(
+            &data,
+            &[],
+            &loose_config(),
+            None,
+            Duration::seconds(10),
+        )
+        .unwrap();
+        assert!(skipped.data.get_data().len() < full.data.get_data().len());
+        assert!(skipped.rr_timepoints[0] > Duration::seconds(10));
+        // the remaining intervals keep their position in time
+        assert_eq!(skipped.rr_timepoints.last(), full.rr_timepoints.last());
+        assert_ne!(skipped.get_rmssd(), full.get_rmssd());
+    }
+
+    #[test]
+    fn test_oversized_window() {
+        let data: Vec<_> = (0..20)
+            .map(|idx| {
+                let rr = if idx % 2 == 0 { 800 } else { 1000 };
+                (
+                    Duration::seconds(idx),
+                    HeartrateMessage::from_values(60, None, &[rr]),
+                )
+            })
+            .collect();
+        let analysis = HrvAnalysisData::from_acquisition(
+            &data,
+            &[],
+            &HrvConfig {
+                window: Some(1000),
+                outlier_filter: 50.0,
+                ..Default::default()
+            },
+            None,
+            Duration::ZERO,
+        );
+        assert!(analysis.is_ok());
+        let analysis = analysis.unwrap();
+        // the last value covers all 20 RR intervals
+        let expected_hr = 60000.0 * 20.0 / (10.0 * 800.0 + 10.0 * 1000.0);
+        assert!((analysis.get_hr().unwrap() - expected_hr).abs() < 1e-9);
+        assert_eq!(analysis.get_hr_ts().len(), 20);
+    }
+
+    #[test]
+    fn test_register_custom_metric() {
+        let data = get_data(64);
+        let mut runtime = HrvAnalysisData::default();
+        runtime.add_measurements(&data[..32], 16).unwrap();
+        runtime.register_metric(Arc::new(MeanRr), 16).unwrap();
+        // already present data is analysed on registration
+        let registered = runtime.get_metric_ts("mean RR").len();
+        assert!(registered > 0);
+        // the default metrics are kept
+        assert!(!runtime.get_metric_ts(SDRR).is_empty());
+        runtime.add_measurements(&data[32..], 16).unwrap();
+        assert!(runtime.get_metric_ts("mean RR").len() > registered);
+        assert!(runtime.get_metric("mean RR").is_some());
+        assert!(runtime
+            .get_metrics()
+            .iter()
+            .any(|series| series.name == "mean RR" && series.unit == "ms"));
+        // names are unique
+        assert!(runtime.register_metric(Arc::new(MeanRr), 16).is_err());
+        assert!(runtime.swap_metric(RMSSD, Arc::new(MeanRr), 16).is_err());
+    }
+
+    #[test]
+    fn test_sdann_sdnn_index() {
+        // 12 minutes of beats alternating +-20 ms around a mean shifting by 50 ms
+        // every 5 minutes
+        let mut elapsed = Duration::ZERO;
+        let mut data = Vec::new();
+        while elapsed < Duration::minutes(12) {
+            let level = if elapsed < Duration::minutes(5) {
+                800
+            } else {
+                850
+            };
+            let rr = if data.len() % 2 == 0 {
+                level - 20
+            } else {
+                level + 20
+            };
+            elapsed += Duration::milliseconds(rr as i64);
+            data.push((elapsed, HeartrateMessage::from_values(75, None, &[rr])));
+        }
+        let full = HrvAnalysisData::from_acquisition(
+            &data,
+            &[],
+            &HrvConfig {
+                window: Some(60),
+                outlier_filter: 50.0,
+                ..Default::default()
+            },
+            None,
+            Duration::ZERO,
+        )
+        .unwrap();
+        // two complete segments with means of ~800 and ~850 ms
+        let sdann = full.get_sdann().unwrap();
+        assert!((sdann - calc_sdrr(&[800.0, 850.0]).unwrap()).abs() < 2.0);
+        let sdnn_index = full.get_sdnn_index().unwrap();
+        assert!((sdnn_index - 20.0).abs() < 1.0);
+
+        let short = HrvAnalysisData::from_acquisition(
+            &data,
+            &[],
+            &HrvConfig {
+                window: Some(60),
+                outlier_filter: 50.0,
+                ..Default::default()
+            },
+            Some((Duration::ZERO, Duration::minutes(8))),
+            Duration::ZERO,
+        )
+        .unwrap();
+        assert!(short.get_sdann().is_none());
+        assert!(short.get_sdnn_index().is_none());
+
+        // 3 minutes at ~800 ms and 6 minutes at ~850 ms after a pause of 2 minutes
+        let mut data = Vec::new();
+        for (start, end, level) in [(0, 3, 800), (5, 11, 850)] {
+            let mut elapsed = Duration::minutes(start);
+            while elapsed < Duration::minutes(end) {
+                let rr = if data.len() % 2 == 0 {
+                    level - 20
+                } else {
+                    level + 20
+                };
+                elapsed += Duration::milliseconds(rr as i64);
+                data.push((elapsed, HeartrateMessage::from_values(75, None, &[rr])));
+            }
+        }
+        let paused = HrvAnalysisData::from_acquisition(
+            &data,
+            &[(Duration::minutes(3), Duration::minutes(5))],
+            &HrvConfig {
+                window: Some(60),
+                outlier_filter: 50.0,
+                ..Default::default()
+            },
+            None,
+            Duration::ZERO,
+        )
+        .unwrap();
+        // the intervals after the pause are binned by their recorded time, not appended to
+        // the first segment
+        let sdann = paused.get_sdann().unwrap();
+        assert!((sdann - calc_sdrr(&[800.0, 850.0]).unwrap()).abs() < 2.0);
+    }
+
+    #[test]
+    fn test_analysis_range_outside_data() {
+        let data = get_data(10);
+        let cropped = HrvAnalysisData::from_acquisition(
+            &data,
+            &[],
+            &HrvConfig::default(),
+            Some((Duration::seconds(100), Duration::seconds(200))),
+            Duration::ZERO,
+        )
+        .unwrap();
+        assert!(!cropped.has_sufficient_data());
+        assert!(cropped.get_rmssd().is_none());
+    }
 }
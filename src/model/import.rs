@@ -0,0 +1,278 @@
+//! RR Import
+//!
+//! This module reads RR interval series exported by other tools, e.g. one interval in
+//! milliseconds or seconds per line or the HRV records of `.fit` activity files, so they can
+//! be analysed like a recording.
+
+use anyhow::Result;
+use fitparser::{profile::MesgNum, Value};
+use serde::{Deserialize, Serialize};
+use time::Duration;
+
+use super::bluetooth::HeartrateMessage;
+use crate::core::error::InvalidData;
+
+/// Median RR interval below which a file of unknown unit is read as seconds.
+///
+/// RR intervals in ms are above 200, RR intervals in s below 3.
+const SECONDS_THRESHOLD: f64 = 10.0;
+
+/// Unit of the RR intervals of an imported file.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RrUnit {
+    /// Seconds if the median interval is below 10, milliseconds otherwise.
+    #[default]
+    Auto,
+    Milliseconds,
+    Seconds,
+}
+
+impl RrUnit {
+    /// All units in the order they are offered.
+    pub const ALL: [RrUnit; 3] = [Self::Auto, Self::Milliseconds, Self::Seconds];
+
+    /// Returns the factor converting the intervals `values` of this unit to ms.
+    fn to_ms_factor(self, values: &[f64]) -> f64 {
+        match self {
+            Self::Milliseconds => 1.0,
+            Self::Seconds => 1000.0,
+            Self::Auto => {
+                let mut sorted = values.to_vec();
+                sorted.sort_by(f64::total_cmp);
+                match sorted.get(sorted.len() / 2) {
+                    Some(median) if *median < SECONDS_THRESHOLD => 1000.0,
+                    _ => 1.0,
+                }
+            }
+        }
+    }
+}
+
+/// Parses RR intervals separated by whitespace, commas or semicolons.
+///
+/// Empty lines and lines starting with `#` are ignored.
+///
+/// # Arguments
+/// * `text` - Content of the RR file.
+/// * `unit` - Unit of the RR intervals, `RrUnit::Auto` detects it from their magnitude.
+///
+/// # Returns
+/// `(elapsed time, message)` pairs as received from a sensor, one RR interval per message.
+pub fn parse_rr(text: &str, unit: RrUnit) -> Result<Vec<(Duration, HeartrateMessage)>> {
+    let mut values = Vec::new();
+    for (line_no, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        for token in line
+            .split(|c: char| c.is_whitespace() || c == ',' || c == ';')
+            .filter(|token| !token.is_empty())
+        {
+            let rr = token
+                .parse::<f64>()
+                .ok()
+                .filter(|rr| rr.is_finite())
+                .ok_or_else(|| {
+                    InvalidData(format!(
+                        "invalid RR interval in line {}: {}",
+                        line_no + 1,
+                        token
+                    ))
+                })?;
+            values.push((line_no, token, rr));
+        }
+    }
+    if values.is_empty() {
+        return Err(InvalidData("no RR intervals found".to_owned()).into());
+    }
+    let rr_values: Vec<f64> = values.iter().map(|(_, _, rr)| *rr).collect();
+    let factor = unit.to_ms_factor(&rr_values);
+    let mut elapsed_ms = 0.0;
+    let mut data = Vec::with_capacity(values.len());
+    for (line_no, token, rr) in values {
+        // rounded to whole microseconds to drop the error of the unit conversion
+        let rr = (rr * factor * 1000.0).round() / 1000.0;
+        if !(1.0..=f64::from(u16::MAX)).contains(&rr) {
+            return Err(InvalidData(format!(
+                "invalid RR interval in line {}: {}",
+                line_no + 1,
+                token
+            ))
+            .into());
+        }
+        elapsed_ms += rr;
+        let hr = (60000.0 / rr).round() as u16;
+        data.push((
+            Duration::microseconds((elapsed_ms * 1000.0).round() as i64),
+            HeartrateMessage::from_fractional_values(hr, None, &[rr]),
+        ));
+    }
+    Ok(data)
+}
+
+/// Reads the RR intervals of the HRV records of a `.fit` file, e.g. exported by Garmin devices.
+///
+/// The intervals are taken from the `time` field of all `hrv` messages in file order, invalid
+/// intervals are skipped.
+///
+/// # Arguments
+/// * `bytes` - Content of the `.fit` file.
+///
+/// # Returns
+/// `(elapsed time, message)` pairs as received from a sensor, one RR interval per message.
+pub fn parse_fit(bytes: &[u8]) -> Result<Vec<(Duration, HeartrateMessage)>> {
+    let records = fitparser::from_bytes(bytes)
+        .map_err(|e| InvalidData(format!("invalid FIT file: {}", e)))?;
+    let mut rr = Vec::new();
+    for record in records
+        .iter()
+        .filter(|record| record.kind() == MesgNum::Hrv)
+    {
+        for field in record
+            .fields()
+            .iter()
+            .filter(|field| field.name() == "time")
+        {
+            match field.value() {
+                Value::Array(values) => rr.extend(values.iter().filter_map(fit_seconds)),
+                value => rr.extend(fit_seconds(value)),
+            }
+        }
+    }
+    if rr.is_empty() {
+        return Err(InvalidData("the FIT file holds no HRV records".to_owned()).into());
+    }
+    let mut elapsed_ms = 0;
+    Ok(rr
+        .into_iter()
+        .filter_map(|rr| {
+            let rr = (rr * 1000.0).round();
+            // 0xFFFF marks an invalid interval
+            if !(1.0..f64::from(u16::MAX)).contains(&rr) {
+                return None;
+            }
+            elapsed_ms += rr as i64;
+            let hr = (60000.0 / rr).round() as u16;
+            Some((
+                Duration::milliseconds(elapsed_ms),
+                HeartrateMessage::from_values(hr, None, &[rr as u16]),
+            ))
+        })
+        .collect())
+}
+
+/// Converts a decoded RR interval of a FIT `hrv` message to seconds.
+fn fit_seconds(value: &Value) -> Option<f64> {
+    match value {
+        Value::Float64(seconds) => Some(*seconds),
+        Value::Float32(seconds) => Some(f64::from(*seconds)),
+        // the profile scale is not applied to raw values
+        Value::UInt16(ms) => Some(f64::from(*ms) / 1000.0),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Computes the CRC of a FIT file as specified by the FIT SDK.
+    fn fit_crc(bytes: &[u8]) -> u16 {
+        const TABLE: [u16; 16] = [
+            0x0000, 0xCC01, 0xD801, 0x1400, 0xF001, 0x3C00, 0x2800, 0xE401, 0xA001, 0x6C00, 0x7800,
+            0xB401, 0x5000, 0x9C01, 0x8801, 0x4400,
+        ];
+        bytes.iter().fold(0, |mut crc, byte| {
+            for nibble in [byte & 0xF, byte >> 4] {
+                let tmp = TABLE[usize::from(crc & 0xF)];
+                crc = (crc >> 4) & 0x0FFF;
+                crc = crc ^ tmp ^ TABLE[usize::from(nibble)];
+            }
+            crc
+        })
+    }
+
+    /// Encodes a FIT file holding one `hrv` message per chunk of `rr` intervals in ms.
+    fn fit_file(rr: &[u16], chunk: usize) -> Vec<u8> {
+        let mut records = Vec::new();
+        for values in rr.chunks(chunk) {
+            // definition of local message 0 as `hrv` (78) with the `time` field (0) holding
+            // an uint16 array
+            records.extend([0x40, 0, 0, 78, 0, 1, 0, (values.len() * 2) as u8, 0x84]);
+            records.push(0x00);
+            records.extend(values.iter().flat_map(|rr| rr.to_le_bytes()));
+        }
+        let mut file = vec![14, 0x10];
+        file.extend(2093u16.to_le_bytes());
+        file.extend((records.len() as u32).to_le_bytes());
+        file.extend(b".FIT");
+        file.extend(fit_crc(&file).to_le_bytes());
+        file.extend(records);
+        file.extend(fit_crc(&file).to_le_bytes());
+        file
+    }
+
+    #[test]
+    fn test_parse_fit() {
+        let rr = [812, 790, 1003, 955, 870, 0xFFFF, 901];
+        let data = parse_fit(&fit_file(&rr, 5)).unwrap();
+        assert_eq!(data.len(), 6);
+        let imported: Vec<f64> = data
+            .iter()
+            .flat_map(|(_, msg)| msg.get_rr_intervals().to_vec())
+            .collect();
+        assert_eq!(imported, vec![812.0, 790.0, 1003.0, 955.0, 870.0, 901.0]);
+        assert_eq!(data[1].0, Duration::milliseconds(1602));
+
+        // a file without HRV records, e.g. recorded without the HRV logging enabled
+        let mut empty = fit_file(&[], 1);
+        assert!(parse_fit(&empty).is_err());
+        empty.truncate(4);
+        assert!(parse_fit(&empty).is_err());
+    }
+
+    #[test]
+    fn test_parse_rr() {
+        let data = parse_rr("# rr [ms]\n1000\n\n800, 1200.4;500\n", RrUnit::Auto).unwrap();
+        let rr: Vec<f64> = data
+            .iter()
+            .flat_map(|(_, msg)| msg.get_rr_intervals().to_vec())
+            .collect();
+        assert_eq!(rr, vec![1000.0, 800.0, 1200.0, 500.0]);
+        assert_eq!(data[0].0, Duration::seconds(1));
+        assert_eq!(data[3].0, Duration::milliseconds(3500));
+        assert_eq!(data[1].1.get_hr(), 75.0);
+
+        assert!(parse_rr("", RrUnit::Auto).unwrap_err().is::<InvalidData>());
+        assert!(parse_rr("1000\nabc", RrUnit::Auto).is_err());
+        assert!(parse_rr("-800", RrUnit::Auto).is_err());
+    }
+
+    #[test]
+    fn test_parse_rr_units() {
+        let ms = "812\n790\n1003\n955\n";
+        let s = "0.812\n0.79\n1.003\n0.955\n";
+        let expected = parse_rr(ms, RrUnit::Milliseconds).unwrap();
+        assert_eq!(parse_rr(ms, RrUnit::Auto).unwrap(), expected);
+        assert_eq!(parse_rr(s, RrUnit::Auto).unwrap(), expected);
+        assert_eq!(parse_rr(s, RrUnit::Seconds).unwrap(), expected);
+        let rr: Vec<f64> = expected
+            .iter()
+            .flat_map(|(_, msg)| msg.get_rr_intervals().to_vec())
+            .collect();
+        assert_eq!(rr, vec![812.0, 790.0, 1003.0, 955.0]);
+        // an explicit unit overrides the detection
+        assert_ne!(parse_rr(s, RrUnit::Milliseconds).unwrap(), expected);
+        assert!(parse_rr(ms, RrUnit::Seconds).is_err());
+
+        // intervals keep their sub-millisecond resolution
+        let fractional = parse_rr("0.8125\n0.79\n", RrUnit::Seconds).unwrap();
+        assert_eq!(fractional[0].1.get_rr_intervals(), &[812.5]);
+        assert_eq!(fractional[1].0, Duration::microseconds(1_602_500));
+        assert_eq!(
+            parse_rr("812.5\n790\n", RrUnit::Milliseconds).unwrap(),
+            fractional
+        );
+    }
+}
@@ -0,0 +1,56 @@
+//! Kubios Export
+//!
+//! This module encodes RR intervals as RR interval text file for the ASCII import of Kubios
+//! HRV, as described in the section "Supported data formats" of the Kubios HRV user's guide
+//! (<https://www.kubios.com/downloads/Kubios_HRV_Users_Guide.pdf>). Kubios reads RR
+//! intervals either as a single column, one interval per line, or with the time of the beat
+//! in a preceding column. The file holds no header lines, every line contains the time of the
+//! beat and the RR interval in seconds, separated by a tab:
+//!
+//! ```text
+//! 0.800	0.800
+//! 1.610	0.810
+//! ```
+//!
+//! The RR intervals are the unfiltered intervals of the recording, Kubios applies its own
+//! artifact correction.
+
+/// Encodes RR intervals in the Kubios RR interval text format.
+///
+/// # Arguments
+/// * `rr_ts` - RR intervals as `[time in s, RR in ms]` points relative to the start of the
+///   recording.
+///
+/// # Returns
+/// The content of the text file, one line per RR interval with the time column first.
+pub fn encode_rr(rr_ts: &[[f64; 2]]) -> String {
+    rr_ts
+        .iter()
+        .map(|[t, rr]| format!("{:.3}\t{:.3}\n", t, rr / 1000.0))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_rr() {
+        let rr_ts = [[0.8, 800.0], [1.61, 810.0], [2.4, 790.4]];
+        let text = encode_rr(&rr_ts);
+        let lines: Vec<&str> = text.lines().collect();
+
+        // no header, one beat per line
+        assert_eq!(lines.len(), rr_ts.len());
+        let columns: Vec<Vec<&str>> = lines
+            .iter()
+            .map(|line| line.split('\t').collect())
+            .collect();
+        assert!(columns.iter().all(|columns| columns.len() == 2));
+        let time: Vec<&str> = columns.iter().map(|columns| columns[0]).collect();
+        assert_eq!(time, ["0.800", "1.610", "2.400"]);
+        let rr: Vec<&str> = columns.iter().map(|columns| columns[1]).collect();
+        assert_eq!(rr, ["0.800", "0.810", "0.790"]);
+        assert!(encode_rr(&[]).is_empty());
+    }
+}
@@ -0,0 +1,171 @@
+//! Metadata Model
+//!
+//! This module describes the subject of a measurement for study data: an anonymized subject ID,
+//! the age, the sex and the posture during the recording. All fields are optional, the
+//! metadata is written to the exports of the measurement as `(field, value)` pairs.
+
+use std::{fmt, ops::RangeInclusive};
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+
+/// Maximum length of the subject ID.
+pub const MAX_SUBJECT_ID_LEN: usize = 64;
+
+/// Supported ages of the subject in years.
+pub const AGE_RANGE: RangeInclusive<u32> = 0..=120;
+
+/// Sex of the subject.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Sex {
+    Female,
+    Male,
+    Other,
+}
+
+impl Sex {
+    /// All values in the order they are offered.
+    pub const ALL: [Sex; 3] = [Self::Female, Self::Male, Self::Other];
+}
+
+impl fmt::Display for Sex {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Sex::Female => "female",
+            Sex::Male => "male",
+            Sex::Other => "other",
+        })
+    }
+}
+
+/// Posture of the subject during the recording.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Posture {
+    Supine,
+    Sitting,
+    Standing,
+}
+
+impl Posture {
+    /// All postures in the order they are offered.
+    pub const ALL: [Posture; 3] = [Self::Supine, Self::Sitting, Self::Standing];
+}
+
+impl fmt::Display for Posture {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Posture::Supine => "supine",
+            Posture::Sitting => "sitting",
+            Posture::Standing => "standing",
+        })
+    }
+}
+
+/// Subject information of a measurement.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MeasurementMetadata {
+    /// Anonymized ID of the subject, empty if unknown.
+    pub subject_id: String,
+    /// Age of the subject in years.
+    pub age: Option<u32>,
+    /// Sex of the subject.
+    pub sex: Option<Sex>,
+    /// Posture of the subject during the recording.
+    pub posture: Option<Posture>,
+}
+
+impl MeasurementMetadata {
+    /// Checks that the subject ID and the age are within their limits.
+    pub fn validate(&self) -> Result<()> {
+        if self.subject_id.chars().count() > MAX_SUBJECT_ID_LEN {
+            return Err(anyhow!(
+                "subject ID longer than {} characters",
+                MAX_SUBJECT_ID_LEN
+            ));
+        }
+        if self.subject_id.contains(['\n', '\r']) {
+            return Err(anyhow!("subject ID must be a single line"));
+        }
+        if let Some(age) = self.age.filter(|age| !AGE_RANGE.contains(age)) {
+            return Err(anyhow!("invalid age: {}", age));
+        }
+        Ok(())
+    }
+
+    /// Checks if no field is set.
+    pub fn is_empty(&self) -> bool {
+        self.fields().is_empty()
+    }
+
+    /// Returns the set fields.
+    ///
+    /// # Returns
+    /// `(field, value)` pairs in a fixed order, unset fields are left out.
+    pub fn fields(&self) -> Vec<(&'static str, String)> {
+        let subject_id = Some(self.subject_id.trim())
+            .filter(|id| !id.is_empty())
+            .map(str::to_owned);
+        [
+            ("subject_id", subject_id),
+            ("age", self.age.map(|age| age.to_string())),
+            ("sex", self.sex.map(|sex| sex.to_string())),
+            ("posture", self.posture.map(|posture| posture.to_string())),
+        ]
+        .into_iter()
+        .filter_map(|(field, value)| value.map(|value| (field, value)))
+        .collect()
+    }
+
+    /// Encodes the set fields as comment lines preceding the header of a CSV file.
+    ///
+    /// # Returns
+    /// One `# field: value` line per set field.
+    pub fn csv_comment(&self) -> String {
+        self.fields()
+            .into_iter()
+            .map(|(field, value)| format!("# {}: {}\n", field, value))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_metadata_fields() {
+        let metadata = MeasurementMetadata {
+            subject_id: " S-017 ".to_owned(),
+            age: Some(34),
+            sex: None,
+            posture: Some(Posture::Supine),
+        };
+        assert!(metadata.validate().is_ok());
+        assert_eq!(
+            metadata.fields(),
+            [
+                ("subject_id", "S-017".to_owned()),
+                ("age", "34".to_owned()),
+                ("posture", "supine".to_owned()),
+            ]
+        );
+        assert_eq!(
+            metadata.csv_comment(),
+            "# subject_id: S-017\n# age: 34\n# posture: supine\n"
+        );
+        assert!(MeasurementMetadata::default().is_empty());
+        assert!(MeasurementMetadata::default().csv_comment().is_empty());
+
+        let invalid = MeasurementMetadata {
+            age: Some(200),
+            ..Default::default()
+        };
+        assert!(invalid.validate().is_err());
+        let invalid = MeasurementMetadata {
+            subject_id: "a\nb".to_owned(),
+            ..Default::default()
+        };
+        assert!(invalid.validate().is_err());
+    }
+}
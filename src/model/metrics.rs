@@ -0,0 +1,605 @@
+//! HRV Metrics
+//!
+//! This module defines the `HrvMetric` trait and the default set of metrics calculated
+//! by the HRV analysis. New metrics are added by implementing `HrvMetric` and registering
+//! the implementor with the analysis, see `HrvAnalysisData::register_metric`.
+
+use anyhow::{anyhow, Result};
+use hrv_algos::analysis::dfa::{DFAnalysis, DetrendStrategy};
+use hrv_algos::analysis::nonlinear::calc_poincare_metrics;
+use hrv_algos::analysis::time::{calc_rmssd, calc_sdrr};
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::fmt::Debug;
+use std::ops::RangeInclusive;
+use std::sync::Arc;
+
+/// A metric calculated over a window of RR intervals.
+pub trait HrvMetric: Send + Sync + Debug {
+    /// Unique name of the metric, used as key for its time series.
+    fn name(&self) -> &str;
+
+    /// Unit of the metric values used for display.
+    fn unit(&self) -> &str {
+        "ms"
+    }
+
+    /// One-line definition of the metric shown to the user.
+    fn description(&self) -> &str {
+        ""
+    }
+
+    /// Whether the metric is calculated from the detrended RR intervals, if enabled.
+    fn detrended(&self) -> bool {
+        true
+    }
+
+    /// Calculates the metric.
+    ///
+    /// # Arguments
+    /// * `rr` - Window of inlier RR intervals in ms.
+    ///
+    /// # Returns
+    /// The metric value or an error if it can not be calculated for the window.
+    fn compute(&self, rr: &[f64]) -> Result<f64>;
+
+    /// Calculates the metric from a window split at the removed beats.
+    ///
+    /// Used with `DifferenceMode::WithinRuns`. Metrics of successive differences override
+    /// this to leave out the differences spanning a removed beat, the default calculates the
+    /// metric from the concatenated runs.
+    ///
+    /// # Arguments
+    /// * `runs` - Runs of adjacent inlier RR intervals in ms, in recording order.
+    fn compute_runs(&self, runs: &[&[f64]]) -> Result<f64> {
+        self.compute(&runs.concat())
+    }
+}
+
+/// Pairing of the inlier RR intervals for the successive differences.
+///
+/// Tools differ in how they treat the beats removed by the outlier filter: pairing the
+/// remaining intervals across a removed beat adds differences that were never measured and
+/// overstates RMSSD and pNNx, leaving them out reduces the number of differences.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum DifferenceMode {
+    /// The inlier intervals are concatenated and paired across removed beats.
+    #[default]
+    AcrossOutliers,
+    /// Only adjacent inlier intervals are paired, differences spanning a removed beat are
+    /// left out.
+    WithinRuns,
+}
+
+impl DifferenceMode {
+    /// All modes in the order they are offered.
+    pub const ALL: [DifferenceMode; 2] = [Self::AcrossOutliers, Self::WithinRuns];
+}
+
+impl std::fmt::Display for DifferenceMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::AcrossOutliers => write!(f, "across removed beats"),
+            Self::WithinRuns => write!(f, "within inlier runs"),
+        }
+    }
+}
+
+/// Pools a deviation of the successive differences over runs of RR intervals.
+///
+/// The deviation of every run is weighted by its number of differences, the same as if it
+/// was calculated from the differences of all runs. Runs the deviation can not be
+/// calculated for, e.g. single intervals, are left out.
+///
+/// # Arguments
+/// * `runs` - Runs of adjacent RR intervals in ms.
+/// * `deviation` - The deviation of the successive differences of a single run.
+fn pool_runs(runs: &[&[f64]], deviation: impl Fn(&[f64]) -> Result<f64>) -> Result<f64> {
+    let (mut squares, mut count) = (0.0, 0);
+    for run in runs.iter().filter(|run| run.len() > 1) {
+        let Ok(value) = deviation(run) else {
+            continue;
+        };
+        squares += value * value * (run.len() - 1) as f64;
+        count += run.len() - 1;
+    }
+    if count == 0 {
+        return Err(anyhow!("at least two adjacent RR intervals are required"));
+    }
+    Ok((squares / count as f64).sqrt())
+}
+
+/// Returns the successive differences within each run of RR intervals.
+fn run_differences(runs: &[&[f64]]) -> Vec<f64> {
+    runs.iter()
+        .flat_map(|run| run.windows(2).map(|pair| pair[1] - pair[0]))
+        .collect()
+}
+
+/// Time series of a metric together with its display properties.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MetricSeries {
+    /// Name of the metric.
+    pub name: String,
+    /// Unit of the metric values.
+    pub unit: String,
+    /// `[time in s, value]` points.
+    pub time_series: Vec<[f64; 2]>,
+}
+
+impl MetricSeries {
+    /// Returns the most recent value of the metric.
+    pub fn last(&self) -> Option<f64> {
+        self.time_series.last().map(|v| v[1])
+    }
+}
+
+pub const RMSSD: &str = "RMSSD";
+pub const SDRR: &str = "SDRR";
+pub const SD1: &str = "SD1";
+pub const SD2: &str = "SD2";
+pub const HR: &str = "HR";
+pub const DFA_ALPHA: &str = "DFA 1 alpha";
+
+/// Root mean square of successive RR differences.
+#[derive(Debug, Default)]
+pub struct Rmssd;
+
+impl HrvMetric for Rmssd {
+    fn name(&self) -> &str {
+        RMSSD
+    }
+    fn description(&self) -> &str {
+        "root mean square of the successive RR differences, reflects the short-term vagal activity"
+    }
+    fn compute(&self, rr: &[f64]) -> Result<f64> {
+        calc_rmssd(rr)
+    }
+    fn compute_runs(&self, runs: &[&[f64]]) -> Result<f64> {
+        pool_runs(runs, calc_rmssd)
+    }
+}
+
+/// Standard deviation of the RR intervals.
+#[derive(Debug, Default)]
+pub struct Sdrr;
+
+impl HrvMetric for Sdrr {
+    fn name(&self) -> &str {
+        SDRR
+    }
+    fn description(&self) -> &str {
+        "standard deviation of the RR intervals, reflects the overall variability"
+    }
+    fn compute(&self, rr: &[f64]) -> Result<f64> {
+        calc_sdrr(rr)
+    }
+}
+
+/// Poincaré plot standard deviation perpendicular to the line of identity.
+#[derive(Debug, Default)]
+pub struct Sd1;
+
+impl HrvMetric for Sd1 {
+    fn name(&self) -> &str {
+        SD1
+    }
+    fn description(&self) -> &str {
+        "spread of the Poincaré plot perpendicular to the line of identity, the short-term variability"
+    }
+    fn compute(&self, rr: &[f64]) -> Result<f64> {
+        Ok(calc_poincare_metrics(rr)?.sd1)
+    }
+    fn compute_runs(&self, runs: &[&[f64]]) -> Result<f64> {
+        // SD1 is the deviation of the successive differences
+        pool_runs(runs, |run| self.compute(run))
+    }
+}
+
+/// Poincaré plot standard deviation along the line of identity.
+#[derive(Debug, Default)]
+pub struct Sd2;
+
+impl HrvMetric for Sd2 {
+    fn name(&self) -> &str {
+        SD2
+    }
+    fn description(&self) -> &str {
+        "spread of the Poincaré plot along the line of identity, the long-term variability"
+    }
+    fn compute(&self, rr: &[f64]) -> Result<f64> {
+        Ok(calc_poincare_metrics(rr)?.sd2)
+    }
+}
+
+/// Mean heart rate.
+#[derive(Debug, Default)]
+pub struct HeartRate;
+
+impl HrvMetric for HeartRate {
+    fn name(&self) -> &str {
+        HR
+    }
+    fn unit(&self) -> &str {
+        "1/min"
+    }
+    fn description(&self) -> &str {
+        "mean heart rate of the analysed RR intervals"
+    }
+    fn detrended(&self) -> bool {
+        // the detrending keeps the mean RR interval
+        false
+    }
+    fn compute(&self, rr: &[f64]) -> Result<f64> {
+        Ok(60000.0 * rr.len() as f64 / rr.iter().sum::<f64>())
+    }
+}
+
+/// Threshold of the pNNx calculated by default in ms.
+pub const PNN_DEFAULT_THRESHOLD: f64 = 50.0;
+
+/// Valid thresholds of the pNNx in ms.
+pub const PNN_THRESHOLD_RANGE: RangeInclusive<f64> = 1.0..=200.0;
+
+/// Returns the name of the pNNx with the given threshold, e.g. `pNN50`.
+pub fn pnnx_name(threshold: f64) -> String {
+    format!("pNN{}", threshold)
+}
+
+/// Calculates the percentage of successive RR differences exceeding a threshold.
+///
+/// # Arguments
+/// * `rr` - RR intervals in ms.
+/// * `threshold` - Threshold of the absolute successive differences in ms.
+///
+/// # Returns
+/// The percentage between `0` and `100`, or an error for less than two RR intervals.
+pub fn calc_pnnx(rr: &[f64], threshold: f64) -> Result<f64> {
+    if rr.len() < 2 {
+        return Err(anyhow!("pNNx requires at least two RR intervals"));
+    }
+    let exceeding = rr
+        .windows(2)
+        .filter(|pair| (pair[1] - pair[0]).abs() > threshold)
+        .count();
+    Ok(100.0 * exceeding as f64 / (rr.len() - 1) as f64)
+}
+
+/// Percentage of successive RR differences exceeding a threshold.
+#[derive(Debug)]
+pub struct Pnnx {
+    /// Threshold of the successive differences in ms.
+    threshold: f64,
+    /// Name of the metric including the threshold.
+    name: String,
+}
+
+impl Pnnx {
+    /// Creates the metric for the given threshold in ms.
+    pub fn new(threshold: f64) -> Self {
+        Self {
+            threshold,
+            name: pnnx_name(threshold),
+        }
+    }
+}
+
+impl Default for Pnnx {
+    fn default() -> Self {
+        Self::new(PNN_DEFAULT_THRESHOLD)
+    }
+}
+
+impl HrvMetric for Pnnx {
+    fn name(&self) -> &str {
+        &self.name
+    }
+    fn unit(&self) -> &str {
+        "%"
+    }
+    fn description(&self) -> &str {
+        "share of the successive RR differences exceeding the threshold"
+    }
+    fn compute(&self, rr: &[f64]) -> Result<f64> {
+        calc_pnnx(rr, self.threshold)
+    }
+    fn compute_runs(&self, runs: &[&[f64]]) -> Result<f64> {
+        let diffs = run_differences(runs);
+        if diffs.is_empty() {
+            return Err(anyhow!("pNNx requires at least two adjacent RR intervals"));
+        }
+        let exceeding = diffs.iter().filter(|d| d.abs() > self.threshold).count();
+        Ok(100.0 * exceeding as f64 / diffs.len() as f64)
+    }
+}
+
+/// Name of the detrending applied to the boxes of the DFA.
+pub const DFA_DETREND: &str = "linear";
+
+/// Valid orders of the polynomial removed from the RR intervals.
+pub const DETREND_ORDER_RANGE: RangeInclusive<usize> = 1..=3;
+
+/// Removal of slow trends from the RR intervals before the statistics are calculated.
+///
+/// The trend is removed from every statistics window, the mean of the window is kept so
+/// the heart rate is not affected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum DetrendOption {
+    /// The RR intervals are analysed as recorded.
+    #[default]
+    None,
+    /// A least squares polynomial of the given order is removed.
+    Polynomial(usize),
+}
+
+impl DetrendOption {
+    /// Checks that the order of the polynomial is supported.
+    pub fn validate(&self) -> Result<()> {
+        match self {
+            Self::Polynomial(order) if !DETREND_ORDER_RANGE.contains(order) => {
+                Err(anyhow!("invalid detrend order: {}", order))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Removes the trend from a window of RR intervals.
+    ///
+    /// # Returns
+    /// The detrended RR intervals, or an error if the window is too short for the fit.
+    pub fn apply<'a>(&self, rr: &'a [f64]) -> Result<Cow<'a, [f64]>> {
+        match self {
+            Self::None => Ok(Cow::Borrowed(rr)),
+            Self::Polynomial(order) => Ok(Cow::Owned(detrend_polynomial(rr, *order)?)),
+        }
+    }
+}
+
+impl std::fmt::Display for DetrendOption {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::None => write!(f, "none"),
+            Self::Polynomial(order) => write!(f, "polynomial, order {}", order),
+        }
+    }
+}
+
+/// Removes the least squares polynomial of `order` from `rr`, keeping its mean.
+///
+/// The beat index is scaled to `-1..=1` to keep the normal equations well conditioned.
+pub fn detrend_polynomial(rr: &[f64], order: usize) -> Result<Vec<f64>> {
+    if rr.len() <= order + 1 {
+        return Err(anyhow!("too few RR intervals to detrend"));
+    }
+    let n = order + 1;
+    let scale = 2.0 / (rr.len() - 1) as f64;
+    let t: Vec<f64> = (0..rr.len()).map(|idx| idx as f64 * scale - 1.0).collect();
+    // normal equations [A | b] of the fit
+    let mut system = vec![vec![0.0; n + 1]; n];
+    for (&t, &y) in t.iter().zip(rr) {
+        let powers: Vec<f64> = (0..n).map(|p| t.powi(p as i32)).collect();
+        for row in 0..n {
+            for col in 0..n {
+                system[row][col] += powers[row] * powers[col];
+            }
+            system[row][n] += powers[row] * y;
+        }
+    }
+    // gaussian elimination with partial pivoting
+    for col in 0..n {
+        let pivot = (col..n)
+            .max_by(|&a, &b| system[a][col].abs().total_cmp(&system[b][col].abs()))
+            .unwrap_or(col);
+        system.swap(col, pivot);
+        if system[col][col].abs() < f64::EPSILON {
+            return Err(anyhow!("singular detrend fit"));
+        }
+        let (upper, lower) = system.split_at_mut(col + 1);
+        let pivot_row = &upper[col];
+        for row in lower.iter_mut() {
+            let factor = row[col] / pivot_row[col];
+            for (value, pivot) in row[col..].iter_mut().zip(&pivot_row[col..]) {
+                *value -= factor * pivot;
+            }
+        }
+    }
+    let mut coeffs = vec![0.0; n];
+    for row in (0..n).rev() {
+        let sum: f64 = (row + 1..n).map(|idx| system[row][idx] * coeffs[idx]).sum();
+        coeffs[row] = (system[row][n] - sum) / system[row][row];
+    }
+    let mean = rr.iter().sum::<f64>() / rr.len() as f64;
+    Ok(t.iter()
+        .zip(rr)
+        .map(|(&t, &y)| {
+            let trend: f64 = coeffs
+                .iter()
+                .enumerate()
+                .map(|(p, c)| c * t.powi(p as i32))
+                .sum();
+            y - trend + mean
+        })
+        .collect())
+}
+
+/// Number of boxes of the largest size the RR series must span before the DFA is reported.
+const DFA_WARMUP_BOXES: usize = 4;
+
+/// Box sizes of the detrended fluctuation analysis.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DfaConfig {
+    /// Smallest box size in beats.
+    pub min_box: usize,
+    /// Largest box size in beats.
+    pub max_box: usize,
+}
+
+impl Default for DfaConfig {
+    fn default() -> Self {
+        Self {
+            min_box: 4,
+            max_box: 16,
+        }
+    }
+}
+
+impl DfaConfig {
+    /// Checks that the box sizes allow a fit of the fluctuation.
+    pub fn validate(&self) -> Result<()> {
+        if self.min_box < 4 || self.max_box <= self.min_box {
+            return Err(anyhow!(
+                "invalid DFA box sizes: {} - {}",
+                self.min_box,
+                self.max_box
+            ));
+        }
+        Ok(())
+    }
+
+    /// Returns the number of beats required before the DFA alpha is meaningful.
+    pub fn min_beats(&self) -> usize {
+        self.max_box * DFA_WARMUP_BOXES
+    }
+
+    /// Returns all box sizes of the analysis.
+    pub fn box_sizes(&self) -> Vec<usize> {
+        (self.min_box..=self.max_box).collect()
+    }
+}
+
+/// Short term scaling exponent of the detrended fluctuation analysis.
+#[derive(Debug, Default)]
+pub struct DfaAlpha {
+    /// Box sizes of the analysis.
+    config: DfaConfig,
+}
+
+impl DfaAlpha {
+    /// Creates the metric for the given box sizes.
+    pub fn new(config: DfaConfig) -> Self {
+        Self { config }
+    }
+}
+
+impl HrvMetric for DfaAlpha {
+    fn name(&self) -> &str {
+        DFA_ALPHA
+    }
+    fn unit(&self) -> &str {
+        ""
+    }
+    fn description(&self) -> &str {
+        "short-term scaling exponent of the detrended fluctuation analysis"
+    }
+    fn detrended(&self) -> bool {
+        // the DFA removes the trend of every box itself
+        false
+    }
+    fn compute(&self, rr: &[f64]) -> Result<f64> {
+        let dfa = DFAnalysis::udfa(rr, &self.config.box_sizes(), DetrendStrategy::Linear)?;
+        Ok(dfa.alpha)
+    }
+}
+
+/// Returns the default set of metrics.
+pub fn default_metrics() -> Vec<Arc<dyn HrvMetric>> {
+    vec![
+        Arc::new(Rmssd),
+        Arc::new(Sdrr),
+        Arc::new(Sd1),
+        Arc::new(Sd2),
+        Arc::new(HeartRate),
+        Arc::new(DfaAlpha::default()),
+        Arc::new(Pnnx::default()),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_metric_names_unique() {
+        let metrics = default_metrics();
+        let mut names: Vec<&str> = metrics.iter().map(|m| m.name()).collect();
+        names.sort();
+        names.dedup();
+        assert_eq!(names.len(), metrics.len());
+    }
+
+    #[test]
+    fn test_compute_runs() {
+        let rr = [
+            800.0, 830.0, 790.0, 810.0, 1500.0, 820.0, 780.0, 800.0, 840.0,
+        ];
+        let runs: [&[f64]; 2] = [&rr[..4], &rr[5..]];
+        let diffs = run_differences(&runs);
+        let rmssd = (diffs.iter().map(|d| d * d).sum::<f64>() / diffs.len() as f64).sqrt();
+        assert!((Rmssd.compute_runs(&runs).unwrap() - rmssd).abs() < 1e-9);
+        // a single run equals the metric of the run
+        assert!(
+            (Rmssd.compute_runs(&runs[..1]).unwrap() - calc_rmssd(&rr[..4]).unwrap()).abs() < 1e-9
+        );
+        assert!(
+            (Sd1.compute_runs(&runs[..1]).unwrap() - Sd1.compute(&rr[..4]).unwrap()).abs() < 1e-9
+        );
+        // the differences spanning the removed beat are left out
+        let concatenated = [runs[0], runs[1]].concat();
+        assert!(
+            (Sd1.compute_runs(&runs).unwrap() - Sd1.compute(&concatenated).unwrap()).abs() > 1e-6
+        );
+        assert!(Sd1.compute_runs(&[&rr[..1], &rr[4..5]]).is_err());
+    }
+
+    #[test]
+    fn test_detrend_polynomial() {
+        let line: Vec<f64> = (0..50).map(|idx| 700.0 + 4.0 * idx as f64).collect();
+        let mean = line.iter().sum::<f64>() / line.len() as f64;
+        let detrended = detrend_polynomial(&line, 1).unwrap();
+        assert!(detrended.iter().all(|rr| (rr - mean).abs() < 1e-6));
+        let parabola: Vec<f64> = (0..50).map(|idx| 800.0 + (idx * idx) as f64).collect();
+        let detrended = DetrendOption::Polynomial(2).apply(&parabola).unwrap();
+        assert!(calc_sdrr(&detrended).unwrap() < 1e-6);
+        assert_eq!(
+            DetrendOption::None.apply(&parabola).unwrap().as_ref(),
+            parabola.as_slice()
+        );
+        assert!(detrend_polynomial(&line[..2], 1).is_err());
+        assert!(DetrendOption::Polynomial(0).validate().is_err());
+        assert!(DetrendOption::Polynomial(3).validate().is_ok());
+    }
+
+    #[test]
+    fn test_dfa_config() {
+        let config = DfaConfig::default();
+        assert!(config.validate().is_ok());
+        assert_eq!(config.box_sizes().len(), 13);
+        assert_eq!(config.min_beats(), 64);
+        let invalid = DfaConfig {
+            min_box: 8,
+            max_box: 8,
+        };
+        assert!(invalid.validate().is_err());
+    }
+
+    #[test]
+    fn test_calc_pnnx() {
+        // successive differences: 60, 20, 35, 45
+        let rr = [800.0, 860.0, 840.0, 875.0, 830.0];
+        assert_eq!(calc_pnnx(&rr, 50.0).unwrap(), 25.0);
+        assert_eq!(calc_pnnx(&rr, 30.0).unwrap(), 75.0);
+        assert!(calc_pnnx(&rr[..1], 50.0).is_err());
+
+        let metric = Pnnx::new(30.0);
+        assert_eq!(metric.name(), "pNN30");
+        assert_eq!(metric.compute(&rr).unwrap(), 75.0);
+        assert_eq!(Pnnx::default().name(), "pNN50");
+        assert_eq!(pnnx_name(12.5), "pNN12.5");
+    }
+
+    #[test]
+    fn test_heart_rate() {
+        assert_eq!(HeartRate.compute(&[1000.0, 1000.0]).unwrap(), 60.0);
+    }
+}
@@ -0,0 +1,91 @@
+//! File Operations
+//!
+//! This module tracks the long running file operation of the storage, so the views can show
+//! it and cancel it without waiting for the storage to become available.
+
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Mutex,
+};
+
+use anyhow::{anyhow, Result};
+
+/// State of the running file operation, shared between the storage and the views.
+#[derive(Debug, Default)]
+pub struct FileOperation {
+    /// Description of the running operation, `None` if no operation is running.
+    running: Mutex<Option<String>>,
+    /// Whether cancelling the running operation was requested.
+    cancelled: AtomicBool,
+}
+
+/// Marks an operation as running until it is dropped.
+pub struct OperationGuard<'a> {
+    operation: &'a FileOperation,
+}
+
+impl Drop for OperationGuard<'_> {
+    fn drop(&mut self) {
+        if let Ok(mut running) = self.operation.running.lock() {
+            *running = None;
+        }
+    }
+}
+
+impl FileOperation {
+    /// Marks an operation as running, a previous cancel request is discarded.
+    ///
+    /// # Arguments
+    /// * `description` - Description of the operation shown to the user.
+    ///
+    /// # Returns
+    /// A guard that marks the operation as finished when it is dropped.
+    pub fn start(&self, description: impl Into<String>) -> OperationGuard<'_> {
+        self.cancelled.store(false, Ordering::SeqCst);
+        if let Ok(mut running) = self.running.lock() {
+            *running = Some(description.into());
+        }
+        OperationGuard { operation: self }
+    }
+
+    /// Returns the description of the running operation, if any.
+    pub fn running(&self) -> Option<String> {
+        self.running.lock().ok().and_then(|running| running.clone())
+    }
+
+    /// Requests cancelling the running operation.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+    }
+
+    /// Returns an error if cancelling the running operation was requested.
+    pub fn check(&self) -> Result<()> {
+        if self.cancelled.load(Ordering::SeqCst) {
+            Err(anyhow!("operation cancelled"))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_file_operation() {
+        let operation = FileOperation::default();
+        assert_eq!(operation.running(), None);
+        {
+            let _guard = operation.start("Loading");
+            assert_eq!(operation.running().as_deref(), Some("Loading"));
+            assert!(operation.check().is_ok());
+            operation.cancel();
+            assert!(operation.check().is_err());
+        }
+        assert_eq!(operation.running(), None);
+        // a new operation is not affected by a previous cancel request
+        let _guard = operation.start("Saving");
+        assert!(operation.check().is_ok());
+    }
+}
@@ -0,0 +1,136 @@
+//! Readiness Score
+//!
+//! This module maps the RMSSD of a recording to a daily readiness score. The natural logarithm
+//! of the RMSSD is scaled linearly between the bounds of the personal baseline, so the score
+//! is `0` at the lower and `100` at the upper bound.
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+
+/// Scores below this value are in the `Low` band.
+const LOW_BAND_LIMIT: f64 = 33.0;
+/// Scores below this value are in the `Moderate` band.
+const MODERATE_BAND_LIMIT: f64 = 66.0;
+
+/// Personal range of the RMSSD the readiness is scored against.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReadinessBaseline {
+    /// RMSSD in ms scored as `0`.
+    pub low: f64,
+    /// RMSSD in ms scored as `100`.
+    pub high: f64,
+}
+
+impl Default for ReadinessBaseline {
+    fn default() -> Self {
+        Self {
+            low: 20.0,
+            high: 100.0,
+        }
+    }
+}
+
+impl ReadinessBaseline {
+    /// Checks that the bounds are positive and ordered.
+    pub fn validate(&self) -> Result<()> {
+        if self.low > 0.0 && self.low < self.high {
+            Ok(())
+        } else {
+            Err(anyhow!(
+                "invalid readiness baseline: {} - {} ms",
+                self.low,
+                self.high
+            ))
+        }
+    }
+
+    /// Creates the baseline spanned by the RMSSD values of previous recordings.
+    ///
+    /// # Arguments
+    /// * `rmssd` - RMSSD values in ms of the previous recordings.
+    ///
+    /// # Returns
+    /// `None` if the values do not span a valid range.
+    pub fn from_history(rmssd: &[f64]) -> Option<Self> {
+        let baseline = Self {
+            low: rmssd.iter().copied().fold(f64::INFINITY, f64::min),
+            high: rmssd.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+        };
+        baseline.validate().ok().map(|_| baseline)
+    }
+
+    /// Scores an RMSSD against the baseline.
+    ///
+    /// # Arguments
+    /// * `rmssd` - The RMSSD in ms.
+    ///
+    /// # Returns
+    /// The readiness between `0` and `100`, `None` for an invalid baseline or RMSSD.
+    pub fn score(&self, rmssd: f64) -> Option<f64> {
+        if self.validate().is_err() || rmssd <= 0.0 || !rmssd.is_finite() {
+            return None;
+        }
+        let (low, high) = (self.low.ln(), self.high.ln());
+        Some((100.0 * (rmssd.ln() - low) / (high - low)).clamp(0.0, 100.0))
+    }
+}
+
+/// Color band of a readiness score.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReadinessBand {
+    Low,
+    Moderate,
+    High,
+}
+
+impl ReadinessBand {
+    /// Returns the band of a readiness score.
+    pub fn of(score: f64) -> Self {
+        if score < LOW_BAND_LIMIT {
+            Self::Low
+        } else if score < MODERATE_BAND_LIMIT {
+            Self::Moderate
+        } else {
+            Self::High
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_readiness_score() {
+        let baseline = ReadinessBaseline {
+            low: 20.0,
+            high: 80.0,
+        };
+        // ln(40) lies halfway between ln(20) and ln(80)
+        assert!((baseline.score(40.0).unwrap() - 50.0).abs() < 1e-9);
+        assert_eq!(baseline.score(20.0), Some(0.0));
+        assert_eq!(baseline.score(10.0), Some(0.0));
+        assert_eq!(baseline.score(200.0), Some(100.0));
+        assert_eq!(baseline.score(0.0), None);
+        assert_eq!(ReadinessBand::of(50.0), ReadinessBand::Moderate);
+        assert_eq!(ReadinessBand::of(10.0), ReadinessBand::Low);
+        assert_eq!(ReadinessBand::of(90.0), ReadinessBand::High);
+
+        let invalid = ReadinessBaseline {
+            low: 50.0,
+            high: 50.0,
+        };
+        assert!(invalid.validate().is_err());
+        assert_eq!(invalid.score(50.0), None);
+
+        assert_eq!(
+            ReadinessBaseline::from_history(&[45.0, 30.0, 60.0]),
+            Some(ReadinessBaseline {
+                low: 30.0,
+                high: 60.0
+            })
+        );
+        assert_eq!(ReadinessBaseline::from_history(&[45.0]), None);
+        assert_eq!(ReadinessBaseline::from_history(&[]), None);
+    }
+}
@@ -0,0 +1,91 @@
+//! Replay Model
+//!
+//! This module schedules the messages of a stored measurement to replay them as if they
+//! arrived from the sensor, e.g. for demonstrations and teaching. The replay speed only
+//! shortens or stretches the pauses between the messages, the RR intervals are replayed as
+//! recorded.
+
+use std::ops::RangeInclusive;
+
+use anyhow::{anyhow, Result};
+use time::Duration;
+
+use crate::model::bluetooth::HeartrateMessage;
+
+/// Valid replay speeds as multiple of real time.
+pub const REPLAY_SPEED_RANGE: RangeInclusive<f64> = 0.1..=20.0;
+
+/// Checks that messages can be replayed at `speed`.
+///
+/// # Arguments
+/// * `messages` - The messages of the measurement to replay.
+/// * `speed` - Replay speed as multiple of real time.
+pub fn validate(messages: &[(Duration, HeartrateMessage)], speed: f64) -> Result<()> {
+    if !REPLAY_SPEED_RANGE.contains(&speed) {
+        return Err(anyhow!("invalid replay speed: {}", speed));
+    }
+    if messages.is_empty() {
+        return Err(anyhow!("measurement has no messages to replay"));
+    }
+    Ok(())
+}
+
+/// Schedules messages for replay.
+///
+/// # Arguments
+/// * `messages` - The messages with their elapsed time since the start of the recording.
+/// * `speed` - Replay speed as multiple of real time.
+///
+/// # Returns
+/// The messages in timestamp order, each with the pause preceding it, or an error if they
+/// can not be replayed, see `validate`.
+pub fn schedule(
+    messages: &[(Duration, HeartrateMessage)],
+    speed: f64,
+) -> Result<Vec<(std::time::Duration, HeartrateMessage)>> {
+    validate(messages, speed)?;
+    let mut messages = messages.to_vec();
+    messages.sort_by_key(|(elapsed, _)| *elapsed);
+    let mut previous = Duration::ZERO;
+    Ok(messages
+        .into_iter()
+        .map(|(elapsed, msg)| {
+            let pause = (elapsed - previous).as_seconds_f64().max(0.0) / speed;
+            previous = elapsed;
+            (std::time::Duration::from_secs_f64(pause), msg)
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_schedule() {
+        let messages = [
+            (
+                Duration::milliseconds(1800),
+                HeartrateMessage::from_values(62, None, &[1000]),
+            ),
+            (
+                Duration::milliseconds(800),
+                HeartrateMessage::from_values(60, None, &[800]),
+            ),
+            (
+                Duration::milliseconds(2600),
+                HeartrateMessage::from_values(64, None, &[800]),
+            ),
+        ];
+        let scheduled = schedule(&messages, 2.0).unwrap();
+        for ((pause, _), expected) in scheduled.iter().zip([0.4, 0.5, 0.4]) {
+            assert!((pause.as_secs_f64() - expected).abs() < 1e-6, "{:?}", pause);
+        }
+        let hr: Vec<f64> = scheduled.iter().map(|(_, msg)| msg.get_hr()).collect();
+        assert_eq!(hr, [60.0, 62.0, 64.0]);
+
+        assert!(schedule(&messages, 0.0).is_err());
+        assert!(schedule(&messages, 20.5).is_err());
+        assert!(schedule(&[], 1.0).is_err());
+    }
+}
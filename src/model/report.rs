@@ -0,0 +1,247 @@
+//! HTML Report
+//!
+//! This module writes a measurement as a single self-contained HTML file. The plots are
+//! rendered as PNG images and embedded as base64 data URIs, so the report opens in any
+//! browser without further files.
+
+use std::io::Cursor;
+
+use anyhow::{anyhow, Result};
+use image::{ImageFormat, Rgb, RgbImage};
+use time::{macros::format_description, OffsetDateTime};
+
+use super::{hrv::HrvSummary, metadata::MeasurementMetadata};
+
+/// Size of the rendered plots in pixels.
+pub const PLOT_SIZE: (u32, u32) = (800, 240);
+
+/// Space between the border of a plot image and its frame in pixels.
+const PLOT_MARGIN: u32 = 10;
+
+const BACKGROUND: Rgb<u8> = Rgb([255, 255, 255]);
+const FRAME: Rgb<u8> = Rgb([160, 160, 160]);
+const LINE: Rgb<u8> = Rgb([30, 90, 200]);
+
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Encodes `data` as base64 with padding.
+pub fn base64(data: &[u8]) -> String {
+    let mut encoded = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let bytes = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let triple = u32::from_be_bytes([0, bytes[0], bytes[1], bytes[2]]);
+        for idx in 0..4 {
+            if idx <= chunk.len() {
+                let sextet = (triple >> (18 - 6 * idx)) & 0x3f;
+                encoded.push(BASE64_ALPHABET[sextet as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+/// Escapes the characters of `text` with a special meaning in HTML.
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Draws a straight line between two pixels.
+fn draw_line(image: &mut RgbImage, from: (i64, i64), to: (i64, i64), color: Rgb<u8>) {
+    let steps = (to.0 - from.0).abs().max((to.1 - from.1).abs()).max(1);
+    for step in 0..=steps {
+        let x = from.0 + (to.0 - from.0) * step / steps;
+        let y = from.1 + (to.1 - from.1) * step / steps;
+        if (0..image.width() as i64).contains(&x) && (0..image.height() as i64).contains(&y) {
+            image.put_pixel(x as u32, y as u32, color);
+        }
+    }
+}
+
+/// Renders a time series as line plot.
+///
+/// # Arguments
+/// * `points` - `[x, y]` points of the series, sorted by `x`.
+/// * `size` - Width and height of the image in pixels.
+///
+/// # Returns
+/// The PNG encoded image, or an error if the series has less than two finite points.
+pub fn render_plot_png(points: &[[f64; 2]], size: (u32, u32)) -> Result<Vec<u8>> {
+    let points: Vec<[f64; 2]> = points
+        .iter()
+        .copied()
+        .filter(|[x, y]| x.is_finite() && y.is_finite())
+        .collect();
+    if points.len() < 2 {
+        return Err(anyhow!("not enough points to plot"));
+    }
+    let (width, height) = size;
+    if width <= 2 * PLOT_MARGIN || height <= 2 * PLOT_MARGIN {
+        return Err(anyhow!("invalid plot size: {}x{}", width, height));
+    }
+    let bounds = |axis: usize| {
+        points
+            .iter()
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), p| {
+                (lo.min(p[axis]), hi.max(p[axis]))
+            })
+    };
+    let ((x_min, x_max), (y_min, y_max)) = (bounds(0), bounds(1));
+    let (left, top) = (PLOT_MARGIN as f64, PLOT_MARGIN as f64);
+    let (right, bottom) = ((width - PLOT_MARGIN) as f64, (height - PLOT_MARGIN) as f64);
+    // constant series are drawn in the middle of the plot
+    let scale = |value: f64, min: f64, max: f64| {
+        if max > min {
+            (value - min) / (max - min)
+        } else {
+            0.5
+        }
+    };
+    let to_pixel = |[x, y]: [f64; 2]| {
+        (
+            (left + scale(x, x_min, x_max) * (right - left)).round() as i64,
+            (bottom - scale(y, y_min, y_max) * (bottom - top)).round() as i64,
+        )
+    };
+
+    let mut image = RgbImage::from_pixel(width, height, BACKGROUND);
+    let corners = [
+        (left as i64, top as i64),
+        (right as i64, top as i64),
+        (right as i64, bottom as i64),
+        (left as i64, bottom as i64),
+    ];
+    for (idx, corner) in corners.iter().enumerate() {
+        draw_line(&mut image, *corner, corners[(idx + 1) % 4], FRAME);
+    }
+    for pair in points.windows(2) {
+        draw_line(&mut image, to_pixel(pair[0]), to_pixel(pair[1]), LINE);
+    }
+
+    let mut png = Vec::new();
+    image.write_to(&mut Cursor::new(&mut png), ImageFormat::Png)?;
+    Ok(png)
+}
+
+/// Writes the report of a measurement.
+///
+/// # Arguments
+/// * `start_time` - Start time of the measurement.
+/// * `summary` - Summary of the HRV statistics of the measurement.
+/// * `metadata` - Subject information of the measurement, if entered.
+/// * `plots` - `(title, PNG image)` pairs of the plots to embed.
+///
+/// # Returns
+/// The HTML document.
+pub fn html_report(
+    start_time: &OffsetDateTime,
+    summary: &HrvSummary,
+    metadata: Option<&MeasurementMetadata>,
+    plots: &[(&str, Vec<u8>)],
+) -> Result<String> {
+    let date = start_time.format(format_description!(
+        "[year]-[month]-[day] [hour]:[minute]:[second]"
+    ))?;
+    let optional = |val: Option<f64>, unit: &str| {
+        val.map_or("–".to_owned(), |val| format!("{:.2} {}", val, unit))
+    };
+    let rows = [
+        ("RMSSD", optional(summary.rmssd, "ms")),
+        ("SDRR", optional(summary.sdrr, "ms")),
+        ("SD1", optional(summary.sd1, "ms")),
+        ("SD2", optional(summary.sd2, "ms")),
+        ("HR", optional(summary.hr, "1/min")),
+        ("DFA 1 alpha", optional(summary.dfa_alpha, "")),
+        ("SDANN", optional(summary.sdann, "ms")),
+        ("SDNN index", optional(summary.sdnn_index, "ms")),
+        ("mean RR", optional(summary.mean_rr, "ms")),
+        ("RR intervals", summary.rr_count.to_string()),
+        (
+            "artifacts",
+            format!("{:.1} %", 100.0 * summary.artifact_ratio),
+        ),
+        ("dropped events", summary.dropped_events.to_string()),
+        (
+            "duration",
+            format!("{:.0} s", summary.duration.as_seconds_f64()),
+        ),
+    ];
+
+    let mut html = String::new();
+    html.push_str("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n");
+    html.push_str(&format!(
+        "<title>HRV report {}</title>\n",
+        escape_html(&date)
+    ));
+    html.push_str(
+        "<style>body{font-family:sans-serif;margin:2em}\
+         table{border-collapse:collapse}\
+         td{border:1px solid #ccc;padding:4px 12px}\
+         img{max-width:100%}</style>\n</head>\n<body>\n",
+    );
+    html.push_str(&format!("<h1>HRV report {}</h1>\n", escape_html(&date)));
+    if let Some(metadata) = metadata.filter(|metadata| !metadata.is_empty()) {
+        html.push_str("<h2>Subject</h2>\n<table>\n");
+        for (field, value) in metadata.fields() {
+            html.push_str(&format!(
+                "<tr><td>{}</td><td>{}</td></tr>\n",
+                field.replace('_', " "),
+                escape_html(&value)
+            ));
+        }
+        html.push_str("</table>\n<h2>Statistics</h2>\n");
+    }
+    html.push_str("<table>\n");
+    for (name, value) in rows {
+        html.push_str(&format!(
+            "<tr><td>{}</td><td>{}</td></tr>\n",
+            name,
+            escape_html(value.trim_end())
+        ));
+    }
+    html.push_str("</table>\n");
+    for (title, png) in plots {
+        html.push_str(&format!(
+            "<h2>{}</h2>\n<img alt=\"{}\" src=\"data:image/png;base64,{}\">\n",
+            escape_html(title),
+            escape_html(title),
+            base64(png)
+        ));
+    }
+    html.push_str("</body>\n</html>\n");
+    Ok(html)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_base64() {
+        assert_eq!(base64(b""), "");
+        assert_eq!(base64(b"f"), "Zg==");
+        assert_eq!(base64(b"fo"), "Zm8=");
+        assert_eq!(base64(b"foo"), "Zm9v");
+        assert_eq!(base64(b"foobar"), "Zm9vYmFy");
+    }
+
+    #[test]
+    fn test_render_plot_png() {
+        let png = render_plot_png(&[[0.0, 1.0], [1.0, 3.0], [2.0, 2.0]], PLOT_SIZE).unwrap();
+        assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+        // constant series are valid
+        assert!(render_plot_png(&[[0.0, 1.0], [1.0, 1.0]], PLOT_SIZE).is_ok());
+        assert!(render_plot_png(&[[0.0, 1.0]], PLOT_SIZE).is_err());
+        assert!(render_plot_png(&[[0.0, 1.0], [1.0, f64::NAN]], PLOT_SIZE).is_err());
+        assert!(render_plot_png(&[[0.0, 1.0], [1.0, 2.0]], (5, 5)).is_err());
+    }
+}
@@ -0,0 +1,1102 @@
+//! Settings Model
+//!
+//! This module defines the user configurable settings of the application.
+
+use super::{
+    alert::HrAlert,
+    bluetooth::AdapterDescriptor,
+    hrv::{HrvConfig, DEFAULT_STABILIZATION_THRESHOLD, STABILIZATION_THRESHOLD_RANGE},
+    import::RrUnit,
+    readiness::ReadinessBaseline,
+};
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fmt,
+    io::ErrorKind,
+    ops::RangeInclusive,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+use time::{format_description, OffsetDateTime, UtcOffset};
+
+/// Default format of displayed dates.
+pub const DEFAULT_DATE_FORMAT: &str = "[year]-[month]-[day] [hour]:[minute]";
+
+/// Default number of decimals of displayed metrics.
+pub const DEFAULT_PRECISION: usize = 2;
+
+/// Supported numbers of decimals of displayed metrics.
+pub const PRECISION_RANGE: RangeInclusive<usize> = 0..=4;
+
+/// Default scaling factor of the user interface.
+pub const DEFAULT_UI_SCALE: f32 = 1.5;
+
+/// Supported scaling factors of the user interface.
+pub const UI_SCALE_RANGE: RangeInclusive<f32> = 0.5..=4.0;
+
+/// Supported weights of the previous average when smoothing the live readout.
+pub const SMOOTHING_RANGE: RangeInclusive<f64> = 0.0..=0.95;
+
+/// Default time between two refreshes of the device list in seconds.
+pub const DEFAULT_SCAN_INTERVAL: f64 = 2.0;
+
+/// Supported times between two refreshes of the device list in seconds.
+pub const SCAN_INTERVAL_RANGE: RangeInclusive<f64> = 1.0..=30.0;
+
+/// Default number of attempts to reconnect a lost sensor.
+pub const DEFAULT_RECONNECT_ATTEMPTS: usize = 15;
+
+/// Supported numbers of attempts to reconnect a lost sensor.
+pub const RECONNECT_ATTEMPTS_RANGE: RangeInclusive<usize> = 1..=100;
+
+/// Default minimum time between two repaints while receiving data in ms.
+pub const DEFAULT_REPAINT_INTERVAL: u64 = 50;
+
+/// Supported minimum times between two repaints in ms, `0` repaints on every event.
+pub const REPAINT_INTERVAL_RANGE: RangeInclusive<u64> = 0..=1000;
+
+/// Default time the analysis parameters have to settle before the statistics are recalculated in ms.
+pub const DEFAULT_RECOMPUTE_DEBOUNCE: u64 = 150;
+
+/// Supported settling times of the analysis parameters in ms, `0` recalculates on every change.
+pub const RECOMPUTE_DEBOUNCE_RANGE: RangeInclusive<u64> = 0..=1000;
+
+/// Supported numbers of DFA windows the plotted DFA alpha is smoothed over.
+pub const DFA_SMOOTHING_RANGE: RangeInclusive<usize> = 1..=15;
+
+/// Supported maximum heart rates in 1/min.
+pub const MAX_HR_RANGE: RangeInclusive<f64> = 100.0..=250.0;
+
+/// Prefix of the file names of auto-saved recordings.
+pub const AUTO_SAVE_PREFIX: &str = "hrv_";
+
+/// Supported numbers of kept auto-saved recordings.
+pub const RETENTION_FILES_RANGE: RangeInclusive<usize> = 1..=10000;
+
+/// Supported ages in days of kept auto-saved recordings.
+pub const RETENTION_DAYS_RANGE: RangeInclusive<u64> = 1..=3650;
+
+/// Default minimum number of inlier beats of a recording before it can be saved.
+pub const DEFAULT_MIN_SAVE_BEATS: usize = 30;
+
+/// Supported minimum numbers of inlier beats of a recording before it can be saved.
+pub const MIN_SAVE_BEATS_RANGE: RangeInclusive<usize> = 0..=1000;
+
+/// Supported countdowns in seconds before a recording starts automatically.
+pub const AUTO_START_RANGE: RangeInclusive<f64> = 0.0..=60.0;
+
+/// Supported periods of the automatic markers in seconds.
+pub const MARKER_PERIOD_RANGE: RangeInclusive<u64> = 5..=3600;
+
+/// Supported numbers of beats between two automatic markers.
+pub const MARKER_BEATS_RANGE: RangeInclusive<usize> = 10..=10000;
+
+/// Maximum length of the alias of a Bluetooth adapter.
+pub const MAX_ADAPTER_ALIAS_LEN: usize = 32;
+
+/// Name of the settings file in the configuration directory.
+const SETTINGS_FILE: &str = "hrv-rs/settings.json";
+
+/// Settings controlling how data is presented.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DisplaySettings {
+    /// Format description of displayed dates, see `time::format_description`.
+    date_format: String,
+    /// Offset displayed times are converted to. Timestamps are stored in UTC.
+    ///
+    /// The offset is determined at startup and not persisted.
+    #[serde(skip, default = "utc")]
+    utc_offset: UtcOffset,
+    /// Number of decimals of displayed metrics.
+    precision: usize,
+}
+
+fn utc() -> UtcOffset {
+    UtcOffset::UTC
+}
+
+impl Default for DisplaySettings {
+    fn default() -> Self {
+        Self {
+            date_format: DEFAULT_DATE_FORMAT.to_owned(),
+            utc_offset: UtcOffset::UTC,
+            precision: DEFAULT_PRECISION,
+        }
+    }
+}
+
+impl DisplaySettings {
+    /// Returns the format description of displayed dates.
+    pub fn get_date_format(&self) -> &str {
+        &self.date_format
+    }
+
+    /// Sets the format description of displayed dates.
+    ///
+    /// # Returns
+    /// An error if `format` is not a valid format description.
+    pub fn set_date_format(&mut self, format: String) -> Result<()> {
+        format_description::parse(&format)?;
+        self.date_format = format;
+        Ok(())
+    }
+
+    /// Returns the offset displayed times are converted to.
+    #[allow(dead_code)]
+    pub fn get_utc_offset(&self) -> UtcOffset {
+        self.utc_offset
+    }
+
+    /// Sets the offset displayed times are converted to.
+    pub fn set_utc_offset(&mut self, offset: UtcOffset) {
+        self.utc_offset = offset;
+    }
+
+    /// Returns the number of decimals of displayed metrics.
+    pub fn get_precision(&self) -> usize {
+        self.precision
+    }
+
+    /// Sets the number of decimals of displayed metrics.
+    ///
+    /// # Returns
+    /// An error if `precision` is outside of `PRECISION_RANGE`.
+    pub fn set_precision(&mut self, precision: usize) -> Result<()> {
+        if !PRECISION_RANGE.contains(&precision) {
+            return Err(anyhow::anyhow!("invalid precision: {}", precision));
+        }
+        self.precision = precision;
+        Ok(())
+    }
+
+    /// Formats a timestamp in the configured offset and date format.
+    pub fn format_time(&self, time: &OffsetDateTime) -> Result<String> {
+        let format = format_description::parse(&self.date_format)?;
+        Ok(time.to_offset(self.utc_offset).format(format.as_slice())?)
+    }
+}
+
+/// Marker shapes of plotted points.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MarkerStyle {
+    Circle,
+    Diamond,
+    Square,
+    Cross,
+}
+
+impl MarkerStyle {
+    /// All marker shapes in the order they are offered.
+    pub const ALL: [MarkerStyle; 4] = [Self::Circle, Self::Diamond, Self::Square, Self::Cross];
+}
+
+/// Rendering of the Poincaré points.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PoincareMode {
+    /// Every pair of RR intervals is drawn as a point.
+    #[default]
+    Scatter,
+    /// The inlier pairs are binned and the bins are shaded by their number of pairs.
+    Density,
+}
+
+impl PoincareMode {
+    /// All rendering modes in the order they are offered.
+    pub const ALL: [PoincareMode; 2] = [Self::Scatter, Self::Density];
+}
+
+impl std::fmt::Display for PoincareMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Scatter => write!(f, "scatter"),
+            Self::Density => write!(f, "density"),
+        }
+    }
+}
+
+/// Appearance of the Poincaré plot.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PoincareStyle {
+    /// RGB color of the inlier points.
+    pub inlier_color: [u8; 3],
+    /// RGB color of the outlier points.
+    pub outlier_color: [u8; 3],
+    /// Marker shape of the points.
+    pub marker: MarkerStyle,
+    /// Whether the SD1/SD2 ellipse is drawn.
+    pub show_ellipse: bool,
+    /// Rendering of the points, a density map keeps long recordings readable.
+    pub mode: PoincareMode,
+}
+
+impl Default for PoincareStyle {
+    fn default() -> Self {
+        Self {
+            inlier_color: [255, 0, 0],
+            outlier_color: [160, 160, 160],
+            marker: MarkerStyle::Diamond,
+            show_ellipse: true,
+            mode: PoincareMode::Scatter,
+        }
+    }
+}
+
+/// Built-in colors of the plots.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ColorPalette {
+    /// The original colors.
+    #[default]
+    Standard,
+    /// The Okabe-Ito colors, distinguishable with the common color vision deficiencies.
+    ColorBlindSafe,
+}
+
+impl ColorPalette {
+    /// All palettes in the order they are offered.
+    pub const ALL: [ColorPalette; 2] = [Self::Standard, Self::ColorBlindSafe];
+
+    /// Returns the RGB colors of the metric time series, in registration order.
+    pub fn metric_colors(&self) -> &'static [[u8; 3]] {
+        match self {
+            ColorPalette::Standard => &[
+                [255, 0, 0],
+                [0, 100, 0],
+                [0, 0, 255],
+                [255, 255, 0],
+                [0, 255, 0],
+                [240, 230, 140],
+            ],
+            ColorPalette::ColorBlindSafe => &[
+                [213, 94, 0],
+                [0, 158, 115],
+                [0, 114, 178],
+                [230, 159, 0],
+                [86, 180, 233],
+                [204, 121, 167],
+            ],
+        }
+    }
+
+    /// Returns the RGB color of the metric at `idx`, the colors repeat after the last one.
+    pub fn metric_color(&self, idx: usize) -> [u8; 3] {
+        let colors = self.metric_colors();
+        colors[idx % colors.len()]
+    }
+
+    /// Resolves the inlier and outlier colors of the Poincaré plot.
+    ///
+    /// Colors left at their default are taken from the palette, custom colors are kept.
+    pub fn poincare_colors(&self, style: &PoincareStyle) -> ([u8; 3], [u8; 3]) {
+        let default = PoincareStyle::default();
+        let inlier = if style.inlier_color == default.inlier_color {
+            self.metric_color(0)
+        } else {
+            style.inlier_color
+        };
+        (inlier, style.outlier_color)
+    }
+}
+
+impl fmt::Display for ColorPalette {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ColorPalette::Standard => "standard",
+            ColorPalette::ColorBlindSafe => "color-blind safe",
+        })
+    }
+}
+
+/// Heart rate zones shaded behind the time series.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HrZones {
+    /// Whether the zones are drawn.
+    pub show: bool,
+    /// Maximum heart rate in 1/min.
+    pub max_hr: f64,
+    /// Bounds of the five zones as fraction of the maximum heart rate, in ascending order.
+    pub bounds: [f64; 6],
+}
+
+impl Default for HrZones {
+    fn default() -> Self {
+        Self {
+            show: false,
+            max_hr: 190.0,
+            bounds: [0.5, 0.6, 0.7, 0.8, 0.9, 1.0],
+        }
+    }
+}
+
+impl HrZones {
+    /// Checks the maximum heart rate and that the bounds are ascending fractions.
+    pub fn validate(&self) -> Result<()> {
+        if !MAX_HR_RANGE.contains(&self.max_hr) {
+            return Err(anyhow::anyhow!(
+                "invalid maximum heart rate: {}",
+                self.max_hr
+            ));
+        }
+        let ascending = self.bounds.windows(2).all(|pair| pair[0] < pair[1]);
+        if !ascending || self.bounds[0] <= 0.0 || self.bounds[5] > 1.0 {
+            return Err(anyhow::anyhow!("invalid zone bounds: {:?}", self.bounds));
+        }
+        Ok(())
+    }
+
+    /// Returns the `(lower, upper)` heart rates in 1/min of the five zones.
+    pub fn zones(&self) -> Vec<(f64, f64)> {
+        self.bounds
+            .windows(2)
+            .map(|pair| (pair[0] * self.max_hr, pair[1] * self.max_hr))
+            .collect()
+    }
+}
+
+/// Cleanup of old auto-saved recordings at startup.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Retention {
+    /// Whether old auto-saved recordings are removed.
+    pub enabled: bool,
+    /// Number of the newest auto-saved recordings that are kept.
+    pub max_files: usize,
+    /// Age in days after which auto-saved recordings are removed.
+    pub max_days: u64,
+}
+
+impl Default for Retention {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_files: 100,
+            max_days: 90,
+        }
+    }
+}
+
+impl Retention {
+    /// Checks the number of files and the age.
+    pub fn validate(&self) -> Result<()> {
+        if !RETENTION_FILES_RANGE.contains(&self.max_files) {
+            return Err(anyhow::anyhow!(
+                "invalid number of kept files: {}",
+                self.max_files
+            ));
+        }
+        if !RETENTION_DAYS_RANGE.contains(&self.max_days) {
+            return Err(anyhow::anyhow!(
+                "invalid retention time: {} days",
+                self.max_days
+            ));
+        }
+        Ok(())
+    }
+
+    /// Removes the auto-saved recordings in `dir` exceeding the retention.
+    ///
+    /// Only files named like auto-saved recordings are considered, other files in the
+    /// directory are never removed. The age is determined by the modification time.
+    ///
+    /// # Arguments
+    /// * `dir` - The auto-save directory.
+    /// * `now` - The current time.
+    ///
+    /// # Returns
+    /// The paths of the removed files.
+    pub fn prune(&self, dir: &Path, now: SystemTime) -> Result<Vec<PathBuf>> {
+        if !self.enabled {
+            return Ok(Vec::new());
+        }
+        let mut files = Vec::new();
+        for entry in std::fs::read_dir(dir)? {
+            let entry = entry?;
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            let is_storage = name.ends_with(".json") || name.ends_with(".json.gz");
+            if !(name.starts_with(AUTO_SAVE_PREFIX) && is_storage) {
+                continue;
+            }
+            let metadata = entry.metadata()?;
+            if metadata.is_file() {
+                files.push((metadata.modified()?, entry.path()));
+            }
+        }
+        // newest first
+        files.sort_by_key(|(modified, _)| std::cmp::Reverse(*modified));
+        let max_age = std::time::Duration::from_secs(self.max_days * 24 * 60 * 60);
+        let mut removed = Vec::new();
+        for (idx, (modified, path)) in files.into_iter().enumerate() {
+            let expired = now.duration_since(modified).is_ok_and(|age| age > max_age);
+            if idx >= self.max_files || expired {
+                std::fs::remove_file(&path)?;
+                removed.push(path);
+            }
+        }
+        Ok(removed)
+    }
+}
+
+/// Interval of the markers inserted automatically during a recording, e.g. to segment
+/// the phases of a paced breathing protocol.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MarkerInterval {
+    /// A marker every period in seconds of the recording.
+    Seconds(u64),
+    /// A marker every number of received beats.
+    Beats(usize),
+}
+
+impl MarkerInterval {
+    /// Checks that the interval is within the supported range.
+    pub fn validate(&self) -> Result<()> {
+        let valid = match self {
+            Self::Seconds(period) => MARKER_PERIOD_RANGE.contains(period),
+            Self::Beats(beats) => MARKER_BEATS_RANGE.contains(beats),
+        };
+        if !valid {
+            return Err(anyhow::anyhow!("invalid marker interval: {:?}", self));
+        }
+        Ok(())
+    }
+}
+
+/// Analysis parameters applied whenever a device is selected.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DeviceProfile {
+    /// Bluetooth address of the device.
+    pub address: String,
+    /// Name of the profile shown in the settings.
+    pub name: String,
+    /// Parameters of the analysis.
+    pub config: HrvConfig,
+}
+
+/// Named analysis parameters applied to measurements on request.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AnalysisTemplate {
+    /// Name of the template shown in the selection.
+    pub name: String,
+    /// Parameters of the analysis.
+    pub config: HrvConfig,
+}
+
+/// Application wide settings.
+///
+/// The settings are loaded at startup and saved whenever they change.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AppSettings {
+    /// Presentation of data.
+    pub display: DisplaySettings,
+    /// Directory stopped recordings are automatically saved to, if enabled.
+    pub auto_save_dir: Option<PathBuf>,
+    /// Scaling factor of the user interface.
+    pub ui_scale: f32,
+    /// Weight of the previous average when smoothing the live readout, `0` disables it.
+    pub smoothing: f64,
+    /// Appearance of the Poincaré plot.
+    pub poincare: PoincareStyle,
+    /// Colors of the metric time series and the Poincaré plot.
+    pub palette: ColorPalette,
+    /// Time between two refreshes of the device list in seconds.
+    pub scan_interval: f64,
+    /// Number of attempts to reconnect a lost sensor before the recording continues without
+    /// it.
+    pub reconnect_attempts: usize,
+    /// Whether the scan only discovers devices advertising the heart rate service.
+    pub scan_heart_rate_only: bool,
+    /// Minimum time between two repaints while receiving data in ms.
+    pub repaint_interval: u64,
+    /// Time the window and outlier sliders have to settle before the statistics are
+    /// recalculated in ms.
+    pub recompute_debounce: u64,
+    /// Analysis profiles of known devices.
+    pub profiles: Vec<DeviceProfile>,
+    /// Named analysis parameters.
+    pub templates: Vec<AnalysisTemplate>,
+    /// Name of the template applied to new recordings and imports, `None` keeps the defaults.
+    pub default_template: Option<String>,
+    /// Personal RMSSD range the readiness is scored against, `None` hides the readiness.
+    pub readiness: Option<ReadinessBaseline>,
+    /// Heart rate zones shaded behind the time series.
+    pub hr_zones: HrZones,
+    /// Whether a recording is stopped and stored when the lost sensor does not reconnect.
+    pub auto_stop_on_disconnect: bool,
+    /// Whether storage files are saved as gzip compressed JSON.
+    pub compress_files: bool,
+    /// Countdown in seconds before the recording starts once the sensor connected, `None`
+    /// waits for the user to start it.
+    pub auto_start: Option<f64>,
+    /// Interval of the markers inserted automatically during a recording, `None` disables
+    /// them.
+    pub auto_markers: Option<MarkerInterval>,
+    /// Minimum number of inlier beats of a recording before it can be saved.
+    pub min_save_beats: usize,
+    /// Bounds of the heart rate raising an alert during a recording.
+    pub hr_alert: HrAlert,
+    /// Whether loaded measurements are analysed when they are shown instead of while loading.
+    pub lazy_analysis: bool,
+    /// Number of threads the analysis is limited to, `None` uses all cores.
+    pub analysis_threads: Option<usize>,
+    /// Cleanup of old auto-saved recordings at startup.
+    pub retention: Retention,
+    /// Number of windows the plotted DFA alpha is median filtered over, `1` disables it.
+    pub dfa_smoothing: usize,
+    /// Names of the metrics hidden in the time series plot, the y-axis scales to the others.
+    pub hidden_metrics: Vec<String>,
+    /// Unit of the RR intervals of imported files.
+    pub rr_unit: RrUnit,
+    /// Maximum change of the mean heart rate in 1/min of a stabilized recording.
+    pub stabilization_threshold: f64,
+    /// Aliases of the Bluetooth adapters, keyed by the name the platform reports for the
+    /// adapter.
+    pub adapter_aliases: BTreeMap<String, String>,
+}
+
+impl Default for AppSettings {
+    fn default() -> Self {
+        Self {
+            display: DisplaySettings::default(),
+            auto_save_dir: None,
+            ui_scale: DEFAULT_UI_SCALE,
+            smoothing: 0.0,
+            poincare: PoincareStyle::default(),
+            palette: ColorPalette::Standard,
+            scan_interval: DEFAULT_SCAN_INTERVAL,
+            reconnect_attempts: DEFAULT_RECONNECT_ATTEMPTS,
+            scan_heart_rate_only: false,
+            repaint_interval: DEFAULT_REPAINT_INTERVAL,
+            recompute_debounce: DEFAULT_RECOMPUTE_DEBOUNCE,
+            profiles: Vec::new(),
+            templates: Vec::new(),
+            default_template: None,
+            readiness: None,
+            hr_zones: HrZones::default(),
+            auto_stop_on_disconnect: false,
+            compress_files: false,
+            auto_start: None,
+            auto_markers: None,
+            min_save_beats: DEFAULT_MIN_SAVE_BEATS,
+            hr_alert: HrAlert::default(),
+            lazy_analysis: false,
+            analysis_threads: None,
+            retention: Retention::default(),
+            dfa_smoothing: 1,
+            hidden_metrics: Vec::new(),
+            rr_unit: RrUnit::Auto,
+            stabilization_threshold: DEFAULT_STABILIZATION_THRESHOLD,
+            adapter_aliases: BTreeMap::new(),
+        }
+    }
+}
+
+impl AppSettings {
+    /// Returns the extension of saved storage files, `json.gz` if they are compressed.
+    pub fn storage_extension(&self) -> &'static str {
+        if self.compress_files {
+            "json.gz"
+        } else {
+            "json"
+        }
+    }
+
+    /// Returns the path of the settings file in the platform configuration directory.
+    ///
+    /// # Returns
+    /// `None` if the configuration directory can not be determined.
+    pub fn default_path() -> Option<PathBuf> {
+        let config_dir = if cfg!(target_os = "windows") {
+            std::env::var_os("APPDATA").map(PathBuf::from)
+        } else if cfg!(target_os = "macos") {
+            std::env::var_os("HOME")
+                .map(|home| PathBuf::from(home).join("Library/Application Support"))
+        } else {
+            std::env::var_os("XDG_CONFIG_HOME")
+                .map(PathBuf::from)
+                .or_else(|| {
+                    std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config"))
+                })
+        };
+        config_dir.map(|dir| dir.join(SETTINGS_FILE))
+    }
+
+    /// Loads the settings from `path`.
+    ///
+    /// Settings outside of their supported range are reset to their defaults.
+    ///
+    /// # Returns
+    /// The default settings if the file does not exist, an error if it can not be read.
+    pub fn load(path: &Path) -> Result<Self> {
+        let json = match std::fs::read_to_string(path) {
+            Ok(json) => json,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(e.into()),
+        };
+        let mut settings: Self = serde_json::from_str(&json)?;
+        if format_description::parse(&settings.display.date_format).is_err() {
+            settings.display.date_format = DEFAULT_DATE_FORMAT.to_owned();
+        }
+        let defaults = Self::default();
+        if !UI_SCALE_RANGE.contains(&settings.ui_scale) {
+            settings.ui_scale = defaults.ui_scale;
+        }
+        if !SMOOTHING_RANGE.contains(&settings.smoothing) {
+            settings.smoothing = defaults.smoothing;
+        }
+        if !SCAN_INTERVAL_RANGE.contains(&settings.scan_interval) {
+            settings.scan_interval = defaults.scan_interval;
+        }
+        if !RECONNECT_ATTEMPTS_RANGE.contains(&settings.reconnect_attempts) {
+            settings.reconnect_attempts = defaults.reconnect_attempts;
+        }
+        if !REPAINT_INTERVAL_RANGE.contains(&settings.repaint_interval) {
+            settings.repaint_interval = defaults.repaint_interval;
+        }
+        if !RECOMPUTE_DEBOUNCE_RANGE.contains(&settings.recompute_debounce) {
+            settings.recompute_debounce = defaults.recompute_debounce;
+        }
+        if settings
+            .auto_start
+            .is_some_and(|countdown| !AUTO_START_RANGE.contains(&countdown))
+        {
+            settings.auto_start = defaults.auto_start;
+        }
+        if !MIN_SAVE_BEATS_RANGE.contains(&settings.min_save_beats) {
+            settings.min_save_beats = defaults.min_save_beats;
+        }
+        if !DFA_SMOOTHING_RANGE.contains(&settings.dfa_smoothing) {
+            settings.dfa_smoothing = defaults.dfa_smoothing;
+        }
+        if !STABILIZATION_THRESHOLD_RANGE.contains(&settings.stabilization_threshold) {
+            settings.stabilization_threshold = defaults.stabilization_threshold;
+        }
+        Ok(settings)
+    }
+
+    /// Returns the analysis profile of the device with the given address, if any.
+    pub fn get_profile(&self, address: &str) -> Option<&DeviceProfile> {
+        self.profiles.iter().find(|p| p.address == address)
+    }
+
+    /// Adds a profile, replacing the profile of the same device.
+    pub fn save_profile(&mut self, profile: DeviceProfile) {
+        self.profiles.retain(|p| p.address != profile.address);
+        self.profiles.push(profile);
+    }
+
+    /// Returns the analysis template with the given name, if any.
+    pub fn get_template(&self, name: &str) -> Option<&AnalysisTemplate> {
+        self.templates.iter().find(|t| t.name == name)
+    }
+
+    /// Adds a template, replacing the template of the same name.
+    pub fn save_template(&mut self, template: AnalysisTemplate) {
+        self.templates.retain(|t| t.name != template.name);
+        self.templates.push(template);
+    }
+
+    /// Deletes the template with the given name, it is no longer the default either.
+    pub fn delete_template(&mut self, name: &str) {
+        self.templates.retain(|t| t.name != name);
+        if self.default_template.as_deref() == Some(name) {
+            self.default_template = None;
+        }
+    }
+
+    /// Returns the parameters of the default template, if one is set.
+    pub fn default_config(&self) -> Option<&HrvConfig> {
+        self.default_template
+            .as_deref()
+            .and_then(|name| self.get_template(name))
+            .map(|template| &template.config)
+    }
+
+    /// Returns the label of `adapter` in the adapter selection.
+    ///
+    /// The label starts with the alias assigned to the adapter, if any, followed by the
+    /// discovery index and the name reported by the system.
+    pub fn adapter_label(&self, adapter: &AdapterDescriptor) -> String {
+        match self.adapter_aliases.get(&adapter.get_key()) {
+            Some(alias) => format!("{} ({})", alias, adapter),
+            None => adapter.to_string(),
+        }
+    }
+
+    /// Assigns an alias to the adapter with the stable `key`.
+    ///
+    /// `None` or a blank alias removes the alias of the adapter.
+    pub fn set_adapter_alias(&mut self, key: String, alias: Option<String>) -> Result<()> {
+        match alias
+            .as_deref()
+            .map(str::trim)
+            .filter(|alias| !alias.is_empty())
+        {
+            Some(alias) if alias.chars().count() > MAX_ADAPTER_ALIAS_LEN => {
+                return Err(anyhow::anyhow!(
+                    "adapter alias longer than {} characters",
+                    MAX_ADAPTER_ALIAS_LEN
+                ));
+            }
+            Some(alias) => {
+                self.adapter_aliases.insert(key, alias.to_owned());
+            }
+            None => {
+                self.adapter_aliases.remove(&key);
+            }
+        }
+        Ok(())
+    }
+
+    /// Saves the settings to `path`, creating the parent directories if necessary.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::macros::{datetime, offset};
+
+    #[test]
+    fn test_format_time_in_local_offset() {
+        let mut settings = DisplaySettings::default();
+        let time = datetime!(2024-03-01 22:30 UTC);
+        assert_eq!(settings.format_time(&time).unwrap(), "2024-03-01 22:30");
+        settings.set_utc_offset(offset!(+2));
+        assert_eq!(settings.format_time(&time).unwrap(), "2024-03-02 00:30");
+        settings
+            .set_date_format("[day].[month]. [hour]h".to_owned())
+            .unwrap();
+        assert_eq!(settings.format_time(&time).unwrap(), "02.03. 00h");
+    }
+
+    #[test]
+    fn test_invalid_date_format() {
+        let mut settings = DisplaySettings::default();
+        assert!(settings.set_date_format("[year".to_owned()).is_err());
+        assert_eq!(settings.get_date_format(), DEFAULT_DATE_FORMAT);
+    }
+
+    #[test]
+    fn test_invalid_precision() {
+        let mut settings = DisplaySettings::default();
+        assert!(settings.set_precision(3).is_ok());
+        assert!(settings.set_precision(10).is_err());
+        assert_eq!(settings.get_precision(), 3);
+    }
+
+    #[test]
+    fn test_load_save_round_trip() {
+        let temp_dir = tempdir::TempDir::new("test").unwrap();
+        let path = temp_dir.path().join("config/settings.json");
+        // a missing file yields the defaults
+        assert_eq!(AppSettings::load(&path).unwrap(), AppSettings::default());
+
+        let mut settings = AppSettings {
+            auto_save_dir: Some(PathBuf::from("/tmp/recordings")),
+            ui_scale: 2.0,
+            poincare: PoincareStyle {
+                marker: MarkerStyle::Cross,
+                show_ellipse: false,
+                mode: PoincareMode::Density,
+                ..Default::default()
+            },
+            palette: ColorPalette::ColorBlindSafe,
+            recompute_debounce: 300,
+            scan_heart_rate_only: true,
+            readiness: Some(ReadinessBaseline::default()),
+            auto_stop_on_disconnect: true,
+            compress_files: true,
+            auto_start: Some(3.0),
+            auto_markers: Some(MarkerInterval::Seconds(60)),
+            min_save_beats: 120,
+            lazy_analysis: true,
+            analysis_threads: Some(2),
+            dfa_smoothing: 5,
+            hidden_metrics: vec!["SDRR".to_owned()],
+            rr_unit: RrUnit::Seconds,
+            stabilization_threshold: 1.5,
+            adapter_aliases: BTreeMap::from([("hci1#0".to_owned(), "USB dongle".to_owned())]),
+            retention: Retention {
+                enabled: true,
+                max_files: 10,
+                ..Default::default()
+            },
+            hr_alert: HrAlert {
+                enabled: true,
+                high: 160.0,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        settings.save_profile(DeviceProfile {
+            address: "AA:BB:CC:DD:EE:FF".to_owned(),
+            name: "chest strap".to_owned(),
+            config: HrvConfig {
+                outlier_filter: 3.0,
+                ..Default::default()
+            },
+        });
+        settings
+            .display
+            .set_date_format("[day].[month].[year]".to_owned())
+            .unwrap();
+        settings.display.set_utc_offset(offset!(+2));
+        settings.save(&path).unwrap();
+
+        let loaded = AppSettings::load(&path).unwrap();
+        assert_eq!(loaded.auto_save_dir, settings.auto_save_dir);
+        assert_eq!(loaded.ui_scale, 2.0);
+        assert_eq!(loaded.poincare, settings.poincare);
+        assert_eq!(loaded.palette, ColorPalette::ColorBlindSafe);
+        assert_eq!(loaded.recompute_debounce, 300);
+        assert!(loaded.scan_heart_rate_only);
+        assert_eq!(loaded.profiles, settings.profiles);
+        assert_eq!(loaded.readiness, settings.readiness);
+        assert!(loaded.auto_stop_on_disconnect);
+        assert!(loaded.compress_files);
+        assert_eq!(loaded.storage_extension(), "json.gz");
+        assert_eq!(loaded.auto_start, Some(3.0));
+        assert_eq!(loaded.auto_markers, Some(MarkerInterval::Seconds(60)));
+        assert_eq!(loaded.min_save_beats, 120);
+        assert_eq!(loaded.hr_alert, settings.hr_alert);
+        assert!(loaded.lazy_analysis);
+        assert_eq!(loaded.analysis_threads, Some(2));
+        assert_eq!(loaded.retention, settings.retention);
+        assert_eq!(loaded.dfa_smoothing, 5);
+        assert_eq!(loaded.hidden_metrics, ["SDRR"]);
+        assert_eq!(loaded.rr_unit, RrUnit::Seconds);
+        assert_eq!(loaded.stabilization_threshold, 1.5);
+        assert_eq!(loaded.adapter_aliases, settings.adapter_aliases);
+        assert_eq!(loaded.display.get_date_format(), "[day].[month].[year]");
+        // the offset is determined at startup
+        assert_eq!(loaded.display.get_utc_offset(), UtcOffset::UTC);
+    }
+
+    #[test]
+    fn test_adapter_aliases() {
+        let adapters = [
+            AdapterDescriptor::new("hci0".to_owned()).with_index(0),
+            AdapterDescriptor::new("hci1".to_owned()).with_index(1),
+        ];
+        let mut settings = AppSettings::default();
+        let labels: Vec<String> = adapters.iter().map(|a| settings.adapter_label(a)).collect();
+        assert_eq!(labels, ["#0 hci0", "#1 hci1"]);
+
+        settings
+            .set_adapter_alias(adapters[1].get_key(), Some(" USB dongle ".to_owned()))
+            .unwrap();
+        let labels: Vec<String> = adapters.iter().map(|a| settings.adapter_label(a)).collect();
+        assert_eq!(labels, ["#0 hci0", "USB dongle (#1 hci1)"]);
+        // the alias follows the adapter discovered at another position
+        let replugged = AdapterDescriptor::new("hci1".to_owned()).with_index(0);
+        assert_eq!(settings.adapter_label(&replugged), "USB dongle (#0 hci1)");
+        // another adapter of the same name does not share the alias
+        let twin = AdapterDescriptor::new("hci1".to_owned())
+            .with_index(2)
+            .with_occurrence(1);
+        assert_eq!(settings.adapter_label(&twin), "#2 hci1");
+
+        assert!(settings
+            .set_adapter_alias(adapters[0].get_key(), Some("x".repeat(100)))
+            .is_err());
+        settings
+            .set_adapter_alias(adapters[1].get_key(), Some(String::new()))
+            .unwrap();
+        assert!(settings.adapter_aliases.is_empty());
+    }
+
+    #[test]
+    fn test_palette_colors() {
+        let standard = ColorPalette::Standard;
+        let safe = ColorPalette::ColorBlindSafe;
+        for idx in 0..standard.metric_colors().len() {
+            assert_ne!(standard.metric_color(idx), safe.metric_color(idx));
+        }
+        assert_eq!(
+            safe.metric_color(safe.metric_colors().len()),
+            safe.metric_color(0)
+        );
+
+        let style = PoincareStyle::default();
+        assert_eq!(standard.poincare_colors(&style).0, style.inlier_color);
+        assert_eq!(safe.poincare_colors(&style).0, safe.metric_color(0));
+        // custom colors take precedence over the palette
+        let custom = PoincareStyle {
+            inlier_color: [10, 20, 30],
+            ..Default::default()
+        };
+        assert_eq!(safe.poincare_colors(&custom).0, [10, 20, 30]);
+    }
+
+    #[test]
+    fn test_retention_prune() {
+        let temp_dir = tempdir::TempDir::new("test").unwrap();
+        let dir = temp_dir.path();
+        let now = SystemTime::now();
+        let day = std::time::Duration::from_secs(24 * 60 * 60);
+        let touch = |name: &str, age: u32| {
+            let file = std::fs::File::create(dir.join(name)).unwrap();
+            file.set_modified(now - day * age).unwrap();
+        };
+        touch("hrv_recent.json", 1);
+        touch("hrv_week.json", 7);
+        touch("hrv_old.json", 100);
+        touch("hrv_compressed.json.gz", 50);
+        // files not written by the auto-save are kept
+        touch("notes.json", 100);
+
+        let retention = Retention {
+            enabled: true,
+            max_files: 10,
+            max_days: 30,
+        };
+        assert!(retention.validate().is_ok());
+        let disabled = Retention {
+            enabled: false,
+            ..retention.clone()
+        };
+        assert!(disabled.prune(dir, now).unwrap().is_empty());
+
+        assert_eq!(
+            retention.prune(dir, now).unwrap(),
+            vec![dir.join("hrv_compressed.json.gz"), dir.join("hrv_old.json")]
+        );
+        assert!(dir.join("notes.json").exists());
+        let retention = Retention {
+            max_files: 1,
+            ..retention
+        };
+        assert_eq!(
+            retention.prune(dir, now).unwrap(),
+            vec![dir.join("hrv_week.json")]
+        );
+        assert!(dir.join("hrv_recent.json").exists());
+        assert!(Retention {
+            max_files: 0,
+            ..retention
+        }
+        .validate()
+        .is_err());
+    }
+
+    #[test]
+    fn test_hr_zones() {
+        let zones = HrZones {
+            max_hr: 200.0,
+            ..Default::default()
+        };
+        assert!(zones.validate().is_ok());
+        assert_eq!(
+            zones.zones(),
+            vec![
+                (100.0, 120.0),
+                (120.0, 140.0),
+                (140.0, 160.0),
+                (160.0, 180.0),
+                (180.0, 200.0)
+            ]
+        );
+        let invalid = HrZones {
+            bounds: [0.5, 0.7, 0.6, 0.8, 0.9, 1.0],
+            ..Default::default()
+        };
+        assert!(invalid.validate().is_err());
+        let invalid = HrZones {
+            max_hr: 50.0,
+            ..Default::default()
+        };
+        assert!(invalid.validate().is_err());
+    }
+
+    #[test]
+    fn test_save_profile_replaces_device() {
+        let mut settings = AppSettings::default();
+        for name in ["first", "second"] {
+            settings.save_profile(DeviceProfile {
+                address: "AA:BB:CC:DD:EE:FF".to_owned(),
+                name: name.to_owned(),
+                config: HrvConfig::default(),
+            });
+        }
+        assert_eq!(settings.profiles.len(), 1);
+        assert_eq!(
+            settings.get_profile("AA:BB:CC:DD:EE:FF").unwrap().name,
+            "second"
+        );
+        assert!(settings.get_profile("00:00:00:00:00:00").is_none());
+    }
+
+    #[test]
+    fn test_templates() {
+        let mut settings = AppSettings::default();
+        assert!(settings.default_config().is_none());
+        for outlier_filter in [3.0, 4.0] {
+            settings.save_template(AnalysisTemplate {
+                name: "resting".to_owned(),
+                config: HrvConfig {
+                    outlier_filter,
+                    ..Default::default()
+                },
+            });
+        }
+        assert_eq!(settings.templates.len(), 1);
+        assert_eq!(
+            settings
+                .get_template("resting")
+                .unwrap()
+                .config
+                .outlier_filter,
+            4.0
+        );
+        settings.default_template = Some("resting".to_owned());
+        assert_eq!(settings.default_config().unwrap().outlier_filter, 4.0);
+        settings.delete_template("resting");
+        assert!(settings.templates.is_empty());
+        assert!(settings.default_template.is_none());
+    }
+
+    #[test]
+    fn test_load_invalid_date_format() {
+        let temp_dir = tempdir::TempDir::new("test").unwrap();
+        let path = temp_dir.path().join("settings.json");
+        std::fs::write(&path, r#"{"display": {"date_format": "[year"}}"#).unwrap();
+        let settings = AppSettings::load(&path).unwrap();
+        assert_eq!(settings.display.get_date_format(), DEFAULT_DATE_FORMAT);
+        assert_eq!(settings.ui_scale, DEFAULT_UI_SCALE);
+    }
+
+    #[test]
+    fn test_load_out_of_range() {
+        let temp_dir = tempdir::TempDir::new("test").unwrap();
+        let path = temp_dir.path().join("settings.json");
+        std::fs::write(
+            &path,
+            r#"{"scan_interval": -1, "reconnect_attempts": 0, "repaint_interval": 5000,
+                "ui_scale": 1e30, "auto_start": 120, "dfa_smoothing": 4}"#,
+        )
+        .unwrap();
+        let settings = AppSettings::load(&path).unwrap();
+        assert_eq!(settings.scan_interval, DEFAULT_SCAN_INTERVAL);
+        assert_eq!(settings.reconnect_attempts, DEFAULT_RECONNECT_ATTEMPTS);
+        assert_eq!(settings.repaint_interval, DEFAULT_REPAINT_INTERVAL);
+        assert_eq!(settings.ui_scale, DEFAULT_UI_SCALE);
+        assert!(settings.auto_start.is_none());
+        // settings within their range are kept
+        assert_eq!(settings.dfa_smoothing, 4);
+
+        std::fs::write(&path, r#"{"scan_interval": 1e300}"#).unwrap();
+        let settings = AppSettings::load(&path).unwrap();
+        assert_eq!(settings.scan_interval, DEFAULT_SCAN_INTERVAL);
+    }
+}
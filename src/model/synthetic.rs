@@ -0,0 +1,180 @@
+//! Synthetic Data
+//!
+//! This module generates synthetic recordings for validating the analysis without hardware.
+//! The RR series is modulated by respiratory sinus arrhythmia (RSA), jittered by random
+//! noise and interspersed with premature (ectopic) beats followed by a compensatory pause.
+
+use anyhow::{anyhow, Result};
+use rand::{Rng, SeedableRng};
+use std::f64::consts::PI;
+use time::Duration;
+
+use super::{bluetooth::HeartrateMessage, hrv::HrvSummary};
+
+/// Parameters of a synthetic recording.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SyntheticConfig {
+    /// Mean heart rate in 1/min.
+    pub heart_rate: f64,
+    /// Amplitude of the respiratory modulation of the RR intervals in ms.
+    pub rsa_depth: f64,
+    /// Respiration rate in 1/min.
+    pub respiration_rate: f64,
+    /// Amplitude of the uniform RR noise in ms.
+    pub noise: f64,
+    /// Probability of a beat being ectopic.
+    pub ectopic_rate: f64,
+    /// Duration of the recording.
+    pub duration: Duration,
+    /// Seed of the random number generator.
+    pub seed: u64,
+}
+
+impl Default for SyntheticConfig {
+    fn default() -> Self {
+        Self {
+            heart_rate: 60.0,
+            rsa_depth: 50.0,
+            respiration_rate: 12.0,
+            noise: 10.0,
+            ectopic_rate: 0.01,
+            duration: Duration::minutes(10),
+            seed: 42,
+        }
+    }
+}
+
+/// Generates a synthetic recording with one RR interval per message.
+///
+/// # Arguments
+/// * `config` - The parameters of the recording.
+///
+/// # Returns
+/// `(elapsed time, message)` pairs as received from a sensor.
+pub fn generate(config: &SyntheticConfig) -> Vec<(Duration, HeartrateMessage)> {
+    let mut rng = rand::rngs::StdRng::seed_from_u64(config.seed);
+    let mean_rr = 60000.0 / config.heart_rate;
+    let resp_freq = config.respiration_rate / 60.0;
+    let mut elapsed = 0.0;
+    let mut compensate = false;
+    let mut data = Vec::new();
+    while elapsed < config.duration.as_seconds_f64() {
+        let mut rr = mean_rr
+            + config.rsa_depth * (2.0 * PI * resp_freq * elapsed).sin()
+            + config.noise * rng.gen_range(-1.0..=1.0);
+        if compensate {
+            rr *= 1.4;
+            compensate = false;
+        } else if rng.gen_bool(config.ectopic_rate.clamp(0.0, 1.0)) {
+            rr *= 0.6;
+            compensate = true;
+        }
+        let rr = rr.round().clamp(1.0, f64::from(u16::MAX));
+        elapsed += rr / 1000.0;
+        data.push((
+            Duration::seconds_f64(elapsed),
+            HeartrateMessage::from_values(config.heart_rate.round() as u16, None, &[rr as u16]),
+        ));
+    }
+    data
+}
+
+/// Checks that the analysis of a synthetic recording matches its parameters.
+///
+/// # Arguments
+/// * `config` - The parameters of the recording.
+/// * `summary` - The summary of the analysed recording.
+///
+/// # Returns
+/// An error describing the first implausible metric.
+pub fn validate(config: &SyntheticConfig, summary: &HrvSummary) -> Result<()> {
+    let mean_rr = summary.mean_rr.ok_or(anyhow!("no mean RR"))?;
+    let hr = 60000.0 / mean_rr;
+    if (hr - config.heart_rate).abs() > 5.0 {
+        return Err(anyhow!(
+            "mean heart rate {:.2} deviates from {:.2}",
+            hr,
+            config.heart_rate
+        ));
+    }
+    let rmssd = summary.rmssd.ok_or(anyhow!("no RMSSD"))?;
+    if rmssd <= 0.0 || rmssd > 2.0 * (config.rsa_depth + config.noise) {
+        return Err(anyhow!("implausible RMSSD {:.2}", rmssd));
+    }
+    let sdrr = summary.sdrr.ok_or(anyhow!("no SDRR"))?;
+    if sdrr <= 0.0 || sdrr > config.rsa_depth + config.noise {
+        return Err(anyhow!("implausible SDRR {:.2}", sdrr));
+    }
+    if summary.artifact_ratio > 2.0 * config.ectopic_rate + 0.05 {
+        return Err(anyhow!(
+            "artifact ratio {:.3} exceeds the ectopic rate {:.3}",
+            summary.artifact_ratio,
+            config.ectopic_rate
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::hrv::{HrvAnalysisData, HrvConfig};
+
+    fn analyse(config: &SyntheticConfig) -> HrvSummary {
+        HrvAnalysisData::from_acquisition(
+            &generate(config),
+            &[],
+            &HrvConfig::default(),
+            None,
+            Duration::ZERO,
+        )
+        .unwrap()
+        .summary()
+    }
+
+    #[test]
+    fn test_generate_duration() {
+        let config = SyntheticConfig::default();
+        let data = generate(&config);
+        let last = data.last().unwrap().0;
+        assert!(last >= config.duration);
+        assert!(last < config.duration + Duration::seconds(2));
+        // roughly one beat per second at 60 1/min
+        assert!((data.len() as i64 - 600).abs() < 20);
+    }
+
+    #[test]
+    fn test_metrics_in_expected_range() {
+        let config = SyntheticConfig::default();
+        let summary = analyse(&config);
+        assert!(validate(&config, &summary).is_ok());
+        let hr = summary.hr.unwrap();
+        assert!((55.0..65.0).contains(&hr));
+        // RSA at a mean RR of 1000 ms and 12 breaths per minute dominates the variability
+        let rmssd = summary.rmssd.unwrap();
+        assert!((20.0..80.0).contains(&rmssd), "RMSSD {}", rmssd);
+        assert!(summary.sd2.unwrap() > summary.sd1.unwrap());
+        assert!(summary.artifact_ratio < 0.05);
+    }
+
+    #[test]
+    fn test_ectopic_beats_are_filtered() {
+        let config = SyntheticConfig {
+            ectopic_rate: 0.05,
+            ..Default::default()
+        };
+        let summary = analyse(&config);
+        assert!(summary.artifact_ratio > 0.0);
+        assert!(validate(&config, &summary).is_ok());
+    }
+
+    #[test]
+    fn test_validate_rejects_mismatch() {
+        let summary = analyse(&SyntheticConfig::default());
+        let config = SyntheticConfig {
+            heart_rate: 90.0,
+            ..Default::default()
+        };
+        assert!(validate(&config, &summary).is_err());
+    }
+}
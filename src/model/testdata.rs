@@ -0,0 +1,25 @@
+//! Test Data
+//!
+//! Seeded random RR data shared by the tests of the analysis and the components.
+
+use rand::{Rng, SeedableRng};
+use time::Duration;
+
+use super::bluetooth::HeartrateMessage;
+
+/// Generates `len` messages, one per second, with random RR intervals.
+///
+/// The random generator is seeded, so every call returns the same messages.
+pub fn get_data(len: usize) -> Vec<(Duration, HeartrateMessage)> {
+    let mut rng = rand::rngs::StdRng::seed_from_u64(42);
+    (0..len)
+        .map(|idx| {
+            let rr = rng.gen_range(500..1500);
+            let hr = rng.gen_range(55..65);
+            (
+                Duration::seconds(idx as _),
+                HeartrateMessage::from_values(hr, None, &[rr]),
+            )
+        })
+        .collect()
+}
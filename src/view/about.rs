@@ -0,0 +1,119 @@
+//! About View
+//!
+//! This module provides a window describing the application: its version, the implemented
+//! metrics, the Bluetooth services it uses and where it keeps its files.
+
+use crate::{
+    api::view::ViewApi,
+    core::{constants::HEARTRATE_MEASUREMENT_UUID, events::AppEvent},
+    model::{metrics::default_metrics, settings::AppSettings},
+};
+
+/// Version of the application.
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Lists the registered metrics with their definitions.
+///
+/// # Returns
+/// `(name with unit, definition)` pairs in display order.
+pub fn metric_definitions() -> Vec<(String, String)> {
+    default_metrics()
+        .iter()
+        .map(|metric| {
+            let name = match metric.unit() {
+                "" => metric.name().to_owned(),
+                unit => format!("{} [{}]", metric.name(), unit),
+            };
+            (name, metric.description().to_owned())
+        })
+        .collect()
+}
+
+/// The `AboutView` renders a window with information about the application.
+#[derive(Debug, Default)]
+pub struct AboutView {
+    /// Whether the window is shown.
+    open: bool,
+}
+
+impl AboutView {
+    /// Opens the window.
+    pub fn open(&mut self) {
+        self.open = true;
+    }
+
+    /// Renders the information about the application.
+    fn render_about(ui: &mut egui::Ui) {
+        ui.heading(format!("HRV analysis tool {}", VERSION));
+        ui.separator();
+
+        ui.strong("Metrics");
+        egui::Grid::new("about metrics")
+            .num_columns(2)
+            .show(ui, |ui| {
+                for (name, definition) in metric_definitions() {
+                    ui.label(name);
+                    ui.label(definition);
+                    ui.end_row();
+                }
+            });
+        ui.separator();
+
+        ui.strong("Sensors");
+        ui.label(format!(
+            "Bluetooth LE heart rate sensors providing the Heart Rate service (0x180D). RR \
+             intervals are read from the Heart Rate Measurement characteristic ({}).",
+            HEARTRATE_MEASUREMENT_UUID
+        ));
+        ui.separator();
+
+        ui.strong("Files");
+        let settings = AppSettings::default_path()
+            .map_or("unknown".to_owned(), |path| path.display().to_string());
+        ui.label(format!("Settings: {}", settings));
+        ui.label(
+            "Measurements are saved as JSON files where chosen in the file dialog, stopped \
+             recordings are additionally saved to the auto-save directory if one is set.",
+        );
+    }
+}
+
+impl ViewApi for AboutView {
+    /// Renders the about window if it is open.
+    ///
+    /// # Arguments
+    /// * `_publish` - Function to publish `UiInputEvent`s (unused).
+    /// * `ctx` - Egui context for rendering.
+    ///
+    /// # Returns
+    /// `Result<(), String>` indicating success or an error message.
+    fn render<F: Fn(AppEvent) + ?Sized>(
+        &mut self,
+        _publish: &F,
+        ctx: &egui::Context,
+    ) -> Result<(), String> {
+        egui::Window::new("About")
+            .open(&mut self.open)
+            .collapsible(false)
+            .show(ctx, Self::render_about);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_metric_definitions() {
+        let definitions = metric_definitions();
+        let metrics = default_metrics();
+        assert_eq!(definitions.len(), metrics.len());
+        for (metric, (name, definition)) in metrics.iter().zip(&definitions) {
+            assert!(name.starts_with(metric.name()), "{}", name);
+            assert!(!definition.is_empty(), "{} has no definition", name);
+        }
+        assert!(definitions.iter().any(|(name, _)| name == "RMSSD [ms]"));
+        assert!(definitions.iter().any(|(name, _)| name == "DFA 1 alpha"));
+    }
+}
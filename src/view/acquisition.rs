@@ -5,16 +5,42 @@
 
 use eframe::egui;
 use egui::Color32;
-use egui_plot::{Legend, Plot, Points};
+use egui_plot::{Bar, BarChart, Legend, Plot, PlotPoints, Points, Polygon, VLine};
 use std::ops::RangeInclusive;
+use time::Duration;
 
+use super::{
+    format::{MetricFormat, HEARTRATE, SDANN, SDNN_INDEX},
+    lock,
+    smoothing::{moving_median, MetricSmoother},
+};
 use crate::{
     api::{
         controller::OutlierFilter,
-        model::{BluetoothModelApi, MeasurementModelApi, ModelHandle},
+        model::{BluetoothModelApi, MeasurementModelApi, ModelHandle, ModelSnapshot},
         view::ViewApi,
     },
-    core::events::{AppEvent, BluetoothEvent, MeasurementEvent, RecordingEvent, StateChangeEvent},
+    core::events::{
+        AppEvent, BluetoothEvent, MeasurementEvent, RecordingEvent, SettingsEvent, StateChangeEvent,
+    },
+    model::{
+        bluetooth::{ConnectionState, DeviceDescriptor},
+        hrv::{
+            detect_stabilization, AnalysisState, HrvSummary, BLANK_AFTER_GAP_RANGE,
+            IDENTICAL_RUN_RANGE, INSTANT_SD1_BEATS, LONG_TERM_SEGMENT, MIN_BEATS,
+            MIN_LONG_TERM_SEGMENTS, OUTLIER_SCALE_RANGE, RECENT_ARTIFACT_PERIOD,
+            SETTLING_PERIOD_RANGE,
+        },
+        metadata::{MeasurementMetadata, Posture, Sex, AGE_RANGE, MAX_SUBJECT_ID_LEN},
+        metrics::{
+            DetrendOption, DifferenceMode, MetricSeries, DETREND_ORDER_RANGE, DFA_ALPHA,
+            DFA_DETREND, PNN_THRESHOLD_RANGE,
+        },
+        settings::{
+            AnalysisTemplate, AppSettings, ColorPalette, DeviceProfile, HrZones, MarkerStyle,
+            PoincareMode, PoincareStyle, MAX_ADAPTER_ALIAS_LEN,
+        },
+    },
 };
 
 fn render_labelled_data(ui: &mut egui::Ui, label: &str, data: Option<String>) {
@@ -26,13 +52,62 @@ fn render_labelled_data(ui: &mut egui::Ui, label: &str, data: Option<String>) {
     }
 }
 
-pub fn render_stats(ui: &mut egui::Ui, model: &dyn MeasurementModelApi, hr: f64) {
+/// Completeness below which lost beats are highlighted.
+const COMPLETENESS_WARNING: f64 = 0.95;
+
+/// Renders the most recent statistics.
+///
+/// # Arguments
+/// * `model` - The measurement to render.
+/// * `hr` - The heart rate to display.
+/// * `smoother` - Smooths the displayed values of a live recording, if any.
+/// * `precision` - Number of decimals of the displayed metrics.
+pub fn render_stats(
+    ui: &mut egui::Ui,
+    model: &dyn MeasurementModelApi,
+    hr: f64,
+    mut smoother: Option<&mut MetricSmoother>,
+    precision: usize,
+) {
     ui.heading("Statistics");
+    match model.get_analysis_state() {
+        AnalysisState::NoData if !model.is_rr_available() => {
+            ui.colored_label(Color32::ORANGE, "RR not available; HRV metrics disabled");
+            ui.label(format!(
+                "{}: {}",
+                HEARTRATE,
+                MetricFormat::of(HEARTRATE, "BPM", 0).format(hr)
+            ));
+            if let Some(energy) = model.get_total_energy() {
+                ui.label(format!("Energy expended: {:.0} kJ", energy));
+            }
+            return;
+        }
+        AnalysisState::NoData => {
+            // a new recording starts, forget the previous averages
+            if let Some(smoother) = smoother {
+                smoother.reset();
+            }
+            ui.label("Waiting for first beats…");
+            return;
+        }
+        AnalysisState::Insufficient => {
+            ui.label(format!(
+                "Collecting beats, statistics need at least {}…",
+                MIN_BEATS
+            ));
+        }
+        AnalysisState::Ready => {}
+    }
+    let hr = smoother.as_deref_mut().map_or(hr, |smoother| {
+        smoother.smooth(HEARTRATE, model.get_beat_count(), hr)
+    });
     egui::Grid::new("stats grid").num_columns(2).show(ui, |ui| {
-        let desc = egui::Label::new("Heartrate: ");
-        ui.add(desc);
-        let val = egui::Label::new(format!("{:.2} BPM", hr));
-        ui.add(val);
+        render_labelled_data(
+            ui,
+            HEARTRATE,
+            Some(MetricFormat::of(HEARTRATE, "BPM", precision).format(hr)),
+        );
         ui.end_row();
 
         let desc = egui::Label::new("Elapsed time: ");
@@ -40,108 +115,670 @@ pub fn render_stats(ui: &mut egui::Ui, model: &dyn MeasurementModelApi, hr: f64)
         let val = egui::Label::new(format!("{} s", model.get_elapsed_time().whole_seconds()));
         ui.add(val);
         ui.end_row();
-        render_labelled_data(
-            ui,
-            "RMSSD",
-            model.get_rmssd().map(|val| format!("{:.2} ms", val)),
-        );
-        ui.end_row();
-        render_labelled_data(
-            ui,
-            "SDRR",
-            model.get_sdrr().map(|val| format!("{:.2} ms", val)),
-        );
-        ui.end_row();
-        render_labelled_data(
-            ui,
-            "SD1",
-            model.get_sd1().map(|val| format!("{:.2} ms", val)),
-        );
-        ui.end_row();
-        render_labelled_data(
-            ui,
-            "SD2",
-            model.get_sd2().map(|val| format!("{:.2} ms", val)),
-        );
-        ui.end_row();
-        render_labelled_data(
-            ui,
-            "DFA 1 alpha",
-            model.get_dfa1a().map(|val| format!("{:.2} ms", val)),
-        );
+        if let Some(energy) = model.get_total_energy() {
+            render_labelled_data(ui, "Energy expended: ", Some(format!("{:.0} kJ", energy)));
+            ui.end_row();
+        }
+        let dfa_warmup = model.get_dfa_warmup();
+        for metric in model.get_metrics() {
+            if let (DFA_ALPHA, Some((beats, required))) = (metric.name.as_str(), dfa_warmup) {
+                render_labelled_data(
+                    ui,
+                    &metric.name,
+                    Some(format!("warming up ({}/{} beats)", beats, required)),
+                );
+                ui.end_row();
+                continue;
+            }
+            let value = metric.last().map(|val| {
+                smoother.as_deref_mut().map_or(val, |smoother| {
+                    smoother.smooth(&metric.name, metric.time_series.len(), val)
+                })
+            });
+            let format = MetricFormat::of(&metric.name, &metric.unit, precision);
+            render_labelled_data(ui, &format.label, value.map(|val| format.format(val)));
+            ui.end_row();
+        }
+        // long term metrics are only meaningful for recordings of several segments
+        let min_duration = LONG_TERM_SEGMENT * MIN_LONG_TERM_SEGMENTS as u32;
+        let too_short = format!("needs {} min", min_duration.whole_minutes());
+        for (name, value) in [
+            (SDANN, model.get_sdann()),
+            (SDNN_INDEX, model.get_sdnn_index()),
+        ] {
+            let format = MetricFormat::of(name, "ms", precision);
+            render_labelled_data(
+                ui,
+                &format.label,
+                Some(value.map_or(too_short.clone(), |val| format.format(val))),
+            );
+            ui.end_row();
+        }
         ui.end_row();
+        let corrected = model.get_correction_log().len();
+        if corrected > 0 {
+            render_labelled_data(ui, "Excluded beats: ", Some(corrected.to_string()));
+            ui.end_row();
+        }
+        if let Some(completeness) = model.get_completeness() {
+            let color = if completeness < COMPLETENESS_WARNING {
+                Color32::ORANGE
+            } else {
+                ui.visuals().text_color()
+            };
+            ui.label("Completeness: ")
+                .on_hover_text("received beats of the beats expected from the elapsed time");
+            ui.colored_label(color, format!("{:.1} %", 100.0 * completeness));
+            ui.end_row();
+        }
+        let dropped = model.get_dropped_events();
+        if dropped > 0 {
+            ui.label("Dropped events: ");
+            ui.colored_label(Color32::ORANGE, dropped.to_string());
+            ui.end_row();
+        }
+    });
+    ui.horizontal(|ui| {
+        if ui.button("copy summary").clicked() {
+            ui.ctx().copy_text(model.get_summary().to_tsv());
+        }
+        if ui.button("copy with header").clicked() {
+            ui.ctx().copy_text(format!(
+                "{}\n{}",
+                HrvSummary::TSV_HEADER,
+                model.get_summary().to_tsv()
+            ));
+        }
     });
 }
 
-pub fn render_time_series(ui: &mut egui::Ui, model: &dyn MeasurementModelApi) {
-    let plot: Plot<'_> = Plot::new("Time series").legend(Legend::default());
+/// Link group of the plots sharing the time axis.
+const TIME_AXIS_GROUP: &str = "time axis";
 
-    plot.show(ui, |plot_ui| {
-        plot_ui.line(
-            egui_plot::Line::new(model.get_rmssd_ts())
-                .name("RMSSD [ms]")
-                .color(Color32::RED),
-        );
-        plot_ui.line(
-            egui_plot::Line::new(model.get_sdrr_ts())
-                .name("SDRR [ms]")
-                .color(Color32::DARK_GREEN),
-        );
-        plot_ui.line(
-            egui_plot::Line::new(model.get_sd1_ts())
-                .name("SD1 [ms]")
-                .color(Color32::BLUE),
+/// Creates a plot whose x-axis (time) is linked to the other time based plots.
+///
+/// # Arguments
+/// * `id` - The id source of the plot.
+/// * `reset` - Resets zoom and pan to fit the data.
+fn time_plot(id: &str, reset: bool) -> Plot<'static> {
+    let plot = Plot::new(id).legend(Legend::default()).link_axis(
+        egui::Id::new(TIME_AXIS_GROUP),
+        true,
+        false,
+    );
+    if reset {
+        plot.reset()
+    } else {
+        plot
+    }
+}
+
+/// Selects the metric series to plot.
+///
+/// # Arguments
+/// * `metrics` - All metric series of the measurement.
+/// * `hidden` - Names of the hidden metrics.
+///
+/// # Returns
+/// The series not in `hidden` with their index among all series, which keeps their colors.
+pub fn visible_metrics(
+    metrics: Vec<MetricSeries>,
+    hidden: &[String],
+) -> Vec<(usize, MetricSeries)> {
+    metrics
+        .into_iter()
+        .enumerate()
+        .filter(|(_, metric)| !hidden.contains(&metric.name))
+        .collect()
+}
+
+/// Renders the tachogram and the metric time series side by side on a shared time axis.
+///
+/// The y-axis of the time series scales to the visible metrics, toggling a metric publishes
+/// the hidden metrics to the settings. The metrics are passed in, so the overview can plot the
+/// decimated series cached for a stored measurement.
+pub fn render_time_plots<F: Fn(AppEvent) + ?Sized>(
+    ui: &mut egui::Ui,
+    publish: &F,
+    model: &dyn MeasurementModelApi,
+    metrics: Vec<MetricSeries>,
+    settings: &AppSettings,
+) {
+    let reset = ui
+        .horizontal(|ui| {
+            let reset = ui.button("reset zoom").clicked();
+            ui.separator();
+            for metric in &metrics {
+                let mut visible = !settings.hidden_metrics.contains(&metric.name);
+                if ui.checkbox(&mut visible, metric.name.as_str()).changed() {
+                    let mut hidden = settings.hidden_metrics.clone();
+                    hidden.retain(|name| name != &metric.name);
+                    if !visible {
+                        hidden.push(metric.name.clone());
+                    }
+                    publish(AppEvent::Settings(SettingsEvent::SetHiddenMetrics(hidden)));
+                }
+            }
+            reset
+        })
+        .inner;
+    let metrics = visible_metrics(metrics, &settings.hidden_metrics);
+    ui.columns(2, |columns| {
+        render_tachogram(&mut columns[0], model, reset, settings.palette);
+        render_time_series(
+            &mut columns[1],
+            model,
+            metrics,
+            reset,
+            &settings.hr_zones,
+            settings.dfa_smoothing,
+            settings.palette,
         );
-        plot_ui.line(
-            egui_plot::Line::new(model.get_sd2_ts())
-                .name("SD2 [ms]")
-                .color(Color32::YELLOW),
+    });
+}
+
+/// Colors of the heart rate zones, from zone 1 to zone 5.
+const ZONE_COLORS: [Color32; 5] = [
+    Color32::from_rgba_premultiplied(0, 40, 60, 40),
+    Color32::from_rgba_premultiplied(0, 60, 0, 40),
+    Color32::from_rgba_premultiplied(60, 60, 0, 40),
+    Color32::from_rgba_premultiplied(60, 30, 0, 40),
+    Color32::from_rgba_premultiplied(60, 0, 0, 40),
+];
+
+/// Adds a marker during the acquisition.
+const MARKER_SHORTCUT: egui::KeyboardShortcut =
+    egui::KeyboardShortcut::new(egui::Modifiers::COMMAND, egui::Key::M);
+
+/// Color of the markers added during a recording.
+const MARKER_COLOR: Color32 = Color32::from_rgb(200, 120, 255);
+
+/// Draws the markers of the recording, the labels are shown when hovering a marker.
+fn render_markers(plot_ui: &mut egui_plot::PlotUi, model: &dyn MeasurementModelApi) {
+    let markers = model.get_markers();
+    for (elapsed, _) in markers {
+        plot_ui.vline(
+            VLine::new(elapsed.as_seconds_f64())
+                .name("markers")
+                .color(MARKER_COLOR),
         );
-        plot_ui.line(
-            egui_plot::Line::new(model.get_hr_ts())
-                .name("HR [1/min]")
-                .color(Color32::GREEN),
+    }
+    let Some(pointer) = plot_ui.pointer_coordinate() else {
+        return;
+    };
+    // markers within 1 % of the visible time span count as hovered
+    let tolerance = plot_ui.plot_bounds().width() / 100.0;
+    let hovered: Vec<String> = markers
+        .iter()
+        .filter(|(elapsed, _)| (elapsed.as_seconds_f64() - pointer.x).abs() <= tolerance)
+        .map(|(elapsed, label)| format!("{:.0} s: {}", elapsed.as_seconds_f64(), label))
+        .collect();
+    if !hovered.is_empty() {
+        let _ = plot_ui.response().clone().on_hover_text(hovered.join("\n"));
+    }
+}
+
+/// Fill color of the time without sensor contact.
+const NO_CONTACT_COLOR: Color32 = Color32::from_rgba_premultiplied(80, 40, 40, 40);
+
+/// Joins the RR intervals without sensor contact into time spans.
+///
+/// # Returns
+/// `(start, end)` in s of the runs of intervals without contact, an interval spans from the
+/// previous beat to its own beat.
+fn no_contact_spans(track: &[(f64, bool)]) -> Vec<(f64, f64)> {
+    let mut spans: Vec<(f64, f64)> = Vec::new();
+    let mut previous = None;
+    let mut open = false;
+    for &(time, contact) in track {
+        if !contact {
+            match spans.last_mut() {
+                Some(span) if open => span.1 = time,
+                _ => spans.push((previous.unwrap_or(time), time)),
+            }
+        }
+        open = !contact;
+        previous = Some(time);
+    }
+    spans
+}
+
+/// Shades the time without sensor contact between `lower` and `upper`.
+fn render_no_contact(
+    plot_ui: &mut egui_plot::PlotUi,
+    model: &dyn MeasurementModelApi,
+    (lower, upper): (f64, f64),
+) {
+    for (start, end) in no_contact_spans(&model.get_contact_track()) {
+        let span = vec![[start, lower], [end, lower], [end, upper], [start, upper]];
+        plot_ui.polygon(
+            Polygon::new(PlotPoints::new(span))
+                .name("no contact")
+                .fill_color(NO_CONTACT_COLOR)
+                .stroke(egui::Stroke::NONE),
         );
+    }
+}
+
+/// Fill color of the pauses and lost connections of the recording.
+const GAP_COLOR: Color32 = Color32::from_rgba_premultiplied(40, 40, 40, 60);
 
-        plot_ui.line(
-            egui_plot::Line::new(model.get_dfa1a_ts())
-                .name("DFA 1 alpha")
-                .color(Color32::KHAKI),
+/// Shades the pauses and lost connections of the recording between `lower` and `upper`.
+fn render_gaps(
+    plot_ui: &mut egui_plot::PlotUi,
+    model: &dyn MeasurementModelApi,
+    (lower, upper): (f64, f64),
+) {
+    for (start, end) in model.get_gaps() {
+        if end <= start {
+            // lost events are marked as gap without duration
+            continue;
+        }
+        let (start, end) = (start.as_seconds_f64(), end.as_seconds_f64());
+        let span = vec![[start, lower], [end, lower], [end, upper], [start, upper]];
+        plot_ui.polygon(
+            Polygon::new(PlotPoints::new(span))
+                .name("interrupted")
+                .fill_color(GAP_COLOR)
+                .stroke(egui::Stroke::NONE),
         );
+    }
+}
+
+/// Returns the `(min, max)` of the values of the series, `None` if there are none.
+fn value_range<'a>(series: impl IntoIterator<Item = &'a [f64; 2]>) -> Option<(f64, f64)> {
+    series
+        .into_iter()
+        .map(|p| p[1])
+        .filter(|val| val.is_finite())
+        .fold(None, |range, val| match range {
+            None => Some((val, val)),
+            Some((min, max)) => Some((f64::min(min, val), f64::max(max, val))),
+        })
+}
+
+/// Splits a series into the runs of finite points.
+///
+/// Points with a NaN or infinite coordinate, e.g. a DFA alpha that could not be fitted, are
+/// dropped and break the series, so the plotted line has a gap instead of a corrupted range.
+///
+/// # Arguments
+/// * `series` - The `[time, value]` points.
+///
+/// # Returns
+/// The non-empty runs of consecutive finite points.
+pub fn finite_segments(series: &[[f64; 2]]) -> Vec<Vec<[f64; 2]>> {
+    series
+        .split(|p| !(p[0].is_finite() && p[1].is_finite()))
+        .filter(|segment| !segment.is_empty())
+        .map(<[[f64; 2]]>::to_vec)
+        .collect()
+}
+
+/// Draws a series as line with gaps at its non-finite points.
+///
+/// The segments share name and color, so they appear as one entry of the legend.
+fn plot_series(plot_ui: &mut egui_plot::PlotUi, series: &[[f64; 2]], name: &str, color: Color32) {
+    for segment in finite_segments(series) {
+        plot_ui.line(egui_plot::Line::new(segment).name(name).color(color));
+    }
+}
+
+/// Converts an RGB color of the settings.
+fn rgb([r, g, b]: [u8; 3]) -> Color32 {
+    Color32::from_rgb(r, g, b)
+}
+
+pub fn render_tachogram(
+    ui: &mut egui::Ui,
+    model: &dyn MeasurementModelApi,
+    reset: bool,
+    palette: ColorPalette,
+) {
+    time_plot("Tachogram", reset).show(ui, |plot_ui| {
+        let rr_ts = model.get_rr_ts();
+        if let Some(range) = value_range(&rr_ts) {
+            render_no_contact(plot_ui, model, range);
+            render_gaps(plot_ui, model, range);
+        }
+        plot_series(plot_ui, &rr_ts, "RR [ms]", rgb(palette.metric_color(0)));
+        render_markers(plot_ui, model);
+    });
+}
+
+pub fn render_time_series(
+    ui: &mut egui::Ui,
+    model: &dyn MeasurementModelApi,
+    metrics: Vec<(usize, MetricSeries)>,
+    reset: bool,
+    zones: &HrZones,
+    dfa_smoothing: usize,
+    palette: ColorPalette,
+) {
+    time_plot("Time series", reset).show(ui, |plot_ui| {
+        if zones.show {
+            // shade the zones over the recorded time, behind the lines
+            let end = model.get_elapsed_time().as_seconds_f64();
+            for (idx, (lower, upper)) in zones.zones().into_iter().enumerate() {
+                let band = vec![[0.0, lower], [end, lower], [end, upper], [0.0, upper]];
+                plot_ui.polygon(
+                    Polygon::new(PlotPoints::new(band))
+                        .name(format!("zone {}", idx + 1))
+                        .fill_color(ZONE_COLORS[idx % ZONE_COLORS.len()])
+                        .stroke(egui::Stroke::NONE),
+                );
+            }
+        }
+        if let Some(range) = value_range(metrics.iter().flat_map(|(_, m)| &m.time_series)) {
+            render_no_contact(plot_ui, model, range);
+            render_gaps(plot_ui, model, range);
+        }
+        for (idx, metric) in metrics {
+            // the jumpy DFA alpha of the single windows is only smoothed for display
+            let series = if metric.name == DFA_ALPHA {
+                moving_median(&metric.time_series, dfa_smoothing)
+            } else {
+                metric.time_series
+            };
+            let name = if metric.unit.is_empty() {
+                metric.name
+            } else {
+                format!("{} [{}]", metric.name, metric.unit)
+            };
+            plot_series(plot_ui, &series, &name, rgb(palette.metric_color(idx)));
+        }
+        if !model.is_rr_available() {
+            // fall back to the heart rate reported by sensors without RR intervals
+            plot_series(
+                plot_ui,
+                &model.get_sensor_hr_ts(),
+                "sensor HR [1/min]",
+                rgb(palette.metric_color(0)),
+            );
+        }
+
+        if let Some((start, end)) = model.get_analysis_range() {
+            for ts in [start, end] {
+                plot_ui.vline(
+                    VLine::new(ts.as_seconds_f64())
+                        .name("analysis range")
+                        .color(Color32::GRAY),
+                );
+            }
+        }
+        render_markers(plot_ui, model);
     });
 }
 
-pub fn render_poincare_plot(ui: &mut egui::Ui, model: &dyn MeasurementModelApi) {
+/// Width of the bins of the ΔRR histogram in ms.
+const RR_DIFFERENCE_BIN: f64 = 10.0;
+
+/// Counts `values` in bins of `width` centered on multiples of `width`.
+///
+/// # Returns
+/// `(bin center, count)` of the non-empty bins in ascending order.
+fn histogram(values: &[f64], width: f64) -> Vec<(f64, usize)> {
+    let mut bins = std::collections::BTreeMap::new();
+    for value in values {
+        *bins.entry((value / width).round() as i64).or_insert(0) += 1;
+    }
+    bins.into_iter()
+        .map(|(bin, count)| (bin as f64 * width, count))
+        .collect()
+}
+
+/// Renders the distribution of the differences of successive RR intervals.
+///
+/// The pNNx threshold is marked on both sides, isolated large differences indicate
+/// ectopic beats.
+pub fn render_rr_difference_histogram(ui: &mut egui::Ui, model: &dyn MeasurementModelApi) {
+    egui::CollapsingHeader::new("ΔRR distribution").show(ui, |ui| {
+        let bars = histogram(&model.get_rr_differences(), RR_DIFFERENCE_BIN)
+            .into_iter()
+            .map(|(center, count)| Bar::new(center, count as f64).width(RR_DIFFERENCE_BIN))
+            .collect();
+        let threshold = model.get_pnn_threshold();
+        Plot::new("rr difference histogram")
+            .height(150.0)
+            .allow_scroll(false)
+            .x_axis_label("ΔRR [ms]")
+            .show(ui, |plot_ui| {
+                plot_ui.bar_chart(BarChart::new(bars).name("beats").color(Color32::RED));
+                for x in [-threshold, threshold] {
+                    plot_ui.vline(
+                        VLine::new(x)
+                            .name(format!("pNN{:.0} threshold", threshold))
+                            .color(Color32::GRAY),
+                    );
+                }
+            });
+    });
+}
+
+/// Edge length of the bins of the Poincaré density in ms.
+const POINCARE_DENSITY_BIN: f64 = 10.0;
+
+/// Counts `points` in square bins of `width` centered on multiples of `width`.
+///
+/// # Returns
+/// `(bin center, count)` of the non-empty bins in ascending order of x, then y.
+fn histogram_2d(points: &[[f64; 2]], width: f64) -> Vec<([f64; 2], usize)> {
+    let mut bins = std::collections::BTreeMap::new();
+    for [x, y] in points {
+        let bin = ((x / width).round() as i64, (y / width).round() as i64);
+        *bins.entry(bin).or_insert(0) += 1;
+    }
+    bins.into_iter()
+        .map(|((x, y), count)| ([x as f64 * width, y as f64 * width], count))
+        .collect()
+}
+
+/// Draws the inlier Poincaré pairs as bins shaded by their number of pairs.
+///
+/// The opacity grows with the logarithm of the count, so sparse bins next to a dense core
+/// stay visible.
+fn render_poincare_density(plot_ui: &mut egui_plot::PlotUi, inliers: &[[f64; 2]], color: Color32) {
+    let bins = histogram_2d(inliers, POINCARE_DENSITY_BIN);
+    let max = bins.iter().map(|(_, count)| *count).max().unwrap_or(1) as f64;
+    let half = POINCARE_DENSITY_BIN / 2.0;
+    for ([x, y], count) in bins {
+        let density = (1.0 + count as f64).ln() / (1.0 + max).ln();
+        let alpha = (40.0 + 215.0 * density) as u8;
+        let bin = vec![
+            [x - half, y - half],
+            [x + half, y - half],
+            [x + half, y + half],
+            [x - half, y + half],
+        ];
+        plot_ui.polygon(
+            Polygon::new(PlotPoints::new(bin))
+                .name("R-R density")
+                .fill_color(Color32::from_rgba_unmultiplied(
+                    color.r(),
+                    color.g(),
+                    color.b(),
+                    alpha,
+                ))
+                .stroke(egui::Stroke::NONE),
+        );
+    }
+}
+
+pub fn render_poincare_plot(
+    ui: &mut egui::Ui,
+    model: &dyn MeasurementModelApi,
+    style: &PoincareStyle,
+    palette: ColorPalette,
+) {
     let plot = Plot::new("Poincare Plot")
         .legend(Legend::default())
         .data_aspect(1.0);
+    let shape = match style.marker {
+        MarkerStyle::Circle => egui_plot::MarkerShape::Circle,
+        MarkerStyle::Diamond => egui_plot::MarkerShape::Diamond,
+        MarkerStyle::Square => egui_plot::MarkerShape::Square,
+        MarkerStyle::Cross => egui_plot::MarkerShape::Cross,
+    };
+    let (inlier_color, outlier_color) = palette.poincare_colors(style);
+    let (inlier_color, outlier_color) = (rgb(inlier_color), rgb(outlier_color));
 
+    ui.horizontal(|ui| {
+        ui.colored_label(outlier_color, "●");
+        ui.label(format!(
+            "outliers: {:.1} % ⓘ",
+            100.0 * model.get_artifact_ratio()
+        ))
+        .on_hover_text(outlier_legend(model));
+    });
     plot.show(ui, |plot_ui| {
         if let Ok((inliers, outliers)) = model.get_poincare_points() {
-            plot_ui.points(
-                Points::new(inliers)
-                    .name("R-R")
-                    .shape(egui_plot::MarkerShape::Diamond)
-                    .color(Color32::RED)
-                    .radius(5.0),
-            );
+            match style.mode {
+                PoincareMode::Scatter => plot_ui.points(
+                    Points::new(inliers)
+                        .name("R-R")
+                        .shape(shape)
+                        .color(inlier_color)
+                        .radius(5.0),
+                ),
+                PoincareMode::Density => render_poincare_density(plot_ui, &inliers, inlier_color),
+            }
             plot_ui.points(
                 Points::new(outliers)
                     .name("R-R outliers")
-                    .shape(egui_plot::MarkerShape::Diamond)
-                    .color(Color32::GRAY)
+                    .shape(shape)
+                    .color(outlier_color)
                     .radius(5.0),
             );
         }
+        if style.show_ellipse {
+            if let Ok(ellipse) = model.get_poincare_ellipse() {
+                plot_ui.line(
+                    egui_plot::Line::new(ellipse.polyline(100))
+                        .name("SD1/SD2 ellipse")
+                        .color(Color32::BLUE),
+                );
+            }
+        }
     });
 }
 
+/// Number of RR intervals shown in the live readout.
+const RECENT_RR_COUNT: usize = 20;
+
+/// Renders a scrolling list of the most recent RR intervals, outliers are grayed out.
+pub fn render_recent_rr(ui: &mut egui::Ui, model: &dyn MeasurementModelApi) {
+    ui.heading("Recent RR intervals");
+    egui::ScrollArea::vertical()
+        .max_height(150.0)
+        .stick_to_bottom(true)
+        .show(ui, |ui| {
+            for (rr, is_outlier) in model.get_recent_rr(RECENT_RR_COUNT) {
+                if is_outlier {
+                    ui.colored_label(Color32::GRAY, format!("{:.0} ms (outlier)", rr));
+                } else {
+                    ui.label(format!("{:.0} ms", rr));
+                }
+            }
+        });
+}
+
+/// SD1 in ms filling the instantaneous SD1 gauge.
+const INSTANT_SD1_FULL_SCALE: f64 = 100.0;
+
+/// Renders the SD1 of the most recent beats as gauge.
+pub fn render_instant_sd1(ui: &mut egui::Ui, model: &dyn MeasurementModelApi, precision: usize) {
+    let Some(sd1) = model.get_instant_sd1() else {
+        return;
+    };
+    ui.label(format!("Instant SD1 (last {} beats)", INSTANT_SD1_BEATS));
+    ui.add(
+        egui::ProgressBar::new((sd1 / INSTANT_SD1_FULL_SCALE).clamp(0.0, 1.0) as f32)
+            .text(format!("{:.*} ms", precision, sd1)),
+    );
+}
+
+/// Recent artifact rates up to which the signal is shown as clean and as acceptable.
+const ARTIFACT_RATE_LEVELS: (f64, f64) = (0.02, 0.1);
+
+/// Renders how clean the signal of the most recent beats is.
+pub fn render_signal_quality(ui: &mut egui::Ui, model: &dyn MeasurementModelApi) {
+    let Some(rate) = model.get_recent_artifact_rate() else {
+        return;
+    };
+    let (clean, acceptable) = ARTIFACT_RATE_LEVELS;
+    let color = if rate <= clean {
+        Color32::LIGHT_GREEN
+    } else if rate <= acceptable {
+        Color32::YELLOW
+    } else {
+        Color32::RED
+    };
+    ui.horizontal(|ui| {
+        ui.colored_label(color, "⏺");
+        ui.label(format!(
+            "artifacts (last {} s): {:.1} %",
+            RECENT_ARTIFACT_PERIOD.whole_seconds(),
+            100.0 * rate
+        ));
+    })
+    .response
+    .on_hover_text("adjust the strap until the indicator turns green");
+    ui.label(format!(
+        "{} beats since the last outlier",
+        model.get_beats_since_outlier()
+    ));
+}
+
+/// State of the manually entered device address.
+#[derive(Default)]
+pub struct ManualAddress {
+    /// The address text as entered.
+    input: String,
+    /// Error message of the last connection attempt.
+    error: Option<String>,
+}
+
+/// State of the alias entered for the selected adapter.
+#[derive(Default)]
+pub struct AliasInput {
+    /// Key of the adapter the input was prefilled for.
+    key: Option<String>,
+    /// The alias text as entered.
+    input: String,
+}
+
+/// Renders the connection state as colored badge.
+fn render_connection_badge(ui: &mut egui::Ui, state: ConnectionState) {
+    let color = match state {
+        ConnectionState::Idle => Color32::GRAY,
+        ConnectionState::Scanning | ConnectionState::Connecting => Color32::YELLOW,
+        ConnectionState::Connected => Color32::LIGHT_GREEN,
+        ConnectionState::Recording => Color32::RED,
+    };
+    ui.label(
+        egui::RichText::new(format!(" {} ", state))
+            .color(Color32::BLACK)
+            .background_color(color)
+            .strong(),
+    );
+}
+
+/// Renders the selection of the adapter and the device.
+///
+/// Adapters are labeled with their alias from the settings, the alias of the selected adapter
+/// can be edited.
 pub fn render_bluetooth<F: Fn(AppEvent) + ?Sized>(
     ui: &mut egui::Ui,
     publish: &F,
     model: &dyn BluetoothModelApi,
+    manual: &mut ManualAddress,
+    settings: &AppSettings,
+    alias: &mut AliasInput,
 ) {
-    ui.heading("Bluetooth settings:");
+    ui.horizontal(|ui| {
+        ui.heading("Bluetooth settings:");
+        render_connection_badge(ui, model.get_connection_state());
+    });
     ui.add_enabled_ui(model.get_selected_adapter().is_none(), |ui| {
         let current = model.get_selected_adapter();
         egui::ComboBox::from_label("Adapter")
@@ -149,7 +786,7 @@ pub fn render_bluetooth<F: Fn(AppEvent) + ?Sized>(
                 model
                     .get_selected_adapter()
                     .as_ref()
-                    .map_or(Default::default(), |a| a.get_name().to_owned()),
+                    .map_or(Default::default(), |a| settings.adapter_label(a)),
             )
             .show_ui(ui, |ui| {
                 for adapter in model.get_adapters() {
@@ -158,7 +795,7 @@ pub fn render_bluetooth<F: Fn(AppEvent) + ?Sized>(
                             current
                                 .as_ref()
                                 .map_or(false, |a| a.get_uuid() == adapter.get_uuid()),
-                            adapter.get_name(),
+                            settings.adapter_label(adapter),
                         )
                         .clicked()
                     {
@@ -169,6 +806,35 @@ pub fn render_bluetooth<F: Fn(AppEvent) + ?Sized>(
                 }
             });
     });
+    if let Some(adapter) = model.get_selected_adapter() {
+        let key = adapter.get_key();
+        if alias.key.as_ref() != Some(&key) {
+            // start editing from the current alias of a newly selected adapter
+            alias.input = settings
+                .adapter_aliases
+                .get(&key)
+                .cloned()
+                .unwrap_or_default();
+            alias.key = Some(key.clone());
+        }
+        ui.horizontal(|ui| {
+            ui.add(
+                egui::TextEdit::singleline(&mut alias.input)
+                    .hint_text("alias")
+                    .char_limit(MAX_ADAPTER_ALIAS_LEN),
+            );
+            if ui
+                .button("set alias")
+                .on_hover_text("names the adapter, e.g. to tell the internal radio from a dongle")
+                .clicked()
+            {
+                publish(AppEvent::Settings(SettingsEvent::SetAdapterAlias((
+                    key,
+                    Some(alias.input.clone()),
+                ))));
+            }
+        });
+    }
 
     ui.add_enabled_ui(model.is_listening_to().is_none(), |ui| {
         let current = model.get_selected_device();
@@ -180,7 +846,11 @@ pub fn render_bluetooth<F: Fn(AppEvent) + ?Sized>(
                     .map_or(Default::default(), |a| a.name.to_owned()),
             )
             .show_ui(ui, |ui| {
-                let dlock = model.get_devices().blocking_read();
+                // the device list is refreshed while scanning
+                let Ok(dlock) = model.get_devices().try_read() else {
+                    ui.spinner();
+                    return;
+                };
                 for device in dlock.iter() {
                     if ui
                         .selectable_label(
@@ -197,9 +867,38 @@ pub fn render_bluetooth<F: Fn(AppEvent) + ?Sized>(
                     }
                 }
             });
+        ui.horizontal(|ui| {
+            ui.add(
+                egui::TextEdit::singleline(&mut manual.input)
+                    .hint_text("AA:BB:CC:DD:EE:FF")
+                    .desired_width(140.0),
+            );
+            if ui.button("use address").clicked() {
+                match DeviceDescriptor::from_address(&manual.input) {
+                    Ok(device) => {
+                        manual.error = None;
+                        publish(AppEvent::Bluetooth(BluetoothEvent::SelectPeripheral(
+                            device,
+                        )));
+                    }
+                    Err(e) => manual.error = Some(e.to_string()),
+                }
+            }
+        });
+        if let Some(error) = &manual.error {
+            ui.colored_label(Color32::RED, error.as_str());
+        }
     });
 }
 
+/// Fraction of artifacts targeted when tuning the outlier filter automatically.
+const AUTO_ARTIFACT_RATIO: f64 = 0.02;
+
+/// Smallest selectable statistics window in number of samples.
+const MIN_WINDOW_SAMPLES: usize = 30;
+/// Largest selectable statistics window in number of samples.
+const MAX_WINDOW_SAMPLES: usize = 300;
+
 pub fn render_filter_params<F: Fn(AppEvent)>(
     ui: &mut egui::Ui,
     publish: &F,
@@ -207,72 +906,732 @@ pub fn render_filter_params<F: Fn(AppEvent)>(
 ) {
     ui.heading("Filter parameters:");
     egui::Grid::new("a grid").num_columns(2).show(ui, |ui| {
-        let mut samples = model.get_stats_window().unwrap_or(usize::MAX).to_owned();
+        let rr_ts = model.get_rr_ts();
+        let max_samples = rr_ts.len().clamp(MIN_WINDOW_SAMPLES, MAX_WINDOW_SAMPLES);
+        let mut samples = model
+            .get_stats_window()
+            .unwrap_or(usize::MAX)
+            .min(max_samples);
         let desc = egui::Label::new("window size [# samples]");
         ui.add(desc);
-        let slider = egui::Slider::new(&mut samples, RangeInclusive::new(30, 300));
-        if ui.add(slider).changed() {
-            publish(AppEvent::Measurement(MeasurementEvent::SetStatsWindow(
-                samples,
-            )));
-        }
+        ui.horizontal(|ui| {
+            let slider = egui::Slider::new(
+                &mut samples,
+                RangeInclusive::new(MIN_WINDOW_SAMPLES, max_samples),
+            );
+            if ui.add(slider).changed() {
+                publish(AppEvent::Measurement(MeasurementEvent::SetStatsWindow(
+                    samples,
+                )));
+            }
+            // duration covered by the most recent window
+            let window_ms: f64 = rr_ts.iter().rev().take(samples).map(|p| p[1]).sum();
+            ui.label(format!("≈ {:.0} s", window_ms / 1000.0));
+        });
         ui.end_row();
         let mut outlier_value = model.get_outlier_filter_value();
         let desc = egui::Label::new("outlier filter scale");
         ui.add(desc);
-        let slider = egui::Slider::new(&mut outlier_value, RangeInclusive::new(0.5, 10.0));
-        if ui.add(slider).changed() {
-            publish(AppEvent::Measurement(MeasurementEvent::SetOutlierFilter(
-                OutlierFilter::MovingMAD {
-                    parameter: outlier_value,
-                    _window: 5,
-                },
+        ui.horizontal(|ui| {
+            let slider = egui::Slider::new(&mut outlier_value, OUTLIER_SCALE_RANGE);
+            if ui.add(slider).changed() {
+                publish(AppEvent::Measurement(MeasurementEvent::SetOutlierFilter(
+                    OutlierFilter::MovingMAD {
+                        parameter: outlier_value,
+                        _window: 5,
+                    },
+                )));
+            }
+            let auto = ui.button(format!("auto ({:.0} %)", AUTO_ARTIFACT_RATIO * 100.0));
+            if auto
+                .on_hover_text("choose the scale flagging this fraction of the beats")
+                .clicked()
+            {
+                publish(AppEvent::Measurement(
+                    MeasurementEvent::AutoTuneOutlierScale(AUTO_ARTIFACT_RATIO),
+                ));
+            }
+        });
+        ui.end_row();
+        let settling = model.get_settling_filter();
+        let mut enabled = settling.is_some();
+        let mut selected = settling.unwrap_or_default();
+        let mut period = selected.period.whole_seconds();
+        let desc = egui::Label::new("settling phase filter");
+        ui.add(desc).on_hover_text(
+            "judge the beats at the start of the recording by a separate, looser scale",
+        );
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut enabled, "");
+            ui.add_enabled_ui(enabled, |ui| {
+                ui.add(egui::Slider::new(&mut selected.scale, OUTLIER_SCALE_RANGE));
+                ui.add(
+                    egui::DragValue::new(&mut period)
+                        .range(SETTLING_PERIOD_RANGE)
+                        .suffix(" s"),
+                );
+            });
+        });
+        selected.period = Duration::seconds(period);
+        let selected = enabled.then_some(selected);
+        if selected != settling {
+            publish(AppEvent::Measurement(MeasurementEvent::SetSettlingFilter(
+                selected,
+            )));
+        }
+        ui.end_row();
+        let mut threshold = model.get_pnn_threshold();
+        let desc = egui::Label::new("pNNx threshold [ms]");
+        ui.add(desc);
+        if ui
+            .add(egui::DragValue::new(&mut threshold).range(PNN_THRESHOLD_RANGE))
+            .changed()
+        {
+            publish(AppEvent::Measurement(MeasurementEvent::SetPnnThreshold(
+                threshold,
+            )));
+        }
+        ui.end_row();
+        let detrend = model.get_detrend();
+        let mut selected = detrend;
+        let desc = egui::Label::new("detrend RR");
+        ui.add(desc)
+            .on_hover_text("remove slow trends before calculating the statistics");
+        egui::ComboBox::from_id_salt("detrend")
+            .selected_text(detrend.to_string())
+            .show_ui(ui, |ui| {
+                ui.selectable_value(&mut selected, DetrendOption::None, "none");
+                for order in DETREND_ORDER_RANGE {
+                    let option = DetrendOption::Polynomial(order);
+                    ui.selectable_value(&mut selected, option, option.to_string());
+                }
+            });
+        if selected != detrend {
+            publish(AppEvent::Measurement(MeasurementEvent::SetDetrend(
+                selected,
             )));
         }
         ui.end_row();
+        let differences = model.get_differences();
+        let mut selected = differences;
+        let desc = egui::Label::new("successive differences");
+        ui.add(desc).on_hover_text(
+            "pair RR intervals across removed beats, or only adjacent ones for RMSSD and pNNx",
+        );
+        egui::ComboBox::from_id_salt("differences")
+            .selected_text(differences.to_string())
+            .show_ui(ui, |ui| {
+                for mode in DifferenceMode::ALL {
+                    ui.selectable_value(&mut selected, mode, mode.to_string());
+                }
+            });
+        if selected != differences {
+            publish(AppEvent::Measurement(MeasurementEvent::SetDifferences(
+                selected,
+            )));
+        }
+        ui.end_row();
+        let elapsed = model.get_elapsed_time().as_seconds_f64();
+        let (mut start, mut end) = model
+            .get_analysis_range()
+            .map_or((0.0, elapsed), |(start, end)| {
+                (start.as_seconds_f64(), end.as_seconds_f64())
+            });
+        let mut skip = model.get_skip_initial().as_seconds_f64();
+        let desc = egui::Label::new("skip initial [s]");
+        ui.add(desc);
+        if ui
+            .add(egui::DragValue::new(&mut skip).range(0.0..=600.0))
+            .changed()
+        {
+            publish(AppEvent::Measurement(MeasurementEvent::SetSkipInitial(
+                Duration::seconds_f64(skip),
+            )));
+        }
+        ui.end_row();
+        let desc = egui::Label::new("analysis range [s]");
+        ui.add(desc);
+        ui.horizontal(|ui| {
+            let start_changed = ui
+                .add(egui::DragValue::new(&mut start).range(0.0..=end))
+                .changed();
+            let end_changed = ui
+                .add(egui::DragValue::new(&mut end).range(start..=elapsed.max(start)))
+                .changed();
+            if start_changed || end_changed {
+                publish(AppEvent::Measurement(MeasurementEvent::SetAnalysisRange(
+                    Some((Duration::seconds_f64(start), Duration::seconds_f64(end))),
+                )));
+            }
+            if ui.button("full").clicked() {
+                publish(AppEvent::Measurement(MeasurementEvent::SetAnalysisRange(
+                    None,
+                )));
+            }
+        });
+        ui.end_row();
+        let mut config = model.get_hrv_config();
+        let desc = egui::Label::new("DFA box sizes [beats]");
+        ui.add(desc);
+        ui.horizontal(|ui| {
+            let max_box = config.dfa.max_box;
+            let min_changed = ui
+                .add(egui::DragValue::new(&mut config.dfa.min_box).range(4..=max_box - 1))
+                .changed();
+            let min_box = config.dfa.min_box;
+            let max_changed = ui
+                .add(egui::DragValue::new(&mut config.dfa.max_box).range(min_box + 1..=64))
+                .changed();
+            if min_changed || max_changed {
+                publish(AppEvent::Measurement(MeasurementEvent::SetHrvConfig(
+                    config.clone(),
+                )));
+            }
+        });
+        ui.end_row();
+        let desc = egui::Label::new("blank after gap [beats]");
+        ui.add(desc)
+            .on_hover_text("discards the beats the sensor buffered while disconnected");
+        if ui
+            .add(egui::DragValue::new(&mut config.blank_after_gap).range(BLANK_AFTER_GAP_RANGE))
+            .changed()
+        {
+            publish(AppEvent::Measurement(MeasurementEvent::SetHrvConfig(
+                config.clone(),
+            )));
+        }
+        ui.end_row();
+        let desc = egui::Label::new("max identical RR [beats]");
+        ui.add(desc).on_hover_text(
+            "discards repetitions of an identical interval, a flatline of the sensor",
+        );
+        ui.horizontal(|ui| {
+            let mut enabled = config.max_identical_run.is_some();
+            let mut run = config
+                .max_identical_run
+                .unwrap_or(*IDENTICAL_RUN_RANGE.start() + 3);
+            let enabled_changed = ui.checkbox(&mut enabled, "").changed();
+            let run_changed = ui
+                .add_enabled(
+                    enabled,
+                    egui::DragValue::new(&mut run).range(IDENTICAL_RUN_RANGE),
+                )
+                .changed();
+            if enabled_changed || run_changed {
+                config.max_identical_run = enabled.then_some(run);
+                publish(AppEvent::Measurement(MeasurementEvent::SetHrvConfig(
+                    config.clone(),
+                )));
+            }
+        });
+        ui.end_row();
     });
 }
+
+/// Name of the method classifying RR intervals as outliers.
+const OUTLIER_METHOD: &str = "moving quantile";
+
+/// Explains which RR intervals are classified as outliers.
+///
+/// # Returns
+/// The description of the active filter and the share of outliers.
+pub fn outlier_legend(model: &dyn MeasurementModelApi) -> String {
+    format!(
+        "Outlier filter: {} with scale {:.2}.\n\
+         RR intervals outside the range spanned by the quantiles of the surrounding intervals, \
+         widened by the scale, are outliers. They are drawn in the outlier color and excluded \
+         from the statistics.\n\
+         Outliers: {:.1} % of {} beats.",
+        OUTLIER_METHOD,
+        model.get_outlier_filter_value(),
+        100.0 * model.get_artifact_ratio(),
+        model.get_beat_count()
+    )
+}
+
+/// Lists the parameters the displayed metrics were calculated with.
+///
+/// # Returns
+/// `(parameter, value)` pairs in display order.
+pub fn analysis_parameters(model: &dyn MeasurementModelApi) -> Vec<(&'static str, String)> {
+    let config = model.get_hrv_config();
+    let rr_ts = model.get_rr_ts();
+    let window = match config.window {
+        Some(samples) => {
+            // duration covered by the most recent window
+            let window_ms: f64 = rr_ts.iter().rev().take(samples).map(|p| p[1]).sum();
+            format!("{} samples (≈ {:.0} s)", samples, window_ms / 1000.0)
+        }
+        None => "all samples".to_owned(),
+    };
+    let range = model
+        .get_analysis_range()
+        .map_or("full recording".to_owned(), |(start, end)| {
+            format!(
+                "{:.1} - {:.1} s",
+                start.as_seconds_f64(),
+                end.as_seconds_f64()
+            )
+        });
+    vec![
+        ("window", window),
+        (
+            "outlier filter",
+            format!("{}, scale {:.2}", OUTLIER_METHOD, config.outlier_filter),
+        ),
+        ("correction", "exclude outliers".to_owned()),
+        (
+            "settling phase filter",
+            model
+                .get_settling_filter()
+                .map_or("none".to_owned(), |settling| settling.to_string()),
+        ),
+        ("detrend", model.get_detrend().to_string()),
+        (
+            "successive differences",
+            model.get_differences().to_string(),
+        ),
+        (
+            "DFA boxes",
+            format!(
+                "{} - {} beats, {} detrend",
+                config.dfa.min_box, config.dfa.max_box, DFA_DETREND
+            ),
+        ),
+        (
+            "RR gating",
+            format!(
+                "statistics from {} beats, DFA from {} beats",
+                MIN_BEATS,
+                config.dfa.min_beats()
+            ),
+        ),
+        (
+            "skip initial",
+            format!("{:.1} s", model.get_skip_initial().as_seconds_f64()),
+        ),
+        (
+            "blank after gap",
+            format!("{} beats", config.blank_after_gap),
+        ),
+        (
+            "max identical RR",
+            config
+                .max_identical_run
+                .map_or("off".to_owned(), |run| format!("{} beats", run)),
+        ),
+        ("analysis range", range),
+    ]
+}
+
+/// Renders the start of the stable part of a recording, which can be accepted as start of
+/// the analysis range.
+///
+/// # Arguments
+/// * `ui` - The `egui::Ui` to render into.
+/// * `publish` - Function to publish the accepted analysis range.
+/// * `model` - The measurement.
+/// * `threshold` - Maximum change of the mean heart rate in 1/min of a stable recording.
+pub fn render_stabilization<F: Fn(AppEvent) + ?Sized>(
+    ui: &mut egui::Ui,
+    publish: &F,
+    model: &dyn MeasurementModelApi,
+    threshold: f64,
+) {
+    ui.horizontal(
+        |ui| match detect_stabilization(&model.get_rr_ts(), threshold) {
+            Some(start) => {
+                ui.label(format!("stable from {:.0} s", start.as_seconds_f64()));
+                let end = model
+                    .get_analysis_range()
+                    .map_or(model.get_elapsed_time(), |(_, end)| end);
+                if ui
+                    .button("crop")
+                    .on_hover_text("start the analysis range at the stable part")
+                    .clicked()
+                {
+                    publish(AppEvent::Measurement(MeasurementEvent::SetAnalysisRange(
+                        Some((start, end)),
+                    )));
+                }
+            }
+            None => {
+                ui.label("no stable part detected");
+            }
+        },
+    );
+}
+
+/// Renders the read-only list of the analysis parameters.
+pub fn render_analysis_params(ui: &mut egui::Ui, model: &dyn MeasurementModelApi) {
+    egui::CollapsingHeader::new("Analysis parameters").show(ui, |ui| {
+        egui::Grid::new("analysis parameters")
+            .num_columns(2)
+            .show(ui, |ui| {
+                for (name, value) in analysis_parameters(model) {
+                    ui.label(name);
+                    ui.label(value);
+                    ui.end_row();
+                }
+            });
+    });
+}
+
+/// Renders the subject information of a measurement.
+///
+/// # Arguments
+/// * `ui` - The `egui::Ui` to render into.
+/// * `publish` - Function to publish the entered metadata.
+/// * `model` - The measurement.
+/// * `edit` - Metadata as currently entered, `None` shows the metadata of `model`.
+pub fn render_metadata<F: Fn(AppEvent) + ?Sized>(
+    ui: &mut egui::Ui,
+    publish: &F,
+    model: &dyn MeasurementModelApi,
+    edit: &mut Option<MeasurementMetadata>,
+) {
+    egui::CollapsingHeader::new("Subject").show(ui, |ui| {
+        let metadata =
+            edit.get_or_insert_with(|| model.get_metadata().cloned().unwrap_or_default());
+        egui::Grid::new("subject metadata")
+            .num_columns(2)
+            .show(ui, |ui| {
+                ui.label("subject ID");
+                ui.add(
+                    egui::TextEdit::singleline(&mut metadata.subject_id)
+                        .char_limit(MAX_SUBJECT_ID_LEN)
+                        .hint_text("anonymized"),
+                );
+                ui.end_row();
+
+                ui.label("age");
+                ui.horizontal(|ui| {
+                    let mut known = metadata.age.is_some();
+                    ui.checkbox(&mut known, "");
+                    let mut age = metadata.age.unwrap_or(30);
+                    ui.add_enabled(
+                        known,
+                        egui::DragValue::new(&mut age)
+                            .range(AGE_RANGE)
+                            .suffix(" years"),
+                    );
+                    metadata.age = known.then_some(age);
+                });
+                ui.end_row();
+
+                ui.label("sex");
+                egui::ComboBox::from_id_salt("subject sex")
+                    .selected_text(metadata.sex.map_or("–".to_owned(), |sex| sex.to_string()))
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(&mut metadata.sex, None, "–");
+                        for sex in Sex::ALL {
+                            ui.selectable_value(&mut metadata.sex, Some(sex), sex.to_string());
+                        }
+                    });
+                ui.end_row();
+
+                ui.label("posture");
+                egui::ComboBox::from_id_salt("subject posture")
+                    .selected_text(
+                        metadata
+                            .posture
+                            .map_or("–".to_owned(), |posture| posture.to_string()),
+                    )
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(&mut metadata.posture, None, "–");
+                        for posture in Posture::ALL {
+                            ui.selectable_value(
+                                &mut metadata.posture,
+                                Some(posture),
+                                posture.to_string(),
+                            );
+                        }
+                    });
+                ui.end_row();
+            });
+        let changed = model.get_metadata().cloned().unwrap_or_default() != *metadata;
+        if ui
+            .add_enabled(changed, egui::Button::new("Apply"))
+            .clicked()
+        {
+            publish(AppEvent::Measurement(MeasurementEvent::SetMetadata(
+                edit.take(),
+            )));
+        }
+    });
+}
+
+/// Renders the analysis profile of the selected device.
+///
+/// A saved profile is applied by the controller whenever the device is selected.
+pub fn render_profile<F: Fn(AppEvent) + ?Sized>(
+    ui: &mut egui::Ui,
+    publish: &F,
+    model: &dyn MeasurementModelApi,
+    bt_model: &dyn BluetoothModelApi,
+    settings: &AppSettings,
+    name: &mut String,
+) {
+    ui.heading("Device profile:");
+    let Some(device) = bt_model.get_selected_device() else {
+        ui.label("select a device to save its profile");
+        return;
+    };
+    let address = device.address.to_string();
+    match settings.get_profile(&address) {
+        Some(profile) => ui.label(format!("profile: {}", profile.name)),
+        None => ui.label("no profile saved"),
+    };
+    ui.horizontal(|ui| {
+        ui.add(
+            egui::TextEdit::singleline(name)
+                .hint_text(device.name.as_str())
+                .desired_width(140.0),
+        );
+        if ui.button("save profile").clicked() {
+            let name = if name.is_empty() {
+                device.name.clone()
+            } else {
+                name.clone()
+            };
+            publish(AppEvent::Settings(SettingsEvent::SaveProfile(
+                DeviceProfile {
+                    address,
+                    name,
+                    config: model.get_hrv_config(),
+                },
+            )));
+        }
+    });
+}
+
+/// Renders the selection of the analysis templates.
+///
+/// A template is applied to the shown measurement when it is selected, the default template
+/// is applied to new recordings and imports.
+pub fn render_templates<F: Fn(AppEvent) + ?Sized>(
+    ui: &mut egui::Ui,
+    publish: &F,
+    model: &dyn MeasurementModelApi,
+    settings: &AppSettings,
+    name: &mut String,
+) {
+    egui::CollapsingHeader::new("Analysis templates").show(ui, |ui| {
+        egui::Grid::new("templates").num_columns(2).show(ui, |ui| {
+            ui.label("apply");
+            egui::ComboBox::from_id_salt("apply template")
+                .selected_text("select template")
+                .show_ui(ui, |ui| {
+                    for template in &settings.templates {
+                        if ui.selectable_label(false, template.name.as_str()).clicked() {
+                            publish(AppEvent::Measurement(MeasurementEvent::SetHrvConfig(
+                                template.config.clone(),
+                            )));
+                        }
+                    }
+                });
+            ui.end_row();
+            ui.label("default")
+                .on_hover_text("applied to new recordings and imports");
+            let mut default = settings.default_template.clone();
+            egui::ComboBox::from_id_salt("default template")
+                .selected_text(default.as_deref().unwrap_or("none"))
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut default, None, "none");
+                    for template in &settings.templates {
+                        ui.selectable_value(
+                            &mut default,
+                            Some(template.name.clone()),
+                            template.name.as_str(),
+                        );
+                    }
+                });
+            if default != settings.default_template {
+                publish(AppEvent::Settings(SettingsEvent::SetDefaultTemplate(
+                    default,
+                )));
+            }
+            ui.end_row();
+        });
+        ui.horizontal(|ui| {
+            ui.add(
+                egui::TextEdit::singleline(name)
+                    .hint_text("template name")
+                    .desired_width(140.0),
+            );
+            let named = !name.trim().is_empty();
+            if ui
+                .add_enabled(named, egui::Button::new("save"))
+                .on_hover_text("save the current parameters, replacing a template of the same name")
+                .clicked()
+            {
+                publish(AppEvent::Settings(SettingsEvent::SaveTemplate(
+                    AnalysisTemplate {
+                        name: name.trim().to_owned(),
+                        config: model.get_hrv_config(),
+                    },
+                )));
+            }
+            let exists = settings.get_template(name.trim()).is_some();
+            if ui
+                .add_enabled(exists, egui::Button::new("delete"))
+                .clicked()
+            {
+                publish(AppEvent::Settings(SettingsEvent::DeleteTemplate(
+                    name.trim().to_owned(),
+                )));
+            }
+        });
+    });
+}
+
 /// `HrvView` structure.
 ///
 /// Represents the view for visualizing HRV analysis results, including statistics and charts.
 pub struct AcquisitionView {
-    /// Shared access to the runtime HRV data model.
-    model: ModelHandle<dyn MeasurementModelApi>,
+    /// Snapshots of the runtime HRV data model.
+    model: ModelSnapshot,
     bt_model: ModelHandle<dyn BluetoothModelApi>,
+    /// Manually entered device address.
+    manual_address: ManualAddress,
+    /// Alias of the selected adapter as currently entered.
+    adapter_alias: AliasInput,
+    /// Shared settings.
+    settings: ModelHandle<AppSettings>,
+    /// Smooths the live readout of the statistics.
+    smoother: MetricSmoother,
+    /// Name of the device profile to save.
+    profile_name: String,
+    /// Name of the analysis template to save or delete.
+    template_name: String,
+    /// Label of the next marker.
+    marker_label: String,
+    /// Subject information as currently entered.
+    metadata: Option<MeasurementMetadata>,
 }
 
 impl AcquisitionView {
     /// Creates a new `HrvView` instance.
     ///
     /// # Arguments
-    /// * `model` - Shared access to the runtime HRV data.
+    /// * `model` - Snapshots of the runtime HRV data.
+    /// * `bt_model` - Shared access to the bluetooth model.
+    /// * `settings` - The shared settings.
     ///
     /// # Returns
     /// A new `HrvView` instance.
     pub fn new(
-        model: ModelHandle<dyn MeasurementModelApi>,
+        model: ModelSnapshot,
         bt_model: ModelHandle<dyn BluetoothModelApi>,
+        settings: ModelHandle<AppSettings>,
     ) -> Self {
-        Self { model, bt_model }
+        Self {
+            model,
+            bt_model,
+            manual_address: ManualAddress::default(),
+            adapter_alias: AliasInput::default(),
+            settings,
+            smoother: MetricSmoother::default(),
+            profile_name: String::new(),
+            template_name: String::new(),
+            marker_label: String::new(),
+            metadata: None,
+        }
+    }
+
+    /// Checks if the view shows a recording that was not stored yet.
+    pub fn has_unsaved_data(&self) -> bool {
+        self.model.borrow().get_last_msg().is_some()
+    }
+
+    /// Renders the input of the next marker.
+    ///
+    /// The marker is added by the button or the `MARKER_SHORTCUT`, markers without label
+    /// are numbered.
+    fn render_marker<F: Fn(AppEvent)>(
+        ui: &mut egui::Ui,
+        publish: &F,
+        label: &mut String,
+        count: usize,
+    ) {
+        ui.horizontal(|ui| {
+            ui.add(egui::TextEdit::singleline(label).hint_text("marker label"));
+            let shortcut = ui.input_mut(|i| i.consume_shortcut(&MARKER_SHORTCUT));
+            let button = ui
+                .button("add marker")
+                .on_hover_text(ui.ctx().format_shortcut(&MARKER_SHORTCUT));
+            if button.clicked() || shortcut {
+                let label = std::mem::take(label);
+                let label = if label.trim().is_empty() {
+                    format!("marker {}", count + 1)
+                } else {
+                    label.trim().to_owned()
+                };
+                publish(AppEvent::Measurement(MeasurementEvent::AddMarker(label)));
+            }
+        });
+    }
+
+    /// Explains why the recording can not be saved yet.
+    ///
+    /// # Arguments
+    /// * `model` - The recording.
+    /// * `min_beats` - Minimum number of inlier beats of a saved recording.
+    ///
+    /// # Returns
+    /// The reason, or `None` if the recording has enough inlier beats to be saved.
+    fn save_blocked(model: &dyn MeasurementModelApi, min_beats: usize) -> Option<String> {
+        let inliers = model.get_inlier_count();
+        (inliers < min_beats).then(|| {
+            format!(
+                "{} of {} usable beats recorded, shorter recordings can only be discarded",
+                inliers, min_beats
+            )
+        })
     }
 
-    fn render_acq<F: Fn(AppEvent)>(&self, ui: &mut egui::Ui, publish: &F) {
+    fn render_acq<F: Fn(AppEvent)>(
+        ui: &mut egui::Ui,
+        publish: &F,
+        is_paused: bool,
+        save_blocked: Option<String>,
+    ) {
         ui.heading("Acquisition");
         ui.horizontal(|ui| {
             if ui.button("start").clicked() {
                 publish(AppEvent::Recording(RecordingEvent::StartRecording));
             }
+            if is_paused {
+                if ui.button("resume").clicked() {
+                    publish(AppEvent::Recording(RecordingEvent::ResumeRecording));
+                }
+            } else if ui.button("pause").clicked() {
+                publish(AppEvent::Recording(RecordingEvent::PauseRecording));
+            }
             if ui.button("stop").clicked() {
                 publish(AppEvent::Recording(RecordingEvent::StopRecording));
             }
             if ui.button("discard").clicked() {
-                publish(AppEvent::Recording(RecordingEvent::StopRecording));
+                // discard first, so the recording is not auto-saved on stop
                 publish(AppEvent::AppState(StateChangeEvent::DiscardRecording));
+                publish(AppEvent::Recording(RecordingEvent::StopRecording));
             }
-            if ui.button("Save").clicked() {
+            let reason = save_blocked.as_deref().unwrap_or_default();
+            if ui
+                .add_enabled(save_blocked.is_none(), egui::Button::new("Save"))
+                .on_disabled_hover_text(reason)
+                .clicked()
+            {
                 publish(AppEvent::Recording(RecordingEvent::StopRecording));
                 publish(AppEvent::AppState(StateChangeEvent::StoreRecording));
             }
+            if ui
+                .add_enabled(save_blocked.is_none(), egui::Button::new("Save & New"))
+                .on_disabled_hover_text(reason)
+                .clicked()
+            {
+                publish(AppEvent::AppState(StateChangeEvent::StoreAndContinue));
+            }
         });
     }
 }
@@ -292,22 +1651,67 @@ impl ViewApi for AcquisitionView {
         publish: &F,
         ctx: &egui::Context,
     ) -> Result<(), String> {
-        let model = self.model.blocking_read();
-        let bt_model = self.bt_model.blocking_read();
+        // release the snapshot right away, rendering works on the cloned handle
+        let model = self.model.borrow().clone();
+        let bt_model = lock::read(&self.bt_model)?;
+        let settings = lock::read(&self.settings)?.clone();
         // Extract HRV statistics and Poincare plot points from the model.
 
         // Render the left panel with HRV statistics.
         egui::SidePanel::left("left_sidebar").show(ctx, |ui| {
-            render_bluetooth(ui, publish, &*bt_model);
+            render_bluetooth(
+                ui,
+                publish,
+                &*bt_model,
+                &mut self.manual_address,
+                &settings,
+                &mut self.adapter_alias,
+            );
             ui.separator();
 
-            self.render_acq(ui, &publish);
+            Self::render_acq(
+                ui,
+                &publish,
+                model.is_paused(),
+                Self::save_blocked(&*model, settings.min_save_beats),
+            );
+            Self::render_marker(
+                ui,
+                &publish,
+                &mut self.marker_label,
+                model.get_markers().len(),
+            );
+            render_metadata(ui, publish, &*model, &mut self.metadata);
             ui.separator();
             render_filter_params(ui, &publish, &*model);
+            render_templates(ui, publish, &*model, &settings, &mut self.template_name);
+            render_analysis_params(ui, &*model);
+            ui.separator();
+            render_profile(
+                ui,
+                publish,
+                &*model,
+                &*bt_model,
+                &settings,
+                &mut self.profile_name,
+            );
             let msg = model.get_last_msg();
-            if let Some(msg) = msg {
+            ui.separator();
+            self.smoother.set_smoothing(settings.smoothing);
+            let precision = settings.display.get_precision();
+            render_stats(
+                ui,
+                &*model,
+                msg.map_or(0.0, |msg| msg.get_hr()),
+                Some(&mut self.smoother),
+                precision,
+            );
+            render_instant_sd1(ui, &*model, precision);
+            render_signal_quality(ui, &*model);
+            render_rr_difference_histogram(ui, &*model);
+            if msg.is_some() {
                 ui.separator();
-                render_stats(ui, &*model, msg.get_hr());
+                render_recent_rr(ui, &*model);
             }
         });
 
@@ -315,12 +1719,292 @@ impl ViewApi for AcquisitionView {
             .min_height(100.0)
             .resizable(true)
             .show(ctx, |ui| {
-                render_time_series(ui, &*model);
+                render_time_plots(ui, publish, &*model, model.get_metrics(), &settings);
             });
         egui::CentralPanel::default().show(ctx, |ui| {
-            render_poincare_plot(ui, &*model);
+            render_poincare_plot(ui, &*model, &settings.poincare, settings.palette);
         });
 
         Ok(()) // no errors
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        api::controller::{MeasurementApi, RecordingApi},
+        components::measurement::MeasurementData,
+        model::{
+            bluetooth::HeartrateMessage,
+            hrv::{tests::get_data, HrvConfig, SettlingFilter},
+            metrics::DfaConfig,
+        },
+    };
+    use egui_plot::{PlotBounds, PlotMemory};
+
+    #[tokio::test]
+    async fn test_save_blocked_below_min_beats() {
+        let mut measurement = MeasurementData::default();
+        measurement.start_recording().await.unwrap();
+        for idx in 0..10u16 {
+            let msg = HeartrateMessage::from_values(60, None, &[800 + 20 * (idx % 2)]);
+            measurement.record_message(msg).await.unwrap();
+        }
+        let inliers = measurement.get_inlier_count();
+        assert!(inliers <= 10);
+        let reason = AcquisitionView::save_blocked(&measurement, 30);
+        assert!(reason.is_some_and(|reason| reason.starts_with(&format!("{} of 30", inliers))));
+        assert!(AcquisitionView::save_blocked(&measurement, 0).is_none());
+
+        for idx in 10..60u16 {
+            let msg = HeartrateMessage::from_values(60, None, &[800 + 20 * (idx % 2)]);
+            measurement.record_message(msg).await.unwrap();
+        }
+        assert!(measurement.get_inlier_count() >= 30);
+        assert!(AcquisitionView::save_blocked(&measurement, 30).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_analysis_parameters() {
+        let mut measurement = MeasurementData::default();
+        measurement.start_recording().await.unwrap();
+        for (_, msg) in get_data(60) {
+            measurement.record_message(msg).await.unwrap();
+        }
+        let defaults = analysis_parameters(&measurement);
+        measurement
+            .set_hrv_config(HrvConfig {
+                window: Some(30),
+                outlier_filter: 3.0,
+                dfa: DfaConfig {
+                    min_box: 5,
+                    max_box: 10,
+                },
+                blank_after_gap: 3,
+                max_identical_run: Some(4),
+            })
+            .await
+            .unwrap();
+        measurement
+            .set_skip_initial(Duration::seconds(10))
+            .await
+            .unwrap();
+        measurement
+            .set_settling_filter(Some(SettlingFilter {
+                period: Duration::seconds(30),
+                scale: 6.0,
+            }))
+            .await
+            .unwrap();
+        measurement
+            .set_analysis_range(Some((Duration::seconds(10), Duration::seconds(50))))
+            .await
+            .unwrap();
+        let params = analysis_parameters(&measurement);
+        let value = |name: &str| {
+            params
+                .iter()
+                .find(|(param, _)| *param == name)
+                .map(|(_, value)| value.clone())
+                .unwrap()
+        };
+        assert_eq!(params.len(), defaults.len());
+        assert!(value("window").starts_with("30 samples"));
+        assert_eq!(value("outlier filter"), "moving MAD, scale 3.00");
+        assert_eq!(value("DFA boxes"), "5 - 10 beats, linear detrend");
+        assert_eq!(value("max identical RR"), "4 beats");
+        assert_eq!(
+            value("RR gating"),
+            format!("statistics from {} beats, DFA from 40 beats", MIN_BEATS)
+        );
+        assert_eq!(value("skip initial"), "10.0 s");
+        assert_eq!(value("blank after gap"), "3 beats");
+        assert_eq!(value("settling phase filter"), "scale 6.0 for 30 s");
+        assert_eq!(value("analysis range"), "10.0 - 50.0 s");
+        assert_ne!(params, defaults);
+    }
+
+    #[tokio::test]
+    async fn test_outlier_legend() {
+        let mut measurement = MeasurementData::default();
+        let mut data = get_data(60);
+        data[30].1 = HeartrateMessage::from_values(60, None, &[20000]);
+        measurement.import_messages(data).unwrap();
+        measurement
+            .set_outlier_filter(OutlierFilter::MovingMAD {
+                parameter: 4.0,
+                _window: 5,
+            })
+            .await
+            .unwrap();
+        let legend = outlier_legend(&measurement);
+        assert!(legend.starts_with("Outlier filter: moving quantile with scale 4.00."));
+        assert!(legend.contains("quantiles of the surrounding intervals"));
+        assert!(!legend.contains("MAD"));
+        let ratio = 100.0 * measurement.get_artifact_ratio();
+        assert!(ratio > 0.0);
+        assert!(legend.ends_with(&format!("Outliers: {:.1} % of 60 beats.", ratio)));
+    }
+
+    #[test]
+    fn test_histogram() {
+        let bins = histogram(&[-12.0, 3.0, -4.0, 8.0, 14.9, 250.0], 10.0);
+        assert_eq!(bins, vec![(-10.0, 1), (0.0, 2), (10.0, 2), (250.0, 1)]);
+        assert!(histogram(&[], 10.0).is_empty());
+    }
+
+    #[test]
+    fn test_histogram_2d() {
+        let points = [
+            [1000.0, 1000.0],
+            [1004.0, 996.0],
+            [1006.0, 1000.0],
+            [1000.0, 1012.0],
+            [995.0, 1004.9],
+            [1200.0, 800.0],
+        ];
+        let bins = histogram_2d(&points, 10.0);
+        assert_eq!(
+            bins,
+            vec![
+                ([1000.0, 1000.0], 3),
+                ([1000.0, 1010.0], 1),
+                ([1010.0, 1000.0], 1),
+                ([1200.0, 800.0], 1),
+            ]
+        );
+        assert_eq!(
+            bins.iter().map(|(_, count)| count).sum::<usize>(),
+            points.len()
+        );
+        assert!(histogram_2d(&[], 10.0).is_empty());
+    }
+
+    #[test]
+    fn test_no_contact_spans() {
+        let track = [
+            (1.0, true),
+            (2.0, false),
+            (3.0, false),
+            (4.0, true),
+            (5.0, false),
+        ];
+        assert_eq!(no_contact_spans(&track), vec![(1.0, 3.0), (4.0, 5.0)]);
+        assert_eq!(no_contact_spans(&[(1.0, false)]), vec![(1.0, 1.0)]);
+        assert!(no_contact_spans(&[(1.0, true), (2.0, true)]).is_empty());
+        assert_eq!(
+            value_range(&[[0.0, 3.0], [1.0, f64::NAN], [2.0, -1.0]]),
+            Some((-1.0, 3.0))
+        );
+        assert_eq!(value_range(&[]), None);
+    }
+
+    #[test]
+    fn test_visible_metrics() {
+        let series = |name: &str| MetricSeries {
+            name: name.to_owned(),
+            unit: "ms".to_owned(),
+            time_series: vec![[0.0, 1.0]],
+        };
+        let metrics = vec![series("RMSSD"), series("SDRR"), series("SD1")];
+        let names = |visible: &[(usize, MetricSeries)]| -> Vec<(usize, String)> {
+            visible
+                .iter()
+                .map(|(idx, metric)| (*idx, metric.name.clone()))
+                .collect()
+        };
+
+        let all = visible_metrics(metrics.clone(), &[]);
+        assert_eq!(names(&all).len(), 3);
+        // hidden metrics are not drawn, the others keep their colors
+        let visible = visible_metrics(metrics.clone(), &["SDRR".to_owned()]);
+        assert_eq!(
+            names(&visible),
+            [(0, "RMSSD".to_owned()), (2, "SD1".to_owned())]
+        );
+        // unknown names are ignored
+        let visible = visible_metrics(metrics, &["DFA α".to_owned(), "RMSSD".to_owned()]);
+        assert_eq!(
+            names(&visible),
+            [(1, "SDRR".to_owned()), (2, "SD1".to_owned())]
+        );
+    }
+
+    #[test]
+    fn test_finite_segments() {
+        let series = [
+            [0.0, f64::NAN],
+            [1.0, 1.0],
+            [2.0, 1.1],
+            [3.0, f64::NAN],
+            [4.0, 0.9],
+            [5.0, f64::INFINITY],
+            [f64::NAN, 1.0],
+        ];
+        assert_eq!(
+            finite_segments(&series),
+            vec![vec![[1.0, 1.0], [2.0, 1.1]], vec![[4.0, 0.9]]]
+        );
+        assert!(finite_segments(&[[0.0, f64::NAN]]).is_empty());
+        assert_eq!(finite_segments(&[[0.0, 1.0]]), vec![vec![[0.0, 1.0]]]);
+
+        // a series with NaN is drawn as gapped line within finite bounds
+        let ctx = egui::Context::default();
+        let mut bounds = None;
+        let _ = ctx.run(egui::RawInput::default(), |ctx| {
+            egui::CentralPanel::default().show(ctx, |ui| {
+                let response = Plot::new("gapped").show(ui, |plot_ui| {
+                    plot_series(plot_ui, &series, "DFA α", Color32::RED);
+                });
+                bounds = Some(*response.transform.bounds());
+            });
+        });
+        let bounds = bounds.unwrap();
+        assert!(bounds.is_finite());
+        assert!(bounds.min()[0] <= 1.0 && bounds.max()[0] >= 4.0);
+    }
+
+    #[tokio::test]
+    async fn test_render_linked_time_plots() {
+        let mut measurement = MeasurementData::default();
+        measurement.start_recording().await.unwrap();
+        for (_, msg) in get_data(60) {
+            measurement.record_message(msg).await.unwrap();
+        }
+        let ctx = egui::Context::default();
+        let mut ids = Vec::new();
+        let _ = ctx.run(egui::RawInput::default(), |ctx| {
+            egui::CentralPanel::default().show(ctx, |ui| {
+                // zooms the time axis of the link group
+                time_plot("zoomed", false).show(ui, |plot_ui| {
+                    plot_ui.set_plot_bounds(PlotBounds::from_min_max([10.0, 0.0], [20.0, 1.0]));
+                });
+                ui.columns(2, |columns| {
+                    ids.push(columns[0].make_persistent_id("Tachogram"));
+                    render_tachogram(
+                        &mut columns[0],
+                        &measurement,
+                        false,
+                        ColorPalette::ColorBlindSafe,
+                    );
+                    ids.push(columns[1].make_persistent_id("Time series"));
+                    render_time_series(
+                        &mut columns[1],
+                        &measurement,
+                        visible_metrics(measurement.get_metrics(), &[]),
+                        false,
+                        &HrZones::default(),
+                        1,
+                        ColorPalette::ColorBlindSafe,
+                    );
+                });
+            });
+        });
+        // both plots follow the zoomed time axis instead of fitting their data
+        for id in ids {
+            let bounds = *PlotMemory::load(&ctx, id).unwrap().bounds();
+            assert_eq!((bounds.min()[0], bounds.max()[0]), (10.0, 20.0));
+        }
+    }
+}
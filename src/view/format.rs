@@ -0,0 +1,67 @@
+//! Metric Formatting
+//!
+//! This module centralizes how metric values are displayed, so every view shows a metric
+//! with the same label, unit and number of decimals.
+
+/// Label of the heart rate reported by the sensor.
+pub const HEARTRATE: &str = "Heartrate";
+/// Label of the standard deviation of the segment mean RR intervals.
+pub const SDANN: &str = "SDANN";
+/// Label of the mean of the segment standard deviations.
+pub const SDNN_INDEX: &str = "SDNN index";
+
+/// Display properties of a metric.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MetricFormat {
+    /// Label shown next to the value.
+    pub label: String,
+    /// Unit appended to the value, empty for unitless metrics.
+    pub unit: String,
+    /// Number of decimals.
+    pub precision: usize,
+}
+
+impl MetricFormat {
+    /// Returns the format of the metric `name`.
+    ///
+    /// # Arguments
+    /// * `name` - Name of the metric.
+    /// * `unit` - Unit of the metric, as reported by `HrvMetric::unit` for the analysed
+    ///   metrics.
+    /// * `precision` - Number of decimals.
+    pub fn of(name: &str, unit: &str, precision: usize) -> Self {
+        Self {
+            label: name.to_owned(),
+            unit: unit.to_owned(),
+            precision,
+        }
+    }
+
+    /// Formats a value of the metric.
+    pub fn format(&self, value: f64) -> String {
+        if self.unit.is_empty() {
+            format!("{:.*}", self.precision, value)
+        } else {
+            format!("{:.*} {}", self.precision, value, self.unit)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::metrics::{DfaAlpha, HeartRate, HrvMetric, Rmssd};
+
+    #[test]
+    fn test_metric_format() {
+        let format = |metric: &dyn HrvMetric, precision| {
+            MetricFormat::of(metric.name(), metric.unit(), precision)
+        };
+        assert_eq!(format(&Rmssd, 2).format(42.123), "42.12 ms");
+        assert_eq!(format(&HeartRate, 0).format(61.6), "62 1/min");
+        // unitless metrics are formatted without a trailing space
+        assert_eq!(format(&DfaAlpha::default(), 3).format(0.98765), "0.988");
+        assert_eq!(MetricFormat::of(SDANN, "ms", 1).format(42.16), "42.2 ms");
+        assert_eq!(MetricFormat::of("pNN50", "%", 2).format(12.0), "12.00 %");
+    }
+}
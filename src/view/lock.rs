@@ -0,0 +1,51 @@
+//! Lock Access
+//!
+//! The views render on the egui thread, while the components hold the shared tokio locks
+//! across `await` points. Blocking on such a lock would freeze the UI, and `blocking_read`
+//! panics if the frame is rendered from within the runtime. The views therefore only try
+//! to acquire the locks and skip the frame if a lock is momentarily held.
+
+use tokio::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+/// Error returned by a view that skipped its frame because a lock was held.
+pub const SKIPPED_FRAME: &str = "lock held, frame skipped";
+
+/// Time until a skipped frame is rendered again.
+const RETRY_INTERVAL: std::time::Duration = std::time::Duration::from_millis(50);
+
+/// Tries to lock `lock` for reading without blocking.
+///
+/// # Returns
+/// The guard, or `SKIPPED_FRAME` if the lock is held for writing.
+pub fn read<T: ?Sized>(lock: &RwLock<T>) -> Result<RwLockReadGuard<'_, T>, String> {
+    lock.try_read().map_err(|_| SKIPPED_FRAME.to_owned())
+}
+
+/// Tries to lock `lock` for writing without blocking.
+///
+/// # Returns
+/// The guard, or `SKIPPED_FRAME` if the lock is held.
+pub fn write<T: ?Sized>(lock: &RwLock<T>) -> Result<RwLockWriteGuard<'_, T>, String> {
+    lock.try_write().map_err(|_| SKIPPED_FRAME.to_owned())
+}
+
+/// Checks if a render error only reports a skipped frame.
+pub fn is_skipped(error: &str) -> bool {
+    error == SKIPPED_FRAME
+}
+
+/// Renders the placeholder of a skipped frame and schedules the next attempt.
+///
+/// # Arguments
+/// * `ctx` - The Egui context.
+pub fn render_refreshing(ctx: &egui::Context) {
+    egui::CentralPanel::default().show(ctx, |ui| {
+        ui.centered_and_justified(|ui| {
+            ui.horizontal(|ui| {
+                ui.spinner();
+                ui.label("refreshing…");
+            });
+        });
+    });
+    ctx.request_repaint_after(RETRY_INTERVAL);
+}
@@ -9,7 +9,7 @@
 ///
 /// Returns an `Ok(HrvStatistics)` containing the calculated HRV statistics, or
 /// an `Err` if there is insufficient data.
-use std::sync::Arc;
+use std::{sync::Arc, time::Instant};
 
 use eframe::App;
 use log::error;
@@ -23,13 +23,21 @@ use tokio::{
 
 use crate::{
     api::{
-        model::{BluetoothModelApi, MeasurementModelApi, ModelHandle, StorageModelApi},
+        model::{BluetoothModelApi, ModelHandle, ModelSnapshot, StorageModelApi},
         view::ViewApi,
     },
-    core::events::AppEvent,
+    core::{
+        error::ErrorKind,
+        events::{AppEvent, RecordingEvent, StateChangeEvent, StorageEvent},
+    },
+    model::{operation::FileOperation, settings::AppSettings},
 };
 
-use super::{acquisition::AcquisitionView, overview::StorageView};
+use super::{
+    acquisition::AcquisitionView,
+    lock,
+    overview::{storage_dialog, StorageView},
+};
 
 /// Represents the different states of the application's view.
 ///
@@ -37,19 +45,23 @@ use super::{acquisition::AcquisitionView, overview::StorageView};
 #[derive(Clone, Debug)]
 pub enum ViewState {
     /// The overview view displaying stored acquisitions.
-    Overview(
-        (
-            ModelHandle<dyn StorageModelApi>,
-            Option<ModelHandle<dyn MeasurementModelApi>>,
-        ),
-    ),
+    Overview((ModelHandle<dyn StorageModelApi>, Option<ModelSnapshot>)),
     /// The acquisition view for real-time data collection.
-    Acquisition(
-        (
-            ModelHandle<dyn MeasurementModelApi>,
-            ModelHandle<dyn BluetoothModelApi>,
-        ),
-    ),
+    Acquisition((ModelSnapshot, ModelHandle<dyn BluetoothModelApi>)),
+}
+
+/// A message shown on top of the active view until it is dismissed.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Notification {
+    /// An operation failed, with the kind of the error to offer a way to resolve it.
+    Error((ErrorKind, String)),
+    /// Something happened that needs attention, e.g. the sensor was disconnected.
+    Warning(String),
+    /// A condition of the recorded person that needs immediate attention, shown as banner.
+    Alert(String),
+    /// A recording starts automatically at the given time, `None` when it was cancelled or
+    /// started. Replaces the previous countdown.
+    Countdown(Option<Instant>),
 }
 
 /// Enumeration of the application's views.
@@ -86,23 +98,32 @@ impl ViewApi for View {
     }
 }
 
-impl From<ViewState> for View {
+impl View {
+    /// Checks if closing the view would lose a recording.
+    fn has_unsaved_data(&self) -> bool {
+        match self {
+            Self::Acquisition(v) => v.has_unsaved_data(),
+            _ => false,
+        }
+    }
+
     /// Converts a `ViewState` into a `View`.
     ///
     /// Initializes the appropriate view based on the state.
     ///
     /// # Arguments
     /// * `val` - The `ViewState` to convert.
+    /// * `settings` - The settings shared with the views.
     ///
     /// # Returns
     /// A `View` instance.
-    fn from(val: ViewState) -> Self {
+    fn from_state(val: ViewState, settings: ModelHandle<AppSettings>) -> Self {
         match val {
             ViewState::Acquisition((model, bt_model)) => {
-                View::Acquisition(AcquisitionView::new(model, bt_model))
+                View::Acquisition(AcquisitionView::new(model, bt_model, settings))
             }
             ViewState::Overview((model, measurement)) => {
-                View::Overview(StorageView::new(model, measurement))
+                View::Overview(StorageView::new(model, measurement, settings))
             }
         }
     }
@@ -116,8 +137,26 @@ pub struct ViewManager {
     e_tx: Sender<AppEvent>,
     /// The currently active view wrapped in a thread-safe `Arc<RwLock>`.
     active_view: Arc<RwLock<View>>,
+    /// The settings shared with the views.
+    settings: ModelHandle<AppSettings>,
+    /// Notifications that were not dismissed yet.
+    notifications: Arc<RwLock<Vec<Notification>>>,
+    /// The running file operation of the storage.
+    file_operation: Arc<FileOperation>,
+    /// Whether the user is asked to save the recording before exiting.
+    exit_prompt: bool,
+    /// Whether the user confirmed to exit.
+    exit_confirmed: bool,
     /// Handle for the background task that listens for view state changes.
-    _task_handle: JoinHandle<()>,
+    task_handle: JoinHandle<()>,
+}
+
+impl Drop for ViewManager {
+    /// Stops the background task, so it does not keep receiving view states and
+    /// notifications once the views are gone.
+    fn drop(&mut self) {
+        self.task_handle.abort();
+    }
 }
 
 impl ViewManager {
@@ -125,23 +164,54 @@ impl ViewManager {
     ///
     /// # Arguments
     /// * `v_rx` - Receiver for `ViewState` updates.
+    /// * `n_rx` - Receiver for `Notification`s.
     /// * `e_tx` - Sender for `AppEvent`s.
+    /// * `settings` - The settings shared with the views.
+    /// * `file_operation` - The running file operation of the storage.
     ///
     /// # Returns
     /// A new instance of `ViewManager`.
-    pub fn new(mut v_rx: Receiver<ViewState>, e_tx: Sender<AppEvent>) -> Self {
+    pub fn new(
+        mut v_rx: Receiver<ViewState>,
+        mut n_rx: Receiver<Notification>,
+        e_tx: Sender<AppEvent>,
+        settings: ModelHandle<AppSettings>,
+        file_operation: Arc<FileOperation>,
+    ) -> Self {
         let active_view = Arc::new(RwLock::new(View::Empty));
+        let notifications = Arc::new(RwLock::new(Vec::new()));
         let task_view = active_view.clone();
-        let _task_handle = tokio::spawn(async move {
-            while let Ok(s) = v_rx.recv().await {
-                *task_view.write().await = s.into();
+        let task_notifications = notifications.clone();
+        let task_settings = settings.clone();
+        let task_handle = tokio::spawn(async move {
+            loop {
+                tokio::select! {
+                    Ok(s) = v_rx.recv() => {
+                        *task_view.write().await = View::from_state(s, task_settings.clone());
+                    }
+                    Ok(n) = n_rx.recv() => {
+                        let mut notifications = task_notifications.write().await;
+                        if matches!(n, Notification::Countdown(_)) {
+                            notifications.retain(|n| !matches!(n, Notification::Countdown(_)));
+                        }
+                        if n != Notification::Countdown(None) {
+                            notifications.push(n);
+                        }
+                    }
+                    else => break,
+                }
             }
         });
 
         Self {
             e_tx,
             active_view,
-            _task_handle,
+            settings,
+            notifications,
+            file_operation,
+            exit_prompt: false,
+            exit_confirmed: false,
+            task_handle,
         }
     }
 
@@ -154,6 +224,240 @@ impl ViewManager {
             error!("View failed to send event: {}", e.to_string())
         }
     }
+
+    /// Renders the notifications that were not dismissed yet.
+    ///
+    /// # Arguments
+    /// * `ctx` - The Egui context.
+    fn render_notifications(&self, ctx: &egui::Context) {
+        // notifications arriving meanwhile are shown in the next frame
+        let Ok(mut notifications) = self.notifications.try_write() else {
+            return;
+        };
+        if notifications
+            .iter()
+            .all(|n| matches!(n, Notification::Alert(_) | Notification::Countdown(_)))
+        {
+            return;
+        }
+        egui::Window::new("Notifications")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::RIGHT_TOP, [-10.0, 10.0])
+            .show(ctx, |ui| {
+                let mut dismissed = None;
+                for (idx, notification) in notifications.iter().enumerate() {
+                    let (color, msg, kind) = match notification {
+                        Notification::Error((kind, msg)) => (egui::Color32::RED, msg, Some(kind)),
+                        Notification::Warning(msg) => (egui::Color32::YELLOW, msg, None),
+                        // alerts and countdowns are shown in banners
+                        Notification::Alert(_) | Notification::Countdown(_) => continue,
+                    };
+                    ui.horizontal(|ui| {
+                        ui.colored_label(color, msg.as_str());
+                        // the sensor may have been out of range while scanning
+                        if kind == Some(&ErrorKind::Bluetooth) && ui.button("rescan").clicked() {
+                            self.publish(AppEvent::AppState(StateChangeEvent::Rescan));
+                            dismissed = Some(idx);
+                        }
+                        if ui.button("dismiss").clicked() {
+                            dismissed = Some(idx);
+                        }
+                    });
+                }
+                if let Some(idx) = dismissed {
+                    notifications.remove(idx);
+                }
+            });
+    }
+
+    /// Renders the alerts that were not dismissed yet as banner above the active view.
+    ///
+    /// # Arguments
+    /// * `ctx` - The Egui context.
+    fn render_alerts(&self, ctx: &egui::Context) {
+        let Ok(mut notifications) = self.notifications.try_write() else {
+            return;
+        };
+        if !notifications
+            .iter()
+            .any(|n| matches!(n, Notification::Alert(_)))
+        {
+            return;
+        }
+        egui::TopBottomPanel::top("alerts")
+            .frame(
+                egui::Frame::default()
+                    .fill(egui::Color32::DARK_RED)
+                    .inner_margin(8.0),
+            )
+            .show(ctx, |ui| {
+                let mut dismissed = None;
+                for (idx, notification) in notifications.iter().enumerate() {
+                    let Notification::Alert(msg) = notification else {
+                        continue;
+                    };
+                    ui.horizontal(|ui| {
+                        ui.label(
+                            egui::RichText::new(format!("⚠ {}", msg))
+                                .heading()
+                                .color(egui::Color32::WHITE),
+                        );
+                        if ui.button("dismiss").clicked() {
+                            dismissed = Some(idx);
+                        }
+                    });
+                }
+                if let Some(idx) = dismissed {
+                    notifications.remove(idx);
+                }
+            });
+    }
+
+    /// Renders the countdown of an automatically starting recording with a button to cancel it.
+    ///
+    /// # Arguments
+    /// * `ctx` - The Egui context.
+    fn render_countdown(&self, ctx: &egui::Context) {
+        let Some(start) = self
+            .notifications
+            .try_read()
+            .ok()
+            .and_then(|notifications| {
+                notifications.iter().find_map(|n| match n {
+                    Notification::Countdown(start) => *start,
+                    _ => None,
+                })
+            })
+        else {
+            return;
+        };
+        let remaining = start.saturating_duration_since(Instant::now());
+        egui::TopBottomPanel::top("countdown")
+            .frame(
+                egui::Frame::default()
+                    .fill(egui::Color32::DARK_GREEN)
+                    .inner_margin(8.0),
+            )
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(
+                        egui::RichText::new(format!(
+                            "recording starts in {:.0} s",
+                            remaining.as_secs_f64().ceil()
+                        ))
+                        .heading()
+                        .color(egui::Color32::WHITE),
+                    );
+                    if ui.button("Cancel").clicked() {
+                        self.publish(AppEvent::AppState(StateChangeEvent::CancelAutoStart));
+                    }
+                });
+            });
+        // keep counting down until the recording started
+        ctx.request_repaint_after(std::time::Duration::from_millis(100));
+    }
+
+    /// Shows the running file operation with a button to cancel it.
+    ///
+    /// # Arguments
+    /// * `ctx` - The Egui context.
+    fn render_file_operation(&self, ctx: &egui::Context) {
+        let Some(description) = self.file_operation.running() else {
+            return;
+        };
+        egui::Window::new("Working")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.spinner();
+                    ui.label(description);
+                });
+                if ui.button("Cancel").clicked() {
+                    self.file_operation.cancel();
+                }
+            });
+        // keep polling until the operation finished
+        ctx.request_repaint_after(std::time::Duration::from_millis(100));
+    }
+
+    /// Asks to save an unsaved recording before the application exits.
+    ///
+    /// # Arguments
+    /// * `ctx` - The Egui context.
+    fn render_exit_prompt(&mut self, ctx: &egui::Context) {
+        if ctx.input(|i| i.viewport().close_requested())
+            && !self.exit_confirmed
+            // a view that is just being replaced may hold a recording
+            && self
+                .active_view
+                .try_read()
+                .map_or(true, |view| view.has_unsaved_data())
+        {
+            ctx.send_viewport_cmd(egui::ViewportCommand::CancelClose);
+            self.exit_prompt = true;
+        }
+        if !self.exit_prompt {
+            return;
+        }
+        let mut exit = false;
+        egui::Window::new("Exit")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                ui.label("Save the current recording before exiting?");
+                ui.horizontal(|ui| {
+                    if ui.button("Save...").clicked() {
+                        let extension = self
+                            .settings
+                            .try_read()
+                            .map_or("json", |settings| settings.storage_extension());
+                        if let Some(file) = storage_dialog(extension).save_file() {
+                            self.publish(AppEvent::Recording(RecordingEvent::StopRecording));
+                            self.publish(AppEvent::AppState(StateChangeEvent::StoreRecording));
+                            self.publish(AppEvent::Storage(StorageEvent::StoreToFile(file)));
+                            exit = true;
+                        }
+                    }
+                    if ui.button("Discard").clicked() {
+                        self.publish(AppEvent::AppState(StateChangeEvent::DiscardRecording));
+                        exit = true;
+                    }
+                    if ui.button("Cancel").clicked() {
+                        self.exit_prompt = false;
+                    }
+                });
+            });
+        if exit {
+            self.exit_prompt = false;
+            self.exit_confirmed = true;
+            ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+        }
+    }
+
+    /// Renders the active view.
+    ///
+    /// If the view or one of its models is locked, the frame is skipped and a placeholder is
+    /// shown instead of blocking the UI.
+    ///
+    /// # Arguments
+    /// * `ctx` - The Egui context.
+    fn render_view(&self, ctx: &egui::Context) {
+        // keep the previous scale while the settings are being changed
+        if let Ok(settings) = self.settings.try_read() {
+            ctx.set_pixels_per_point(settings.ui_scale);
+        }
+        let result = lock::write(&self.active_view)
+            .and_then(|mut view| view.render(&|e| self.publish(e), ctx));
+        match result {
+            Err(e) if lock::is_skipped(&e) => lock::render_refreshing(ctx),
+            Err(e) => error!("View failed to render: {}", e),
+            Ok(()) => {}
+        }
+    }
 }
 
 impl App for ViewManager {
@@ -165,27 +469,42 @@ impl App for ViewManager {
     /// * `ctx` - The Egui context.
     /// * `_frame` - The eframe frame (unused).
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
-        // Set the UI scaling factor for better readability.
-        ctx.set_pixels_per_point(1.5);
-        if let Err(e) = self
-            .active_view
-            .blocking_write()
-            .render(&|e| self.publish(e), ctx)
-        {
-            error!("View failed to render: {}", e)
-        }
+        // panels have to be added before the central panel of the view
+        self.render_alerts(ctx);
+        self.render_countdown(ctx);
+        self.render_view(ctx);
+        self.render_file_operation(ctx);
+        self.render_notifications(ctx);
+        self.render_exit_prompt(ctx);
+    }
+
+    /// Shuts the application controller down before the application exits.
+    ///
+    /// Called by the eframe framework after the window was closed.
+    fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
+        self.publish(AppEvent::AppState(StateChangeEvent::Shutdown));
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::components::{application::tests::MockBluetooth, measurement::MeasurementData};
+    use crate::api::model::MeasurementModelApi;
+    use crate::components::{
+        application::tests::MockBluetooth, measurement::MeasurementData, storage::StorageComponent,
+    };
 
     fn setup_test_manager() -> (ViewManager, Sender<ViewState>) {
         let (v_tx, v_rx) = tokio::sync::broadcast::channel(1);
+        let (_n_tx, n_rx) = tokio::sync::broadcast::channel(1);
         let (e_tx, _e_rx) = tokio::sync::broadcast::channel(1);
-        let manager = ViewManager::new(v_rx, e_tx);
+        let manager = ViewManager::new(
+            v_rx,
+            n_rx,
+            e_tx,
+            Arc::new(RwLock::new(AppSettings::default())),
+            Arc::new(FileOperation::default()),
+        );
         (manager, v_tx)
     }
 
@@ -199,9 +518,12 @@ mod tests {
     #[tokio::test]
     async fn test_view_manager_state_switch() {
         let (manager, v_tx) = setup_test_manager();
+        let (_snapshot_tx, snapshot) = tokio::sync::watch::channel(Arc::new(
+            MeasurementData::default(),
+        )
+            as Arc<dyn MeasurementModelApi>);
         v_tx.send(ViewState::Acquisition((
-            Arc::new(RwLock::new(MeasurementData::default()))
-                as ModelHandle<dyn MeasurementModelApi>,
+            snapshot,
             Arc::new(RwLock::new(MockBluetooth::new())) as ModelHandle<dyn BluetoothModelApi>,
         )))
         .unwrap();
@@ -209,4 +531,44 @@ mod tests {
         let view = manager.active_view.read().await;
         assert!(matches!(&*view, View::Acquisition(_)));
     }
+
+    #[tokio::test]
+    async fn test_drop_stops_listener() {
+        let (manager, v_tx) = setup_test_manager();
+        assert_eq!(v_tx.receiver_count(), 1);
+        drop(manager);
+        // the aborted task releases its receiver once it is cancelled
+        for _ in 0..100 {
+            if v_tx.receiver_count() == 0 {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        assert_eq!(v_tx.receiver_count(), 0);
+        assert!(v_tx
+            .send(ViewState::Overview((
+                Arc::new(RwLock::new(StorageComponent::<MeasurementData>::default()))
+                    as ModelHandle<dyn StorageModelApi>,
+                None,
+            )))
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_contended_lock_skips_frame() {
+        let (manager, _v_tx) = setup_test_manager();
+        let ctx = egui::Context::default();
+        // rendering within the runtime while the locks are held neither blocks nor panics
+        let _settings = manager.settings.write().await;
+        let view = manager.active_view.write().await;
+        assert_eq!(
+            lock::read(&manager.active_view).err().as_deref(),
+            Some(lock::SKIPPED_FRAME)
+        );
+        let _ = ctx.run(egui::RawInput::default(), |ctx| manager.render_view(ctx));
+
+        drop(view);
+        assert!(lock::write(&manager.active_view).is_ok());
+        let _ = ctx.run(egui::RawInput::default(), |ctx| manager.render_view(ctx));
+    }
 }
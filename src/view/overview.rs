@@ -3,18 +3,38 @@
 //! This module provides the view layer for managing stored acquisitions in the HRV analysis tool.
 //! It includes structures and methods for rendering the UI for selecting and interacting with stored acquisitions.
 
-use time::macros::format_description;
+use std::sync::Arc;
+
+use egui::Color32;
+use egui_plot::{HLine, Legend, Plot, Points};
 
 use crate::{
     api::{
-        model::{MeasurementModelApi, ModelHandle, StorageModelApi},
+        model::{MeasurementModelApi, ModelHandle, ModelSnapshot, StorageModelApi},
         view::ViewApi,
     },
-    core::events::{AppEvent, StateChangeEvent, StorageEvent},
+    core::events::{AppEvent, MeasurementEvent, SettingsEvent, StateChangeEvent, StorageEvent},
+    model::{
+        cache::SessionCache,
+        comparison::{calc_agreement, match_beats, Agreement},
+        correlation::CorrelationMatrix,
+        import::RrUnit,
+        metadata::MeasurementMetadata,
+        readiness::{ReadinessBand, ReadinessBaseline},
+        replay::REPLAY_SPEED_RANGE,
+        settings::{AppSettings, DisplaySettings},
+    },
 };
 
-use super::acquisition::{
-    render_filter_params, render_poincare_plot, render_stats, render_time_series,
+use super::{
+    about::AboutView,
+    acquisition::{
+        render_analysis_params, render_filter_params, render_metadata, render_poincare_plot,
+        render_rr_difference_histogram, render_stabilization, render_stats, render_templates,
+        render_time_plots,
+    },
+    lock,
+    settings::SettingsView,
 };
 
 /// The `StorageView` renders a UI for managing stored acquisitions.
@@ -23,8 +43,268 @@ use super::acquisition::{
 pub struct StorageView {
     /// The shared storage model that provides acquisition information.
     model: ModelHandle<dyn StorageModelApi>,
-    /// The currently selected acquisition.
-    selected: Option<ModelHandle<dyn MeasurementModelApi>>,
+    /// Snapshots of the currently selected acquisition.
+    selected: Option<ModelSnapshot>,
+    /// Maximum time difference in seconds for beats of compared acquisitions to match.
+    tolerance: f64,
+    /// Shared settings.
+    settings: ModelHandle<AppSettings>,
+    /// Window for editing the settings.
+    settings_view: SettingsView,
+    /// Whether the correlations of the metrics across the acquisitions are shown.
+    show_correlations: bool,
+    /// Window with information about the application.
+    about_view: AboutView,
+    /// Speed of replaying the selected acquisition as multiple of real time.
+    replay_speed: f64,
+    /// Subject information of the selected acquisition as currently entered.
+    metadata: Option<MeasurementMetadata>,
+    /// Name of the analysis template to save or delete.
+    template_name: String,
+}
+
+/// Checks if the landing screen is shown instead of an empty overview.
+///
+/// The landing screen is shown while there are no acquisitions and no active recording.
+fn shows_empty_state(model: &dyn StorageModelApi) -> bool {
+    model.get_acquisitions().is_empty() && !model.has_recording()
+}
+
+/// Returns the dialog asking for the name of a storage file.
+///
+/// # Arguments
+/// * `extension` - Extension of the suggested file name, it selects the file format.
+pub fn storage_dialog(extension: &str) -> rfd::FileDialog {
+    rfd::FileDialog::new().set_file_name(format!("measurements.{}", extension))
+}
+
+/// Asks for an RR interval file and publishes its import.
+fn import_rr<F: Fn(AppEvent) + ?Sized>(publish: &F, unit: RrUnit) {
+    if let Some(file) = rfd::FileDialog::new()
+        .add_filter("RR intervals", &["txt", "csv"])
+        .pick_file()
+    {
+        publish(AppEvent::Storage(StorageEvent::ImportRr((file, unit))))
+    }
+}
+
+/// Asks for a `.fit` activity file and publishes the import of its HRV records.
+fn import_fit<F: Fn(AppEvent) + ?Sized>(publish: &F) {
+    if let Some(file) = rfd::FileDialog::new()
+        .add_filter("FIT activity", &["fit"])
+        .pick_file()
+    {
+        publish(AppEvent::Storage(StorageEvent::ImportFit(file)))
+    }
+}
+
+/// Renders the landing screen with the actions to get started.
+///
+/// # Arguments
+/// * `ui` - The `egui::Ui` to render into.
+/// * `publish` - Function to publish `AppEvent`s.
+/// * `unit` - Unit of the RR intervals of imported files.
+fn render_empty_state<F: Fn(AppEvent) + ?Sized>(ui: &mut egui::Ui, publish: &F, unit: RrUnit) {
+    ui.vertical_centered(|ui| {
+        ui.add_space(ui.available_height() / 4.0);
+        ui.heading("No measurements yet");
+        ui.label("Record a new session or open existing data to start the analysis.");
+        ui.add_space(20.0);
+        let size = [200.0, 40.0];
+        if ui
+            .add_sized(size, egui::Button::new("Record new"))
+            .clicked()
+        {
+            publish(AppEvent::AppState(StateChangeEvent::ToRecordingState));
+        }
+        if ui.add_sized(size, egui::Button::new("Open file")).clicked() {
+            if let Some(file) = rfd::FileDialog::new().pick_file() {
+                publish(AppEvent::Storage(StorageEvent::LoadFromFile(file)))
+            }
+        }
+        if ui.add_sized(size, egui::Button::new("Import RR")).clicked() {
+            import_rr(publish, unit);
+        }
+        if ui
+            .add_sized(size, egui::Button::new("Import FIT"))
+            .clicked()
+        {
+            import_fit(publish);
+        }
+        if ui
+            .add_sized(size, egui::Button::new("Try demo data"))
+            .clicked()
+        {
+            publish(AppEvent::Storage(StorageEvent::LoadDemo));
+        }
+    });
+}
+
+/// Renders the readiness of the selected acquisition as big number.
+///
+/// # Arguments
+/// * `ui` - The `egui::Ui` to render into.
+/// * `publish` - Function to publish `AppEvent`s.
+/// * `model` - The selected acquisition.
+/// * `baseline` - The personal baseline, `None` if not configured.
+/// * `history` - The final RMSSD values of the stored acquisitions.
+fn render_readiness<F: Fn(AppEvent) + ?Sized>(
+    ui: &mut egui::Ui,
+    publish: &F,
+    model: &dyn MeasurementModelApi,
+    baseline: Option<&ReadinessBaseline>,
+    history: &[f64],
+) {
+    ui.heading("Readiness");
+    let score = baseline.zip(model.get_final_rmssd());
+    match score.and_then(|(baseline, rmssd)| baseline.score(rmssd)) {
+        Some(score) => {
+            let color = match ReadinessBand::of(score) {
+                ReadinessBand::Low => Color32::RED,
+                ReadinessBand::Moderate => Color32::YELLOW,
+                ReadinessBand::High => Color32::LIGHT_GREEN,
+            };
+            ui.label(
+                egui::RichText::new(format!("{:.0}", score))
+                    .size(48.0)
+                    .color(color)
+                    .strong(),
+            );
+        }
+        None if baseline.is_none() => {
+            ui.label("no baseline configured");
+        }
+        None => {
+            ui.label("not enough data");
+        }
+    }
+    let from_history = ReadinessBaseline::from_history(history);
+    if ui
+        .add_enabled(
+            from_history.is_some(),
+            egui::Button::new("baseline from stored sessions"),
+        )
+        .clicked()
+    {
+        publish(AppEvent::Settings(SettingsEvent::SetReadinessBaseline(
+            from_history,
+        )));
+    }
+}
+
+/// Returns the label of an acquisition in the list of past measurements.
+///
+/// The label shows the start time, the duration and the number of recorded beats.
+fn entry_label(settings: &DisplaySettings, acq: &dyn MeasurementModelApi) -> String {
+    let elapsed = acq.get_elapsed_time();
+    format!(
+        "{}\n{}:{:02} min, {} beats{}",
+        settings
+            .format_time(acq.get_start_time())
+            .unwrap_or_default(),
+        elapsed.whole_minutes(),
+        elapsed.whole_seconds() % 60,
+        acq.get_beat_count(),
+        if acq.is_excluded() { " (excluded)" } else { "" }
+    )
+}
+
+/// Selects the stored acquisitions the aggregates are computed from.
+///
+/// Excluded acquisitions are left out. Each acquisition is paired with its cache entry if
+/// the entry is still valid.
+///
+/// # Arguments
+/// * `acquisitions` - The stored acquisitions in storage order.
+/// * `model` - The storage holding the cache.
+fn aggregated<'a>(
+    acquisitions: impl IntoIterator<Item = &'a dyn MeasurementModelApi>,
+    model: &dyn StorageModelApi,
+) -> Vec<(&'a dyn MeasurementModelApi, Option<Arc<SessionCache>>)> {
+    acquisitions
+        .into_iter()
+        .enumerate()
+        .filter(|(_, acq)| !acq.is_excluded())
+        .map(|(idx, acq)| {
+            let cache = model
+                .get_session_cache(idx)
+                .filter(|cache| cache.is_valid_for(acq));
+            (acq, cache)
+        })
+        .collect()
+}
+
+/// Renders a Bland-Altman plot of the agreement between two acquisitions.
+fn render_agreement_plot(ui: &mut egui::Ui, agreement: &Agreement) {
+    ui.label(format!(
+        "bias: {:.2} ms, limits of agreement: [{:.2}, {:.2}] ms",
+        agreement.bias, agreement.lower_loa, agreement.upper_loa
+    ));
+    let plot = Plot::new("Bland-Altman Plot").legend(Legend::default());
+    plot.show(ui, |plot_ui| {
+        plot_ui.points(
+            Points::new(agreement.points.clone())
+                .name("mean vs. difference [ms]")
+                .color(Color32::RED)
+                .radius(3.0),
+        );
+        plot_ui.hline(HLine::new(agreement.bias).name("bias").color(Color32::BLUE));
+        for loa in [agreement.lower_loa, agreement.upper_loa] {
+            plot_ui.hline(
+                HLine::new(loa)
+                    .name("limits of agreement")
+                    .color(Color32::GRAY),
+            );
+        }
+    });
+}
+
+/// Renders the correlations of the metrics as a matrix colored by the coefficient.
+///
+/// Positive correlations are shaded blue, negative ones red, pairs with too few measurements
+/// are left empty.
+fn render_correlation_matrix(ui: &mut egui::Ui, matrix: &CorrelationMatrix) {
+    egui::Grid::new("correlation matrix")
+        .spacing([2.0, 2.0])
+        .show(ui, |ui| {
+            ui.label("");
+            for label in &matrix.labels {
+                ui.strong(*label);
+            }
+            ui.end_row();
+            for (row, label) in matrix.labels.iter().enumerate() {
+                ui.strong(*label);
+                for (col, value) in matrix.values[row].iter().enumerate() {
+                    let (text, fill) = match value {
+                        Some(r) => {
+                            let base = if *r >= 0.0 {
+                                Color32::BLUE
+                            } else {
+                                Color32::RED
+                            };
+                            (
+                                format!("{:.2}", r),
+                                base.gamma_multiply(r.abs() as f32 * 0.8),
+                            )
+                        }
+                        None => (String::new(), Color32::TRANSPARENT),
+                    };
+                    egui::Frame::default()
+                        .fill(fill)
+                        .inner_margin(4.0)
+                        .show(ui, |ui| {
+                            ui.set_min_width(40.0);
+                            ui.label(text)
+                        })
+                        .response
+                        .on_hover_text(format!(
+                            "{} vs. {}: {} measurements",
+                            label, matrix.labels[col], matrix.counts[row][col]
+                        ));
+                }
+                ui.end_row();
+            }
+        });
 }
 
 impl StorageView {
@@ -32,14 +312,28 @@ impl StorageView {
     ///
     /// # Arguments
     /// * `model` - The storage model handle.
+    /// * `selected` - The selected acquisition, if any.
+    /// * `settings` - The shared settings.
     ///
     /// # Returns
     /// A new instance of `StorageView`.
     pub fn new(
         model: ModelHandle<dyn StorageModelApi>,
-        selected: Option<ModelHandle<dyn MeasurementModelApi>>,
+        selected: Option<ModelSnapshot>,
+        settings: ModelHandle<AppSettings>,
     ) -> Self {
-        Self { model, selected }
+        Self {
+            model,
+            selected,
+            tolerance: 0.3,
+            settings_view: SettingsView::new(settings.clone()),
+            settings,
+            show_correlations: false,
+            about_view: AboutView::default(),
+            replay_speed: 1.0,
+            metadata: None,
+            template_name: String::new(),
+        }
     }
 }
 
@@ -57,7 +351,41 @@ impl ViewApi for StorageView {
         publish: &F,
         ctx: &egui::Context,
     ) -> Result<(), String> {
-        let model = self.model.blocking_read();
+        // the storage is locked while a file operation is running, which the view manager shows
+        let model = lock::read(&self.model)?;
+        let app_settings = lock::read(&self.settings)?.clone();
+        let settings = &app_settings.display;
+        let (history, labels, reference, correlations) = {
+            let acquisitions = model
+                .get_acquisitions()
+                .iter()
+                .map(|acq| lock::read(&**acq))
+                .collect::<Result<Vec<_>, _>>()?;
+            // only cached results are shown, the controller computes the missing ones in the
+            // background instead of analysing them on the UI thread
+            let included = aggregated(acquisitions.iter().map(|acq| &**acq), &*model);
+            let history: Vec<f64> = included
+                .iter()
+                .filter_map(|(_, cache)| cache.as_ref().and_then(|cache| cache.final_rmssd))
+                .collect();
+            let labels: Vec<String> = acquisitions
+                .iter()
+                .map(|acq| entry_label(settings, &**acq))
+                .collect();
+            let reference = model
+                .get_compare_index()
+                .and_then(|idx| acquisitions.get(idx))
+                .map(|acq| (*acq.get_start_time(), acq.get_rr_ts()));
+            let correlations = self.show_correlations.then(|| {
+                let summaries: Vec<_> = included
+                    .iter()
+                    .filter_map(|(_, cache)| cache.as_ref().map(|cache| cache.summary.clone()))
+                    .collect();
+                CorrelationMatrix::from_summaries(&summaries)
+            });
+            (history, labels, reference, correlations)
+        };
+        let has_measurements = !model.get_acquisitions().is_empty();
         // Render the top menu bar
         egui::TopBottomPanel::top("menu_bar").show(ctx, |ui| {
             egui::menu::bar(ui, |ui| {
@@ -69,70 +397,352 @@ impl ViewApi for StorageView {
                         }
                         ui.close_menu(); // Close the menu after selection
                     }
+                    if ui.button("Add file to workspace").clicked() {
+                        if let Some(file) = rfd::FileDialog::new().pick_file() {
+                            publish(AppEvent::Storage(StorageEvent::AppendFromFile(file)))
+                        }
+                        ui.close_menu();
+                    }
+                    if ui.button("Import RR").clicked() {
+                        import_rr(publish, app_settings.rr_unit);
+                        ui.close_menu();
+                    }
+                    if ui.button("Import FIT").clicked() {
+                        import_fit(publish);
+                        ui.close_menu();
+                    }
                     if ui.button("Save").clicked() {
-                        if let Some(file) = rfd::FileDialog::new().save_file() {
+                        let dialog = storage_dialog(app_settings.storage_extension());
+                        if let Some(file) = dialog.save_file() {
                             publish(AppEvent::Storage(StorageEvent::StoreToFile(file)))
                         }
                         ui.close_menu();
                     }
+                    let export = egui::Button::new("Export EDF+");
+                    if ui.add_enabled(self.selected.is_some(), export).clicked() {
+                        if let Some(file) = rfd::FileDialog::new()
+                            .add_filter("EDF+", &["edf"])
+                            .save_file()
+                        {
+                            publish(AppEvent::Storage(StorageEvent::ExportEdf(file)))
+                        }
+                        ui.close_menu();
+                    }
+                    let export = egui::Button::new("Export for Kubios");
+                    if ui.add_enabled(self.selected.is_some(), export).clicked() {
+                        if let Some(file) = rfd::FileDialog::new()
+                            .add_filter("Text", &["txt"])
+                            .save_file()
+                        {
+                            publish(AppEvent::Storage(StorageEvent::ExportKubios(file)))
+                        }
+                        ui.close_menu();
+                    }
+                    let export = egui::Button::new("Export HTML report");
+                    if ui.add_enabled(self.selected.is_some(), export).clicked() {
+                        if let Some(file) = rfd::FileDialog::new()
+                            .add_filter("HTML", &["html"])
+                            .save_file()
+                        {
+                            publish(AppEvent::Storage(StorageEvent::ExportHtml(file)))
+                        }
+                        ui.close_menu();
+                    }
+                    let export = egui::Button::new("Export all measurements");
+                    if ui.add_enabled(has_measurements, export).clicked() {
+                        if let Some(dir) = rfd::FileDialog::new().pick_folder() {
+                            publish(AppEvent::Storage(StorageEvent::ExportAll(dir)))
+                        }
+                        ui.close_menu();
+                    }
+                    let export = egui::Button::new("Export correction log");
+                    if ui.add_enabled(self.selected.is_some(), export).clicked() {
+                        if let Some(file) = rfd::FileDialog::new()
+                            .add_filter("CSV", &["csv"])
+                            .save_file()
+                        {
+                            publish(AppEvent::Storage(StorageEvent::ExportCorrectionLog(file)))
+                        }
+                        ui.close_menu();
+                    }
                     if ui.button("New").clicked() {
                         publish(AppEvent::Storage(StorageEvent::Clear));
 
                         ui.close_menu();
                     }
+                    ui.separator();
+                    if ui.button("Settings...").clicked() {
+                        self.settings_view.open(&app_settings);
+                        ui.close_menu();
+                    }
+                });
+                ui.menu_button("Help", |ui| {
+                    if ui.button("About").clicked() {
+                        self.about_view.open();
+                        ui.close_menu();
+                    }
                 });
             });
         });
+        self.settings_view.render(publish, ctx)?;
+        self.about_view.render(publish, ctx)?;
+
+        if shows_empty_state(&*model) {
+            egui::CentralPanel::default().show(ctx, |ui| {
+                render_empty_state(ui, publish, app_settings.rr_unit)
+            });
+            return Ok(());
+        }
 
         // Render the left side panel with past measurements
-        let fd = format_description!("[year]-[month]-[day] [hour]:[minute]");
         egui::SidePanel::left("left_overview").show(ctx, |ui| {
             ui.add_space(10.0);
             ui.heading("Past Measurements:");
-            for (idx, acq) in model.get_acquisitions().iter().enumerate() {
-                let btn: egui::Button<'_> = egui::Button::new(
-                    acq.blocking_read()
-                        .get_start_time()
-                        .format(fd)
-                        .unwrap()
-                        .to_string(),
-                );
-                if ui.add_sized([ui.available_width(), 20.0], btn).clicked() {
+            // entries are dragged onto another entry to move them to its position
+            let mut moved = None;
+            for (idx, label) in labels.iter().enumerate() {
+                let entry = ui.dnd_drag_source(egui::Id::new(("measurement", idx)), idx, |ui| {
+                    let btn: egui::Button<'_> = egui::Button::new(label.as_str());
+                    ui.add_sized([ui.available_width(), 40.0], btn)
+                });
+                if entry.inner.clicked() {
                     publish(AppEvent::AppState(StateChangeEvent::SelectMeasurement(idx)));
                 }
+                if entry.response.dnd_hover_payload::<usize>().is_some() {
+                    let rect = entry.response.rect;
+                    ui.painter()
+                        .hline(rect.x_range(), rect.top(), ui.visuals().selection.stroke);
+                }
+                if let Some(from) = entry.response.dnd_release_payload::<usize>() {
+                    moved = Some((*from, idx));
+                }
+            }
+            if let Some(indices) = moved.filter(|(from, to)| from != to) {
+                publish(AppEvent::Storage(StorageEvent::MoveMeasurement(indices)));
             }
             ui.separator();
             if ui.button("New Acquisition").clicked() {
                 publish(AppEvent::AppState(StateChangeEvent::ToRecordingState));
             }
+            ui.toggle_value(&mut self.show_correlations, "Metric correlations");
         });
 
+        if let Some(matrix) = &correlations {
+            let mut open = true;
+            egui::Window::new("Metric correlations")
+                .open(&mut open)
+                .show(ctx, |ui| {
+                    ui.label("Pearson correlation across the stored measurements");
+                    render_correlation_matrix(ui, matrix);
+                });
+            self.show_correlations &= open;
+        }
+
+        let compare = model.get_compare_index();
+        let selected_index = model.get_selected_index();
+
         // Render the right side panel with selected acquisition details
         if let Some(selected) = &self.selected {
-            let lck = selected.blocking_read();
+            let lck = selected.borrow().clone();
+            let metrics = selected_index
+                .and_then(|idx| model.get_session_cache(idx))
+                .filter(|cache| cache.is_valid_for(&*lck))
+                .map_or_else(|| lck.get_metrics(), |cache| cache.metrics.clone());
             egui::SidePanel::right("right:overview").show(ctx, |ui| {
                 let model = &*lck;
                 let hr = model.get_hr().unwrap_or(0.0);
-                render_stats(ui, model, hr);
+                render_readiness(
+                    ui,
+                    &publish,
+                    model,
+                    app_settings.readiness.as_ref(),
+                    &history,
+                );
+                ui.separator();
+                render_stats(ui, model, hr, None, settings.get_precision());
+                render_rr_difference_histogram(ui, model);
                 ui.separator();
                 render_filter_params(ui, &publish, model);
+                render_templates(ui, &publish, model, &app_settings, &mut self.template_name);
+                render_stabilization(ui, &publish, model, app_settings.stabilization_threshold);
+                render_analysis_params(ui, model);
+                render_metadata(ui, &publish, model, &mut self.metadata);
+                let mut excluded = model.is_excluded();
+                if ui
+                    .checkbox(&mut excluded, "exclude from aggregates")
+                    .on_hover_text("keeps the recording, but leaves it out of the baseline, the correlations and the batch export")
+                    .changed()
+                {
+                    publish(AppEvent::Measurement(MeasurementEvent::SetExcluded(excluded)));
+                }
+                ui.separator();
+                ui.heading("Comparison");
+                let mut selection = compare;
+                egui::ComboBox::from_label("compare with")
+                    .selected_text(
+                        compare
+                            .and_then(|idx| labels.get(idx).cloned())
+                            .unwrap_or_default(),
+                    )
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(&mut selection, None, "none");
+                        for (idx, label) in labels.iter().enumerate() {
+                            ui.selectable_value(&mut selection, Some(idx), label.as_str());
+                        }
+                    });
+                if selection != compare {
+                    publish(AppEvent::AppState(StateChangeEvent::CompareMeasurement(
+                        selection,
+                    )));
+                }
+                ui.add(
+                    egui::Slider::new(&mut self.tolerance, 0.05..=1.0).text("match tolerance [s]"),
+                );
+                if let Some(selected_idx) = selected_index {
+                    ui.menu_button("Merge with", |ui| {
+                        for (idx, label) in labels.iter().enumerate() {
+                            if idx != selected_idx && ui.button(label.as_str()).clicked() {
+                                publish(AppEvent::Storage(StorageEvent::MergeMeasurements((
+                                    selected_idx,
+                                    idx,
+                                ))));
+                                ui.close_menu();
+                            }
+                        }
+                    });
+                    ui.add_enabled_ui(!model.get_markers().is_empty(), |ui| {
+                        ui.menu_button("Split at marker", |ui| {
+                            for (elapsed, label) in model.get_markers() {
+                                let text = format!("{:.0} s {}", elapsed.as_seconds_f64(), label);
+                                if ui.button(text).clicked() {
+                                    publish(AppEvent::Storage(StorageEvent::SplitMeasurement((
+                                        selected_idx,
+                                        *elapsed,
+                                    ))));
+                                    ui.close_menu();
+                                }
+                            }
+                        });
+                    });
+                    ui.separator();
+                    ui.heading("Replay");
+                    ui.add(
+                        egui::Slider::new(&mut self.replay_speed, REPLAY_SPEED_RANGE)
+                            .logarithmic(true)
+                            .text("speed [x real time]"),
+                    );
+                    if ui.button("Replay as live recording").clicked() {
+                        publish(AppEvent::Storage(StorageEvent::ReplayMeasurement((
+                            selected_idx,
+                            self.replay_speed,
+                        ))));
+                    }
+                }
             });
 
+            if compare.is_some() {
+                let agreement = reference.and_then(|(start, rr_ts)| {
+                    // align the reference beats to the time axis of the selection
+                    let offset = (start - *lck.get_start_time()).as_seconds_f64();
+                    let rr_ts: Vec<[f64; 2]> =
+                        rr_ts.iter().map(|p| [p[0] + offset, p[1]]).collect();
+                    calc_agreement(&match_beats(&lck.get_rr_ts(), &rr_ts, self.tolerance)).ok()
+                });
+                let mut open = true;
+                egui::Window::new("Bland-Altman agreement")
+                    .open(&mut open)
+                    .show(ctx, |ui| match &agreement {
+                        Some(agreement) => render_agreement_plot(ui, agreement),
+                        None => {
+                            ui.label("no matching beats within the tolerance");
+                        }
+                    });
+                if !open {
+                    publish(AppEvent::AppState(StateChangeEvent::CompareMeasurement(
+                        None,
+                    )));
+                }
+            }
+
             // Render the bottom panel with time series data
             egui::TopBottomPanel::bottom("time series panel")
                 .min_height(100.0)
                 .resizable(true)
                 .show(ctx, |ui| {
                     let model = &*lck;
-                    render_time_series(ui, model);
+                    render_time_plots(ui, &publish, model, metrics.clone(), &app_settings);
                 });
 
             // Render the central panel with Poincaré plot
             egui::CentralPanel::default().show(ctx, |ui| {
                 let model = &*lck;
-                render_poincare_plot(ui, model);
+                render_poincare_plot(ui, model, &app_settings.poincare, app_settings.palette);
             });
         }
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::controller::{MeasurementApi, StorageApi};
+    use crate::components::{measurement::MeasurementData, storage::StorageComponent};
+    use crate::model::testdata::get_data;
+    use tokio::sync::RwLock;
+
+    #[test]
+    fn test_empty_state() {
+        let mut storage = StorageComponent::<MeasurementData>::default();
+        assert!(shows_empty_state(&storage));
+        storage.new_measurement().unwrap();
+        assert!(!shows_empty_state(&storage));
+
+        let mut storage = StorageComponent::<MeasurementData>::default();
+        let measurement = Arc::new(RwLock::new(MeasurementData::default()));
+        storage.store_measurement(measurement).unwrap();
+        assert!(!shows_empty_state(&storage));
+    }
+
+    #[test]
+    fn test_empty_state_after_discard() {
+        let mut storage = StorageComponent::<MeasurementData>::default();
+        storage.new_measurement().unwrap();
+        assert!(!shows_empty_state(&storage));
+        storage.discard_recording().unwrap();
+        assert!(shows_empty_state(&storage));
+    }
+
+    #[test]
+    fn test_entry_label() {
+        let settings = DisplaySettings::default();
+        let mut data = serde_json::to_value(MeasurementData::default()).unwrap();
+        data["measurements"] = serde_json::to_value(get_data(90)).unwrap();
+        let data: MeasurementData = serde_json::from_value(data).unwrap();
+        let label = entry_label(&settings, &data);
+        assert!(label.contains("1:29 min"), "{}", label);
+        assert!(label.contains("90 beats"), "{}", label);
+    }
+
+    #[tokio::test]
+    async fn test_excluded_not_aggregated() {
+        let mut storage = StorageComponent::<MeasurementData>::default();
+        for excluded in [false, true, false] {
+            let mut measurement = MeasurementData::default();
+            measurement.import_messages(get_data(400)).unwrap();
+            measurement.set_excluded(excluded).await.unwrap();
+            storage
+                .store_measurement(Arc::new(RwLock::new(measurement)))
+                .unwrap();
+        }
+        let acquisitions: Vec<_> = storage
+            .get_acquisitions()
+            .iter()
+            .map(|acq| lock::read(&**acq).unwrap())
+            .collect();
+        let included = aggregated(acquisitions.iter().map(|acq| &**acq), &storage);
+        assert_eq!(included.len(), 2);
+        assert!(included.iter().all(|(acq, _)| !acq.is_excluded()));
+        assert!(entry_label(&DisplaySettings::default(), &*acquisitions[1]).contains("excluded"));
+    }
+}
@@ -0,0 +1,629 @@
+//! Settings View
+//!
+//! This module provides a window for editing the application settings.
+//! Changes are published as `SettingsEvent`s and applied by the application controller.
+
+use crate::{
+    api::{model::ModelHandle, view::ViewApi},
+    core::events::{AppEvent, SettingsEvent},
+    model::{
+        alert::{HrAlert, HR_ALERT_HOLD_RANGE},
+        hrv::{ANALYSIS_THREADS_RANGE, STABILIZATION_THRESHOLD_RANGE},
+        import::RrUnit,
+        readiness::ReadinessBaseline,
+        settings::{
+            AppSettings, ColorPalette, HrZones, MarkerInterval, MarkerStyle, PoincareMode,
+            PoincareStyle, Retention, AUTO_START_RANGE, DFA_SMOOTHING_RANGE, MARKER_BEATS_RANGE,
+            MARKER_PERIOD_RANGE, MAX_HR_RANGE, MIN_SAVE_BEATS_RANGE, PRECISION_RANGE,
+            RECOMPUTE_DEBOUNCE_RANGE, RECONNECT_ATTEMPTS_RANGE, REPAINT_INTERVAL_RANGE,
+            RETENTION_DAYS_RANGE, RETENTION_FILES_RANGE, SCAN_INTERVAL_RANGE, SMOOTHING_RANGE,
+            UI_SCALE_RANGE,
+        },
+    },
+};
+
+use super::lock;
+
+/// The `SettingsView` renders a window for editing the `AppSettings`.
+pub struct SettingsView {
+    /// The shared settings.
+    settings: ModelHandle<AppSettings>,
+    /// Whether the window is shown.
+    open: bool,
+    /// Date format as currently entered.
+    date_format: String,
+    /// Number of decimals of displayed metrics as currently entered.
+    precision: usize,
+    /// UI scale as currently entered.
+    ui_scale: f32,
+    /// Smoothing of the live readout as currently entered.
+    smoothing: f64,
+    /// Appearance of the Poincaré plot as currently entered.
+    poincare: PoincareStyle,
+    /// Colors of the plots as currently entered.
+    palette: ColorPalette,
+    /// Time between two refreshes of the device list as currently entered.
+    scan_interval: f64,
+    /// Number of attempts to reconnect a lost sensor as currently entered.
+    reconnect_attempts: usize,
+    /// Whether the scan is restricted to heart rate sensors, as currently entered.
+    scan_heart_rate_only: bool,
+    /// Minimum time between two repaints as currently entered.
+    repaint_interval: u64,
+    /// Settling time of the analysis parameters as currently entered.
+    recompute_debounce: u64,
+    /// Whether the readiness is scored, as currently entered.
+    readiness_enabled: bool,
+    /// Readiness baseline as currently entered.
+    readiness: ReadinessBaseline,
+    /// Heart rate zones as currently entered.
+    hr_zones: HrZones,
+    /// Whether recordings stop on a lost connection, as currently entered.
+    auto_stop_on_disconnect: bool,
+    /// Whether storage files are compressed, as currently entered.
+    compress_files: bool,
+    /// Whether recordings start once the sensor connected, as currently entered.
+    auto_start_enabled: bool,
+    /// Countdown before a recording starts automatically as currently entered.
+    auto_start: f64,
+    /// Whether markers are inserted automatically, as currently entered.
+    auto_markers_enabled: bool,
+    /// Interval of the automatic markers as currently entered.
+    auto_markers: MarkerInterval,
+    /// Minimum number of inlier beats to save a recording as currently entered.
+    min_save_beats: usize,
+    /// Heart rate alert as currently entered.
+    hr_alert: HrAlert,
+    /// Whether loaded measurements are analysed on demand, as currently entered.
+    lazy_analysis: bool,
+    /// Whether the analysis threads are limited, as currently entered.
+    analysis_threads_enabled: bool,
+    /// Number of analysis threads as currently entered.
+    analysis_threads: usize,
+    /// Cleanup of old auto-saved recordings as currently entered.
+    retention: Retention,
+    /// Number of windows the plotted DFA alpha is smoothed over, as currently entered.
+    dfa_smoothing: usize,
+    /// Unit of imported RR intervals as currently entered.
+    rr_unit: RrUnit,
+    /// Threshold of the stabilization detection as currently entered.
+    stabilization_threshold: f64,
+}
+
+impl SettingsView {
+    /// Creates a new, closed `SettingsView`.
+    ///
+    /// # Arguments
+    /// * `settings` - The shared settings.
+    pub fn new(settings: ModelHandle<AppSettings>) -> Self {
+        Self {
+            settings,
+            open: false,
+            date_format: String::new(),
+            precision: 0,
+            ui_scale: 1.0,
+            smoothing: 0.0,
+            poincare: PoincareStyle::default(),
+            palette: ColorPalette::Standard,
+            scan_interval: 0.0,
+            reconnect_attempts: 1,
+            scan_heart_rate_only: false,
+            repaint_interval: 0,
+            recompute_debounce: 0,
+            readiness_enabled: false,
+            readiness: ReadinessBaseline::default(),
+            hr_zones: HrZones::default(),
+            auto_stop_on_disconnect: false,
+            compress_files: false,
+            auto_start_enabled: false,
+            auto_start: 0.0,
+            auto_markers_enabled: false,
+            auto_markers: MarkerInterval::Seconds(60),
+            min_save_beats: 0,
+            hr_alert: HrAlert::default(),
+            lazy_analysis: false,
+            analysis_threads_enabled: false,
+            analysis_threads: 1,
+            retention: Retention::default(),
+            dfa_smoothing: 1,
+            rr_unit: RrUnit::Auto,
+            stabilization_threshold: 0.0,
+        }
+    }
+
+    /// Opens the window with the current settings.
+    ///
+    /// # Arguments
+    /// * `settings` - The current settings.
+    pub fn open(&mut self, settings: &AppSettings) {
+        self.date_format = settings.display.get_date_format().to_owned();
+        self.precision = settings.display.get_precision();
+        self.ui_scale = settings.ui_scale;
+        self.smoothing = settings.smoothing;
+        self.poincare = settings.poincare.clone();
+        self.palette = settings.palette;
+        self.scan_interval = settings.scan_interval;
+        self.reconnect_attempts = settings.reconnect_attempts;
+        self.scan_heart_rate_only = settings.scan_heart_rate_only;
+        self.repaint_interval = settings.repaint_interval;
+        self.recompute_debounce = settings.recompute_debounce;
+        self.readiness_enabled = settings.readiness.is_some();
+        self.readiness = settings.readiness.clone().unwrap_or_default();
+        self.hr_zones = settings.hr_zones.clone();
+        self.auto_stop_on_disconnect = settings.auto_stop_on_disconnect;
+        self.compress_files = settings.compress_files;
+        self.auto_start_enabled = settings.auto_start.is_some();
+        self.auto_start = settings.auto_start.unwrap_or(3.0);
+        self.auto_markers_enabled = settings.auto_markers.is_some();
+        self.auto_markers = settings.auto_markers.unwrap_or(MarkerInterval::Seconds(60));
+        self.min_save_beats = settings.min_save_beats;
+        self.hr_alert = settings.hr_alert.clone();
+        self.lazy_analysis = settings.lazy_analysis;
+        self.analysis_threads_enabled = settings.analysis_threads.is_some();
+        self.analysis_threads = settings.analysis_threads.unwrap_or(2);
+        self.retention = settings.retention.clone();
+        self.dfa_smoothing = settings.dfa_smoothing;
+        self.rr_unit = settings.rr_unit;
+        self.stabilization_threshold = settings.stabilization_threshold;
+        self.open = true;
+    }
+
+    /// Renders the editable settings and publishes the applied changes.
+    fn render_settings<F: Fn(AppEvent) + ?Sized>(
+        &mut self,
+        ui: &mut egui::Ui,
+        publish: &F,
+        settings: &AppSettings,
+    ) {
+        let (auto_save_dir, profiles) = (&settings.auto_save_dir, &settings.profiles);
+        egui::Grid::new("settings grid")
+            .num_columns(2)
+            .show(ui, |ui| {
+                ui.label("date format");
+                ui.text_edit_singleline(&mut self.date_format);
+                ui.end_row();
+
+                ui.label("decimals");
+                ui.add(egui::Slider::new(&mut self.precision, PRECISION_RANGE));
+                ui.end_row();
+
+                ui.label("UI scale");
+                ui.add(egui::Slider::new(&mut self.ui_scale, UI_SCALE_RANGE).step_by(0.1));
+                ui.end_row();
+
+                ui.label("live smoothing");
+                ui.add(egui::Slider::new(&mut self.smoothing, SMOOTHING_RANGE).step_by(0.05));
+                ui.end_row();
+
+                ui.label("DFA alpha smoothing [windows]");
+                ui.add(egui::Slider::new(
+                    &mut self.dfa_smoothing,
+                    DFA_SMOOTHING_RANGE,
+                ))
+                .on_hover_text("median of the plotted DFA alpha, 1 disables it");
+                ui.end_row();
+
+                ui.label("device scan interval [s]");
+                ui.add(
+                    egui::Slider::new(&mut self.scan_interval, SCAN_INTERVAL_RANGE).step_by(0.5),
+                );
+                ui.end_row();
+
+                ui.label("reconnect attempts");
+                ui.add(egui::Slider::new(
+                    &mut self.reconnect_attempts,
+                    RECONNECT_ATTEMPTS_RANGE,
+                ))
+                .on_hover_text("attempts to reconnect a lost sensor before it is given up");
+                ui.end_row();
+
+                ui.label("scan for heart rate sensors only").on_hover_text(
+                    "only discover devices advertising the heart rate service, \
+                     applies to the next scan",
+                );
+                ui.checkbox(&mut self.scan_heart_rate_only, "");
+                ui.end_row();
+
+                ui.label("plot refresh interval [ms]");
+                ui.add(egui::Slider::new(
+                    &mut self.repaint_interval,
+                    REPAINT_INTERVAL_RANGE,
+                ));
+                ui.end_row();
+
+                ui.label("recalculation delay [ms]").on_hover_text(
+                    "time the filter sliders have to rest before the statistics are updated",
+                );
+                ui.add(egui::Slider::new(
+                    &mut self.recompute_debounce,
+                    RECOMPUTE_DEBOUNCE_RANGE,
+                ));
+                ui.end_row();
+
+                ui.label("auto-save");
+                ui.horizontal(|ui| {
+                    ui.label(
+                        auto_save_dir
+                            .as_ref()
+                            .map_or("disabled".to_owned(), |dir| dir.display().to_string()),
+                    );
+                    if ui.button("choose...").clicked() {
+                        if let Some(dir) = rfd::FileDialog::new().pick_folder() {
+                            publish(AppEvent::Settings(SettingsEvent::SetAutoSaveDir(Some(dir))))
+                        }
+                    }
+                    if ui
+                        .add_enabled(auto_save_dir.is_some(), egui::Button::new("disable"))
+                        .clicked()
+                    {
+                        publish(AppEvent::Settings(SettingsEvent::SetAutoSaveDir(None)));
+                    }
+                });
+                ui.end_row();
+
+                ui.label("file format");
+                ui.checkbox(&mut self.compress_files, "compress saved files (.json.gz)")
+                    .on_hover_text(
+                        "compressed files are smaller, plain JSON can be read by other tools",
+                    );
+                ui.end_row();
+
+                ui.label("keep auto-saves");
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut self.retention.enabled, "");
+                    ui.add_enabled_ui(self.retention.enabled, |ui| {
+                        ui.add(
+                            egui::DragValue::new(&mut self.retention.max_files)
+                                .range(RETENTION_FILES_RANGE)
+                                .prefix("newest ")
+                                .suffix(" files"),
+                        );
+                        ui.add(
+                            egui::DragValue::new(&mut self.retention.max_days)
+                                .range(RETENTION_DAYS_RANGE)
+                                .prefix("of ")
+                                .suffix(" days"),
+                        );
+                    });
+                })
+                .response
+                .on_hover_text("older recordings are removed at startup");
+                ui.end_row();
+
+                ui.label("imported RR unit");
+                egui::ComboBox::from_id_salt("rr unit")
+                    .selected_text(format!("{:?}", self.rr_unit))
+                    .show_ui(ui, |ui| {
+                        for unit in RrUnit::ALL {
+                            ui.selectable_value(&mut self.rr_unit, unit, format!("{:?}", unit));
+                        }
+                    })
+                    .response
+                    .on_hover_text("Auto reads intervals with a median below 10 as seconds");
+                ui.end_row();
+
+                ui.label("stable HR change");
+                ui.add(
+                    egui::DragValue::new(&mut self.stabilization_threshold)
+                        .range(STABILIZATION_THRESHOLD_RANGE)
+                        .speed(0.1)
+                        .prefix("below ")
+                        .suffix(" 1/min"),
+                )
+                .on_hover_text(
+                    "change of the 30 s mean HR from which a recording counts as settled",
+                );
+                ui.end_row();
+
+                ui.label("loading files");
+                ui.checkbox(&mut self.lazy_analysis, "analyse measurements when shown")
+                    .on_hover_text("opens large files faster");
+                ui.end_row();
+
+                ui.label("analysis");
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut self.analysis_threads_enabled, "limit threads")
+                        .on_hover_text("keeps the system responsive during long analyses");
+                    ui.add_enabled(
+                        self.analysis_threads_enabled,
+                        egui::DragValue::new(&mut self.analysis_threads)
+                            .range(ANALYSIS_THREADS_RANGE)
+                            .prefix("to "),
+                    );
+                });
+                ui.end_row();
+
+                ui.label("on sensor disconnect");
+                ui.checkbox(
+                    &mut self.auto_stop_on_disconnect,
+                    "stop and store recording",
+                )
+                .on_hover_text("once all reconnect attempts failed");
+                ui.end_row();
+
+                ui.label("on sensor connect");
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut self.auto_start_enabled, "start recording");
+                    ui.add_enabled(
+                        self.auto_start_enabled,
+                        egui::DragValue::new(&mut self.auto_start)
+                            .range(AUTO_START_RANGE)
+                            .speed(0.5)
+                            .prefix("after ")
+                            .suffix(" s"),
+                    );
+                });
+                ui.end_row();
+
+                ui.label("auto markers");
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut self.auto_markers_enabled, "insert every")
+                        .on_hover_text("segments the recording, e.g. into protocol phases");
+                    ui.add_enabled_ui(self.auto_markers_enabled, |ui| {
+                        match &mut self.auto_markers {
+                            MarkerInterval::Seconds(period) => ui.add(
+                                egui::DragValue::new(period)
+                                    .range(MARKER_PERIOD_RANGE)
+                                    .suffix(" s"),
+                            ),
+                            MarkerInterval::Beats(beats) => ui.add(
+                                egui::DragValue::new(beats)
+                                    .range(MARKER_BEATS_RANGE)
+                                    .suffix(" beats"),
+                            ),
+                        };
+                        let by_beats = matches!(self.auto_markers, MarkerInterval::Beats(_));
+                        if ui.selectable_label(!by_beats, "time").clicked() && by_beats {
+                            self.auto_markers = MarkerInterval::Seconds(60);
+                        }
+                        if ui.selectable_label(by_beats, "beats").clicked() && !by_beats {
+                            self.auto_markers = MarkerInterval::Beats(100);
+                        }
+                    });
+                });
+                ui.end_row();
+
+                ui.label("save recordings from");
+                ui.add(
+                    egui::DragValue::new(&mut self.min_save_beats)
+                        .range(MIN_SAVE_BEATS_RANGE)
+                        .suffix(" beats"),
+                )
+                .on_hover_text("shorter recordings can only be discarded");
+                ui.end_row();
+
+                ui.label("HR alert [1/min]");
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut self.hr_alert.enabled, "");
+                    ui.add_enabled_ui(self.hr_alert.enabled, |ui| {
+                        ui.add(
+                            egui::DragValue::new(&mut self.hr_alert.low)
+                                .range(1.0..=self.hr_alert.high - 1.0)
+                                .prefix("below "),
+                        );
+                        ui.add(
+                            egui::DragValue::new(&mut self.hr_alert.high)
+                                .range(self.hr_alert.low + 1.0..=300.0)
+                                .prefix("above "),
+                        );
+                        ui.add(
+                            egui::DragValue::new(&mut self.hr_alert.hold)
+                                .range(HR_ALERT_HOLD_RANGE)
+                                .prefix("for ")
+                                .suffix(" s"),
+                        );
+                    });
+                });
+                ui.end_row();
+
+                ui.label("color palette");
+                egui::ComboBox::from_id_salt("color palette")
+                    .selected_text(self.palette.to_string())
+                    .show_ui(ui, |ui| {
+                        for palette in ColorPalette::ALL {
+                            ui.selectable_value(&mut self.palette, palette, palette.to_string());
+                        }
+                    });
+                ui.end_row();
+
+                ui.label("Poincaré inliers");
+                ui.color_edit_button_srgb(&mut self.poincare.inlier_color);
+                ui.end_row();
+
+                ui.label("Poincaré outliers");
+                ui.color_edit_button_srgb(&mut self.poincare.outlier_color);
+                ui.end_row();
+
+                ui.label("Poincaré markers");
+                egui::ComboBox::from_id_salt("poincare markers")
+                    .selected_text(format!("{:?}", self.poincare.marker))
+                    .show_ui(ui, |ui| {
+                        for marker in MarkerStyle::ALL {
+                            ui.selectable_value(
+                                &mut self.poincare.marker,
+                                marker,
+                                format!("{:?}", marker),
+                            );
+                        }
+                    });
+                ui.end_row();
+
+                ui.label("SD1/SD2 ellipse");
+                ui.checkbox(&mut self.poincare.show_ellipse, "show");
+                ui.end_row();
+
+                ui.label("Poincaré rendering")
+                    .on_hover_text("shade binned inlier pairs by density for long recordings");
+                egui::ComboBox::from_id_salt("poincare mode")
+                    .selected_text(self.poincare.mode.to_string())
+                    .show_ui(ui, |ui| {
+                        for mode in PoincareMode::ALL {
+                            ui.selectable_value(&mut self.poincare.mode, mode, mode.to_string());
+                        }
+                    });
+                ui.end_row();
+
+                ui.label("readiness baseline [ms]");
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut self.readiness_enabled, "");
+                    ui.add_enabled_ui(self.readiness_enabled, |ui| {
+                        ui.add(
+                            egui::DragValue::new(&mut self.readiness.low)
+                                .range(1.0..=self.readiness.high)
+                                .prefix("RMSSD "),
+                        );
+                        ui.label("-");
+                        ui.add(
+                            egui::DragValue::new(&mut self.readiness.high)
+                                .range(self.readiness.low..=500.0),
+                        );
+                    });
+                });
+                ui.end_row();
+
+                ui.label("HR zones");
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut self.hr_zones.show, "show");
+                    ui.add(
+                        egui::DragValue::new(&mut self.hr_zones.max_hr)
+                            .range(MAX_HR_RANGE)
+                            .prefix("max HR "),
+                    );
+                });
+                ui.end_row();
+
+                ui.label("zone bounds [% max HR]");
+                ui.horizontal(|ui| {
+                    for bound in self.hr_zones.bounds.iter_mut() {
+                        let mut percent = *bound * 100.0;
+                        if ui
+                            .add(egui::DragValue::new(&mut percent).range(1.0..=100.0))
+                            .changed()
+                        {
+                            *bound = percent / 100.0;
+                        }
+                    }
+                });
+                ui.end_row();
+            });
+        if !profiles.is_empty() {
+            ui.separator();
+            ui.label("Device profiles");
+            for profile in profiles {
+                ui.horizontal(|ui| {
+                    ui.label(format!("{} ({})", profile.name, profile.address));
+                    if ui.button("delete").clicked() {
+                        publish(AppEvent::Settings(SettingsEvent::DeleteProfile(
+                            profile.address.clone(),
+                        )));
+                    }
+                });
+            }
+        }
+        ui.separator();
+        ui.horizontal(|ui| {
+            if ui.button("Apply").clicked() {
+                publish(AppEvent::Settings(SettingsEvent::SetDateFormat(
+                    self.date_format.clone(),
+                )));
+                publish(AppEvent::Settings(SettingsEvent::SetPrecision(
+                    self.precision,
+                )));
+                publish(AppEvent::Settings(SettingsEvent::SetUiScale(self.ui_scale)));
+                publish(AppEvent::Settings(SettingsEvent::SetSmoothing(
+                    self.smoothing,
+                )));
+                publish(AppEvent::Settings(SettingsEvent::SetPoincareStyle(
+                    self.poincare.clone(),
+                )));
+                publish(AppEvent::Settings(SettingsEvent::SetPalette(self.palette)));
+                publish(AppEvent::Settings(SettingsEvent::SetScanInterval(
+                    self.scan_interval,
+                )));
+                publish(AppEvent::Settings(SettingsEvent::SetReconnectAttempts(
+                    self.reconnect_attempts,
+                )));
+                publish(AppEvent::Settings(SettingsEvent::SetScanHeartRateOnly(
+                    self.scan_heart_rate_only,
+                )));
+                publish(AppEvent::Settings(SettingsEvent::SetRepaintInterval(
+                    self.repaint_interval,
+                )));
+                publish(AppEvent::Settings(SettingsEvent::SetRecomputeDebounce(
+                    self.recompute_debounce,
+                )));
+                publish(AppEvent::Settings(SettingsEvent::SetReadinessBaseline(
+                    self.readiness_enabled.then(|| self.readiness.clone()),
+                )));
+                publish(AppEvent::Settings(SettingsEvent::SetHrZones(
+                    self.hr_zones.clone(),
+                )));
+                publish(AppEvent::Settings(SettingsEvent::SetAutoStopOnDisconnect(
+                    self.auto_stop_on_disconnect,
+                )));
+                publish(AppEvent::Settings(SettingsEvent::SetCompressFiles(
+                    self.compress_files,
+                )));
+                publish(AppEvent::Settings(SettingsEvent::SetAutoStart(
+                    self.auto_start_enabled.then_some(self.auto_start),
+                )));
+                publish(AppEvent::Settings(SettingsEvent::SetAutoMarkers(
+                    self.auto_markers_enabled.then_some(self.auto_markers),
+                )));
+                publish(AppEvent::Settings(SettingsEvent::SetMinSaveBeats(
+                    self.min_save_beats,
+                )));
+                publish(AppEvent::Settings(SettingsEvent::SetHrAlert(
+                    self.hr_alert.clone(),
+                )));
+                publish(AppEvent::Settings(SettingsEvent::SetLazyAnalysis(
+                    self.lazy_analysis,
+                )));
+                publish(AppEvent::Settings(SettingsEvent::SetAnalysisThreads(
+                    self.analysis_threads_enabled
+                        .then_some(self.analysis_threads),
+                )));
+                publish(AppEvent::Settings(SettingsEvent::SetRetention(
+                    self.retention.clone(),
+                )));
+                publish(AppEvent::Settings(SettingsEvent::SetDfaSmoothing(
+                    self.dfa_smoothing,
+                )));
+                publish(AppEvent::Settings(SettingsEvent::SetRrUnit(self.rr_unit)));
+                publish(AppEvent::Settings(
+                    SettingsEvent::SetStabilizationThreshold(self.stabilization_threshold),
+                ));
+            }
+            if ui.button("Close").clicked() {
+                self.open = false;
+            }
+        });
+    }
+}
+
+impl ViewApi for SettingsView {
+    /// Renders the settings window if it is open.
+    ///
+    /// # Arguments
+    /// * `publish` - Function to publish `UiInputEvent`s.
+    /// * `ctx` - Egui context for rendering.
+    ///
+    /// # Returns
+    /// `Result<(), String>` indicating success or an error message.
+    fn render<F: Fn(AppEvent) + ?Sized>(
+        &mut self,
+        publish: &F,
+        ctx: &egui::Context,
+    ) -> Result<(), String> {
+        if !self.open {
+            return Ok(());
+        }
+        let settings = lock::read(&self.settings)?.clone();
+        let mut open = self.open;
+        egui::Window::new("Settings")
+            .open(&mut open)
+            .show(ctx, |ui| self.render_settings(ui, publish, &settings));
+        self.open &= open;
+        Ok(())
+    }
+}
@@ -0,0 +1,156 @@
+//! Display Smoothing
+//!
+//! This module provides an exponential moving average (EMA) for stabilizing the live readout
+//! of noisy metrics and a moving median for plotted series. Only displayed values are
+//! smoothed, the stored time series stay untouched.
+
+use std::collections::HashMap;
+
+/// Exponential moving average.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Ema {
+    /// Current average, `None` until the first value was added.
+    value: Option<f64>,
+}
+
+impl Ema {
+    /// Adds a value to the average.
+    ///
+    /// # Arguments
+    /// * `value` - The new value.
+    /// * `smoothing` - Weight of the previous average in `[0, 1)`, `0` disables smoothing.
+    ///
+    /// # Returns
+    /// The updated average.
+    pub fn update(&mut self, value: f64, smoothing: f64) -> f64 {
+        let smoothed = match self.value {
+            Some(prev) => smoothing * prev + (1.0 - smoothing) * value,
+            None => value,
+        };
+        self.value = Some(smoothed);
+        smoothed
+    }
+}
+
+/// Smooths the most recent values of several named metrics.
+///
+/// The average of a metric is only updated when its series gained new values, so the
+/// readout does not depend on the frame rate.
+#[derive(Debug, Clone, Default)]
+pub struct MetricSmoother {
+    /// Weight of the previous average in `[0, 1)`.
+    smoothing: f64,
+    /// Series length and average of each metric.
+    averages: HashMap<String, (usize, Ema)>,
+}
+
+impl MetricSmoother {
+    /// Sets the weight of the previous average, `0` disables smoothing.
+    pub fn set_smoothing(&mut self, smoothing: f64) {
+        self.smoothing = smoothing;
+    }
+
+    /// Discards all averages, e.g. at the start of a recording.
+    pub fn reset(&mut self) {
+        self.averages.clear();
+    }
+
+    /// Returns the smoothed value of a metric.
+    ///
+    /// # Arguments
+    /// * `name` - Name of the metric.
+    /// * `len` - Length of the metric series, the average is updated if it changed.
+    /// * `value` - The most recent value of the metric.
+    pub fn smooth(&mut self, name: &str, len: usize, value: f64) -> f64 {
+        let smoothing = self.smoothing;
+        let (last_len, ema) = self.averages.entry(name.to_owned()).or_default();
+        if *last_len != len || ema.value.is_none() {
+            *last_len = len;
+            ema.update(value, smoothing);
+        }
+        ema.value.unwrap_or(value)
+    }
+}
+
+/// Smooths a time series with a centered moving median.
+///
+/// The window shrinks at the ends of the series, the times of the points are kept.
+///
+/// # Arguments
+/// * `series` - The `[time, value]` points.
+/// * `window` - Number of points of the window, `1` returns the series unchanged.
+pub fn moving_median(series: &[[f64; 2]], window: usize) -> Vec<[f64; 2]> {
+    let half = window / 2;
+    (0..series.len())
+        .map(|idx| {
+            let from = idx.saturating_sub(half);
+            let to = (idx + half + 1).min(series.len());
+            let mut values: Vec<f64> = series[from..to].iter().map(|p| p[1]).collect();
+            values.sort_by(f64::total_cmp);
+            let mid = values.len() / 2;
+            let median = if values.len().is_multiple_of(2) {
+                (values[mid - 1] + values[mid]) / 2.0
+            } else {
+                values[mid]
+            };
+            [series[idx][0], median]
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ema_step_response() {
+        let mut ema = Ema::default();
+        assert_eq!(ema.update(0.0, 0.5), 0.0);
+        let response: Vec<f64> = (0..4).map(|_| ema.update(1.0, 0.5)).collect();
+        assert_eq!(response, vec![0.5, 0.75, 0.875, 0.9375]);
+
+        // without smoothing the step is followed immediately
+        let mut ema = Ema::default();
+        ema.update(0.0, 0.0);
+        assert_eq!(ema.update(1.0, 0.0), 1.0);
+    }
+
+    #[test]
+    fn test_smoother_updates_on_new_values() {
+        let mut smoother = MetricSmoother::default();
+        smoother.set_smoothing(0.5);
+        assert_eq!(smoother.smooth("RMSSD", 1, 0.0), 0.0);
+        assert_eq!(smoother.smooth("RMSSD", 2, 1.0), 0.5);
+        // repeated frames without a new value keep the average
+        assert_eq!(smoother.smooth("RMSSD", 2, 1.0), 0.5);
+        smoother.reset();
+        assert_eq!(smoother.smooth("RMSSD", 2, 1.0), 1.0);
+    }
+
+    #[test]
+    fn test_moving_median() {
+        // a slow trend with noise and a single spike
+        let noise = [0.2, -0.15, 0.05, -0.2, 0.1];
+        let trend = |time: f64| 1.0 + 0.01 * time;
+        let series: Vec<[f64; 2]> = (0..20)
+            .map(|idx| {
+                let spike = if idx == 10 { 5.0 } else { 0.0 };
+                let time = idx as f64;
+                [time, trend(time) + noise[idx * 3 % 5] + spike]
+            })
+            .collect();
+        assert_eq!(moving_median(&series, 1), series);
+        let smoothed = moving_median(&series, 5);
+        assert_eq!(smoothed.len(), series.len());
+        assert!(smoothed.iter().zip(&series).all(|(s, p)| s[0] == p[0]));
+        // the spike is removed and the noise is reduced
+        let max_error = |ts: &[[f64; 2]]| {
+            ts.iter()
+                .map(|p| (p[1] - trend(p[0])).abs())
+                .fold(0.0, f64::max)
+        };
+        assert!(max_error(&series[2..18]) > 5.0);
+        assert!(max_error(&smoothed[2..18]) < 0.1);
+        assert!(moving_median(&[], 5).is_empty());
+    }
+}